@@ -1,12 +1,57 @@
-#![feature(test)]
-extern crate test;
+use std::time::Instant;
 
+use html_editor::operation::*;
 use html_editor::parse;
-use test::Bencher;
 
-#[bench]
-fn bench(b: &mut Bencher) {
-    b.iter(|| {
-        parse(include_str!("./bench.html")).unwrap();
-    })
+const ITERATIONS: u32 = 1000;
+
+const DOCUMENTS: usize = 50;
+
+const SELECTORS: [&str; 7] = [
+    "div",
+    ".nav-link.active",
+    "a[href^='https://']",
+    "ul > li a",
+    "a.external.text",
+    "div.vector-menu-content ul > li.mw-list-item a",
+    "li.interlanguage-link a.interlanguage-link-target:not(.extiw)",
+];
+
+fn main() {
+    let html = include_str!("./bench.html");
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        parse(html).unwrap();
+    }
+    println!("parse: {:?}/iter", start.elapsed() / ITERATIONS);
+
+    let dom = parse(html).unwrap();
+    for source in SELECTORS {
+        let selector = Selector::from(source);
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            dom.query_all(&selector);
+        }
+        println!(
+            "query_all({:?}): {:?}/iter",
+            source,
+            start.elapsed() / ITERATIONS
+        );
+    }
+
+    // One selector, parsed once, over many documents.
+    let documents: Vec<_> = (0..DOCUMENTS).map(|_| parse(html).unwrap()).collect();
+    let selector = Selector::from(&SELECTORS.join(", ")[..]);
+    let start = Instant::now();
+    for _ in 0..ITERATIONS / 10 {
+        for dom in &documents {
+            dom.query_all(&selector);
+        }
+    }
+    println!(
+        "query_all({:?}) over {} documents: {:?}/iter",
+        selector.to_css_string(),
+        DOCUMENTS,
+        start.elapsed() / (ITERATIONS / 10)
+    );
 }
@@ -0,0 +1,104 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use crate::{parse_with_options, Node, ParseOptions};
+
+/// Templates with the same hash, with their html.
+type Bucket = Vec<(String, Arc<Vec<Node>>)>;
+
+/// Cache of parsed templates, keyed by the hash of their html, for
+/// servers editing the same templates with different data again and
+/// again.
+///
+/// The parsed nodes are shared, so getting them is cheap: clone them
+/// with [`Arc::make_mut`] or [`Arc::unwrap_or_clone`] only when they
+/// need to be edited.
+///
+/// Requires the `cache` feature.
+///
+/// ```
+/// use std::sync::Arc;
+/// use html_editor::operation::*;
+/// use html_editor::{Node, TemplateCache};
+///
+/// let cache = TemplateCache::new();
+/// let template = r#"<p class="greeting"></p>"#;
+///
+/// let mut dom = Arc::unwrap_or_clone(cache.get(template).unwrap());
+/// dom.insert_to(&Selector::from(".greeting"), Node::Text("Hi".to_string()));
+/// assert_eq!(dom.html(), r#"<p class="greeting">Hi</p>"#);
+///
+/// // The cached nodes are left untouched.
+/// assert_eq!(cache.get(template).unwrap().html(), r#"<p class="greeting"></p>"#);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct TemplateCache {
+    options: ParseOptions,
+    entries: Mutex<HashMap<u64, Bucket>>,
+}
+
+impl TemplateCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a cache parsing the templates with `options`.
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Get the nodes of `html`, parsing it if it is not cached yet.
+    ///
+    /// Templates failing to parse are not cached.
+    pub fn get(&self, html: &str) -> Result<Arc<Vec<Node>>, String> {
+        let mut hasher = DefaultHasher::new();
+        html.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if let Some(nodes) = self.lookup(hash, html) {
+            return Ok(nodes);
+        }
+        // Parse without holding the lock, so other templates can be
+        // got meanwhile.
+        let nodes = Arc::new(parse_with_options(html, &self.options)?);
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = entries.entry(hash).or_default();
+        match bucket.iter().find(|(source, _)| source == html) {
+            Some((_, cached)) => Ok(Arc::clone(cached)),
+            None => {
+                bucket.push((html.to_string(), Arc::clone(&nodes)));
+                Ok(nodes)
+            }
+        }
+    }
+
+    fn lookup(&self, hash: u64, html: &str) -> Option<Arc<Vec<Node>>> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = entries.get(&hash)?;
+        let (_, nodes) = bucket.iter().find(|(source, _)| source == html)?;
+        Some(Arc::clone(nodes))
+    }
+
+    /// Get the number of cached templates.
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.values().map(Vec::len).sum()
+    }
+
+    /// Check if no template is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the cached templates.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.clear();
+    }
+}
@@ -0,0 +1,260 @@
+//! Minimal CSS handling for the operations working on styles.
+
+/// Split the declarations of an inline `style` attribute into pairs of
+/// lowercased property and trimmed value.
+///
+/// Semicolons inside quotes or parentheses, like in `url("a;b")`, do not
+/// end a declaration. Declarations without a colon are skipped.
+pub(crate) fn declarations(style: &str) -> Vec<(String, String)> {
+    let mut declarations = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, ch) in style.char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ';') if depth <= 0 => {
+                declarations.extend(declaration(&style[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.extend(declaration(&style[start..]));
+    declarations
+}
+
+fn declaration(text: &str) -> Option<(String, String)> {
+    let (property, value) = text.split_once(':')?;
+    let property = property.trim().to_ascii_lowercase();
+    (!property.is_empty()).then(|| (property, value.trim().to_string()))
+}
+
+/// Join declarations back into the value of a `style` attribute.
+pub(crate) fn serialize(declarations: &[(String, String)]) -> String {
+    declarations
+        .iter()
+        .map(|(property, value)| format!("{}: {}", property, value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Part of a stylesheet, as split by [`stylesheet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Item {
+    /// Style rule, like `p, .note { color: red }`.
+    Rule { prelude: String, block: String },
+    /// Conditional group rule, like `@media print { ... }`, with the
+    /// items it contains.
+    Group { prelude: String, items: Vec<Item> },
+    /// Any other at-rule, like `@import` or `@font-face`, kept as is.
+    Other(String),
+}
+
+/// At-rules containing style rules, split into a [`Item::Group`].
+const GROUP_RULES: [&str; 5] = ["@media", "@supports", "@layer", "@container", "@document"];
+
+/// Split a stylesheet into its rules, dropping the comments.
+///
+/// Braces and semicolons inside quotes don't end a rule, and the
+/// preludes are trimmed while the blocks are kept as is.
+pub(crate) fn stylesheet(css: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut chars = css.char_indices().peekable();
+    let mut prelude = String::new();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '/' if chars.peek().map(|(_, c)| *c) == Some('*') => {
+                chars.next();
+                let mut last = ' ';
+                for (_, c) in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '"' | '\'' => {
+                prelude.push(ch);
+                for (_, c) in chars.by_ref() {
+                    prelude.push(c);
+                    if c == ch {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                let statement = prelude.trim();
+                if !statement.is_empty() {
+                    items.push(Item::Other(format!("{};", statement)));
+                }
+                prelude.clear();
+            }
+            '{' => {
+                let end = block_end(css, i + 1);
+                while chars.peek().is_some_and(|(j, _)| *j < end) {
+                    chars.next();
+                }
+                chars.next();
+                items.push(item(prelude.trim(), &css[i + 1..end]));
+                prelude.clear();
+            }
+            _ => prelude.push(ch),
+        }
+    }
+    if !prelude.trim().is_empty() {
+        items.push(Item::Other(prelude.trim().to_string()));
+    }
+    items
+}
+
+fn item(prelude: &str, block: &str) -> Item {
+    let at_rule = prelude
+        .split(|c: char| c.is_ascii_whitespace() || c == '(')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if GROUP_RULES.contains(&at_rule.as_str()) {
+        Item::Group {
+            prelude: prelude.to_string(),
+            items: stylesheet(block),
+        }
+    } else if prelude.starts_with('@') {
+        Item::Other(format!("{}{{{}}}", prelude, block))
+    } else {
+        Item::Rule {
+            prelude: prelude.to_string(),
+            block: block.to_string(),
+        }
+    }
+}
+
+/// Index of the `}` closing the block starting at `start`, or the end
+/// of `css` if it is never closed.
+fn block_end(css: &str, start: usize) -> usize {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for (i, ch) in css[start..].char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '{') => depth += 1,
+            (None, '}') if depth == 0 => return start + i,
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+/// Join the items of a stylesheet back together.
+pub(crate) fn serialize_stylesheet(items: &[Item]) -> String {
+    let mut css = String::new();
+    for item in items {
+        match item {
+            Item::Rule { prelude, block } => css.push_str(&format!("{}{{{}}}", prelude, block)),
+            Item::Group { prelude, items } => {
+                css.push_str(&format!("{}{{{}}}", prelude, serialize_stylesheet(items)))
+            }
+            Item::Other(text) => css.push_str(text),
+        }
+    }
+    css
+}
+
+/// Split a selector list on its top-level commas, trimming the
+/// selectors.
+pub(crate) fn selector_list(prelude: &str) -> Vec<&str> {
+    let mut selectors = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, ch) in prelude.char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, ',') if depth <= 0 => {
+                selectors.push(prelude[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    selectors.push(prelude[start..].trim());
+    selectors.retain(|selector| !selector.is_empty());
+    selectors
+}
+
+/// Class or id referenced by a selector, found by [`references`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Reference {
+    /// Either `.` or `#`.
+    pub kind: char,
+    /// Name with its escapes resolved, like `md:flex` for `.md\:flex`.
+    pub name: String,
+    /// Whether it is inside a functional pseudo-class, like in
+    /// `:not(.hidden)`, so that the selector may match without it.
+    pub nested: bool,
+}
+
+/// Find the classes and ids referenced by a selector, skipping the
+/// attribute selectors and the strings.
+pub(crate) fn references(selector: &str) -> Vec<Reference> {
+    let mut references = Vec::new();
+    let mut chars = selector.chars().peekable();
+    let mut depth = 0;
+    let mut brackets = 0;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' | '\'' => {
+                for c in chars.by_ref() {
+                    if c == ch {
+                        break;
+                    }
+                }
+            }
+            '\\' => {
+                chars.next();
+            }
+            '[' => brackets += 1,
+            ']' => brackets -= 1,
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '.' | '#' if brackets == 0 => {
+                let starts_ident = chars
+                    .peek()
+                    .is_some_and(|c| c.is_alphabetic() || matches!(c, '-' | '_' | '\\'));
+                if !starts_ident {
+                    continue;
+                }
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '\\' {
+                        chars.next();
+                        name.extend(chars.next());
+                    } else if c.is_alphanumeric() || matches!(c, '-' | '_') || !c.is_ascii() {
+                        name.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                references.push(Reference {
+                    kind: ch,
+                    name,
+                    nested: depth > 0,
+                });
+            }
+            _ => {}
+        }
+    }
+    references
+}
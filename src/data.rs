@@ -2,3 +2,244 @@ pub const VOID_TAGS: [&str; 15] = [
     "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param",
     "source", "track", "wbr",
 ];
+
+/// Attributes whose value is a URL.
+pub const URL_ATTRS: [&str; 11] = [
+    "action",
+    "background",
+    "cite",
+    "data",
+    "formaction",
+    "href",
+    "longdesc",
+    "poster",
+    "src",
+    "srcset",
+    "xlink:href",
+];
+
+/// Html attributes with a different name in JSX, other than the event
+/// handlers.
+pub const JSX_ATTRS: [(&str, &str); 37] = [
+    ("accept-charset", "acceptCharset"),
+    ("accesskey", "accessKey"),
+    ("allowfullscreen", "allowFullScreen"),
+    ("autocomplete", "autoComplete"),
+    ("autofocus", "autoFocus"),
+    ("autoplay", "autoPlay"),
+    ("cellpadding", "cellPadding"),
+    ("cellspacing", "cellSpacing"),
+    ("charset", "charSet"),
+    ("class", "className"),
+    ("colspan", "colSpan"),
+    ("contenteditable", "contentEditable"),
+    ("crossorigin", "crossOrigin"),
+    ("datetime", "dateTime"),
+    ("enctype", "encType"),
+    ("for", "htmlFor"),
+    ("formaction", "formAction"),
+    ("frameborder", "frameBorder"),
+    ("hreflang", "hrefLang"),
+    ("http-equiv", "httpEquiv"),
+    ("inputmode", "inputMode"),
+    ("itemprop", "itemProp"),
+    ("itemscope", "itemScope"),
+    ("itemtype", "itemType"),
+    ("maxlength", "maxLength"),
+    ("minlength", "minLength"),
+    ("novalidate", "noValidate"),
+    ("playsinline", "playsInline"),
+    ("readonly", "readOnly"),
+    ("referrerpolicy", "referrerPolicy"),
+    ("rowspan", "rowSpan"),
+    ("spellcheck", "spellCheck"),
+    ("srcdoc", "srcDoc"),
+    ("srcset", "srcSet"),
+    ("tabindex", "tabIndex"),
+    ("usemap", "useMap"),
+    ("xlink:href", "xlinkHref"),
+];
+
+/// Event handlers in JSX, whose html name is the lowercased one,
+/// except for `onDoubleClick` which is `ondblclick`.
+pub const JSX_EVENTS: [&str; 30] = [
+    "onBlur",
+    "onChange",
+    "onClick",
+    "onContextMenu",
+    "onCopy",
+    "onDoubleClick",
+    "onDrag",
+    "onDragEnd",
+    "onDragStart",
+    "onDrop",
+    "onError",
+    "onFocus",
+    "onInput",
+    "onKeyDown",
+    "onKeyPress",
+    "onKeyUp",
+    "onLoad",
+    "onMouseDown",
+    "onMouseEnter",
+    "onMouseLeave",
+    "onMouseMove",
+    "onMouseOut",
+    "onMouseOver",
+    "onMouseUp",
+    "onPaste",
+    "onScroll",
+    "onSubmit",
+    "onTouchEnd",
+    "onTouchStart",
+    "onWheel",
+];
+
+/// Elements of html, including the obsolete ones browsers still render.
+pub const HTML_TAGS: [&str; 132] = [
+    "a",
+    "abbr",
+    "acronym",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "basefont",
+    "bdi",
+    "bdo",
+    "big",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "center",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "font",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "keygen",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "marquee",
+    "math",
+    "menu",
+    "meta",
+    "meter",
+    "nav",
+    "nobr",
+    "noframes",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "param",
+    "picture",
+    "plaintext",
+    "pre",
+    "progress",
+    "q",
+    "rb",
+    "rp",
+    "rt",
+    "rtc",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "search",
+    "section",
+    "select",
+    "slot",
+    "small",
+    "source",
+    "span",
+    "strike",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "svg",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "tt",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+/// Elements belonging to the `<head>`, moved into it when it is
+/// synthesized.
+pub const METADATA_TAGS: [&str; 8] = [
+    "base", "link", "meta", "noscript", "script", "style", "template", "title",
+];
+
+/// Elements formatting the text inside a paragraph, which do not
+/// interrupt its prose.
+pub const INLINE_TAGS: [&str; 24] = [
+    "a", "abbr", "b", "bdi", "bdo", "cite", "data", "del", "dfn", "em", "font", "i", "ins", "mark",
+    "q", "s", "small", "span", "strong", "sub", "sup", "time", "u", "var",
+];
@@ -0,0 +1,59 @@
+//! Minimal handling of character references, for the operations needing
+//! the actual text behind the raw text of the nodes.
+
+/// Get the length of the character reference `text` starts with, like
+/// `&amp;` or `&#233;`, known or not.
+pub(crate) fn reference_len(text: &str) -> Option<usize> {
+    let end = text.strip_prefix('&')?.find(';')? + 1;
+    let name = &text[1..end];
+    let is_reference = end <= 32
+        && !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '#');
+    is_reference.then_some(end + 1)
+}
+
+/// Decode the numeric character references and the most common named
+/// ones. Unknown references are kept as is.
+pub(crate) fn decode(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(';').filter(|end| *end <= 10);
+        let ch = end.and_then(|end| {
+            let name = &rest[1..end];
+            match name.strip_prefix('#') {
+                Some(code) => match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => code.parse().ok(),
+                }
+                .and_then(char::from_u32),
+                None => match name {
+                    "Tab" => Some('\t'),
+                    "NewLine" => Some('\n'),
+                    "colon" => Some(':'),
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some('\u{a0}'),
+                    _ => None,
+                },
+            }
+        });
+        match (ch, end) {
+            (Some(ch), Some(end)) => {
+                decoded.push(ch);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
@@ -1,41 +1,198 @@
-use std::{fmt, fmt::Debug, panic::Location};
+use std::{error, fmt, fmt::Debug, panic::Location};
 
 #[derive(Debug)]
 pub struct Error;
 
 impl fmt::Display for Error {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    write!(f, "Unexpected error in HTML Editor")
-  }
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unexpected error in HTML Editor")
+    }
 }
 
 impl std::error::Error for Error {}
 
+type Source = Box<dyn error::Error + Send + Sync + 'static>;
+
+/// Message of an error and of its own sources, to keep the chain of any
+/// error while staying `Send` and `Sync`.
 #[derive(Debug)]
+struct Message {
+    text: String,
+    source: Option<Box<Message>>,
+}
+
+impl Message {
+    fn new(error: &(dyn error::Error + 'static)) -> Self {
+        Self {
+            text: error.to_string(),
+            source: error.source().map(|source| Box::new(Self::new(source))),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl error::Error for Message {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+/// Location in the source code.
+struct Caller {
+    line: u32,
+    column: u32,
+    file: String,
+}
+
+impl Caller {
+    #[track_caller]
+    fn new() -> Self {
+        let caller = Location::caller();
+        let line = Location::line(caller);
+        let column = Location::column(caller);
+        let file = Location::file(caller).to_string();
+        Self { line, column, file }
+    }
+}
+
+impl fmt::Display for Caller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Error with where it was created, its context messages, and the error
+/// it was created from.
+///
+/// As that error may not be `Send` or `Sync`, only its messages and
+/// the ones of its own sources are kept. [`ErrorDetail::source`] returns
+/// their copy, which can't be downcast into the type of the original
+/// error.
+///
+/// ```
+/// use html_editor::error::ErrorDetail;
+/// use std::num::ParseIntError;
+///
+/// let error = ErrorDetail::from("x".parse::<u32>().unwrap_err());
+/// let source = error.source().unwrap();
+///
+/// assert_eq!(source.to_string(), "invalid digit found in string");
+/// assert!(source.downcast_ref::<ParseIntError>().is_none());
+/// ```
 pub struct ErrorDetail {
-  line: u32,
-  column: u32,
-  file: String
+    caller: Caller,
+    source: Option<Source>,
+    /// Messages added by [`ErrorDetail::with_context`], from the first
+    /// one added, with where they were added.
+    context: Vec<(String, Caller)>,
 }
 
 impl ErrorDetail {
-  #[track_caller]
-  pub fn new() -> Self {
-    let caller = Location::caller();
-    let line = Location::line(caller);
-    let column = Location::column(caller);
-    let file = Location::file(caller).to_string();
-    Self {line, column, file}
-  }
-}
-
-impl<T: std::error::Error + 'static> From<T> for ErrorDetail {
-  #[track_caller]
-  fn from(value: T) -> Self {
-    let caller = Location::caller();
-    let line = Location::line(caller);
-    let column = Location::column(caller);
-    let file = Location::file(caller).to_string();
-    Self {line, column, file}
-  }
+    #[track_caller]
+    pub fn new() -> Self {
+        Self {
+            caller: Caller::new(),
+            source: None,
+            context: Vec::new(),
+        }
+    }
+
+    /// Add a message about what was being done when the error happened.
+    /// The messages are chained, from the last one added.
+    ///
+    /// ```
+    /// use html_editor::error::ErrorDetail;
+    ///
+    /// let error = ErrorDetail::from("x".parse::<u32>().unwrap_err())
+    ///     .with_context("while reading the width")
+    ///     .with_context("while replacing <img>");
+    ///
+    /// let message = error.to_string();
+    /// assert!(message.starts_with("while replacing <img>: while reading the width: Error at "));
+    /// assert!(message.ends_with(": invalid digit found in string"));
+    /// ```
+    #[track_caller]
+    pub fn with_context(mut self, message: impl Into<String>) -> Self {
+        self.context.push((message.into(), Caller::new()));
+        self
+    }
+
+    /// Get the copy of the messages of the error this one was created
+    /// from, which can't be downcast into its type.
+    pub fn source(&self) -> Option<&(dyn error::Error + Send + Sync + 'static)> {
+        self.source.as_deref()
+    }
+
+    /// Iterate over the context messages, from the last one added.
+    pub fn context(&self) -> impl Iterator<Item = &str> {
+        self.context
+            .iter()
+            .rev()
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Iterate over the source error and its own sources.
+    fn causes(&self) -> impl Iterator<Item = &(dyn error::Error + 'static)> {
+        let first = self
+            .source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static));
+        std::iter::successors(first, |cause| cause.source())
+    }
+}
+
+impl Default for ErrorDetail {
+    #[track_caller]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for ErrorDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for message in self.context() {
+            write!(f, "{}: ", message)?;
+        }
+        write!(f, "Error at {}", self.caller)?;
+        for cause in self.causes() {
+            write!(f, ": {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for ErrorDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (message, caller) in self.context.iter().rev() {
+            writeln!(f, "{} (at {})", message, caller)?;
+        }
+        write!(f, "Error at {}", self.caller)?;
+        let mut causes = self.causes().peekable();
+        if causes.peek().is_some() {
+            write!(f, "\n\nCaused by:")?;
+            for (index, cause) in causes.enumerate() {
+                write!(f, "\n    {}: {}", index, cause)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: error::Error + 'static> From<T> for ErrorDetail {
+    /// The error is kept by its messages, as it may not be `Send`.
+    #[track_caller]
+    fn from(value: T) -> Self {
+        Self {
+            caller: Caller::new(),
+            source: Some(Box::new(Message::new(&value))),
+            context: Vec::new(),
+        }
+    }
 }
@@ -33,20 +33,49 @@
 //! );
 //! ```
 
+#[cfg(feature = "cache")]
+mod cache;
+mod css;
 mod data;
+mod entity;
 mod parse;
+mod srcset;
+mod url;
 
 pub mod error;
 pub mod operation;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+#[cfg(feature = "cache")]
+pub use cache::TemplateCache;
 pub use parse::parse;
+pub use parse::parse_with_frontmatter;
+pub use parse::parse_with_options;
 pub use parse::try_parse;
+pub use parse::try_parse_with_options;
+pub use parse::try_parse_with_repairs;
+pub use parse::Frontmatter;
+pub use parse::ParseOptions;
+pub use parse::Repair;
+pub use parse::UnknownTagPolicy;
+pub use parse::IMPLICIT_ATTR;
+
+/// Result of the fallible operations, failing with an
+/// [`ErrorDetail`](error::ErrorDetail) by default.
+pub type Result<T, E = error::ErrorDetail> = std::result::Result<T, E>;
 
 /// Doctype of Html or Xml
 #[derive(Clone, Debug)]
 pub enum Doctype {
     Html,
-    Xml { version: String, encoding: String },
+    /// XML declaration with only a version and an encoding, like
+    /// `<?xml version="1.0" encoding="UTF-8"?>`. The other ones are
+    /// parsed into [`Node::ProcessingInstruction`].
+    Xml {
+        version: String,
+        encoding: String,
+    },
 }
 
 /// Node of DOM
@@ -56,6 +85,14 @@ pub enum Node {
     Text(String),
     Comment(String),
     Doctype(Doctype),
+    /// Tag of a template language, like `{% if user %}` or `{{ name }}`,
+    /// or embedded code block, like `<?php echo $name; ?>` or
+    /// `<%= name %>`, kept verbatim with its delimiters.
+    Template(String),
+    /// Processing instruction, like `<?xml-stylesheet href="a.xsl"?>`,
+    /// with its content between `<?` and `?>`. The XML declarations not
+    /// written back the same by [`Doctype::Xml`] are parsed into one too.
+    ProcessingInstruction(String),
 }
 
 impl Node {
@@ -173,6 +210,88 @@ impl Element {
     pub fn into_node(self) -> Node {
         Node::Element(self)
     }
+
+    /// Get the first child node.
+    ///
+    /// ```
+    /// use html_editor::{Element, Node};
+    ///
+    /// let ul = Element::new("ul", vec![], vec![Node::new_element("li", vec![], vec![])]);
+    /// assert!(ul.first_child().unwrap().is_element());
+    /// assert!(Element::new("ul", vec![], vec![]).first_child().is_none());
+    /// ```
+    pub fn first_child(&self) -> Option<&Node> {
+        self.children.first()
+    }
+
+    /// Get the first child node as mutable.
+    pub fn first_child_mut(&mut self) -> Option<&mut Node> {
+        self.children.first_mut()
+    }
+
+    /// Get the child node at `index`.
+    ///
+    /// ```
+    /// use html_editor::{Element, Node};
+    ///
+    /// let p = Element::new("p", vec![], vec![Node::Text("Hi".to_string())]);
+    /// assert!(p.child(0).is_some());
+    /// assert!(p.child(1).is_none());
+    /// ```
+    pub fn child(&self, index: usize) -> Option<&Node> {
+        self.children.get(index)
+    }
+
+    /// Get the child node at `index` as mutable.
+    pub fn child_mut(&mut self, index: usize) -> Option<&mut Node> {
+        self.children.get_mut(index)
+    }
+
+    /// Get the text of an element which only contains a text node, or an
+    /// empty string if it has no children.
+    ///
+    /// Fails if it contains any other node.
+    ///
+    /// ```
+    /// use html_editor::{parse, Element};
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse("<p>Hello</p><p>Hello <b>World</b></p>").unwrap();
+    /// let p: Vec<&Element> = dom.query_all(&Selector::from("p"));
+    ///
+    /// assert_eq!(p[0].only_text().unwrap(), "Hello");
+    /// assert!(p[1].only_text().is_err());
+    /// ```
+    #[track_caller]
+    pub fn only_text(&self) -> Result<&str> {
+        match self.children.as_slice() {
+            [] => Ok(""),
+            [Node::Text(text)] => Ok(text),
+            _ => Err(error::ErrorDetail::new()
+                .with_context(format!("<{}> doesn't contain only text", self.name))),
+        }
+    }
+}
+
+impl Element {
+    pub(crate) fn get_attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn set_attr(&mut self, name: &str, value: &str) {
+        match self.attrs.iter_mut().find(|(key, _)| key == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.attrs.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub(crate) fn remove_attr(&mut self, name: &str) -> Option<String> {
+        let index = self.attrs.iter().position(|(key, _)| key == name)?;
+        Some(self.attrs.remove(index).1)
+    }
 }
 
 impl From<Element> for Node {
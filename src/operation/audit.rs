@@ -0,0 +1,653 @@
+use std::collections::{HashMap, HashSet};
+
+use super::walk::{walk_element_mut, walk_mut};
+use super::{Editable, Htmlifiable, Queryable, Selector};
+use crate::css::{self, Item};
+use crate::{Element, Node};
+
+/// Problem found on an `<img>` by [`Auditable::audit_alt`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AltIssue {
+    /// The image has no `alt` attribute at all.
+    Missing { src: Option<String> },
+    /// The alt text is shared with an image of another source.
+    Duplicate { src: Option<String>, alt: String },
+}
+
+/// Serialized size in bytes of the regions of a document, given by
+/// [`Auditable::weight_report`].
+///
+/// The regions `head` to `footer` don't overlap: an element belongs to
+/// its outermost region, and whatever is outside of all of them goes
+/// to `other`. `scripts` and `inline_css` are counted wherever they
+/// are, so they overlap with the regions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WeightReport {
+    pub total: usize,
+    pub head: usize,
+    pub header: usize,
+    pub nav: usize,
+    /// `<main>` and the elements with `role="main"`.
+    pub main: usize,
+    pub aside: usize,
+    pub footer: usize,
+    pub other: usize,
+    /// `<script>` elements.
+    pub scripts: usize,
+    /// `<style>` elements and `style` attributes.
+    pub inline_css: usize,
+}
+
+/// Problem found among the head elements by [`Auditable::lint_head`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadIssue {
+    /// More than one `<title>`, with their count.
+    MultipleTitles(usize),
+    /// More than one charset declaration, with their values.
+    MultipleCharsets(Vec<String>),
+    /// More than one `<meta name="viewport">`, with their count.
+    MultipleViewports(usize),
+    /// A `<meta http-equiv="refresh">`, with its content.
+    MetaRefresh(String),
+}
+
+/// Which of the duplicated elements to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepPolicy {
+    First,
+    Last,
+}
+
+/// Inline style fixing a width in pixels, found by
+/// [`Auditable::fixed_widths`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixedWidth {
+    /// Name of the element, like `div`.
+    pub tag: String,
+    /// Either `width` or `min-width`.
+    pub property: String,
+    pub px: f32,
+}
+
+/// What [`Auditable::prune_css`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrunedCss {
+    /// Class names removed from the elements, in document order.
+    pub classes: Vec<String>,
+    /// Selectors removed from the `<style>` rules, in order.
+    pub selectors: Vec<String>,
+}
+
+/// Audit the document for common problems.
+pub trait Auditable {
+    /// List the images whose alt text is missing or duplicated.
+    ///
+    /// An empty `alt=""` marks the image as decorative and is never
+    /// reported.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<img src="a.png"><img alt=""><img alt="Logo">"#;
+    /// let issues = parse(html).unwrap().audit_alt();
+    ///
+    /// assert_eq!(issues, vec![AltIssue::Missing { src: Some("a.png".to_string()) }]);
+    /// ```
+    fn audit_alt(&self) -> Vec<AltIssue>;
+
+    /// Fill in the missing alt text of images by their `src`, and
+    /// return how many images have been changed.
+    ///
+    /// Images which already have an `alt` attribute, including the
+    /// decorative `alt=""`, are left untouched.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let alts = HashMap::from([("a.png".to_string(), "A cat".to_string())]);
+    /// let mut dom = parse(r#"<img src="a.png">"#).unwrap();
+    ///
+    /// assert_eq!(dom.fill_alt(&alts), 1);
+    /// assert_eq!(dom.html(), r#"<img src="a.png" alt="A cat">"#);
+    /// ```
+    fn fill_alt(&mut self, alts: &HashMap<String, String>) -> usize;
+
+    /// Fill in the missing alt text of images with the value returned
+    /// by `f`, and return how many images have been changed.
+    ///
+    /// Images which already have an `alt` attribute are skipped, and
+    /// so are the images that `f` returns `None` for.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<img src="a.png">"#).unwrap();
+    /// dom.fill_alt_with(|img| Some(format!("Image {}", img.attrs[0].1)));
+    ///
+    /// assert_eq!(dom.html(), r#"<img src="a.png" alt="Image a.png">"#);
+    /// ```
+    fn fill_alt_with(&mut self, f: impl FnMut(&Element) -> Option<String>) -> usize;
+
+    /// Attribute the serialized size of the document to its regions,
+    /// to see where the bytes of a page go.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = "<head><style>p{}</style></head><body><nav>Home</nav><p>Hi</p></body>";
+    /// let report = parse(html).unwrap().weight_report();
+    ///
+    /// assert_eq!(report.total, html.len());
+    /// assert_eq!(report.head, "<head><style>p{}</style></head>".len());
+    /// assert_eq!(report.nav, "<nav>Home</nav>".len());
+    /// assert_eq!(report.inline_css, "<style>p{}</style>".len());
+    /// ```
+    fn weight_report(&self) -> WeightReport;
+
+    /// Check the elements that should only appear once in the head:
+    /// `<title>`, the charset declarations and the viewport. Meta
+    /// refreshes are reported too, as they hurt accessibility.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<head><meta charset="utf-8"><title>A</title><title>B</title></head>"#;
+    /// let issues = parse(html).unwrap().lint_head();
+    ///
+    /// assert_eq!(issues, vec![HeadIssue::MultipleTitles(2)]);
+    /// ```
+    fn lint_head(&self) -> Vec<HeadIssue>;
+
+    /// Remove the duplicated head elements reported by
+    /// [`Auditable::lint_head`], keeping the first or the last one of
+    /// each kind, and return the issues found before fixing.
+    ///
+    /// A single meta refresh is not a duplicate, so it is kept.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<head><title>A</title><title>B</title></head>").unwrap();
+    /// dom.fix_head(KeepPolicy::Last);
+    ///
+    /// assert_eq!(dom.html(), "<head><title>B</title></head>");
+    /// ```
+    fn fix_head(&mut self, keep: KeepPolicy) -> Vec<HeadIssue>;
+
+    /// Find the inline styles fixing a `width` or `min-width` larger
+    /// than `max_px` pixels, which are likely to overflow on mobile.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<div style="width: 960px">A</div><img style="width:48px">"#;
+    /// let widths = parse(html).unwrap().fixed_widths(320.0);
+    ///
+    /// assert_eq!(widths.len(), 1);
+    /// assert_eq!(widths[0].px, 960.0);
+    /// ```
+    fn fixed_widths(&self, max_px: f32) -> Vec<FixedWidth>;
+
+    /// Cross-reference the classes and ids of the elements with the
+    /// rules of the `<style>` elements, removing the selectors that
+    /// reference a class or id used by no element, then the classes that
+    /// no remaining selector references.
+    ///
+    /// Rules left without selectors are removed, and so are the emptied
+    /// `class` attributes. Ids are kept, being link targets. Only use it
+    /// on self-contained documents: the classes styled by external
+    /// stylesheets or used by scripts are removed too.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(concat!(
+    ///     "<style>.note{color:red}.old, #gone{color:blue}</style>",
+    ///     r#"<p class="note unused">Text</p>"#,
+    /// ))
+    /// .unwrap();
+    /// let pruned = dom.prune_css();
+    ///
+    /// assert_eq!(pruned.classes, vec!["unused"]);
+    /// assert_eq!(pruned.selectors, vec![".old", "#gone"]);
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<style>.note{color:red}</style><p class="note">Text</p>"#
+    /// );
+    /// ```
+    fn prune_css(&mut self) -> PrunedCss;
+}
+
+fn audit_images(images: Vec<&Element>) -> Vec<AltIssue> {
+    let mut sources: HashMap<&str, Vec<Option<&str>>> = HashMap::new();
+    for img in &images {
+        if let Some(alt) = img.get_attr("alt").filter(|alt| !alt.trim().is_empty()) {
+            sources.entry(alt).or_default().push(img.get_attr("src"));
+        }
+    }
+
+    images
+        .iter()
+        .filter_map(|img| {
+            let src = img.get_attr("src").map(|src| src.to_string());
+            match img.get_attr("alt") {
+                None => Some(AltIssue::Missing { src }),
+                Some(alt) => {
+                    let others = sources.get(alt)?;
+                    let duplicated = others.iter().any(|other| *other != img.get_attr("src"));
+                    duplicated.then(|| AltIssue::Duplicate {
+                        src,
+                        alt: alt.to_string(),
+                    })
+                }
+            }
+        })
+        .collect()
+}
+
+fn fill_images<T: Editable>(
+    target: &mut T,
+    mut f: impl FnMut(&Element) -> Option<String>,
+) -> usize {
+    let mut count = 0;
+    target.execute_for(&Selector::from("img"), |img| {
+        if img.get_attr("alt").is_some() {
+            return;
+        }
+        if let Some(alt) = f(img) {
+            img.set_attr("alt", &alt);
+            count += 1;
+        }
+    });
+    count
+}
+
+fn alt_by_src(alts: &HashMap<String, String>) -> impl FnMut(&Element) -> Option<String> + '_ {
+    |img| img.get_attr("src").and_then(|src| alts.get(src)).cloned()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum HeadKind {
+    Title,
+    Charset,
+    Viewport,
+    Refresh,
+}
+
+fn meta_attr_is(element: &Element, name: &str, value: &str) -> bool {
+    element
+        .get_attr(name)
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case(value))
+}
+
+fn head_kind(element: &Element) -> Option<HeadKind> {
+    match element.name.as_str() {
+        "title" => Some(HeadKind::Title),
+        "meta" if charset_of(element).is_some() => Some(HeadKind::Charset),
+        "meta" if meta_attr_is(element, "name", "viewport") => Some(HeadKind::Viewport),
+        "meta" if meta_attr_is(element, "http-equiv", "refresh") => Some(HeadKind::Refresh),
+        _ => None,
+    }
+}
+
+/// Get the charset of `<meta charset>` or of the legacy
+/// `<meta http-equiv="content-type" content="text/html; charset=...">`.
+fn charset_of(element: &Element) -> Option<String> {
+    if let Some(charset) = element.get_attr("charset") {
+        return Some(charset.trim().to_string());
+    }
+    if !meta_attr_is(element, "http-equiv", "content-type") {
+        return None;
+    }
+    let content = element.get_attr("content")?;
+    content.split(';').find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().to_string())
+    })
+}
+
+fn collect_head_elements<'a>(nodes: &'a [Node], found: &mut Vec<(HeadKind, &'a Element)>) {
+    for el in nodes.iter().filter_map(Node::as_element) {
+        if let Some(kind) = head_kind(el) {
+            found.push((kind, el));
+        }
+        collect_head_elements(&el.children, found);
+    }
+}
+
+fn lint_head_elements(elements: Vec<(HeadKind, &Element)>) -> Vec<HeadIssue> {
+    let of_kind = |kind: HeadKind| {
+        elements
+            .iter()
+            .filter(move |(k, _)| *k == kind)
+            .map(|(_, el)| *el)
+    };
+    let mut issues = Vec::new();
+    let titles = of_kind(HeadKind::Title).count();
+    if titles > 1 {
+        issues.push(HeadIssue::MultipleTitles(titles));
+    }
+    let charsets: Vec<String> = of_kind(HeadKind::Charset).filter_map(charset_of).collect();
+    if charsets.len() > 1 {
+        issues.push(HeadIssue::MultipleCharsets(charsets));
+    }
+    let viewports = of_kind(HeadKind::Viewport).count();
+    if viewports > 1 {
+        issues.push(HeadIssue::MultipleViewports(viewports));
+    }
+    for refresh in of_kind(HeadKind::Refresh) {
+        let content = refresh.get_attr("content").unwrap_or("").to_string();
+        issues.push(HeadIssue::MetaRefresh(content));
+    }
+    issues
+}
+
+/// Remove the duplicated head elements in document order. `seen` and
+/// `totals` are indexed by [`HeadKind`].
+fn dedupe_head_elements(
+    nodes: &mut Vec<Node>,
+    seen: &mut [usize; 4],
+    totals: &[usize; 4],
+    keep: KeepPolicy,
+) {
+    for mut node in std::mem::take(nodes) {
+        if let Node::Element(el) = &mut node {
+            if let Some(kind) = head_kind(el) {
+                let kind = kind as usize;
+                let index = seen[kind];
+                seen[kind] += 1;
+                let kept = match keep {
+                    KeepPolicy::First => index == 0,
+                    KeepPolicy::Last => index + 1 == totals[kind],
+                };
+                if !kept {
+                    continue;
+                }
+            }
+            dedupe_head_elements(&mut el.children, seen, totals, keep);
+        }
+        nodes.push(node);
+    }
+}
+
+fn fix_head_elements(nodes: &mut Vec<Node>, keep: KeepPolicy) -> Vec<HeadIssue> {
+    let mut elements = Vec::new();
+    collect_head_elements(nodes, &mut elements);
+    let mut totals = [0; 4];
+    for (kind, _) in &elements {
+        totals[*kind as usize] += 1;
+    }
+    let issues = lint_head_elements(elements);
+    dedupe_head_elements(nodes, &mut [0; 4], &totals, keep);
+    issues
+}
+
+fn collect_fixed_widths(nodes: &[Node], max_px: f32, found: &mut Vec<FixedWidth>) {
+    for el in nodes.iter().filter_map(Node::as_element) {
+        collect_element_fixed_widths(el, max_px, found);
+    }
+}
+
+fn collect_element_fixed_widths(element: &Element, max_px: f32, found: &mut Vec<FixedWidth>) {
+    if let Some(style) = element.get_attr("style") {
+        for (property, value) in css::declarations(style) {
+            if property != "width" && property != "min-width" {
+                continue;
+            }
+            let value = value.trim_end_matches("!important").trim();
+            let px = value
+                .strip_suffix("px")
+                .and_then(|px| px.trim().parse::<f32>().ok());
+            if let Some(px) = px.filter(|px| *px > max_px) {
+                found.push(FixedWidth {
+                    tag: element.name.clone(),
+                    property,
+                    px,
+                });
+            }
+        }
+    }
+    collect_fixed_widths(&element.children, max_px, found);
+}
+
+fn region_of<'a>(element: &Element, report: &'a mut WeightReport) -> Option<&'a mut usize> {
+    match element.name.as_str() {
+        "head" => Some(&mut report.head),
+        "header" => Some(&mut report.header),
+        "nav" => Some(&mut report.nav),
+        "main" => Some(&mut report.main),
+        "aside" => Some(&mut report.aside),
+        "footer" => Some(&mut report.footer),
+        _ if element.get_attr("role") == Some("main") => Some(&mut report.main),
+        _ => None,
+    }
+}
+
+fn weigh_nodes(nodes: &[Node], in_region: bool, report: &mut WeightReport) {
+    for el in nodes.iter().filter_map(Node::as_element) {
+        weigh_element(el, in_region, report);
+    }
+}
+
+fn weigh_element(element: &Element, mut in_region: bool, report: &mut WeightReport) {
+    if !in_region {
+        if let Some(region) = region_of(element, report) {
+            *region += element.html().len();
+            in_region = true;
+        }
+    }
+    match element.name.as_str() {
+        "script" => report.scripts += element.html().len(),
+        "style" => report.inline_css += element.html().len(),
+        _ => {}
+    }
+    if let Some(style) = element.get_attr("style") {
+        report.inline_css += r#"style="""#.len() + style.len();
+    }
+    weigh_nodes(&element.children, in_region, report);
+}
+
+fn finish_weight_report(total: usize, mut report: WeightReport) -> WeightReport {
+    let regions =
+        report.head + report.header + report.nav + report.main + report.aside + report.footer;
+    report.total = total;
+    report.other = total - regions;
+    report
+}
+
+/// Classes and ids used by the elements, or referenced by the selectors.
+#[derive(Default)]
+struct Names {
+    classes: HashSet<String>,
+    ids: HashSet<String>,
+}
+
+fn prune_items(items: &mut Vec<Item>, used: &Names, kept: &mut Names, removed: &mut Vec<String>) {
+    items.retain_mut(|item| match item {
+        Item::Rule { prelude, .. } => {
+            let list = css::selector_list(prelude);
+            let count = list.len();
+            let mut selectors = Vec::new();
+            for selector in list {
+                let references = css::references(selector);
+                let matches = references
+                    .iter()
+                    .filter(|r| !r.nested)
+                    .all(|r| match r.kind {
+                        '.' => used.classes.contains(&r.name),
+                        _ => used.ids.contains(&r.name),
+                    });
+                if matches {
+                    kept.classes.extend(
+                        references
+                            .into_iter()
+                            .filter(|r| r.kind == '.')
+                            .map(|r| r.name),
+                    );
+                    selectors.push(selector);
+                } else {
+                    removed.push(selector.to_string());
+                }
+            }
+            if selectors.is_empty() {
+                return false;
+            }
+            if selectors.len() < count {
+                *prelude = selectors.join(", ");
+            }
+            true
+        }
+        Item::Group { items, .. } => {
+            let was_empty = items.is_empty();
+            prune_items(items, used, kept, removed);
+            was_empty || !items.is_empty()
+        }
+        Item::Other(_) => true,
+    });
+}
+
+fn prune_css_with(mut walk: impl FnMut(&mut dyn FnMut(&mut Element))) -> PrunedCss {
+    let mut used = Names::default();
+    walk(&mut |el| {
+        if let Some(class) = el.get_attr("class") {
+            used.classes
+                .extend(class.split_ascii_whitespace().map(str::to_string));
+        }
+        if let Some(id) = el.get_attr("id") {
+            used.ids.insert(id.to_string());
+        }
+    });
+
+    let mut pruned = PrunedCss::default();
+    let mut kept = Names::default();
+    walk(&mut |el| {
+        if el.name != "style" {
+            return;
+        }
+        let text = match el.children.as_slice() {
+            [Node::Text(text)] => text,
+            _ => return,
+        };
+        let mut items = css::stylesheet(text);
+        let removed = pruned.selectors.len();
+        prune_items(&mut items, &used, &mut kept, &mut pruned.selectors);
+        if pruned.selectors.len() > removed {
+            el.children = vec![Node::Text(css::serialize_stylesheet(&items))];
+        }
+    });
+
+    walk(&mut |el| {
+        let class = match el.get_attr("class") {
+            Some(class) => class,
+            None => return,
+        };
+        let (classes, unused): (Vec<&str>, Vec<&str>) = class
+            .split_ascii_whitespace()
+            .partition(|name| kept.classes.contains(*name));
+        if unused.is_empty() {
+            return;
+        }
+        for name in unused {
+            if !pruned.classes.iter().any(|removed| removed == name) {
+                pruned.classes.push(name.to_string());
+            }
+        }
+        if classes.is_empty() {
+            el.remove_attr("class");
+        } else {
+            el.set_attr("class", &classes.join(" "));
+        }
+    });
+    pruned
+}
+
+impl Auditable for Vec<Node> {
+    fn audit_alt(&self) -> Vec<AltIssue> {
+        audit_images(self.query_all(&Selector::from("img")))
+    }
+
+    fn fill_alt(&mut self, alts: &HashMap<String, String>) -> usize {
+        fill_images(self, alt_by_src(alts))
+    }
+
+    fn fill_alt_with(&mut self, f: impl FnMut(&Element) -> Option<String>) -> usize {
+        fill_images(self, f)
+    }
+
+    fn weight_report(&self) -> WeightReport {
+        let mut report = WeightReport::default();
+        weigh_nodes(self, false, &mut report);
+        finish_weight_report(self.html().len(), report)
+    }
+
+    fn lint_head(&self) -> Vec<HeadIssue> {
+        let mut elements = Vec::new();
+        collect_head_elements(self, &mut elements);
+        lint_head_elements(elements)
+    }
+
+    fn fix_head(&mut self, keep: KeepPolicy) -> Vec<HeadIssue> {
+        fix_head_elements(self, keep)
+    }
+
+    fn fixed_widths(&self, max_px: f32) -> Vec<FixedWidth> {
+        let mut found = Vec::new();
+        collect_fixed_widths(self, max_px, &mut found);
+        found
+    }
+
+    fn prune_css(&mut self) -> PrunedCss {
+        prune_css_with(|f| walk_mut(self, &mut |el| f(el)))
+    }
+}
+
+impl Auditable for Element {
+    fn audit_alt(&self) -> Vec<AltIssue> {
+        audit_images(self.query_all(&Selector::from("img")))
+    }
+
+    fn fill_alt(&mut self, alts: &HashMap<String, String>) -> usize {
+        fill_images(self, alt_by_src(alts))
+    }
+
+    fn fill_alt_with(&mut self, f: impl FnMut(&Element) -> Option<String>) -> usize {
+        fill_images(self, f)
+    }
+
+    fn weight_report(&self) -> WeightReport {
+        let mut report = WeightReport::default();
+        weigh_element(self, false, &mut report);
+        finish_weight_report(self.html().len(), report)
+    }
+
+    fn lint_head(&self) -> Vec<HeadIssue> {
+        let mut elements = Vec::new();
+        collect_head_elements(&self.children, &mut elements);
+        lint_head_elements(elements)
+    }
+
+    fn fix_head(&mut self, keep: KeepPolicy) -> Vec<HeadIssue> {
+        fix_head_elements(&mut self.children, keep)
+    }
+
+    fn fixed_widths(&self, max_px: f32) -> Vec<FixedWidth> {
+        let mut found = Vec::new();
+        collect_element_fixed_widths(self, max_px, &mut found);
+        found
+    }
+
+    fn prune_css(&mut self) -> PrunedCss {
+        prune_css_with(|f| walk_element_mut(self, &mut |el| f(el)))
+    }
+}
@@ -0,0 +1,604 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::selector::Matcher;
+use super::walk::{is_frozen, raw_text};
+use super::{Htmlifiable, Selector};
+use crate::{parse, Element, Node};
+
+/// Binary file generated for a placeholder, like a QR code image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    /// Suggested file name, like `qr-1.png`.
+    pub name: String,
+    /// Media type, like `image/png`.
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+/// What [`Composable::generate_placeholders`] replaces a placeholder by.
+#[derive(Debug, Clone)]
+pub enum Generated {
+    Nodes(Vec<Node>),
+    /// An asset, given to the emitter, and the element referencing it,
+    /// whose `src` is set to the URL returned by the emitter.
+    Asset {
+        asset: Asset,
+        element: Element,
+    },
+}
+
+/// Error of [`Composable::resolve_includes`]. The chains of sources
+/// start from the outermost include.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncludeError {
+    /// The resolver found nothing for the source.
+    NotFound(Vec<String>),
+    /// The last source includes itself through the other ones.
+    Cycle(Vec<String>),
+    /// The includes are nested deeper than allowed.
+    TooDeep(Vec<String>),
+    /// The html of the last source could not be parsed.
+    Parse(Vec<String>, String),
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncludeError::NotFound(chain) => write!(f, "Include not found: {}", chain.join(" > ")),
+            IncludeError::Cycle(chain) => write!(f, "Include cycle: {}", chain.join(" > ")),
+            IncludeError::TooDeep(chain) => write!(f, "Includes too deep: {}", chain.join(" > ")),
+            IncludeError::Parse(chain, error) => {
+                write!(f, "Invalid include {}: {}", chain.join(" > "), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IncludeError {}
+
+/// Split documents into fragments and put them back together.
+pub trait Composable {
+    /// Extract the subtrees matching the named selectors of `rules` into
+    /// separate fragments, replacing each of them by an
+    /// `<include src="name">` marker.
+    ///
+    /// The first match of a rule is named after it, and the next ones
+    /// get a suffix: `card`, `card-2`, `card-3`... When several rules
+    /// match an element, the first one wins, and the elements inside an
+    /// extracted subtree stay in its fragment.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<body><nav>Menu</nav><p>Text</p></body>").unwrap();
+    /// let fragments = dom.split_components(&[("nav", Selector::from("nav"))]);
+    ///
+    /// assert_eq!(dom.html(), r#"<body><include src="nav"></include><p>Text</p></body>"#);
+    /// assert_eq!(fragments["nav"].html(), "<nav>Menu</nav>");
+    /// ```
+    fn split_components(&mut self, rules: &[(&str, Selector)]) -> HashMap<String, Vec<Node>>;
+
+    /// Replace the `<include src="name">` markers by the fragments of the
+    /// same name, which is the inverse of [`Composable::split_components`].
+    /// Markers without a matching fragment are left untouched.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<body><include src="nav"></include></body>"#).unwrap();
+    /// let fragments = HashMap::from([("nav".to_string(), parse("<nav>Menu</nav>").unwrap())]);
+    /// dom.assemble(&fragments);
+    ///
+    /// assert_eq!(dom.html(), "<body><nav>Menu</nav></body>");
+    /// ```
+    fn assemble(&mut self, fragments: &HashMap<String, Vec<Node>>) -> &mut Self;
+
+    /// Replace the `<include src="...">` elements by the html returned by
+    /// `resolver` for their `src`, parsed and itself resolved, nesting at
+    /// most `max_depth` includes. Returns the number of includes resolved.
+    ///
+    /// Fails on the first include that the resolver cannot find, that
+    /// includes itself or is nested too deep, leaving the document
+    /// partially resolved.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<body><include src="nav.html"></include></body>"#).unwrap();
+    /// dom.resolve_includes(8, |src| match src {
+    ///     "nav.html" => Some(r#"<nav><include src="logo.html"></include></nav>"#.to_string()),
+    ///     "logo.html" => Some("<img>".to_string()),
+    ///     _ => None,
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(dom.html(), "<body><nav><img></nav></body>");
+    ///
+    /// let mut dom = parse(r#"<include src="a"></include>"#).unwrap();
+    /// let error = dom.resolve_includes(8, |_| Some(r#"<include src="a"></include>"#.to_string()));
+    /// assert_eq!(error, Err(IncludeError::Cycle(vec!["a".to_string(), "a".to_string()])));
+    /// ```
+    fn resolve_includes(
+        &mut self,
+        max_depth: usize,
+        resolver: impl FnMut(&str) -> Option<String>,
+    ) -> Result<usize, IncludeError>;
+
+    /// Replace the placeholders, being the elements with a
+    /// `data-generate` attribute, by what `generate` returns for the
+    /// value of the attribute and the element, like the nodes of a chart
+    /// or the image of a QR code. The assets are passed to `emit`, which
+    /// saves or inlines them and returns their URL.
+    ///
+    /// The placeholders for which `generate` returns `None` are left as
+    /// is. Returns the number of placeholders replaced.
+    ///
+    /// ```
+    /// use html_editor::{parse, Element, Node};
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<div data-generate="qr" data-value="https://example.com"></div>"#).unwrap();
+    /// let mut assets = Vec::new();
+    /// dom.generate_placeholders(
+    ///     |kind, placeholder| match kind {
+    ///         "qr" => Some(Generated::Asset {
+    ///             asset: Asset {
+    ///                 name: "qr.png".to_string(),
+    ///                 mime: "image/png".to_string(),
+    ///                 data: placeholder.attrs[1].1.as_bytes().to_vec(),
+    ///             },
+    ///             element: Element::new("img", vec![("alt", "QR code")], vec![]),
+    ///         }),
+    ///         _ => None,
+    ///     },
+    ///     |asset| {
+    ///         assets.push(asset.clone());
+    ///         format!("assets/{}", asset.name)
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(dom.html(), r#"<img alt="QR code" src="assets/qr.png">"#);
+    /// assert_eq!(assets[0].data, b"https://example.com");
+    /// ```
+    fn generate_placeholders(
+        &mut self,
+        generate: impl FnMut(&str, &Element) -> Option<Generated>,
+        emit: impl FnMut(&Asset) -> String,
+    ) -> usize;
+}
+
+/// Extract the subtrees of `nodes` matched by the `matchers` of the
+/// `rules`.
+fn split_nodes(
+    nodes: &mut [Node],
+    rules: &[(&str, Selector)],
+    matchers: &[Matcher],
+    counts: &mut HashMap<String, usize>,
+    fragments: &mut HashMap<String, Vec<Node>>,
+) {
+    for (index, node) in nodes.iter_mut().enumerate() {
+        let element = match node {
+            Node::Element(el) if !is_frozen(el) => el,
+            _ => continue,
+        };
+        let (matched, children): (Vec<_>, Vec<_>) = matchers
+            .iter()
+            .map(|matcher| matcher.child(index, element))
+            .unzip();
+        match matched.iter().position(|&matched| matched) {
+            Some(rule) => {
+                let name = rules[rule].0;
+                let count = counts.entry(name.to_string()).or_insert(0);
+                *count += 1;
+                let key = match *count {
+                    1 => name.to_string(),
+                    n => format!("{}-{}", name, n),
+                };
+                let marker = Node::new_element("include", vec![("src", &key)], vec![]);
+                fragments.insert(key, vec![std::mem::replace(node, marker)]);
+            }
+            None => split_nodes(&mut element.children, rules, &children, counts, fragments),
+        }
+    }
+}
+
+fn assemble_nodes(nodes: &mut Vec<Node>, fragments: &HashMap<String, Vec<Node>>) {
+    let mut i = 0;
+    while i < nodes.len() {
+        let fragment = match &mut nodes[i] {
+            Node::Element(el) if is_frozen(el) => None,
+            Node::Element(el) if el.name == "include" => {
+                el.get_attr("src").and_then(|src| fragments.get(src))
+            }
+            Node::Element(el) => {
+                assemble_nodes(&mut el.children, fragments);
+                None
+            }
+            _ => None,
+        };
+        match fragment {
+            Some(fragment) => {
+                nodes.splice(i..i + 1, fragment.iter().cloned());
+                i += fragment.len();
+            }
+            None => i += 1,
+        }
+    }
+}
+
+fn resolve_nodes(
+    nodes: &mut Vec<Node>,
+    max_depth: usize,
+    chain: &mut Vec<String>,
+    resolver: &mut impl FnMut(&str) -> Option<String>,
+) -> Result<usize, IncludeError> {
+    let mut count = 0;
+    let mut i = 0;
+    while i < nodes.len() {
+        let src = match &mut nodes[i] {
+            Node::Element(el) if is_frozen(el) => None,
+            Node::Element(el) if el.name == "include" => el.get_attr("src").map(str::to_string),
+            Node::Element(el) => {
+                count += resolve_nodes(&mut el.children, max_depth, chain, resolver)?;
+                None
+            }
+            _ => None,
+        };
+        let src = match src {
+            Some(src) => src,
+            None => {
+                i += 1;
+                continue;
+            }
+        };
+
+        let cycle = chain.contains(&src);
+        chain.push(src);
+        if cycle {
+            return Err(IncludeError::Cycle(chain.clone()));
+        }
+        if chain.len() > max_depth {
+            return Err(IncludeError::TooDeep(chain.clone()));
+        }
+        let html =
+            resolver(chain.last().unwrap()).ok_or_else(|| IncludeError::NotFound(chain.clone()))?;
+        let mut fragment =
+            parse(&html).map_err(|error| IncludeError::Parse(chain.clone(), error))?;
+        count += resolve_nodes(&mut fragment, max_depth, chain, resolver)? + 1;
+        chain.pop();
+
+        let len = fragment.len();
+        nodes.splice(i..i + 1, fragment);
+        i += len;
+    }
+    Ok(count)
+}
+
+fn generate_nodes(
+    nodes: &mut Vec<Node>,
+    generate: &mut impl FnMut(&str, &Element) -> Option<Generated>,
+    emit: &mut impl FnMut(&Asset) -> String,
+) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < nodes.len() {
+        let generated = match &mut nodes[i] {
+            Node::Element(el) if is_frozen(el) => None,
+            Node::Element(el) => match el.get_attr("data-generate") {
+                Some(kind) => generate(kind, el),
+                None => {
+                    count += generate_nodes(&mut el.children, generate, emit);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let replacement = match generated {
+            Some(Generated::Nodes(replacement)) => replacement,
+            Some(Generated::Asset { asset, mut element }) => {
+                element.set_attr("src", &emit(&asset));
+                vec![Node::Element(element)]
+            }
+            None => {
+                i += 1;
+                continue;
+            }
+        };
+        count += 1;
+        let len = replacement.len();
+        nodes.splice(i..i + 1, replacement);
+        i += len;
+    }
+    count
+}
+
+impl Composable for Vec<Node> {
+    fn split_components(&mut self, rules: &[(&str, Selector)]) -> HashMap<String, Vec<Node>> {
+        let marks: Vec<_> = rules
+            .iter()
+            .map(|(_, selector)| selector.marks(self))
+            .collect();
+        let matchers: Vec<_> = rules
+            .iter()
+            .zip(&marks)
+            .map(|((_, selector), marks)| Matcher::new(selector, marks.as_deref()))
+            .collect();
+        let mut fragments = HashMap::new();
+        split_nodes(self, rules, &matchers, &mut HashMap::new(), &mut fragments);
+        fragments
+    }
+
+    fn assemble(&mut self, fragments: &HashMap<String, Vec<Node>>) -> &mut Self {
+        assemble_nodes(self, fragments);
+        self
+    }
+
+    fn resolve_includes(
+        &mut self,
+        max_depth: usize,
+        mut resolver: impl FnMut(&str) -> Option<String>,
+    ) -> Result<usize, IncludeError> {
+        resolve_nodes(self, max_depth, &mut Vec::new(), &mut resolver)
+    }
+
+    fn generate_placeholders(
+        &mut self,
+        mut generate: impl FnMut(&str, &Element) -> Option<Generated>,
+        mut emit: impl FnMut(&Asset) -> String,
+    ) -> usize {
+        generate_nodes(self, &mut generate, &mut emit)
+    }
+}
+
+impl Composable for Element {
+    fn split_components(&mut self, rules: &[(&str, Selector)]) -> HashMap<String, Vec<Node>> {
+        if is_frozen(self) {
+            return HashMap::new();
+        }
+        let marks: Vec<_> = rules
+            .iter()
+            .map(|(_, selector)| selector.element_marks(self))
+            .collect();
+        let children: Vec<_> = rules
+            .iter()
+            .zip(&marks)
+            .map(|((_, selector), marks)| Matcher::new(selector, marks.as_deref()).child(0, self).1)
+            .collect();
+        let mut fragments = HashMap::new();
+        split_nodes(
+            &mut self.children,
+            rules,
+            &children,
+            &mut HashMap::new(),
+            &mut fragments,
+        );
+        fragments
+    }
+
+    fn assemble(&mut self, fragments: &HashMap<String, Vec<Node>>) -> &mut Self {
+        if !is_frozen(self) {
+            assemble_nodes(&mut self.children, fragments);
+        }
+        self
+    }
+
+    fn resolve_includes(
+        &mut self,
+        max_depth: usize,
+        mut resolver: impl FnMut(&str) -> Option<String>,
+    ) -> Result<usize, IncludeError> {
+        if is_frozen(self) {
+            return Ok(0);
+        }
+        resolve_nodes(
+            &mut self.children,
+            max_depth,
+            &mut Vec::new(),
+            &mut resolver,
+        )
+    }
+
+    fn generate_placeholders(
+        &mut self,
+        mut generate: impl FnMut(&str, &Element) -> Option<Generated>,
+        mut emit: impl FnMut(&Asset) -> String,
+    ) -> usize {
+        if is_frozen(self) {
+            return 0;
+        }
+        generate_nodes(&mut self.children, &mut generate, &mut emit)
+    }
+}
+
+/// How [`merge_documents`] resolves the titles of the documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TitlePolicy {
+    /// Keep the first title.
+    #[default]
+    First,
+    /// Keep the last title.
+    Last,
+    /// Join the different titles with the separator, like `" | "`,
+    /// which is written as is into the HTML.
+    Join(String),
+}
+
+/// Options of [`merge_documents`].
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    /// Nodes inserted between the bodies of the documents, like `<hr>`.
+    pub separator: Vec<Node>,
+    pub title: TitlePolicy,
+}
+
+/// Merge `documents` into one, keeping the doctype and the attributes of
+/// the `<html>` and `<body>` of the first one.
+///
+/// The heads are combined without the duplicated resources, which are
+/// the elements with the same `src` or `href`, the `<meta>` with the
+/// same `name`, `property`, `http-equiv` or `charset`, and the identical
+/// elements. The bodies are concatenated.
+///
+/// ```
+/// use html_editor::{parse, Node};
+/// use html_editor::operation::*;
+///
+/// let documents = vec![
+///     parse(r#"<head><title>Q1</title><link rel="stylesheet" href="report.css"></head><body><h1>Q1</h1></body>"#).unwrap(),
+///     parse(r#"<head><title>Q2</title><link rel="stylesheet" href="report.css"></head><body><h1>Q2</h1></body>"#).unwrap(),
+/// ];
+/// let options = MergeOptions {
+///     separator: vec![Node::new_element("hr", vec![], vec![])],
+///     title: TitlePolicy::Join(" | ".to_string()),
+/// };
+///
+/// assert_eq!(
+///     merge_documents(documents, &options).html(),
+///     r#"<html><head><title>Q1 | Q2</title><link rel="stylesheet" href="report.css"></head><body><h1>Q1</h1><hr><h1>Q2</h1></body></html>"#
+/// );
+/// ```
+pub fn merge_documents(documents: Vec<Vec<Node>>, options: &MergeOptions) -> Vec<Node> {
+    let mut doctype = None;
+    let mut html_attrs = Vec::new();
+    let mut body_attrs = Vec::new();
+    let mut head = Vec::new();
+    let mut body = Vec::new();
+    let mut titles: Vec<String> = Vec::new();
+    let mut title_index = None;
+    let mut seen = HashSet::new();
+
+    for (i, document) in documents.into_iter().enumerate() {
+        let parts = DocumentParts::from(document);
+        if i == 0 {
+            doctype = parts.doctype;
+            html_attrs = parts.html_attrs;
+            body_attrs = parts.body_attrs;
+        } else {
+            body.extend(options.separator.iter().cloned());
+        }
+
+        for node in parts.head {
+            match &node {
+                Node::Element(el) if el.name == "title" => {
+                    title_index.get_or_insert(head.len());
+                    titles.push(raw_text(&el.children));
+                }
+                Node::Element(el) if !seen.insert(resource_key(el)) => {}
+                Node::Element(_) => head.push(node),
+                // Keep the whitespace and comments of the first head only.
+                _ if i == 0 => head.push(node),
+                _ => {}
+            }
+        }
+        body.extend(parts.body);
+    }
+
+    let title = match &options.title {
+        TitlePolicy::First => titles.first().cloned(),
+        TitlePolicy::Last => titles.last().cloned(),
+        TitlePolicy::Join(separator) => {
+            let mut unique: Vec<&str> = Vec::new();
+            for title in &titles {
+                if !unique.contains(&title.as_str()) {
+                    unique.push(title);
+                }
+            }
+            (!unique.is_empty()).then(|| unique.join(separator))
+        }
+    };
+    if let (Some(title), Some(index)) = (title, title_index) {
+        let title = Node::new_element("title", vec![], vec![Node::Text(title)]);
+        head.insert(index, title);
+    }
+
+    let mut merged: Vec<Node> = doctype.into_iter().collect();
+    merged.push(Node::Element(Element {
+        name: "html".to_string(),
+        attrs: html_attrs,
+        children: vec![
+            Node::new_element("head", vec![], head),
+            Node::Element(Element {
+                name: "body".to_string(),
+                attrs: body_attrs,
+                children: body,
+            }),
+        ],
+    }));
+    merged
+}
+
+/// Parts of a document merged by [`merge_documents`].
+struct DocumentParts {
+    doctype: Option<Node>,
+    html_attrs: Vec<(String, String)>,
+    body_attrs: Vec<(String, String)>,
+    head: Vec<Node>,
+    body: Vec<Node>,
+}
+
+impl From<Vec<Node>> for DocumentParts {
+    fn from(document: Vec<Node>) -> Self {
+        let mut parts = DocumentParts {
+            doctype: None,
+            html_attrs: Vec::new(),
+            body_attrs: Vec::new(),
+            head: Vec::new(),
+            body: Vec::new(),
+        };
+        for node in document {
+            match node {
+                Node::Doctype(_) => parts.doctype = Some(node),
+                Node::Element(html) if html.name == "html" => {
+                    parts.html_attrs = html.attrs;
+                    for node in html.children {
+                        parts.add(node);
+                    }
+                }
+                node => parts.add(node),
+            }
+        }
+        parts
+    }
+}
+
+impl DocumentParts {
+    fn add(&mut self, node: Node) {
+        match node {
+            Node::Element(head) if head.name == "head" => self.head.extend(head.children),
+            Node::Element(body) if body.name == "body" => {
+                self.body_attrs = body.attrs;
+                self.body.extend(body.children);
+            }
+            // The elements only allowed in a head, when it is omitted.
+            Node::Element(el) if matches!(el.name.as_str(), "title" | "meta" | "link" | "base") => {
+                self.head.push(Node::Element(el))
+            }
+            // Ignore the whitespace around the head and body.
+            Node::Text(text) if text.trim().is_empty() => {}
+            node => self.body.push(node),
+        }
+    }
+}
+
+/// Get the key identifying the duplicates of a head element.
+fn resource_key(element: &Element) -> String {
+    if element.name == "meta" {
+        for name in ["charset", "name", "property", "http-equiv"] {
+            if let Some(value) = element.get_attr(name) {
+                let value = if name == "charset" { "" } else { value };
+                return format!("meta {} {}", name, value.to_ascii_lowercase());
+            }
+        }
+    }
+    for name in ["src", "href"] {
+        if let Some(value) = element.get_attr(name) {
+            return format!("{} {}", name, value);
+        }
+    }
+    element.html()
+}
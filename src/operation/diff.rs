@@ -0,0 +1,552 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use super::Htmlifiable;
+use crate::{error::Error, Doctype, Element, Node};
+
+/// Change from one document to another, computed by [`diff`].
+///
+/// Paths are the indexes of the nodes among their siblings, from the top
+/// level down, and refer to the document as left by the previous patches.
+#[derive(Debug, Clone)]
+pub enum Patch {
+    /// Set an attribute of the element at `path`, adding it if needed.
+    SetAttr {
+        path: Vec<usize>,
+        name: String,
+        value: String,
+    },
+    /// Remove an attribute of the element at `path`.
+    RemoveAttr { path: Vec<usize>, name: String },
+    /// Replace the content of the text node at `path`.
+    SetText { path: Vec<usize>, text: String },
+    /// Insert `node` so that it ends up at `path`.
+    Insert { path: Vec<usize>, node: Node },
+    /// Remove the node at `path`.
+    Remove { path: Vec<usize> },
+    /// Replace the node at `path` by `node`.
+    Replace { path: Vec<usize>, node: Node },
+    /// Take the node at `from` out, and insert it back at `to`.
+    Move { from: Vec<usize>, to: Vec<usize> },
+}
+
+/// Version of the JSON format written by [`patches_to_json`], increased
+/// on every incompatible change.
+pub const PATCH_FORMAT_VERSION: u32 = 1;
+
+/// Compute the patches turning `old` into `new`.
+///
+/// Elements keeping their name are patched in place, so that a changed
+/// attribute is a single [`Patch::SetAttr`] rather than the replacement
+/// of the whole element. Identical subtrees found at another index among
+/// their siblings are detected by their hash and reported as
+/// [`Patch::Move`].
+///
+/// ```
+/// use html_editor::parse;
+/// use html_editor::operation::*;
+///
+/// let old = parse(r#"<ul><li>A</li><li>B</li></ul><a href="/a">Link</a>"#).unwrap();
+/// let new = parse(r#"<ul><li>B</li><li>A</li></ul><a href="/b">Link</a>"#).unwrap();
+/// let patches = diff(&old, &new);
+///
+/// assert_eq!(patches.len(), 2);
+/// assert!(matches!(&patches[0], Patch::Move { from, to } if *from == [0, 1] && *to == [0, 0]));
+/// assert!(matches!(&patches[1], Patch::SetAttr { name, .. } if name == "href"));
+/// ```
+pub fn diff(old: &[Node], new: &[Node]) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    diff_children(old, new, &mut Vec::new(), &mut patches);
+    patches
+}
+
+/// Apply the `patches` computed by [`diff`] to `nodes`.
+///
+/// Fails if a patch does not fit the document, in which case the patches
+/// before it are still applied.
+///
+/// ```
+/// use html_editor::parse;
+/// use html_editor::operation::*;
+///
+/// let mut old = parse("<p>Hello</p>").unwrap();
+/// let new = parse(r#"<p class="greeting">Hello</p><p>World</p>"#).unwrap();
+/// let patches = diff(&old, &new);
+/// apply_patch(&mut old, &patches).unwrap();
+///
+/// assert_eq!(old.html(), new.html());
+/// ```
+pub fn apply_patch(nodes: &mut Vec<Node>, patches: &[Patch]) -> Result<(), Error> {
+    for patch in patches {
+        match patch {
+            Patch::SetAttr { path, name, value } => element_at(nodes, path)?.set_attr(name, value),
+            Patch::RemoveAttr { path, name } => {
+                element_at(nodes, path)?.remove_attr(name).ok_or(Error)?;
+            }
+            Patch::SetText { path, text } => match node_at(nodes, path)? {
+                Node::Text(old) => *old = text.clone(),
+                _ => return Err(Error),
+            },
+            Patch::Insert { path, node } => insert_at(nodes, path, node.clone())?,
+            Patch::Remove { path } => {
+                remove_at(nodes, path)?;
+            }
+            Patch::Replace { path, node } => *node_at(nodes, path)? = node.clone(),
+            Patch::Move { from, to } => {
+                let node = remove_at(nodes, from)?;
+                insert_at(nodes, to, node)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Serialize `patches` into JSON for client-side DOM morphing libraries.
+///
+/// The format is an object holding the `version` of the format, see
+/// [`PATCH_FORMAT_VERSION`], and the list of `patches`. Each patch has an
+/// `op` among `setAttr`, `removeAttr`, `setText`, `insert`, `remove`,
+/// `replace` and `move`, the `path` of the node as a list of child
+/// indexes (`from` and `to` for moves), and depending on the operation
+/// a `name`, `value`, `text`, or the `html` of the inserted node.
+///
+/// ```
+/// use html_editor::parse;
+/// use html_editor::operation::*;
+///
+/// let old = parse(r#"<p>Hi</p>"#).unwrap();
+/// let new = parse(r#"<p title="greeting">Hi</p>"#).unwrap();
+///
+/// assert_eq!(
+///     patches_to_json(&diff(&old, &new)),
+///     r#"{"version":1,"patches":[{"op":"setAttr","path":[0],"name":"title","value":"greeting"}]}"#
+/// );
+/// ```
+pub fn patches_to_json(patches: &[Patch]) -> String {
+    let patches: Vec<String> = patches.iter().map(patch_to_json).collect();
+    format!(
+        r#"{{"version":{},"patches":[{}]}}"#,
+        PATCH_FORMAT_VERSION,
+        patches.join(",")
+    )
+}
+
+/// Counts of the nodes of a document, computed by [`stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentStats {
+    /// Number of elements for each tag, in lowercase.
+    pub tags: BTreeMap<String, usize>,
+    pub text_nodes: usize,
+    pub comments: usize,
+    /// Size of the html.
+    pub bytes: usize,
+}
+
+/// Summary of what changed from a document to another, computed by
+/// [`stats_delta`]. Its [`Display`](fmt::Display) is a one-line summary
+/// to log.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsDelta {
+    /// Number of elements added for each tag with more elements.
+    pub added: BTreeMap<String, usize>,
+    /// Number of elements removed for each tag with less elements.
+    pub removed: BTreeMap<String, usize>,
+    pub old_bytes: usize,
+    pub new_bytes: usize,
+}
+
+impl StatsDelta {
+    /// Get the number of bytes saved, which is negative if the document
+    /// grew.
+    pub fn bytes_saved(&self) -> isize {
+        self.old_bytes as isize - self.new_bytes as isize
+    }
+
+    /// Get the number of `<script>` elements removed.
+    pub fn scripts_removed(&self) -> usize {
+        self.removed.get("script").copied().unwrap_or(0)
+    }
+}
+
+impl fmt::Display for StatsDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let added = self.added.iter().map(|(tag, n)| (tag, '+', n));
+        let removed = self.removed.iter().map(|(tag, n)| (tag, '-', n));
+        let mut changes: Vec<_> = added.chain(removed).collect();
+        changes.sort();
+        for (tag, sign, n) in changes {
+            write!(f, "{}{} {}, ", sign, n, tag)?;
+        }
+        write!(f, "{} -> {} bytes", self.old_bytes, self.new_bytes)
+    }
+}
+
+fn count_nodes(nodes: &[Node], stats: &mut DocumentStats) {
+    for node in nodes {
+        match node {
+            Node::Element(el) => {
+                *stats.tags.entry(el.name.to_ascii_lowercase()).or_default() += 1;
+                count_nodes(&el.children, stats);
+            }
+            Node::Text(_) => stats.text_nodes += 1,
+            Node::Comment(_) => stats.comments += 1,
+            _ => {}
+        }
+    }
+}
+
+/// Count the nodes of a document, by kind and by tag for the elements.
+///
+/// ```
+/// use html_editor::parse;
+/// use html_editor::operation::*;
+///
+/// let stats = stats(&parse("<div><p>One</p><!--x--><P>Two</P></div>").unwrap());
+///
+/// assert_eq!(stats.tags.get("p"), Some(&2));
+/// assert_eq!((stats.text_nodes, stats.comments, stats.bytes), (2, 1, 39));
+/// ```
+pub fn stats(nodes: &[Node]) -> DocumentStats {
+    let mut stats = DocumentStats {
+        bytes: nodes.iter().map(|node| node.html().len()).sum(),
+        ..Default::default()
+    };
+    count_nodes(nodes, &mut stats);
+    stats
+}
+
+/// Summarize what a transformation did from `old` to `new`: the
+/// elements added and removed by tag, and the size of the html.
+///
+/// ```
+/// use html_editor::{parse, Node};
+/// use html_editor::operation::*;
+///
+/// let old = parse("<script>track()</script><p>Text</p><script>ads()</script>").unwrap();
+/// let mut new = old.clone();
+/// new.remove_by(&Selector::from("script"))
+///     .insert_to(&Selector::from("p"), Node::new_element("br", vec![], vec![]));
+///
+/// let delta = stats_delta(&old, &new);
+/// assert_eq!(delta.scripts_removed(), 2);
+/// assert_eq!(delta.bytes_saved(), 42);
+/// assert_eq!(delta.to_string(), "+1 br, -2 script, 57 -> 15 bytes");
+/// ```
+pub fn stats_delta(old: &[Node], new: &[Node]) -> StatsDelta {
+    let (old, new) = (stats(old), stats(new));
+    let mut delta = StatsDelta {
+        old_bytes: old.bytes,
+        new_bytes: new.bytes,
+        ..Default::default()
+    };
+    for (tag, &count) in &old.tags {
+        let new_count = new.tags.get(tag).copied().unwrap_or(0);
+        if new_count < count {
+            delta.removed.insert(tag.clone(), count - new_count);
+        }
+    }
+    for (tag, &count) in &new.tags {
+        let old_count = old.tags.get(tag).copied().unwrap_or(0);
+        if count > old_count {
+            delta.added.insert(tag.clone(), count - old_count);
+        }
+    }
+    delta
+}
+
+fn patch_to_json(patch: &Patch) -> String {
+    let path = |path: &[usize]| {
+        let indexes: Vec<String> = path.iter().map(usize::to_string).collect();
+        format!("[{}]", indexes.join(","))
+    };
+    match patch {
+        Patch::SetAttr {
+            path: p,
+            name,
+            value,
+        } => format!(
+            r#"{{"op":"setAttr","path":{},"name":{},"value":{}}}"#,
+            path(p),
+            json_string(name),
+            json_string(value)
+        ),
+        Patch::RemoveAttr { path: p, name } => format!(
+            r#"{{"op":"removeAttr","path":{},"name":{}}}"#,
+            path(p),
+            json_string(name)
+        ),
+        Patch::SetText { path: p, text } => format!(
+            r#"{{"op":"setText","path":{},"text":{}}}"#,
+            path(p),
+            json_string(text)
+        ),
+        Patch::Insert { path: p, node } => format!(
+            r#"{{"op":"insert","path":{},"html":{}}}"#,
+            path(p),
+            json_string(&node.html())
+        ),
+        Patch::Remove { path: p } => format!(r#"{{"op":"remove","path":{}}}"#, path(p)),
+        Patch::Replace { path: p, node } => format!(
+            r#"{{"op":"replace","path":{},"html":{}}}"#,
+            path(p),
+            json_string(&node.html())
+        ),
+        Patch::Move { from, to } => {
+            format!(r#"{{"op":"move","from":{},"to":{}}}"#, path(from), path(to))
+        }
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if ch.is_control() => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn siblings_at<'a>(nodes: &'a mut Vec<Node>, parent: &[usize]) -> Result<&'a mut Vec<Node>, Error> {
+    let mut siblings = nodes;
+    for &index in parent {
+        siblings = match siblings.get_mut(index) {
+            Some(Node::Element(el)) => &mut el.children,
+            _ => return Err(Error),
+        };
+    }
+    Ok(siblings)
+}
+
+fn node_at<'a>(nodes: &'a mut Vec<Node>, path: &[usize]) -> Result<&'a mut Node, Error> {
+    let (index, parent) = path.split_last().ok_or(Error)?;
+    siblings_at(nodes, parent)?.get_mut(*index).ok_or(Error)
+}
+
+fn element_at<'a>(nodes: &'a mut Vec<Node>, path: &[usize]) -> Result<&'a mut Element, Error> {
+    node_at(nodes, path)?.as_element_mut().ok_or(Error)
+}
+
+fn insert_at(nodes: &mut Vec<Node>, path: &[usize], node: Node) -> Result<(), Error> {
+    let (index, parent) = path.split_last().ok_or(Error)?;
+    let siblings = siblings_at(nodes, parent)?;
+    if *index > siblings.len() {
+        return Err(Error);
+    }
+    siblings.insert(*index, node);
+    Ok(())
+}
+
+fn remove_at(nodes: &mut Vec<Node>, path: &[usize]) -> Result<Node, Error> {
+    let (index, parent) = path.split_last().ok_or(Error)?;
+    let siblings = siblings_at(nodes, parent)?;
+    if *index >= siblings.len() {
+        return Err(Error);
+    }
+    Ok(siblings.remove(*index))
+}
+
+fn hash_node(node: &Node, state: &mut DefaultHasher) {
+    match node {
+        Node::Element(el) => {
+            0u8.hash(state);
+            el.name.hash(state);
+            el.attrs.hash(state);
+            el.children.len().hash(state);
+            for child in &el.children {
+                hash_node(child, state);
+            }
+        }
+        Node::Text(text) => {
+            1u8.hash(state);
+            text.hash(state);
+        }
+        Node::Comment(comment) => {
+            2u8.hash(state);
+            comment.hash(state);
+        }
+        Node::Doctype(Doctype::Html) => 3u8.hash(state),
+        Node::Doctype(Doctype::Xml { version, encoding }) => {
+            4u8.hash(state);
+            version.hash(state);
+            encoding.hash(state);
+        }
+        Node::Template(template) => {
+            5u8.hash(state);
+            template.hash(state);
+        }
+        Node::ProcessingInstruction(instruction) => {
+            6u8.hash(state);
+            instruction.hash(state);
+        }
+    }
+}
+
+fn hash_of(node: &Node) -> u64 {
+    let mut state = DefaultHasher::new();
+    hash_node(node, &mut state);
+    state.finish()
+}
+
+/// Check if two nodes with the same hash are really identical.
+fn is_same(a: &Node, b: &Node) -> bool {
+    a.html() == b.html()
+}
+
+/// Check if `new` can be obtained by patching `old` in place.
+fn is_similar(old: &Node, new: &Node) -> bool {
+    match (old, new) {
+        (Node::Element(a), Node::Element(b)) => a.name == b.name,
+        (Node::Text(_), Node::Text(_)) => true,
+        _ => false,
+    }
+}
+
+/// Pairs of old and new indexes in the longest common subsequence of
+/// `old` and `new`.
+fn common_subsequence(old: &[u64], new: &[u64]) -> Vec<(usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn diff_children(old: &[Node], new: &[Node], path: &mut Vec<usize>, patches: &mut Vec<Patch>) {
+    let old_hashes: Vec<u64> = old.iter().map(hash_of).collect();
+    let new_hashes: Vec<u64> = new.iter().map(hash_of).collect();
+
+    // For each new node, the old node it comes from and whether it is
+    // identical to it.
+    let mut sources: Vec<Option<(usize, bool)>> = vec![None; new.len()];
+    let mut used = vec![false; old.len()];
+    for (i, j) in common_subsequence(&old_hashes, &new_hashes) {
+        if is_same(&old[i], &new[j]) {
+            sources[j] = Some((i, true));
+            used[i] = true;
+        }
+    }
+    let mut pair = |sources: &mut [Option<(usize, bool)>], rule: &dyn Fn(usize, usize) -> bool| {
+        for (j, source) in sources.iter_mut().enumerate() {
+            if source.is_some() {
+                continue;
+            }
+            if let Some(i) = (0..old.len()).find(|&i| !used[i] && rule(i, j)) {
+                used[i] = true;
+                *source = Some((i, old_hashes[i] == new_hashes[j]));
+            }
+        }
+    };
+    pair(&mut sources, &|i, j| {
+        old_hashes[i] == new_hashes[j] && is_same(&old[i], &new[j])
+    });
+    pair(&mut sources, &|i, j| is_similar(&old[i], &new[j]));
+
+    let mut current = Vec::new();
+    for i in (0..old.len()).rev() {
+        if used[i] {
+            current.insert(0, Some(i));
+        } else {
+            patches.push(Patch::Remove {
+                path: child_path(path, i),
+            });
+        }
+    }
+
+    for (j, source) in sources.into_iter().enumerate() {
+        match source {
+            Some((i, same)) => {
+                let k = current.iter().position(|&c| c == Some(i)).unwrap_or(j);
+                if k != j {
+                    patches.push(Patch::Move {
+                        from: child_path(path, k),
+                        to: child_path(path, j),
+                    });
+                    current.remove(k);
+                    current.insert(j, Some(i));
+                }
+                if !same {
+                    path.push(j);
+                    diff_node(&old[i], &new[j], path, patches);
+                    path.pop();
+                }
+            }
+            None => {
+                patches.push(Patch::Insert {
+                    path: child_path(path, j),
+                    node: new[j].clone(),
+                });
+                current.insert(j, None);
+            }
+        }
+    }
+}
+
+fn diff_node(old: &Node, new: &Node, path: &mut Vec<usize>, patches: &mut Vec<Patch>) {
+    match (old, new) {
+        (Node::Element(a), Node::Element(b)) if a.name == b.name => {
+            for (name, value) in &b.attrs {
+                if a.get_attr(name) != Some(value) {
+                    patches.push(Patch::SetAttr {
+                        path: path.clone(),
+                        name: name.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+            for (name, _) in &a.attrs {
+                if b.get_attr(name).is_none() {
+                    patches.push(Patch::RemoveAttr {
+                        path: path.clone(),
+                        name: name.clone(),
+                    });
+                }
+            }
+            diff_children(&a.children, &b.children, path, patches);
+        }
+        (Node::Text(a), Node::Text(b)) if a != b => patches.push(Patch::SetText {
+            path: path.clone(),
+            text: b.clone(),
+        }),
+        (Node::Text(_), Node::Text(_)) => {}
+        _ => patches.push(Patch::Replace {
+            path: path.clone(),
+            node: new.clone(),
+        }),
+    }
+}
+
+fn child_path(parent: &[usize], index: usize) -> Vec<usize> {
+    let mut path = parent.to_vec();
+    path.push(index);
+    path
+}
@@ -1,5 +1,11 @@
-use super::Selector;
-use crate::{Element, Node, error};
+use super::selector::Matcher;
+use super::walk::is_frozen;
+use super::{Pipe, PipeTarget, Selector};
+use crate::{entity, error, Element, Node};
+
+/// Attribute marking the elements frozen by [`Editable::freeze`]. It is
+/// left out when stringifying into html.
+pub const FROZEN_ATTR: &str = "data-html-editor-frozen";
 
 /// Insert and remove elements by [`Selector`], and trim the DOM.
 pub trait Editable {
@@ -81,10 +87,11 @@ pub trait Editable {
     /// let selector = Selector::from("p");
     /// let html = parse(html)
     ///     .unwrap()
-    ///     .replace_with(&selector, |p| {
-    ///         let new_text = format!("{} World!", p.children[0].html());
-    ///         Node::Comment(new_text)
+    ///     .replace_with(&selector, &|p| {
+    ///         let new_text = format!("{} World!", p.only_text()?);
+    ///         Ok(Node::Comment(new_text))
     ///     })
+    ///     .unwrap()
     ///     .html();
     ///
     /// assert_eq!(html, r#"
@@ -124,18 +131,88 @@ pub trait Editable {
     /// });
     /// ```
     fn execute_for(&mut self, selector: &Selector, f: impl FnMut(&mut Element));
+
+    /// Freeze the elements that matches the `selector`, so that the
+    /// following edits leave them and their descendants untouched, like
+    /// third-party embeds which must stay as they are.
+    ///
+    /// The frozen elements are marked with the [`FROZEN_ATTR`] attribute,
+    /// which can be removed to unfreeze them. Parsed with their source
+    /// spans, they are written back exactly as in the input with
+    /// [`SourceMappable::html_with_source`](super::SourceMappable::html_with_source).
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<div class="embed"><p class="ad">Partner</p></div><p class="ad">Ad</p>"#;
+    ///
+    /// let html = parse(html)
+    ///     .unwrap()
+    ///     .freeze(&Selector::from(".embed"))
+    ///     .remove_by(&Selector::from(".ad"))
+    ///     .html();
+    ///
+    /// assert_eq!(html, r#"<div class="embed"><p class="ad">Partner</p></div>"#)
+    /// ```
+    fn freeze(&mut self, selector: &Selector) -> &mut Self;
+
+    /// Truncate the values of the attribute `name` of the elements that
+    /// matches the `selector` to `max_len` characters, ending them with
+    /// `…`, like the overly long `alt` or `content` of a description.
+    ///
+    /// The values are cut between the characters, counting a character
+    /// reference like `&amp;` as one, so they stay valid.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<img alt="Café &amp; crème brûlée"><img alt="Short">"#;
+    ///
+    /// let html = parse(html)
+    ///     .unwrap()
+    ///     .truncate_attr(&Selector::from("img"), "alt", 11)
+    ///     .html();
+    ///
+    /// assert_eq!(html, r#"<img alt="Café &amp; crè…"><img alt="Short">"#)
+    /// ```
+    fn truncate_attr(&mut self, selector: &Selector, name: &str, max_len: usize) -> &mut Self;
+
+    /// Start a [`Pipe`] of edits, applied to `self` in a single traversal
+    /// by [`Pipe::run`], which returns the error of the first invalid
+    /// selector instead of ignoring it.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<a href="/">Home</a><a href="https://example.com">Out</a>"#).unwrap();
+    /// dom.pipe()
+    ///     .set_attr("a[href^=https]", "rel", "noopener")
+    ///     .add_class("a[rel]", "external")
+    ///     .run()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<a href="/">Home</a><a href="https://example.com" rel="noopener" class="external">Out</a>"#
+    /// );
+    /// ```
+    fn pipe(&mut self) -> Pipe<'_>;
 }
 
 // We meed this function to allow the trait interface to use `impl FnMut(&mut Element)` instead of `&mut impl FnMut(&mut Element)`
 fn nodes_execute_for_internal(
-    nodes: &mut Vec<Node>,
-    selector: &Selector,
+    nodes: &mut [Node],
+    matcher: Matcher,
     f: &mut impl FnMut(&mut Element),
 ) {
-    for node in nodes {
-        if let Some(element) = node.as_element_mut() {
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if let Some(element) = node.as_element_mut().filter(|el| !is_frozen(el)) {
+            let (matched, children) = matcher.child(index, element);
             // Recursively traverse the descendants nodes
-            element_execute_for_internal(element, selector, f);
+            element_execute_for_internal(element, matched, children, f);
         }
     }
 }
@@ -143,64 +220,123 @@ fn nodes_execute_for_internal(
 // We meed this function to allow the trait interface to use `impl FnMut(&mut Element)` instead of `&mut impl FnMut(&mut Element)`
 fn element_execute_for_internal(
     element: &mut Element,
-    selector: &Selector,
+    matched: bool,
+    children: Matcher,
     f: &mut impl FnMut(&mut Element),
 ) {
-    if selector.matches(element) {
+    if matched {
         f(element);
     }
-    nodes_execute_for_internal(&mut element.children, selector, f);
+    nodes_execute_for_internal(&mut element.children, children, f);
+}
+
+fn insert_to_nodes(nodes: &mut [Node], matcher: Matcher, target: &Node) {
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if let Some(el) = node.as_element_mut().filter(|el| !is_frozen(el)) {
+            let (matched, children) = matcher.child(index, el);
+            insert_to_nodes(&mut el.children, children, target);
+            if matched {
+                el.children.push(target.clone());
+            }
+        }
+    }
+}
+
+fn remove_from_nodes(nodes: &mut Vec<Node>, matcher: Matcher) {
+    let mut index = 0;
+    nodes.retain_mut(|node| {
+        let keep = match node {
+            Node::Element(el) if !is_frozen(el) => {
+                let (matched, children) = matcher.child(index, el);
+                if !matched {
+                    remove_from_nodes(&mut el.children, children);
+                }
+                !matched
+            }
+            _ => true,
+        };
+        index += 1;
+        keep
+    });
+}
+
+/// Truncate the raw `value` for [`Editable::truncate_attr`], or return
+/// `None` if it is short enough.
+fn truncate_value(value: &str, max_len: usize) -> Option<String> {
+    // The end of each character, with the references as one.
+    let mut ends = Vec::new();
+    let mut end = 0;
+    while let Some(ch) = value[end..].chars().next() {
+        end += entity::reference_len(&value[end..]).unwrap_or(ch.len_utf8());
+        ends.push(end);
+    }
+    if ends.len() <= max_len {
+        return None;
+    }
+    let kept = match max_len {
+        0 => return Some(String::new()),
+        1 => "",
+        max_len => value[..ends[max_len - 2]].trim_end(),
+    };
+    Some(format!("{}…", kept))
+}
+
+fn truncate_attr_of(element: &mut Element, name: &str, max_len: usize) {
+    for (key, value) in element.attrs.iter_mut() {
+        if key.eq_ignore_ascii_case(name) {
+            if let Some(truncated) = truncate_value(value, max_len) {
+                *value = truncated;
+            }
+        }
+    }
+}
+
+fn freeze_element(element: &mut Element) {
+    element.attrs.push((FROZEN_ATTR.to_string(), String::new()));
+}
+
+fn replace_in_nodes<F>(nodes: &mut [Node], matcher: Matcher, f: &F) -> Result<(), error::Error>
+where
+    F: Fn(&Element) -> Result<Node, error::ErrorDetail>,
+{
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if let Some(el) = node.as_element_mut().filter(|el| !is_frozen(el)) {
+            let (matched, children) = matcher.child(index, el);
+            if matched {
+                *node = f(el).map_err(|_| error::Error)?;
+            } else {
+                replace_in_nodes(&mut el.children, children, f)?;
+            }
+        }
+    }
+    Ok(())
 }
 
 impl Editable for Vec<Node> {
     fn trim(&mut self) -> &mut Self {
         self.retain(|node| match node {
-            Node::Doctype(..) => true,
+            Node::Doctype(..) | Node::Template(..) | Node::ProcessingInstruction(..) => true,
             Node::Comment(..) => false,
             Node::Text(text) => !text.trim().is_empty(),
             Node::Element { .. } => true,
         });
         for node in self.iter_mut() {
-            if let Node::Element(el) = node {
-                el.children.trim();
+            if let Some(el) = node.as_element_mut() {
+                el.trim();
             }
         }
         self
     }
 
     fn insert_to(&mut self, selector: &Selector, target: Node) -> &mut Self {
-        for node in self.iter_mut() {
-            if let Node::Element(el) = node {
-                el.children.insert_to(selector, target.clone());
-                if selector.matches(&Element {
-                    name: el.name.clone(),
-                    attrs: el.attrs.clone(),
-                    children: vec![],
-                }) {
-                    el.children.push(target.clone());
-                }
-            }
-        }
+        let marks = selector.marks(self);
+        insert_to_nodes(self, Matcher::new(selector, marks.as_deref()), &target);
         self
     }
 
     fn remove_by(&mut self, selector: &Selector) -> &mut Self {
-        self.retain(|node| {
-            if let Node::Element(el) = node {
-                let element = Element {
-                    name: el.name.clone(),
-                    attrs: el.attrs.clone(),
-                    children: vec![],
-                };
-                return !selector.matches(&element);
-            }
-            true
-        });
-        for node in self.iter_mut() {
-            if let Node::Element(el) = node {
-                el.remove_by(selector);
-            }
-        }
+        let marks = selector.marks(self);
+        remove_from_nodes(self, Matcher::new(selector, marks.as_deref()));
         self
     }
 
@@ -208,39 +344,59 @@ impl Editable for Vec<Node> {
     where
         F: Fn(&Element) -> Result<Node, error::ErrorDetail>,
     {
-        for node in self.iter_mut() {
-            if let Node::Element(ref mut el) = node {
-                if selector.matches(el) {
-                    *node = f(el).map_err(|_| error::Error)?;
-                } else {
-                    el.replace_with(selector, f);
-                }
-            }
-        }
+        let marks = selector.marks(self);
+        replace_in_nodes(self, Matcher::new(selector, marks.as_deref()), f)?;
         Ok(self)
     }
 
     fn execute_for(&mut self, selector: &Selector, mut f: impl FnMut(&mut Element)) {
-        nodes_execute_for_internal(self, selector, &mut f);
+        let marks = selector.marks(self);
+        nodes_execute_for_internal(self, Matcher::new(selector, marks.as_deref()), &mut f);
+    }
+
+    fn freeze(&mut self, selector: &Selector) -> &mut Self {
+        self.execute_for(selector, freeze_element);
+        self
+    }
+
+    fn truncate_attr(&mut self, selector: &Selector, name: &str, max_len: usize) -> &mut Self {
+        self.execute_for(selector, |element| truncate_attr_of(element, name, max_len));
+        self
+    }
+
+    fn pipe(&mut self) -> Pipe<'_> {
+        Pipe::new(PipeTarget::Nodes(self))
     }
 }
 
 impl Editable for Element {
     fn trim(&mut self) -> &mut Self {
-        self.children.trim();
+        if !is_frozen(self) {
+            self.children.trim();
+        }
         self
     }
 
     fn insert_to(&mut self, selector: &Selector, target: Node) -> &mut Self {
-        self.children.insert_to(selector, target.clone());
-        if selector.matches(self) {
+        if is_frozen(self) {
+            return self;
+        }
+        let marks = selector.element_marks(self);
+        let (matched, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        insert_to_nodes(&mut self.children, children, &target);
+        if matched {
             self.children.push(target);
         }
         self
     }
 
     fn remove_by(&mut self, selector: &Selector) -> &mut Self {
-        self.children.remove_by(selector);
+        if is_frozen(self) {
+            return self;
+        }
+        let marks = selector.element_marks(self);
+        let (_, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        remove_from_nodes(&mut self.children, children);
         self
     }
 
@@ -248,11 +404,35 @@ impl Editable for Element {
     where
         F: Fn(&Element) -> Result<Node, error::ErrorDetail>,
     {
-        self.children.replace_with(selector, f)?;
+        if is_frozen(self) {
+            return Ok(self);
+        }
+        let marks = selector.element_marks(self);
+        let (_, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        replace_in_nodes(&mut self.children, children, f)?;
         Ok(self)
     }
 
     fn execute_for(&mut self, selector: &Selector, mut f: impl FnMut(&mut Element)) {
-        element_execute_for_internal(self, selector, &mut f);
+        if is_frozen(self) {
+            return;
+        }
+        let marks = selector.element_marks(self);
+        let (matched, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        element_execute_for_internal(self, matched, children, &mut f);
+    }
+
+    fn freeze(&mut self, selector: &Selector) -> &mut Self {
+        self.execute_for(selector, freeze_element);
+        self
+    }
+
+    fn truncate_attr(&mut self, selector: &Selector, name: &str, max_len: usize) -> &mut Self {
+        self.execute_for(selector, |element| truncate_attr_of(element, name, max_len));
+        self
+    }
+
+    fn pipe(&mut self) -> Pipe<'_> {
+        Pipe::new(PipeTarget::Element(self))
     }
 }
@@ -0,0 +1,295 @@
+use super::walk::{is_frozen, raw_text, walk_mut};
+use super::{is_prose, rewrite_nodes};
+use crate::entity::decode;
+use crate::{Element, Node};
+
+/// Attribute marking the email addresses obfuscated by
+/// [`Obfuscatable::obfuscate_emails`], holding the name of the strategy.
+pub const OBFUSCATED_EMAIL_ATTR: &str = "data-obfuscated-email";
+
+/// How [`Obfuscatable::obfuscate_emails`] hides the email addresses from
+/// the harvesting bots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailObfuscation {
+    /// Write each character as a numeric character reference, which the
+    /// browsers show as usual.
+    Entities,
+    /// Write the address backwards, shown the right way by CSS, with the
+    /// `href` of the links moved to a reversed `data-email`.
+    Reversed,
+    /// Move the address to `data-user` and `data-domain`, to be put back
+    /// by a script, with the `href` of the links removed.
+    DataAttributes,
+}
+
+impl EmailObfuscation {
+    fn name(self) -> &'static str {
+        match self {
+            EmailObfuscation::Entities => "entities",
+            EmailObfuscation::Reversed => "reversed",
+            EmailObfuscation::DataAttributes => "data",
+        }
+    }
+}
+
+/// Hide the email addresses of documents from the harvesting bots.
+pub trait Obfuscatable {
+    /// Obfuscate the `mailto:` links and the email addresses written in
+    /// the prose text with the `strategy`, marking them with the
+    /// [`OBFUSCATED_EMAIL_ATTR`] attribute. The addresses of the text are
+    /// wrapped in a `<span>`. Returns the number of addresses obfuscated.
+    ///
+    /// They can be put back with [`Obfuscatable::reveal_emails`].
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<p>Write to <a href="mailto:a@b.io">us</a></p>"#).unwrap();
+    /// dom.obfuscate_emails(EmailObfuscation::DataAttributes);
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<p>Write to <a data-obfuscated-email="data" data-user="a" data-domain="b.io">us</a></p>"#
+    /// );
+    /// ```
+    fn obfuscate_emails(&mut self, strategy: EmailObfuscation) -> usize;
+
+    /// Put back the email addresses obfuscated by
+    /// [`Obfuscatable::obfuscate_emails`], returning their number.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = "<p>Mail info@example.com</p>";
+    /// let mut dom = parse(html).unwrap();
+    /// dom.obfuscate_emails(EmailObfuscation::Reversed);
+    /// assert_ne!(dom.html(), html);
+    ///
+    /// dom.reveal_emails();
+    /// assert_eq!(dom.html(), html);
+    /// ```
+    fn reveal_emails(&mut self) -> usize;
+}
+
+/// Get the byte ranges of the email addresses in `text`.
+fn find_emails(text: &str) -> Vec<(usize, usize)> {
+    let is_local = |b: &u8| b.is_ascii_alphanumeric() || b"._%+-".contains(b);
+    let is_domain = |b: &u8| b.is_ascii_alphanumeric() || b".-".contains(b);
+    let bytes = text.as_bytes();
+    let mut emails = Vec::new();
+    let mut last = 0;
+    for (at, _) in text.match_indices('@') {
+        if at < last {
+            continue;
+        }
+        let start = at
+            - bytes[last..at]
+                .iter()
+                .rev()
+                .take_while(|b| is_local(b))
+                .count();
+        let mut end = at + 1 + bytes[at + 1..].iter().take_while(|b| is_domain(b)).count();
+        while end > at + 1 && b".-".contains(&bytes[end - 1]) {
+            end -= 1;
+        }
+        let (local, domain) = (&text[start..at], &text[at + 1..end]);
+        let tld = domain.rsplit('.').next().unwrap_or("");
+        let valid = !local.is_empty()
+            && !local.starts_with('.')
+            && !local.ends_with('.')
+            && domain.contains('.')
+            && !domain.starts_with(['.', '-'])
+            && tld.len() >= 2
+            && tld.bytes().all(|b| b.is_ascii_alphabetic());
+        if valid {
+            emails.push((start, end));
+            last = end;
+        }
+    }
+    emails
+}
+
+/// Write each character as a decimal character reference.
+fn encode_numeric(text: &str) -> String {
+    text.chars().map(|c| format!("&#{};", c as u32)).collect()
+}
+
+/// Wrap the email `address` found in the text with the `strategy`.
+fn obfuscated_address(address: &str, strategy: EmailObfuscation) -> Node {
+    let marker = (OBFUSCATED_EMAIL_ATTR, strategy.name());
+    match strategy {
+        EmailObfuscation::Entities => Node::new_element(
+            "span",
+            vec![marker],
+            vec![Node::Text(encode_numeric(address))],
+        ),
+        EmailObfuscation::Reversed => Node::new_element(
+            "span",
+            vec![
+                marker,
+                ("style", "unicode-bidi:bidi-override;direction:rtl"),
+            ],
+            vec![Node::Text(address.chars().rev().collect())],
+        ),
+        EmailObfuscation::DataAttributes => {
+            let (user, domain) = address.split_once('@').unwrap_or((address, ""));
+            let attrs = vec![marker, ("data-user", user), ("data-domain", domain)];
+            Node::new_element("span", attrs, vec![])
+        }
+    }
+}
+
+/// Obfuscate the `mailto:` link, returning whether it is one.
+fn obfuscate_link(link: &mut Element, strategy: EmailObfuscation) -> bool {
+    let address = match link.get_attr("href") {
+        Some(href) if link.name == "a" && link.get_attr(OBFUSCATED_EMAIL_ATTR).is_none() => {
+            match href.get(..7) {
+                Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => href[7..].to_string(),
+                _ => return false,
+            }
+        }
+        _ => return false,
+    };
+    link.set_attr(OBFUSCATED_EMAIL_ATTR, strategy.name());
+    match strategy {
+        EmailObfuscation::Entities => {
+            link.set_attr("href", &encode_numeric(&format!("mailto:{}", address)));
+        }
+        EmailObfuscation::Reversed => {
+            link.remove_attr("href");
+            link.set_attr("data-email", &address.chars().rev().collect::<String>());
+        }
+        EmailObfuscation::DataAttributes => {
+            link.remove_attr("href");
+            let (user, domain) = address.split_once('@').unwrap_or((&address, ""));
+            link.set_attr("data-user", user);
+            link.set_attr("data-domain", domain);
+        }
+    }
+    true
+}
+
+fn obfuscate_nodes(nodes: &mut Vec<Node>, strategy: EmailObfuscation) -> usize {
+    let mut count = 0;
+    let enter = |el: &Element| is_prose(el) && el.get_attr(OBFUSCATED_EMAIL_ATTR).is_none();
+    rewrite_nodes(nodes, None, true, &enter, &mut |text| {
+        let emails = find_emails(text);
+        if emails.is_empty() {
+            return None;
+        }
+        let mut replacement = Vec::new();
+        let mut last = 0;
+        for (start, end) in emails {
+            if start > last {
+                replacement.push(Node::Text(text[last..start].to_string()));
+            }
+            replacement.push(obfuscated_address(&text[start..end], strategy));
+            count += 1;
+            last = end;
+        }
+        if last < text.len() {
+            replacement.push(Node::Text(text[last..].to_string()));
+        }
+        Some(replacement)
+    });
+    walk_mut(nodes, &mut |el| {
+        count += obfuscate_link(el, strategy) as usize
+    });
+    count
+}
+
+/// Get the email address obfuscated by [`Obfuscatable::obfuscate_emails`]
+/// in `element`, or `None` if it isn't one.
+///
+/// ```
+/// use html_editor::parse;
+/// use html_editor::operation::*;
+///
+/// let mut dom = parse(r#"<a href="mailto:a@b.io">Mail</a>"#).unwrap();
+/// dom.obfuscate_emails(EmailObfuscation::Entities);
+///
+/// let link = dom.query(&Selector::from("a")).unwrap();
+/// assert_eq!(decode_email(link).as_deref(), Some("a@b.io"));
+/// ```
+pub fn decode_email(element: &Element) -> Option<String> {
+    let strategy = element.get_attr(OBFUSCATED_EMAIL_ATTR)?;
+    let is_link = element.name == "a";
+    let email = match strategy {
+        "entities" if is_link => {
+            let href = decode(element.get_attr("href")?);
+            href.get(7..)?.to_string()
+        }
+        "entities" => decode(&raw_text(&element.children)),
+        "reversed" if is_link => element.get_attr("data-email")?.chars().rev().collect(),
+        "reversed" => raw_text(&element.children).chars().rev().collect(),
+        "data" => {
+            let (user, domain) = (
+                element.get_attr("data-user")?,
+                element.get_attr("data-domain")?,
+            );
+            format!("{}@{}", user, domain)
+        }
+        _ => return None,
+    };
+    Some(email)
+}
+
+fn reveal_nodes(nodes: &mut [Node]) -> usize {
+    let mut count = 0;
+    for node in nodes.iter_mut() {
+        let Node::Element(el) = node else {
+            continue;
+        };
+        if is_frozen(el) {
+            continue;
+        }
+        match decode_email(el) {
+            Some(email) if el.name == "a" => {
+                for name in [
+                    OBFUSCATED_EMAIL_ATTR,
+                    "data-email",
+                    "data-user",
+                    "data-domain",
+                ] {
+                    el.remove_attr(name);
+                }
+                el.set_attr("href", &format!("mailto:{}", email));
+                count += 1 + reveal_nodes(&mut el.children);
+            }
+            Some(email) => {
+                *node = Node::Text(email);
+                count += 1;
+            }
+            None => count += reveal_nodes(&mut el.children),
+        }
+    }
+    count
+}
+
+impl Obfuscatable for Vec<Node> {
+    fn obfuscate_emails(&mut self, strategy: EmailObfuscation) -> usize {
+        obfuscate_nodes(self, strategy)
+    }
+
+    fn reveal_emails(&mut self) -> usize {
+        reveal_nodes(self)
+    }
+}
+
+impl Obfuscatable for Element {
+    fn obfuscate_emails(&mut self, strategy: EmailObfuscation) -> usize {
+        if is_frozen(self) {
+            return 0;
+        }
+        obfuscate_nodes(&mut self.children, strategy)
+    }
+
+    fn reveal_emails(&mut self) -> usize {
+        if is_frozen(self) {
+            return 0;
+        }
+        reveal_nodes(&mut self.children)
+    }
+}
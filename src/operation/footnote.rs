@@ -0,0 +1,144 @@
+use super::selector::Matcher;
+use super::walk::{has_class, is_frozen};
+use super::{query_editable_mut, Selector};
+use crate::{Element, Node};
+
+/// Turn the notes of documents into footnotes.
+pub trait Footnotable {
+    /// Move the elements matching `selector` to a footnotes section at
+    /// the end of the `<body>`, or of the document if it has none, and
+    /// replace them by numbered links to their note, which links back.
+    ///
+    /// When the footnotes section already exists, the new notes are
+    /// appended to it and numbered after the existing ones. Returns the
+    /// number of footnotes moved.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<p>Rust<span class="fn">Since 2015.</span> is fast.</p>"#).unwrap();
+    /// dom.collect_footnotes(&Selector::from(".fn"));
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     concat!(
+    ///         r##"<p>Rust<sup id="fnref-1"><a href="#fn-1" role="doc-noteref">1</a></sup> is fast.</p>"##,
+    ///         r##"<section class="footnotes" role="doc-endnotes"><ol><li id="fn-1">Since 2015. "##,
+    ///         r##"<a href="#fnref-1" role="doc-backlink">↩</a></li></ol></section>"##,
+    ///     )
+    /// );
+    /// ```
+    fn collect_footnotes(&mut self, selector: &Selector) -> usize;
+}
+
+fn is_footnotes_section(element: &Element) -> bool {
+    element.name == "section" && has_class(element, "footnotes")
+}
+
+/// Replace the footnotes of `nodes` by references, numbered from
+/// `notes.len() + 1`, pushing the notes into `notes`.
+fn take_footnotes(nodes: &mut [Node], matcher: Matcher, first: usize, notes: &mut Vec<Node>) {
+    for (index, node) in nodes.iter_mut().enumerate() {
+        let element = match node {
+            Node::Element(el) if is_footnotes_section(el) || is_frozen(el) => continue,
+            Node::Element(el) => el,
+            _ => continue,
+        };
+        let (matched, children) = matcher.child(index, element);
+        if !matched {
+            take_footnotes(&mut element.children, children, first, notes);
+            continue;
+        }
+
+        let number = (first + notes.len()).to_string();
+        let mut children = std::mem::take(&mut element.children);
+        children.push(Node::Text(" ".to_string()));
+        children.push(Node::new_element(
+            "a",
+            vec![
+                ("href", &format!("#fnref-{}", number)),
+                ("role", "doc-backlink"),
+            ],
+            vec![Node::Text("↩".to_string())],
+        ));
+        notes.push(Node::new_element(
+            "li",
+            vec![("id", &format!("fn-{}", number))],
+            children,
+        ));
+        *node = Node::new_element(
+            "sup",
+            vec![("id", &format!("fnref-{}", number))],
+            vec![Node::new_element(
+                "a",
+                vec![
+                    ("href", &format!("#fn-{}", number)),
+                    ("role", "doc-noteref"),
+                ],
+                vec![Node::Text(number.clone())],
+            )],
+        );
+    }
+}
+
+fn footnote_list(section: &mut Element) -> &mut Element {
+    let index = section
+        .children
+        .iter()
+        .position(|node| node.as_element().is_some_and(|el| el.name == "ol"));
+    let index = index.unwrap_or_else(|| {
+        section
+            .children
+            .push(Node::new_element("ol", vec![], vec![]));
+        section.children.len() - 1
+    });
+    section.children[index].as_element_mut().unwrap()
+}
+
+/// Move the footnotes of `nodes` into their section, which is appended
+/// to the `<body>` or to `nodes` unless it already exists.
+fn collect_nodes_footnotes(nodes: &mut Vec<Node>, matcher: Matcher) -> usize {
+    let sections = Selector::from("section.footnotes");
+    let existing = query_editable_mut(nodes, &sections)
+        .map_or(0, |section| footnote_list(section).children.len());
+
+    let mut notes = Vec::new();
+    take_footnotes(nodes, matcher, existing + 1, &mut notes);
+    let count = notes.len();
+    if count == 0 {
+        return 0;
+    }
+    if let Some(section) = query_editable_mut(nodes, &sections) {
+        footnote_list(section).children.extend(notes);
+        return count;
+    }
+    let section = Node::new_element(
+        "section",
+        vec![("class", "footnotes"), ("role", "doc-endnotes")],
+        vec![Node::new_element("ol", vec![], notes)],
+    );
+    match query_editable_mut(nodes, &Selector::from("body")) {
+        Some(body) => body.children.push(section),
+        None => nodes.push(section),
+    }
+    count
+}
+
+impl Footnotable for Vec<Node> {
+    fn collect_footnotes(&mut self, selector: &Selector) -> usize {
+        let marks = selector.marks(self);
+        collect_nodes_footnotes(self, Matcher::new(selector, marks.as_deref()))
+    }
+}
+
+impl Footnotable for Element {
+    fn collect_footnotes(&mut self, selector: &Selector) -> usize {
+        if is_frozen(self) {
+            return 0;
+        }
+        let marks = selector.element_marks(self);
+        let (_, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        collect_nodes_footnotes(&mut self.children, children)
+    }
+}
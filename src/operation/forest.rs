@@ -0,0 +1,142 @@
+use super::{Queryable, Selector};
+use crate::{entity, try_parse, Element, Node};
+
+/// Where a document of a [`DocumentForest`] comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentOrigin {
+    /// The document the forest was built from.
+    Top,
+    /// The `srcdoc` attribute of an `<iframe>`.
+    Srcdoc,
+    /// The content of a `<template>`.
+    Template,
+}
+
+/// A document of a [`DocumentForest`].
+#[derive(Debug, Clone)]
+pub struct NestedDocument {
+    pub origin: DocumentOrigin,
+    /// The index of the document it is nested in, or `None` for the top
+    /// one.
+    pub parent: Option<usize>,
+    /// The nodes of the document, without the content of its templates,
+    /// which are documents of their own.
+    pub nodes: Vec<Node>,
+}
+
+/// A document with the documents nested in it, being the `srcdoc` of
+/// its iframes and the content of its templates, to query them as one
+/// forest.
+///
+/// The nested documents are parsed once when building the forest, with
+/// [`try_parse`], and come after the document they are nested in.
+#[derive(Debug, Clone)]
+pub struct DocumentForest {
+    documents: Vec<NestedDocument>,
+}
+
+impl DocumentForest {
+    /// Build the forest of `nodes` and the documents nested in them, at
+    /// any depth.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse(concat!(
+    ///     r#"<a href="/top">Top</a>"#,
+    ///     r#"<iframe srcdoc="<a href='/frame'>Frame</a>"></iframe>"#,
+    ///     r#"<template><a href="/template">Template</a></template>"#,
+    /// ))
+    /// .unwrap();
+    /// let forest = DocumentForest::new(&dom);
+    ///
+    /// let links: Vec<_> = forest
+    ///     .query_all(&Selector::from("a"))
+    ///     .into_iter()
+    ///     .map(|(document, a)| (forest.documents()[document].origin, a.attrs[0].1.as_str()))
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     links,
+    ///     vec![
+    ///         (DocumentOrigin::Top, "/top"),
+    ///         (DocumentOrigin::Srcdoc, "/frame"),
+    ///         (DocumentOrigin::Template, "/template"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn new(nodes: &[Node]) -> Self {
+        let mut forest = DocumentForest {
+            documents: Vec::new(),
+        };
+        forest.add(nodes.to_vec(), DocumentOrigin::Top, None);
+        forest
+    }
+
+    /// Add the document and, after it, the documents nested in it.
+    fn add(&mut self, mut nodes: Vec<Node>, origin: DocumentOrigin, parent: Option<usize>) {
+        let index = self.documents.len();
+        let mut nested = Vec::new();
+        take_nested(&mut nodes, &mut nested);
+        self.documents.push(NestedDocument {
+            origin,
+            parent,
+            nodes,
+        });
+        for (origin, nodes) in nested {
+            self.add(nodes, origin, Some(index));
+        }
+    }
+
+    /// Get the documents, the top one first.
+    pub fn documents(&self) -> &[NestedDocument] {
+        &self.documents
+    }
+
+    /// Query the first element matching `selector`, with the index of
+    /// its document, in the order of the documents.
+    pub fn query(&self, selector: &Selector) -> Option<(usize, &Element)> {
+        self.documents
+            .iter()
+            .enumerate()
+            .find_map(|(index, document)| Some((index, document.nodes.query(selector)?)))
+    }
+
+    /// Query all the elements matching `selector`, with the index of
+    /// their document, in the order of the documents. The selectors are
+    /// matched within each document, not across them.
+    pub fn query_all(&self, selector: &Selector) -> Vec<(usize, &Element)> {
+        self.documents
+            .iter()
+            .enumerate()
+            .flat_map(|(index, document)| {
+                let elements = document.nodes.query_all(selector);
+                elements.into_iter().map(move |element| (index, element))
+            })
+            .collect()
+    }
+}
+
+/// Take the documents nested in `nodes` and their descendants, in the
+/// order of their elements.
+fn take_nested(nodes: &mut [Node], nested: &mut Vec<(DocumentOrigin, Vec<Node>)>) {
+    for node in nodes {
+        let Node::Element(element) = node else {
+            continue;
+        };
+        if element.name.eq_ignore_ascii_case("template") {
+            nested.push((
+                DocumentOrigin::Template,
+                std::mem::take(&mut element.children),
+            ));
+            continue;
+        }
+        if element.name.eq_ignore_ascii_case("iframe") {
+            if let Some((_, srcdoc)) = element.attrs.iter().find(|(key, _)| key == "srcdoc") {
+                nested.push((DocumentOrigin::Srcdoc, try_parse(&entity::decode(srcdoc))));
+            }
+        }
+        take_nested(&mut element.children, nested);
+    }
+}
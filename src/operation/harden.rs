@@ -0,0 +1,816 @@
+use std::collections::HashMap;
+
+use super::Htmlifiable;
+use crate::srcset::{self, Candidate};
+use crate::{data::URL_ATTRS, url, Element, Node};
+
+use super::selector::has_prefix;
+use super::walk::{retain, walk_element_mut, walk_mut};
+
+/// Attribute removed by a hardening pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovedAttr {
+    /// Name of the element the attribute belonged to.
+    pub tag: String,
+    pub name: String,
+    pub value: String,
+}
+
+/// Attributes enforced on every `<iframe>` by
+/// [`Hardenable::harden_iframes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IframePolicy {
+    /// Tokens allowed in `sandbox`. An empty list gives the strictest
+    /// sandbox. Tokens already set on a frame but absent here are
+    /// dropped, and a frame is never given more than it had.
+    pub sandbox: Vec<String>,
+    /// Value for `referrerpolicy`, if it should be enforced.
+    pub referrer_policy: Option<String>,
+    /// Whether to add `loading="lazy"` to frames without `loading`.
+    pub lazy: bool,
+    /// Features allowed in the `allow` attribute. The other
+    /// directives are removed.
+    pub allow: Vec<String>,
+}
+
+impl Default for IframePolicy {
+    fn default() -> Self {
+        Self {
+            sandbox: Vec::new(),
+            referrer_policy: Some("no-referrer".to_string()),
+            lazy: true,
+            allow: Vec::new(),
+        }
+    }
+}
+
+/// Frame changed by [`Hardenable::harden_iframes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HardenedIframe {
+    pub src: Option<String>,
+    /// Names of the attributes that have been added, changed or removed.
+    pub changed: Vec<String>,
+}
+
+/// Settings of [`Hardenable::harden_links`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkPolicy {
+    /// Origins considered internal, like `https://example.com`. Links
+    /// to any other origin are external.
+    pub origins: Vec<String>,
+    /// Whether external links should also open in a new tab with
+    /// `target="_blank"`.
+    pub target_blank: bool,
+}
+
+/// Signature of a tracking script or pixel, used by
+/// [`Hardenable::find_trackers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackerSignature {
+    pub name: String,
+    /// URLs serving the tracker, without scheme. `example.com` matches
+    /// the host and its subdomains, and `example.com/tr` also requires
+    /// the path to start with `/tr`.
+    pub urls: Vec<String>,
+    /// Snippets identifying the tracker in inline scripts.
+    pub snippets: Vec<String>,
+}
+
+impl TrackerSignature {
+    /// Create a signature from string slices.
+    pub fn new(name: &str, urls: &[&str], snippets: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            urls: urls.iter().map(|url| url.to_string()).collect(),
+            snippets: snippets.iter().map(|snippet| snippet.to_string()).collect(),
+        }
+    }
+
+    /// The built-in signatures of common analytics and advertising
+    /// trackers. Extend the returned list to detect more of them.
+    pub fn builtin() -> Vec<Self> {
+        vec![
+            Self::new(
+                "Google Analytics",
+                &["google-analytics.com", "googletagmanager.com"],
+                &["gtag(", "GoogleAnalyticsObject"],
+            ),
+            Self::new("DoubleClick", &["doubleclick.net"], &[]),
+            Self::new(
+                "Facebook Pixel",
+                &["connect.facebook.net", "facebook.com/tr"],
+                &["fbq("],
+            ),
+            Self::new("Hotjar", &["hotjar.com"], &["_hjSettings"]),
+            Self::new("Segment", &["cdn.segment.com"], &["analytics.load("]),
+            Self::new("Mixpanel", &["cdn.mxpnl.com"], &["mixpanel.init("]),
+            Self::new("Microsoft Clarity", &["clarity.ms"], &[]),
+            Self::new(
+                "LinkedIn Insight",
+                &["snap.licdn.com", "px.ads.linkedin.com"],
+                &["_linkedin_partner_id"],
+            ),
+            Self::new("Twitter Pixel", &["static.ads-twitter.com"], &["twq("]),
+            Self::new("TikTok Pixel", &["analytics.tiktok.com"], &["ttq.load("]),
+            Self::new("Quantcast", &["quantserve.com"], &[]),
+        ]
+    }
+
+    fn matches_url(&self, url: &str) -> bool {
+        let url = url.trim();
+        let url = match url.split_once("//") {
+            Some((_, rest)) => rest,
+            None => return false,
+        };
+        let (host, path) = url.split_at(url.find(['/', '?', '#']).unwrap_or(url.len()));
+        let host = host.to_ascii_lowercase();
+        self.urls.iter().any(|pattern| {
+            let (pattern_host, pattern_path) =
+                pattern.split_at(pattern.find('/').unwrap_or(pattern.len()));
+            let host_matches =
+                host == pattern_host || host.ends_with(&format!(".{}", pattern_host));
+            host_matches && path.starts_with(pattern_path)
+        })
+    }
+}
+
+/// Tracker found by [`Hardenable::find_trackers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackerMatch {
+    /// Name of the matched signature, or `"Tracking pixel"` for the
+    /// unknown 1x1 images.
+    pub name: String,
+    /// Name of the element, like `script` or `img`.
+    pub tag: String,
+    pub src: Option<String>,
+}
+
+/// What to do with the URLs matched by a [`SchemeRule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemeAction {
+    Allow,
+    /// Remove the attribute, or the candidate from a `srcset`.
+    Block,
+    /// Replace the URL, like with `about:blank`.
+    Rewrite(String),
+}
+
+/// Rule of a [`SchemePolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemeRule {
+    /// Lowercased scheme without the colon, like `data`.
+    pub scheme: String,
+    /// Element the rule is restricted to, like `img`.
+    pub tag: Option<String>,
+    /// Attribute the rule is restricted to, like `src`.
+    pub attr: Option<String>,
+    pub action: SchemeAction,
+}
+
+impl SchemeRule {
+    /// Create a rule applying to all the URL attributes.
+    pub fn new(scheme: &str, action: SchemeAction) -> Self {
+        Self {
+            scheme: scheme.to_ascii_lowercase(),
+            tag: None,
+            attr: None,
+            action,
+        }
+    }
+
+    /// Restrict the rule to the elements named `tag`.
+    pub fn on(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Restrict the rule to the attributes named `attr`.
+    pub fn attr(mut self, attr: &str) -> Self {
+        self.attr = Some(attr.to_string());
+        self
+    }
+
+    fn applies(&self, scheme: &str, tag: &str, attr: &str) -> bool {
+        self.scheme == scheme
+            && self
+                .tag
+                .as_deref()
+                .is_none_or(|t| t.eq_ignore_ascii_case(tag))
+            && self
+                .attr
+                .as_deref()
+                .is_none_or(|a| a.eq_ignore_ascii_case(attr))
+    }
+}
+
+/// Policy on the schemes of the URL attributes, enforced by
+/// [`Hardenable::apply_scheme_policy`].
+///
+/// The most specific rule matching a URL wins: the rules for an element
+/// and attribute override the ones for an element, which override the
+/// ones for an attribute, which override the general ones. Among equally
+/// specific rules, the last one wins. URLs matched by no rule, and the
+/// relative ones, are allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemePolicy {
+    pub rules: Vec<SchemeRule>,
+}
+
+impl SchemePolicy {
+    fn action(&self, url: &str, tag: &str, attr: &str) -> &SchemeAction {
+        let scheme = match url::scheme(url) {
+            Some(scheme) => scheme,
+            None => return &SchemeAction::Allow,
+        };
+        self.rules
+            .iter()
+            .filter(|rule| rule.applies(&scheme, tag, attr))
+            .max_by_key(|rule| (rule.tag.is_some(), rule.attr.is_some()))
+            .map_or(&SchemeAction::Allow, |rule| &rule.action)
+    }
+}
+
+/// URL attribute changed by [`Hardenable::apply_scheme_policy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlChange {
+    /// Name of the element the attribute belongs to.
+    pub tag: String,
+    pub name: String,
+    pub value: String,
+    /// New value of the attribute, or `None` if it has been removed.
+    pub replacement: Option<String>,
+}
+
+/// Attributes kept by [`Hardenable::strip_attributes`].
+///
+/// Names are compared ignoring the case, and a name ending with `*`,
+/// like `data-*`, allows all the attributes starting with it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttrPolicy {
+    /// Attributes allowed on all the elements.
+    pub global: Vec<String>,
+    /// Attributes allowed on the elements with the lowercased name.
+    pub tags: HashMap<String, Vec<String>>,
+}
+
+impl AttrPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow `attrs` on all the elements.
+    pub fn allow(mut self, attrs: &[&str]) -> Self {
+        self.global
+            .extend(attrs.iter().map(|attr| attr.to_string()));
+        self
+    }
+
+    /// Allow `attrs` on the elements named `tag`.
+    pub fn allow_on(mut self, tag: &str, attrs: &[&str]) -> Self {
+        self.tags
+            .entry(tag.to_ascii_lowercase())
+            .or_default()
+            .extend(attrs.iter().map(|attr| attr.to_string()));
+        self
+    }
+
+    fn allows(&self, tag: &str, attr: &str) -> bool {
+        let allowed = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => has_prefix(attr, prefix),
+            None => pattern.eq_ignore_ascii_case(attr),
+        };
+        self.global.iter().any(allowed)
+            || self
+                .tags
+                .get(&tag.to_ascii_lowercase())
+                .is_some_and(|attrs| attrs.iter().any(allowed))
+    }
+}
+
+/// Harden untrusted html against common attacks.
+pub trait Hardenable {
+    /// Remove all the inline event handlers (`on*` attributes) and the
+    /// URL attributes using the `javascript:` scheme, returning what has
+    /// been removed.
+    ///
+    /// It is much lighter than a full sanitizer, and is handy to audit
+    /// third-party snippets: run it on a clone to get the report only.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<a href="javascript:alert(1)">A</a><b onclick="go()">B</b>"#).unwrap();
+    /// let removed = dom.strip_event_handlers();
+    ///
+    /// assert_eq!(dom.html(), "<a>A</a><b>B</b>");
+    /// assert_eq!(removed[1].name, "onclick");
+    /// ```
+    fn strip_event_handlers(&mut self) -> Vec<RemovedAttr>;
+
+    /// Remove all the attributes whose name starts with `prefix`, like
+    /// `data-v-` or `ng-`, ignoring the case, returning what has been
+    /// removed.
+    ///
+    /// Elements with such attributes can be selected with
+    /// `Selector::from("[data-v-*]")`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<div data-v-1a2b="" class="app"><p data-v-1a2b="">Hi</p></div>"#).unwrap();
+    /// let removed = dom.strip_attrs_with_prefix("data-v-");
+    ///
+    /// assert_eq!(dom.html(), r#"<div class="app"><p>Hi</p></div>"#);
+    /// assert_eq!(removed.len(), 2);
+    /// ```
+    fn strip_attrs_with_prefix(&mut self, prefix: &str) -> Vec<RemovedAttr>;
+
+    /// Remove all the attributes not allowed by `policy`, returning what
+    /// has been removed.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<a href="/" style="color: red" data-id="1" target="_blank">Home</a>"#;
+    /// let mut dom = parse(html).unwrap();
+    /// let policy = AttrPolicy::new()
+    ///     .allow(&["id", "class", "data-*"])
+    ///     .allow_on("a", &["href"]);
+    /// dom.strip_attributes(&policy);
+    ///
+    /// assert_eq!(dom.html(), r#"<a href="/" data-id="1">Home</a>"#);
+    /// ```
+    fn strip_attributes(&mut self, policy: &AttrPolicy) -> Vec<RemovedAttr>;
+
+    /// Enforce `policy` on all the `<iframe>` elements, returning the
+    /// frames that have been changed.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<iframe src="https://example.com"></iframe>"#).unwrap();
+    /// let changed = dom.harden_iframes(&IframePolicy {
+    ///     sandbox: vec!["allow-scripts".to_string()],
+    ///     ..Default::default()
+    /// });
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<iframe src="https://example.com" sandbox="allow-scripts" referrerpolicy="no-referrer" loading="lazy"></iframe>"#
+    /// );
+    /// assert_eq!(changed[0].changed, vec!["sandbox", "referrerpolicy", "loading"]);
+    /// ```
+    fn harden_iframes(&mut self, policy: &IframePolicy) -> Vec<HardenedIframe>;
+
+    /// Add `rel="noopener noreferrer"` to the `<a>` and `<area>` links
+    /// pointing outside of the origins in `policy`, keeping the other
+    /// `rel` values, and return how many links have been changed.
+    ///
+    /// Relative links and the ones without an authority, like `mailto:`,
+    /// are internal.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom =
+    ///     parse(r#"<a href="https://example.com/a">A</a><a href="https://evil.com">B</a>"#).unwrap();
+    /// dom.harden_links(&LinkPolicy {
+    ///     origins: vec!["https://example.com".to_string()],
+    ///     target_blank: true,
+    /// });
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<a href="https://example.com/a">A</a><a href="https://evil.com" rel="noopener noreferrer" target="_blank">B</a>"#
+    /// );
+    /// ```
+    fn harden_links(&mut self, policy: &LinkPolicy) -> usize;
+
+    /// Find the scripts, images and frames matching one of the tracker
+    /// `signatures`, together with the images sized 1x1 which are most
+    /// likely tracking pixels.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<script src="https://www.google-analytics.com/analytics.js"></script>"#;
+    /// let trackers = parse(html).unwrap().find_trackers(&TrackerSignature::builtin());
+    ///
+    /// assert_eq!(trackers[0].name, "Google Analytics");
+    /// ```
+    fn find_trackers(&self, signatures: &[TrackerSignature]) -> Vec<TrackerMatch>;
+
+    /// Remove the trackers found by [`Hardenable::find_trackers`] from
+    /// the document, returning what has been removed.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<p>Hi</p><img src="/p.gif" width="1" height="1">"#).unwrap();
+    /// dom.remove_trackers(&TrackerSignature::builtin());
+    ///
+    /// assert_eq!(dom.html(), "<p>Hi</p>");
+    /// ```
+    fn remove_trackers(&mut self, signatures: &[TrackerSignature]) -> Vec<TrackerMatch>;
+
+    /// Block, allow or rewrite the URL attributes according to the
+    /// scheme `policy`, returning the attributes that have been changed.
+    /// Each candidate of a `srcset` is checked on its own.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<img src="data:image/png;base64,iVBO"><script src="data:text/javascript,alert(1)"></script>"#).unwrap();
+    /// dom.apply_scheme_policy(&SchemePolicy {
+    ///     rules: vec![
+    ///         SchemeRule::new("data", SchemeAction::Block),
+    ///         SchemeRule::new("data", SchemeAction::Allow).on("img"),
+    ///     ],
+    /// });
+    ///
+    /// assert_eq!(dom.html(), r#"<img src="data:image/png;base64,iVBO"><script></script>"#);
+    /// ```
+    fn apply_scheme_policy(&mut self, policy: &SchemePolicy) -> Vec<UrlChange>;
+}
+
+fn is_event_handler(name: &str) -> bool {
+    name.len() > 2 && name[..2].eq_ignore_ascii_case("on")
+}
+
+fn is_javascript_url(name: &str, value: &str) -> bool {
+    URL_ATTRS.contains(&name.to_ascii_lowercase().as_str())
+        && url::scheme(value).is_some_and(|scheme| scheme == "javascript")
+}
+
+fn strip_element_attrs(
+    element: &mut Element,
+    removed: &mut Vec<RemovedAttr>,
+    strip: impl Fn(&str, &str) -> bool,
+) {
+    let tag = &element.name;
+    element.attrs.retain(|(name, value)| {
+        if strip(name, value) {
+            removed.push(RemovedAttr {
+                tag: tag.clone(),
+                name: name.clone(),
+                value: value.clone(),
+            });
+            false
+        } else {
+            true
+        }
+    });
+}
+
+fn apply_element_scheme_policy(
+    element: &mut Element,
+    policy: &SchemePolicy,
+    changes: &mut Vec<UrlChange>,
+) {
+    let tag = &element.name;
+    element.attrs.retain_mut(|(name, value)| {
+        let attr = name.to_ascii_lowercase();
+        if !URL_ATTRS.contains(&attr.as_str()) {
+            return true;
+        }
+        let replacement = if attr == "srcset" {
+            let candidates = srcset::parse(value);
+            let kept: Vec<Candidate> = candidates
+                .iter()
+                .filter_map(|candidate| match policy.action(&candidate.url, tag, name) {
+                    SchemeAction::Allow => Some(candidate.clone()),
+                    SchemeAction::Block => None,
+                    SchemeAction::Rewrite(url) => Some(Candidate {
+                        url: url.clone(),
+                        descriptor: candidate.descriptor.clone(),
+                    }),
+                })
+                .collect();
+            if kept == candidates {
+                return true;
+            }
+            (!kept.is_empty()).then(|| srcset::serialize(&kept))
+        } else {
+            match policy.action(value, tag, name) {
+                SchemeAction::Allow => return true,
+                SchemeAction::Block => None,
+                SchemeAction::Rewrite(url) if url == value => return true,
+                SchemeAction::Rewrite(url) => Some(url.clone()),
+            }
+        };
+        changes.push(UrlChange {
+            tag: tag.clone(),
+            name: name.clone(),
+            value: value.clone(),
+            replacement: replacement.clone(),
+        });
+        match replacement {
+            Some(replacement) => {
+                *value = replacement;
+                true
+            }
+            None => false,
+        }
+    });
+}
+
+fn harden_iframe(frame: &mut Element, policy: &IframePolicy) -> Option<HardenedIframe> {
+    let mut changed = Vec::new();
+    let mut enforce = |frame: &mut Element, name: &str, value: Option<String>| {
+        let old = frame.get_attr(name).map(str::to_string);
+        if old == value {
+            return;
+        }
+        match &value {
+            Some(value) => frame.set_attr(name, value),
+            None => {
+                frame.remove_attr(name);
+            }
+        }
+        changed.push(name.to_string());
+    };
+
+    let sandbox = match frame.get_attr("sandbox") {
+        Some(old) => old
+            .split_ascii_whitespace()
+            .filter(|token| policy.sandbox.iter().any(|allowed| allowed == token))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => policy.sandbox.join(" "),
+    };
+    enforce(frame, "sandbox", Some(sandbox));
+
+    if let Some(referrer_policy) = &policy.referrer_policy {
+        enforce(frame, "referrerpolicy", Some(referrer_policy.clone()));
+    }
+
+    if policy.lazy && frame.get_attr("loading").is_none() {
+        enforce(frame, "loading", Some("lazy".to_string()));
+    }
+
+    if let Some(allow) = frame.get_attr("allow") {
+        let allow = allow
+            .split(';')
+            .map(str::trim)
+            .filter(|directive| {
+                let feature = directive.split_ascii_whitespace().next().unwrap_or("");
+                policy.allow.iter().any(|allowed| allowed == feature)
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        enforce(
+            frame,
+            "allow",
+            Some(allow).filter(|allow| !allow.is_empty()),
+        );
+    }
+
+    (!changed.is_empty()).then(|| HardenedIframe {
+        src: frame.get_attr("src").map(str::to_string),
+        changed,
+    })
+}
+
+fn harden_if_iframe(
+    element: &mut Element,
+    policy: &IframePolicy,
+    hardened: &mut Vec<HardenedIframe>,
+) {
+    if element.name == "iframe" {
+        hardened.extend(harden_iframe(element, policy));
+    }
+}
+
+fn harden_link(link: &mut Element, origins: &[url::Origin], target_blank: bool) -> bool {
+    if link.name != "a" && link.name != "area" {
+        return false;
+    }
+    let external = link
+        .get_attr("href")
+        .and_then(url::origin)
+        .is_some_and(|origin| !origins.iter().any(|internal| origin.is_same(internal)));
+    if !external {
+        return false;
+    }
+
+    let old = link.attrs.clone();
+    let mut rel: Vec<String> = link
+        .get_attr("rel")
+        .unwrap_or("")
+        .split_ascii_whitespace()
+        .map(str::to_string)
+        .collect();
+    for token in ["noopener", "noreferrer"] {
+        if !rel.iter().any(|old| old.eq_ignore_ascii_case(token)) {
+            rel.push(token.to_string());
+        }
+    }
+    link.set_attr("rel", &rel.join(" "));
+    if target_blank {
+        link.set_attr("target", "_blank");
+    }
+    link.attrs != old
+}
+
+fn internal_origins(policy: &LinkPolicy) -> Vec<url::Origin> {
+    policy
+        .origins
+        .iter()
+        .filter_map(|origin| url::origin(origin))
+        .collect()
+}
+
+fn is_pixel(element: &Element) -> bool {
+    let tiny = |name: &str| {
+        element
+            .get_attr(name)
+            .is_some_and(|size| matches!(size.trim().trim_end_matches("px"), "0" | "1"))
+    };
+    element.name == "img" && tiny("width") && tiny("height")
+}
+
+fn match_tracker(element: &Element, signatures: &[TrackerSignature]) -> Option<TrackerMatch> {
+    if !matches!(element.name.as_str(), "script" | "img" | "iframe") {
+        return None;
+    }
+    let src = element.get_attr("src");
+    let signature = match src {
+        Some(src) => signatures.iter().find(|sig| sig.matches_url(src)),
+        None if element.name == "script" => {
+            let code = element.children.html();
+            signatures.iter().find(|sig| {
+                sig.snippets
+                    .iter()
+                    .any(|snippet| code.contains(snippet.as_str()))
+            })
+        }
+        None => None,
+    };
+    let name = match signature {
+        Some(signature) => signature.name.clone(),
+        None if is_pixel(element) => "Tracking pixel".to_string(),
+        None => return None,
+    };
+    Some(TrackerMatch {
+        name,
+        tag: element.name.clone(),
+        src: src.map(str::to_string),
+    })
+}
+
+fn find_trackers_in(
+    nodes: &[Node],
+    signatures: &[TrackerSignature],
+    found: &mut Vec<TrackerMatch>,
+) {
+    for el in nodes.iter().filter_map(Node::as_element) {
+        match match_tracker(el, signatures) {
+            Some(tracker) => found.push(tracker),
+            None => find_trackers_in(&el.children, signatures, found),
+        }
+    }
+}
+
+fn remove_trackers_in(nodes: &mut Vec<Node>, signatures: &[TrackerSignature]) -> Vec<TrackerMatch> {
+    let mut removed = Vec::new();
+    retain(nodes, &mut |el| match match_tracker(el, signatures) {
+        Some(tracker) => {
+            removed.push(tracker);
+            false
+        }
+        None => true,
+    });
+    removed
+}
+
+impl Hardenable for Vec<Node> {
+    fn strip_event_handlers(&mut self) -> Vec<RemovedAttr> {
+        let mut removed = Vec::new();
+        walk_mut(self, &mut |el| {
+            strip_element_attrs(el, &mut removed, |name, value| {
+                is_event_handler(name) || is_javascript_url(name, value)
+            })
+        });
+        removed
+    }
+
+    fn strip_attrs_with_prefix(&mut self, prefix: &str) -> Vec<RemovedAttr> {
+        let mut removed = Vec::new();
+        walk_mut(self, &mut |el| {
+            strip_element_attrs(el, &mut removed, |name, _| has_prefix(name, prefix))
+        });
+        removed
+    }
+
+    fn strip_attributes(&mut self, policy: &AttrPolicy) -> Vec<RemovedAttr> {
+        let mut removed = Vec::new();
+        walk_mut(self, &mut |el| {
+            let tag = el.name.clone();
+            strip_element_attrs(el, &mut removed, |name, _| !policy.allows(&tag, name))
+        });
+        removed
+    }
+
+    fn harden_iframes(&mut self, policy: &IframePolicy) -> Vec<HardenedIframe> {
+        let mut hardened = Vec::new();
+        walk_mut(self, &mut |el| harden_if_iframe(el, policy, &mut hardened));
+        hardened
+    }
+
+    fn harden_links(&mut self, policy: &LinkPolicy) -> usize {
+        let origins = internal_origins(policy);
+        let mut count = 0;
+        walk_mut(self, &mut |el| {
+            count += harden_link(el, &origins, policy.target_blank) as usize
+        });
+        count
+    }
+
+    fn find_trackers(&self, signatures: &[TrackerSignature]) -> Vec<TrackerMatch> {
+        let mut found = Vec::new();
+        find_trackers_in(self, signatures, &mut found);
+        found
+    }
+
+    fn remove_trackers(&mut self, signatures: &[TrackerSignature]) -> Vec<TrackerMatch> {
+        remove_trackers_in(self, signatures)
+    }
+
+    fn apply_scheme_policy(&mut self, policy: &SchemePolicy) -> Vec<UrlChange> {
+        let mut changes = Vec::new();
+        walk_mut(self, &mut |el| {
+            apply_element_scheme_policy(el, policy, &mut changes)
+        });
+        changes
+    }
+}
+
+impl Hardenable for Element {
+    fn strip_event_handlers(&mut self) -> Vec<RemovedAttr> {
+        let mut removed = Vec::new();
+        walk_element_mut(self, &mut |el| {
+            strip_element_attrs(el, &mut removed, |name, value| {
+                is_event_handler(name) || is_javascript_url(name, value)
+            })
+        });
+        removed
+    }
+
+    fn strip_attrs_with_prefix(&mut self, prefix: &str) -> Vec<RemovedAttr> {
+        let mut removed = Vec::new();
+        walk_element_mut(self, &mut |el| {
+            strip_element_attrs(el, &mut removed, |name, _| has_prefix(name, prefix))
+        });
+        removed
+    }
+
+    fn strip_attributes(&mut self, policy: &AttrPolicy) -> Vec<RemovedAttr> {
+        let mut removed = Vec::new();
+        walk_element_mut(self, &mut |el| {
+            let tag = el.name.clone();
+            strip_element_attrs(el, &mut removed, |name, _| !policy.allows(&tag, name))
+        });
+        removed
+    }
+
+    fn harden_iframes(&mut self, policy: &IframePolicy) -> Vec<HardenedIframe> {
+        let mut hardened = Vec::new();
+        walk_element_mut(self, &mut |el| harden_if_iframe(el, policy, &mut hardened));
+        hardened
+    }
+
+    fn harden_links(&mut self, policy: &LinkPolicy) -> usize {
+        let origins = internal_origins(policy);
+        let mut count = 0;
+        walk_element_mut(self, &mut |el| {
+            count += harden_link(el, &origins, policy.target_blank) as usize
+        });
+        count
+    }
+
+    fn find_trackers(&self, signatures: &[TrackerSignature]) -> Vec<TrackerMatch> {
+        let mut found = Vec::new();
+        find_trackers_in(&self.children, signatures, &mut found);
+        found
+    }
+
+    fn remove_trackers(&mut self, signatures: &[TrackerSignature]) -> Vec<TrackerMatch> {
+        remove_trackers_in(&mut self.children, signatures)
+    }
+
+    fn apply_scheme_policy(&mut self, policy: &SchemePolicy) -> Vec<UrlChange> {
+        let mut changes = Vec::new();
+        walk_element_mut(self, &mut |el| {
+            apply_element_scheme_policy(el, policy, &mut changes)
+        });
+        changes
+    }
+}
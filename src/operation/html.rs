@@ -1,4 +1,6 @@
-use crate::{data::VOID_TAGS, Doctype, Element, Node};
+use super::FROZEN_ATTR;
+use crate::data::{INLINE_TAGS, VOID_TAGS};
+use crate::{Doctype, Element, Node, IMPLICIT_ATTR};
 
 /// Stringify into html.
 pub trait Htmlifiable {
@@ -25,42 +27,481 @@ pub trait Htmlifiable {
     /// assert_eq!(element.html(), r#"<script src="index.js" defer></script>"#);
     /// ```
     fn html(&self) -> String;
+
+    /// Convert the object to html string like [`Htmlifiable::html`], with
+    /// the `options`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse(r#"<p class="note" title="A short note">One two three four</p>"#).unwrap();
+    /// let options = HtmlOptions {
+    ///     max_line_length: Some(20),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     dom.html_with_options(&options),
+    ///     "<p class=\"note\"\ntitle=\"A short note\">One\ntwo three four</p>"
+    /// );
+    /// ```
+    fn html_with_options(&self, options: &HtmlOptions) -> String;
+
+    /// Pretty-print the object, putting the block elements on their own
+    /// indented lines, and the inline content on one line with its
+    /// whitespace collapsed. The preformatted elements, like `<pre>` and
+    /// `<script>`, are kept as is.
+    ///
+    /// Formatting is idempotent: the html formatted again once parsed is
+    /// the same.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse("<div><h1>Title</h1>  Some\n  <b>bold </b>text<ul><li>One</li></ul></div>").unwrap();
+    /// let formatted = dom.format(&FormatOptions::default());
+    ///
+    /// assert_eq!(
+    ///     formatted,
+    ///     "<div>\n  <h1>Title</h1>\n  Some <b>bold</b> text\n  <ul>\n    <li>One</li>\n  </ul>\n</div>\n"
+    /// );
+    /// assert_eq!(parse(&formatted).unwrap().format(&FormatOptions::default()), formatted);
+    /// ```
+    fn format(&self, options: &FormatOptions) -> String;
+
+    /// Convert the object to html string like [`Htmlifiable::html`], with
+    /// ANSI colors for the tags, the attributes and the comments, and the
+    /// number of each line if `line_numbers`, to be printed in terminals.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse(r#"<p class="x">Hi</p>"#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     dom.render_ansi(false),
+    ///     "\x1b[34m<p\x1b[0m \x1b[36mclass\x1b[0m=\x1b[32m\"x\"\x1b[0m\x1b[34m>\x1b[0mHi\x1b[34m</p>\x1b[0m"
+    /// );
+    ///
+    /// let numbered = parse("<p>\nHi</p>").unwrap().render_ansi(true);
+    /// assert!(numbered.starts_with("\x1b[90m1 │\x1b[0m \x1b[34m<p"));
+    /// assert!(numbered.contains("\n\x1b[90m2 │\x1b[0m Hi"));
+    /// ```
+    fn render_ansi(&self, line_numbers: bool) -> String;
 }
 
-impl Htmlifiable for Element {
-    fn html(&self) -> String {
-        if self.attrs.is_empty() {
-            return if VOID_TAGS.contains(&self.name.as_str()) {
-                format!("<{}>", self.name)
-            } else {
-                format!("<{}>{}</{}>", self.name, self.children.html(), self.name)
-            };
+/// Options of [`Htmlifiable::format`].
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// The indentation of each level, two spaces by default.
+    pub indent: String,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: "  ".to_string(),
+        }
+    }
+}
+
+/// Options of [`Htmlifiable::html_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+    /// Wrap the lines longer than this number of characters where it
+    /// doesn't change the document: between the attributes of a tag, and
+    /// at the spaces of the text outside of `<pre>`, `<textarea>`,
+    /// `<script>` and `<style>`. The words and attributes longer than the
+    /// limit are kept on their own line.
+    pub max_line_length: Option<usize>,
+    /// Write the content of the elements synthesized with
+    /// [`ParseOptions::implicit_tags`](crate::ParseOptions::implicit_tags)
+    /// without their tags, to get back the parsed fragment.
+    pub omit_implicit_tags: bool,
+}
+
+/// Elements whose whitespace is significant.
+const PREFORMATTED_TAGS: [&str; 6] = ["listing", "plaintext", "pre", "script", "style", "textarea"];
+
+/// Elements formatted inline, on the line of their siblings, when they
+/// only have inline content, in addition to the [`INLINE_TAGS`].
+const PHRASING_TAGS: [&str; 11] = [
+    "br", "button", "code", "img", "input", "kbd", "label", "samp", "select", "textarea", "wbr",
+];
+
+/// Attribute `key` with its `value` in a tag, quoted with `'` if it
+/// only contains `"`, and escaping the `"` if it contains both.
+fn attr_html(key: &str, value: &str) -> String {
+    if value.is_empty() {
+        key.to_string()
+    } else if !value.contains('"') {
+        format!(r#"{}="{}""#, key, value)
+    } else if !value.contains('\'') {
+        format!("{}='{}'", key, value)
+    } else {
+        format!(r#"{}="{}""#, key, value.replace('"', "&quot;"))
+    }
+}
+
+/// Check if the attribute `key` is a marker, like [`FROZEN_ATTR`], which
+/// is not written into the html.
+fn is_marker(key: &str) -> bool {
+    key == FROZEN_ATTR || key == IMPLICIT_ATTR
+}
+
+/// Opening tag of `element`, with its attributes apart from the
+/// markers.
+pub(crate) fn open_tag(element: &Element) -> String {
+    let attrs = element
+        .attrs
+        .iter()
+        .filter(|(k, _)| !is_marker(k))
+        .map(|(k, v)| attr_html(k, v))
+        .collect::<Vec<_>>();
+    if attrs.is_empty() {
+        return format!("<{}>", element.name);
+    }
+    format!("<{} {}>", element.name, attrs.join(" "))
+}
+
+/// Closing tag of `element`, which is empty for void elements.
+pub(crate) fn close_tag(element: &Element) -> String {
+    if VOID_TAGS.contains(&element.name.as_str()) {
+        String::new()
+    } else {
+        format!("</{}>", element.name)
+    }
+}
+
+/// Html being written with [`HtmlOptions`], with the column it ends at.
+struct Writer<'a> {
+    options: &'a HtmlOptions,
+    html: String,
+    column: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(options: &'a HtmlOptions) -> Self {
+        Writer {
+            options,
+            html: String::new(),
+            column: 0,
+        }
+    }
+
+    fn push(&mut self, html: &str) {
+        match html.rfind('\n') {
+            Some(end) => self.column = html[end + 1..].chars().count(),
+            None => self.column += html.chars().count(),
+        }
+        self.html.push_str(html);
+    }
+
+    /// Write `html` after a separator which can be wrapped, if needed.
+    fn push_separated(&mut self, separator: char, html: &str) {
+        let overflows = self.options.max_line_length.is_some_and(|max| {
+            self.column > 0
+                && self.column + 1 + html.chars().take_while(|c| *c != '\n').count() > max
+        });
+        if overflows {
+            self.html.push('\n');
+            self.column = 0;
+        } else {
+            self.html.push(separator);
+            self.column += 1;
+        }
+        self.push(html);
+    }
+
+    fn text(&mut self, text: &str, preformatted: bool) {
+        if preformatted || self.options.max_line_length.is_none() {
+            return self.push(text);
+        }
+        let mut words = text.split(' ');
+        if let Some(first) = words.next() {
+            self.push(first);
+        }
+        for word in words {
+            self.push_separated(' ', word);
+        }
+    }
+
+    fn element(&mut self, element: &Element, preformatted: bool) {
+        if self.options.omit_implicit_tags && element.attrs.iter().any(|(k, _)| k == IMPLICIT_ATTR)
+        {
+            return self.nodes(&element.children, preformatted);
+        }
+        self.push("<");
+        self.push(&element.name);
+        for (key, value) in element.attrs.iter().filter(|(k, _)| !is_marker(k)) {
+            self.push_separated(' ', &attr_html(key, value));
+        }
+        self.push(">");
+        if VOID_TAGS.contains(&element.name.as_str()) {
+            return;
+        }
+        let preformatted = preformatted || PREFORMATTED_TAGS.contains(&element.name.as_str());
+        self.nodes(&element.children, preformatted);
+        self.push(&close_tag(element));
+    }
+
+    fn node(&mut self, node: &Node, preformatted: bool) {
+        match node {
+            Node::Element(element) => self.element(element, preformatted),
+            Node::Text(text) => self.text(text, preformatted),
+            node => self.push(&node.html()),
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node], preformatted: bool) {
+        for node in nodes {
+            self.node(node, preformatted);
         }
-        let attrs = self
-            .attrs
-            .iter()
-            .map(|(k, v)| {
-                if v.is_empty() {
-                    k.to_string()
-                } else {
-                    format!(r#"{}="{}""#, k, v)
+    }
+}
+
+const ANSI_TAG: &str = "\x1b[34m";
+const ANSI_ATTR_NAME: &str = "\x1b[36m";
+const ANSI_ATTR_VALUE: &str = "\x1b[32m";
+const ANSI_COMMENT: &str = "\x1b[90m";
+const ANSI_OTHER: &str = "\x1b[35m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Write `text` in `color`, resetting it at the end of each line for the
+/// line numbers.
+fn paint(html: &mut String, color: &str, text: &str) {
+    html.push_str(color);
+    html.push_str(&text.replace('\n', &format!("{}\n{}", ANSI_RESET, color)));
+    html.push_str(ANSI_RESET);
+}
+
+fn ansi_nodes(html: &mut String, nodes: &[Node]) {
+    for node in nodes {
+        match node {
+            Node::Element(element) => {
+                paint(html, ANSI_TAG, &format!("<{}", element.name));
+                for (key, value) in element.attrs.iter().filter(|(k, _)| !is_marker(k)) {
+                    html.push(' ');
+                    paint(html, ANSI_ATTR_NAME, key);
+                    let attr = attr_html(key, value);
+                    if let Some(value) = attr.get(key.len() + 1..) {
+                        html.push('=');
+                        paint(html, ANSI_ATTR_VALUE, value);
+                    }
+                }
+                paint(html, ANSI_TAG, ">");
+                if !VOID_TAGS.contains(&element.name.as_str()) {
+                    ansi_nodes(html, &element.children);
+                    paint(html, ANSI_TAG, &close_tag(element));
                 }
-            })
-            .collect::<Vec<_>>()
-            .join(" ");
+            }
+            Node::Text(text) => html.push_str(text),
+            Node::Comment(_) => paint(html, ANSI_COMMENT, &node.html()),
+            _ => paint(html, ANSI_OTHER, &node.html()),
+        }
+    }
+}
+
+/// Render `nodes` with [`Htmlifiable::render_ansi`].
+fn render_ansi_nodes(nodes: &[Node], line_numbers: bool) -> String {
+    let mut html = String::new();
+    ansi_nodes(&mut html, nodes);
+    if !line_numbers {
+        return html;
+    }
+    let lines: Vec<&str> = html.split('\n').collect();
+    let width = lines.len().to_string().len();
+    let numbered: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            format!(
+                "{}{:>width$} │{} {}",
+                ANSI_COMMENT,
+                i + 1,
+                ANSI_RESET,
+                line,
+                width = width
+            )
+        })
+        .collect();
+    numbered.join("\n")
+}
+
+fn is_inline(node: &Node) -> bool {
+    match node {
+        Node::Element(element) => {
+            let name = element.name.as_str();
+            (INLINE_TAGS.contains(&name) || PHRASING_TAGS.contains(&name))
+                && (PREFORMATTED_TAGS.contains(&name) || element.children.iter().all(is_inline))
+        }
+        Node::Doctype(_) => false,
+        _ => true,
+    }
+}
+
+/// Html of inline content, with its whitespace collapsed.
+#[derive(Default)]
+struct InlineWriter {
+    html: String,
+    /// Whether the whitespace is collapsed into the previous one, being
+    /// at the start or after a space.
+    after_space: bool,
+    /// Whether to write a space before the next content.
+    space: bool,
+}
+
+impl InlineWriter {
+    /// Write `html` after the pending space, if any.
+    fn push(&mut self, html: &str) {
+        if self.space {
+            self.html.push(' ');
+            self.space = false;
+        }
+        self.html.push_str(html);
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => {
+                    for ch in text.chars() {
+                        if ch.is_ascii_whitespace() {
+                            self.space |= !self.after_space;
+                        } else {
+                            self.push(ch.encode_utf8(&mut [0; 4]));
+                            self.after_space = false;
+                        }
+                    }
+                }
+                Node::Element(element) if !PREFORMATTED_TAGS.contains(&element.name.as_str()) => {
+                    self.after_space |= self.space;
+                    self.push(&open_tag(element));
+                    self.nodes(&element.children);
+                    // The trailing space goes after the closing tag.
+                    self.html.push_str(&close_tag(element));
+                    if VOID_TAGS.contains(&element.name.as_str()) {
+                        self.after_space = false;
+                    }
+                }
+                node => {
+                    self.push(&node.html());
+                    self.after_space = false;
+                }
+            }
+        }
+    }
+}
+
+/// Html being pretty-printed with [`FormatOptions`].
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    html: String,
+}
+
+impl Formatter<'_> {
+    fn line(&mut self, depth: usize, line: &str) {
+        for _ in 0..depth {
+            self.html.push_str(&self.options.indent);
+        }
+        self.html.push_str(line);
+        self.html.push('\n');
+    }
 
+    fn inline(&mut self, depth: usize, nodes: &[Node]) {
+        let mut writer = InlineWriter {
+            after_space: true,
+            ..InlineWriter::default()
+        };
+        writer.nodes(nodes);
+        if !writer.html.is_empty() {
+            self.line(depth, &writer.html);
+        }
+    }
+
+    fn nodes(&mut self, depth: usize, nodes: &[Node]) {
+        let mut start = 0;
+        for (i, node) in nodes.iter().enumerate() {
+            if !is_inline(node) {
+                self.inline(depth, &nodes[start..i]);
+                self.block(depth, node);
+                start = i + 1;
+            }
+        }
+        self.inline(depth, &nodes[start..]);
+    }
+
+    fn block(&mut self, depth: usize, node: &Node) {
+        match node {
+            Node::Element(element) => self.element(depth, element),
+            node => self.line(depth, &node.html()),
+        }
+    }
+
+    fn element(&mut self, depth: usize, element: &Element) {
+        if PREFORMATTED_TAGS.contains(&element.name.as_str()) {
+            self.line(depth, &element.html());
+        } else if element.children.iter().all(is_inline) {
+            let mut writer = InlineWriter {
+                after_space: true,
+                ..InlineWriter::default()
+            };
+            writer.nodes(&element.children);
+            let line = format!("{}{}{}", open_tag(element), writer.html, close_tag(element));
+            self.line(depth, &line);
+        } else {
+            self.line(depth, &open_tag(element));
+            self.nodes(depth + 1, &element.children);
+            self.line(depth, &close_tag(element));
+        }
+    }
+
+    /// Format `nodes` at the top level.
+    fn format(options: &FormatOptions, nodes: &[Node]) -> String {
+        let mut formatter = Formatter {
+            options,
+            html: String::new(),
+        };
+        formatter.nodes(0, nodes);
+        formatter.html
+    }
+}
+
+impl Htmlifiable for Element {
+    fn html(&self) -> String {
         if VOID_TAGS.contains(&self.name.as_str()) {
-            format!("<{} {}>", self.name, attrs,)
+            open_tag(self)
         } else {
             format!(
-                "<{} {}>{}</{}>",
-                self.name,
-                attrs,
+                "{}{}{}",
+                open_tag(self),
                 self.children.html(),
-                self.name
+                close_tag(self)
             )
         }
     }
+
+    fn html_with_options(&self, options: &HtmlOptions) -> String {
+        let mut writer = Writer::new(options);
+        writer.element(self, false);
+        writer.html
+    }
+
+    fn format(&self, options: &FormatOptions) -> String {
+        let mut formatter = Formatter {
+            options,
+            html: String::new(),
+        };
+        formatter.element(0, self);
+        formatter.html
+    }
+
+    fn render_ansi(&self, line_numbers: bool) -> String {
+        render_ansi_nodes(&[Node::Element(self.clone())], line_numbers)
+    }
 }
 
 impl Htmlifiable for Node {
@@ -69,6 +510,8 @@ impl Htmlifiable for Node {
             Node::Element(element) => element.html(),
             Node::Text(text) => text.to_string(),
             Node::Comment(comment) => format!("<!--{}-->", comment),
+            Node::Template(template) => template.to_string(),
+            Node::ProcessingInstruction(instruction) => format!("<?{}?>", instruction),
             Node::Doctype(doctype) => match &doctype {
                 Doctype::Html => "<!DOCTYPE html>".to_string(),
                 Doctype::Xml { version, encoding } => {
@@ -77,6 +520,20 @@ impl Htmlifiable for Node {
             },
         }
     }
+
+    fn html_with_options(&self, options: &HtmlOptions) -> String {
+        let mut writer = Writer::new(options);
+        writer.node(self, false);
+        writer.html
+    }
+
+    fn format(&self, options: &FormatOptions) -> String {
+        Formatter::format(options, std::slice::from_ref(self))
+    }
+
+    fn render_ansi(&self, line_numbers: bool) -> String {
+        render_ansi_nodes(std::slice::from_ref(self), line_numbers)
+    }
 }
 
 impl Htmlifiable for Vec<Node> {
@@ -87,4 +544,18 @@ impl Htmlifiable for Vec<Node> {
         }
         html
     }
+
+    fn html_with_options(&self, options: &HtmlOptions) -> String {
+        let mut writer = Writer::new(options);
+        writer.nodes(self, false);
+        writer.html
+    }
+
+    fn format(&self, options: &FormatOptions) -> String {
+        Formatter::format(options, self)
+    }
+
+    fn render_ansi(&self, line_numbers: bool) -> String {
+        render_ansi_nodes(self, line_numbers)
+    }
 }
@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use super::{Htmlifiable, Queryable, Selector};
+use crate::{Element, Node};
+
+/// Generator of unique ids for the elements inserted into a document.
+///
+/// The ids are derived from the content of the document and a seed,
+/// without any randomness, so the same document always gets the same
+/// ids. They never collide with the ids of the document, nor with each
+/// other.
+///
+/// ```
+/// use html_editor::{parse, Node};
+/// use html_editor::operation::*;
+///
+/// let mut dom = parse(r#"<main id="main"></main>"#).unwrap();
+///
+/// let mut ids = IdGenerator::new(&dom, 0).prefix("note-");
+/// let mut note = Node::new_element("aside", vec![], vec![]);
+/// let id = ids.assign(note.as_element_mut().unwrap());
+///
+/// assert!(id.starts_with("note-"));
+/// assert_eq!(IdGenerator::new(&dom, 0).prefix("note-").next_id(), id);
+/// assert_ne!(IdGenerator::new(&dom, 1).prefix("note-").next_id(), id);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IdGenerator {
+    prefix: String,
+    state: u64,
+    used: HashSet<String>,
+}
+
+impl IdGenerator {
+    /// Create a generator for the document `nodes`.
+    pub fn new(nodes: &[Node], seed: u64) -> Self {
+        let selector = Selector::from("[id]");
+        let used = nodes
+            .iter()
+            .flat_map(|node| node.query_all(&selector))
+            .filter_map(|element| element.get_attr("id"))
+            .map(str::to_string)
+            .collect();
+        let html: String = nodes.iter().map(Htmlifiable::html).collect();
+        Self {
+            prefix: "id-".to_string(),
+            state: fnv1a(html.as_bytes()) ^ seed,
+            used,
+        }
+    }
+
+    /// Set the prefix of the ids, `id-` by default.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Generate a new id.
+    pub fn next_id(&mut self) -> String {
+        loop {
+            let value = self.next_value() % 36u64.pow(6);
+            let id = format!("{}{}", self.prefix, base36(value));
+            if self.used.insert(id.clone()) {
+                return id;
+            }
+        }
+    }
+
+    /// Get the id of `element`, giving it a new one if it has none.
+    pub fn assign(&mut self, element: &mut Element) -> String {
+        if let Some(id) = element.get_attr("id") {
+            let id = id.to_string();
+            self.used.insert(id.clone());
+            return id;
+        }
+        let id = self.next_id();
+        element.set_attr("id", &id);
+        id
+    }
+
+    /// Step the state with splitmix64.
+    fn next_value(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Hash `bytes` with FNV-1a, which unlike the std hashers is stable
+/// between Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+/// Format `value` in base 36, padded to 6 digits.
+fn base36(mut value: u64) -> String {
+    let mut digits = [b'0'; 6];
+    for digit in digits.iter_mut().rev() {
+        *digit = b"0123456789abcdefghijklmnopqrstuvwxyz"[(value % 36) as usize];
+        value /= 36;
+    }
+    String::from_utf8_lossy(&digits).into_owned()
+}
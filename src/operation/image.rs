@@ -0,0 +1,411 @@
+use super::selector::Matcher;
+use super::walk::is_frozen;
+use super::{Editable, Selector};
+use crate::srcset::{self, Candidate};
+use crate::{Element, Node};
+
+/// Marks the `<noscript>` inserted by [`Optimizable::inject_noscript`].
+const FALLBACK_ATTR: &str = "data-noscript-fallback";
+
+/// Alternative format offered by [`Optimizable::to_picture`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PictureSource {
+    /// Value of the `type` attribute, like `image/webp`.
+    pub mime: String,
+    /// Extension replacing the one of the image urls, like `webp`.
+    pub extension: String,
+}
+
+impl PictureSource {
+    pub fn new(mime: &str, extension: &str) -> Self {
+        Self {
+            mime: mime.to_string(),
+            extension: extension.to_string(),
+        }
+    }
+}
+
+/// Optimize how the images of the document are loaded.
+pub trait Optimizable {
+    /// Give the matched `<img>` elements a `srcset` with one candidate
+    /// per width in `widths`, whose url is built by `url` from the `src`
+    /// of the image and the width. `sizes` is set too when given.
+    ///
+    /// Width candidates already in the `srcset` are kept unless they are
+    /// generated again, while density ones like `2x` are dropped since
+    /// they cannot be mixed with widths. Images without `src` are left
+    /// untouched.
+    ///
+    /// Returns the number of images rewritten.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<img src="/cat.jpg">"#).unwrap();
+    /// dom.generate_srcset(&Selector::from("img"), &[320, 640], Some("100vw"), |src, width| {
+    ///     format!("{}?w={}", src, width)
+    /// });
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<img src="/cat.jpg" srcset="/cat.jpg?w=320 320w, /cat.jpg?w=640 640w" sizes="100vw">"#
+    /// );
+    /// ```
+    fn generate_srcset(
+        &mut self,
+        selector: &Selector,
+        widths: &[u32],
+        sizes: Option<&str>,
+        url: impl FnMut(&str, u32) -> String,
+    ) -> usize;
+
+    /// Wrap the matched `<img>` elements into a `<picture>` offering each
+    /// of `sources` before the image, which is kept untouched as the
+    /// fallback. The urls of the sources are the ones of the `srcset`,
+    /// or else of the `src`, with their extension replaced.
+    ///
+    /// Images already inside a `<picture>` are skipped. Returns the
+    /// number of images wrapped.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<img src="/cat.jpg">"#).unwrap();
+    /// dom.to_picture(&Selector::from("img"), &[PictureSource::new("image/webp", "webp")]);
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<picture><source type="image/webp" srcset="/cat.webp"><img src="/cat.jpg"></picture>"#
+    /// );
+    /// ```
+    fn to_picture(&mut self, selector: &Selector, sources: &[PictureSource]) -> usize;
+
+    /// Insert after the matched elements a `<noscript>` holding the
+    /// nodes returned by `fallback`, like the eager copy of a lazy image
+    /// built by [`eager_image`].
+    ///
+    /// The inserted `<noscript>` is marked with `data-noscript-fallback`,
+    /// so that running this again updates it, or removes it when
+    /// `fallback` now returns `None`, instead of adding another one.
+    /// Elements inside a `<noscript>` are skipped.
+    ///
+    /// Returns the number of fallbacks inserted or updated.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<img data-src="/cat.jpg">"#).unwrap();
+    /// dom.inject_noscript(&Selector::from("img"), eager_image);
+    /// dom.inject_noscript(&Selector::from("img"), eager_image);
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<img data-src="/cat.jpg"><noscript data-noscript-fallback><img src="/cat.jpg"></noscript>"#
+    /// );
+    /// ```
+    fn inject_noscript(
+        &mut self,
+        selector: &Selector,
+        fallback: impl FnMut(&Element) -> Option<Vec<Node>>,
+    ) -> usize;
+}
+
+/// Build the eager copy of a lazy-loaded image, moving `data-src`,
+/// `data-srcset` and `data-sizes` to the real attributes and dropping
+/// `loading`. Meant to be used with [`Optimizable::inject_noscript`].
+///
+/// Returns `None` for elements which are not images or have no source.
+///
+/// ```
+/// use html_editor::{Element, Node};
+/// use html_editor::operation::*;
+///
+/// let img = Element::new("img", vec![("loading", "lazy"), ("src", "/cat.jpg")], vec![]);
+/// let copy = eager_image(&img).unwrap();
+///
+/// assert_eq!(copy.html(), r#"<img src="/cat.jpg">"#);
+/// ```
+pub fn eager_image(img: &Element) -> Option<Vec<Node>> {
+    if img.name != "img" {
+        return None;
+    }
+    let mut copy = Element::new("img", vec![], vec![]);
+    for (key, value) in &img.attrs {
+        match key.as_str() {
+            "loading" => {}
+            "data-src" | "data-srcset" | "data-sizes" => copy.set_attr(&key[5..], value),
+            "src" | "srcset" | "sizes" if copy.get_attr(key).is_some() => {}
+            _ => copy.set_attr(key, value),
+        }
+    }
+    if copy.get_attr("src").is_none() && copy.get_attr("srcset").is_none() {
+        return None;
+    }
+    Some(vec![copy.into_node()])
+}
+
+fn generate_element_srcset(
+    img: &mut Element,
+    widths: &[u32],
+    sizes: Option<&str>,
+    url: &mut impl FnMut(&str, u32) -> String,
+) -> bool {
+    if img.name != "img" {
+        return false;
+    }
+    let src = match img.get_attr("src") {
+        Some(src) => src.to_string(),
+        None => return false,
+    };
+
+    let mut candidates: Vec<Candidate> = widths
+        .iter()
+        .map(|&width| Candidate {
+            url: url(&src, width),
+            descriptor: Some(format!("{}w", width)),
+        })
+        .collect();
+    let kept = srcset::parse(img.get_attr("srcset").unwrap_or(""))
+        .into_iter()
+        .filter(|old| old.width().is_some_and(|width| !widths.contains(&width)));
+    candidates.extend(kept);
+    candidates.sort_by_key(|candidate| candidate.width());
+
+    img.set_attr("srcset", &srcset::serialize(&candidates));
+    if let Some(sizes) = sizes {
+        img.set_attr("sizes", sizes);
+    }
+    true
+}
+
+/// Replace the extension of the path of `url`, keeping its query and
+/// fragment.
+fn replace_extension(url: &str, extension: &str) -> String {
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(end);
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    let stem = match path[name_start..].rfind('.') {
+        Some(dot) => &path[..name_start + dot],
+        None => path,
+    };
+    format!("{}.{}{}", stem, extension, suffix)
+}
+
+fn picture_of(img: Element, sources: &[PictureSource]) -> Element {
+    let candidates = match img.get_attr("srcset") {
+        Some(srcset) => srcset::parse(srcset),
+        None => vec![Candidate {
+            url: img.get_attr("src").unwrap_or("").to_string(),
+            descriptor: None,
+        }],
+    };
+    let mut children: Vec<Node> = sources
+        .iter()
+        .map(|source| {
+            let candidates: Vec<Candidate> = candidates
+                .iter()
+                .map(|candidate| Candidate {
+                    url: replace_extension(&candidate.url, &source.extension),
+                    descriptor: candidate.descriptor.clone(),
+                })
+                .collect();
+            let mut element = Element::new("source", vec![("type", &source.mime)], vec![]);
+            element.set_attr("srcset", &srcset::serialize(&candidates));
+            if let Some(sizes) = img.get_attr("sizes") {
+                element.set_attr("sizes", sizes);
+            }
+            element.into_node()
+        })
+        .collect();
+    children.push(img.into_node());
+    Element::new("picture", vec![], children)
+}
+
+fn nodes_to_picture(
+    nodes: &mut [Node],
+    matcher: Matcher,
+    sources: &[PictureSource],
+    in_picture: bool,
+) -> usize {
+    let mut count = 0;
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if let Node::Element(el) = node {
+            let (matched, children) = matcher.child(index, el);
+            count += element_to_picture(el, matched, children, sources, in_picture);
+        }
+    }
+    count
+}
+
+/// Wrap `element` in a `<picture>` if it is a `matched` image, or the
+/// images among its descendants matched by `children`.
+fn element_to_picture(
+    element: &mut Element,
+    matched: bool,
+    children: Matcher,
+    sources: &[PictureSource],
+    in_picture: bool,
+) -> usize {
+    if is_frozen(element) {
+        return 0;
+    }
+    let has_src = element.get_attr("srcset").is_some() || element.get_attr("src").is_some();
+    if element.name == "img" && !in_picture && has_src && matched {
+        let img = std::mem::replace(element, Element::new("picture", vec![], vec![]));
+        *element = picture_of(img, sources);
+        return 1;
+    }
+    let in_picture = element.name == "picture";
+    nodes_to_picture(&mut element.children, children, sources, in_picture)
+}
+
+fn is_fallback(node: &Node) -> bool {
+    node.as_element()
+        .is_some_and(|el| el.name == "noscript" && el.get_attr(FALLBACK_ATTR).is_some())
+}
+
+fn inject_nodes_noscript(
+    nodes: &mut Vec<Node>,
+    matcher: Matcher,
+    fallback: &mut impl FnMut(&Element) -> Option<Vec<Node>>,
+) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    // The fallbacks inserted and removed before `i`, to get the index of
+    // the node as the marks of the matcher know it.
+    let (mut inserted, mut removed) = (0, 0);
+    while i < nodes.len() {
+        let index = i + removed - inserted;
+        let element = match &mut nodes[i] {
+            Node::Element(el) if el.name != "noscript" && !is_frozen(el) => el,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        let (matched, children) = matcher.child(index, element);
+        count += inject_element_noscript(element, children, fallback);
+        if !matched {
+            i += 1;
+            continue;
+        }
+
+        let children = fallback(element);
+        let next = nodes[i + 1..]
+            .iter()
+            .position(|node| !matches!(node, Node::Text(text) if text.trim().is_empty()))
+            .map(|offset| i + 1 + offset)
+            .filter(|&next| is_fallback(&nodes[next]));
+        match (children, next) {
+            (Some(children), Some(next)) => {
+                if let Node::Element(noscript) = &mut nodes[next] {
+                    noscript.children = children;
+                }
+                count += 1;
+                i = next + 1;
+            }
+            (Some(children), None) => {
+                let noscript = Node::new_element("noscript", vec![(FALLBACK_ATTR, "")], children);
+                nodes.insert(i + 1, noscript);
+                count += 1;
+                inserted += 1;
+                i += 2;
+            }
+            (None, Some(next)) => {
+                nodes.remove(next);
+                removed += 1;
+                i += 1;
+            }
+            (None, None) => i += 1,
+        }
+    }
+    count
+}
+
+/// Inject the fallbacks of the descendants of `element` matched by
+/// `children`.
+fn inject_element_noscript(
+    element: &mut Element,
+    children: Matcher,
+    fallback: &mut impl FnMut(&Element) -> Option<Vec<Node>>,
+) -> usize {
+    if element.name == "noscript" || is_frozen(element) {
+        return 0;
+    }
+    inject_nodes_noscript(&mut element.children, children, fallback)
+}
+
+impl Optimizable for Vec<Node> {
+    fn generate_srcset(
+        &mut self,
+        selector: &Selector,
+        widths: &[u32],
+        sizes: Option<&str>,
+        mut url: impl FnMut(&str, u32) -> String,
+    ) -> usize {
+        let mut count = 0;
+        self.execute_for(selector, |img| {
+            if generate_element_srcset(img, widths, sizes, &mut url) {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    fn to_picture(&mut self, selector: &Selector, sources: &[PictureSource]) -> usize {
+        let marks = selector.marks(self);
+        nodes_to_picture(
+            self,
+            Matcher::new(selector, marks.as_deref()),
+            sources,
+            false,
+        )
+    }
+
+    fn inject_noscript(
+        &mut self,
+        selector: &Selector,
+        mut fallback: impl FnMut(&Element) -> Option<Vec<Node>>,
+    ) -> usize {
+        let marks = selector.marks(self);
+        let matcher = Matcher::new(selector, marks.as_deref());
+        inject_nodes_noscript(self, matcher, &mut fallback)
+    }
+}
+
+impl Optimizable for Element {
+    fn generate_srcset(
+        &mut self,
+        selector: &Selector,
+        widths: &[u32],
+        sizes: Option<&str>,
+        mut url: impl FnMut(&str, u32) -> String,
+    ) -> usize {
+        let mut count = 0;
+        self.execute_for(selector, |img| {
+            if generate_element_srcset(img, widths, sizes, &mut url) {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    fn to_picture(&mut self, selector: &Selector, sources: &[PictureSource]) -> usize {
+        let marks = selector.element_marks(self);
+        let (matched, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        element_to_picture(self, matched, children, sources, false)
+    }
+
+    fn inject_noscript(
+        &mut self,
+        selector: &Selector,
+        mut fallback: impl FnMut(&Element) -> Option<Vec<Node>>,
+    ) -> usize {
+        let marks = selector.element_marks(self);
+        let (_, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        inject_element_noscript(self, children, &mut fallback)
+    }
+}
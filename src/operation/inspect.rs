@@ -0,0 +1,370 @@
+use std::fmt;
+
+use super::{Editable, Queryable, Selector};
+use crate::{Element, Node};
+
+/// Redirect declared by `<meta http-equiv="refresh">`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaRefresh {
+    /// Delay in seconds.
+    pub delay: u32,
+    /// Target of the redirect, or `None` if the page just reloads.
+    pub url: Option<String>,
+}
+
+impl MetaRefresh {
+    /// Parse the `content` of a meta refresh, like `5; url=/next`,
+    /// following the leniency of browsers.
+    ///
+    /// ```
+    /// use html_editor::operation::MetaRefresh;
+    ///
+    /// let refresh = MetaRefresh::parse("0;URL='https://example.com'").unwrap();
+    /// assert_eq!(refresh.delay, 0);
+    /// assert_eq!(refresh.url.as_deref(), Some("https://example.com"));
+    ///
+    /// assert!(MetaRefresh::parse("soon").is_none());
+    /// ```
+    pub fn parse(content: &str) -> Option<Self> {
+        let content = content.trim_start();
+        let digits = content
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(content.len());
+        let delay = match &content[..digits] {
+            "" if content.starts_with('.') => 0,
+            "" => return None,
+            delay => delay.parse().unwrap_or(u32::MAX),
+        };
+
+        let rest = content[digits..].trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+        let rest = rest.trim_start();
+        let rest = rest.strip_prefix([';', ',']).unwrap_or(rest).trim_start();
+        let rest = match rest.get(..3) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("url") => {
+                match rest[3..].trim_start().strip_prefix('=') {
+                    Some(url) => url.trim_start(),
+                    None => rest,
+                }
+            }
+            _ => rest,
+        };
+        let url = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or(""),
+            _ => rest.trim_end(),
+        };
+
+        Some(Self {
+            delay,
+            url: (!url.is_empty()).then(|| url.to_string()),
+        })
+    }
+}
+
+/// Language variant declared by `<link rel="alternate" hreflang="...">`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlternateLink {
+    pub hreflang: String,
+    pub href: String,
+}
+
+/// Width set by the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewportWidth {
+    DeviceWidth,
+    Pixels(u32),
+}
+
+/// Content of `<meta name="viewport">`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Viewport {
+    pub width: Option<ViewportWidth>,
+    pub initial_scale: Option<f32>,
+    pub user_scalable: Option<bool>,
+    /// The other properties, like `maximum-scale`, or the ones above
+    /// when their value is invalid, kept in order.
+    pub other: Vec<(String, String)>,
+}
+
+impl Viewport {
+    /// Parse the `content` of a viewport meta.
+    ///
+    /// ```
+    /// use html_editor::operation::*;
+    ///
+    /// let viewport = Viewport::parse("width=device-width, initial-scale=1, user-scalable=no");
+    /// assert_eq!(viewport.width, Some(ViewportWidth::DeviceWidth));
+    /// assert_eq!(viewport.initial_scale, Some(1.0));
+    /// assert_eq!(viewport.user_scalable, Some(false));
+    /// ```
+    pub fn parse(content: &str) -> Self {
+        let mut viewport = Viewport::default();
+        for property in content.split([',', ';']) {
+            let (key, value) = property.split_once('=').unwrap_or((property, ""));
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            let parsed = match key.as_str() {
+                "width" if value.eq_ignore_ascii_case("device-width") => {
+                    viewport.width = Some(ViewportWidth::DeviceWidth);
+                    true
+                }
+                "width" => value
+                    .parse()
+                    .map(|px| viewport.width = Some(ViewportWidth::Pixels(px)))
+                    .is_ok(),
+                "initial-scale" => value
+                    .parse()
+                    .map(|scale| viewport.initial_scale = Some(scale))
+                    .is_ok(),
+                "user-scalable" => match value.to_ascii_lowercase().as_str() {
+                    "yes" | "1" => {
+                        viewport.user_scalable = Some(true);
+                        true
+                    }
+                    "no" | "0" => {
+                        viewport.user_scalable = Some(false);
+                        true
+                    }
+                    _ => false,
+                },
+                "" => continue,
+                _ => false,
+            };
+            if !parsed {
+                viewport.other.push((key, value.to_string()));
+            }
+        }
+        viewport
+    }
+
+    /// Set the width, returning `self` for chaining.
+    pub fn width(mut self, width: ViewportWidth) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set the initial scale, returning `self` for chaining.
+    pub fn initial_scale(mut self, scale: f32) -> Self {
+        self.initial_scale = Some(scale);
+        self
+    }
+
+    /// Set whether users can zoom, returning `self` for chaining.
+    pub fn user_scalable(mut self, scalable: bool) -> Self {
+        self.user_scalable = Some(scalable);
+        self
+    }
+}
+
+impl fmt::Display for Viewport {
+    /// Format the viewport back into the `content` of the meta.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut properties = Vec::new();
+        match self.width {
+            Some(ViewportWidth::DeviceWidth) => properties.push("width=device-width".to_string()),
+            Some(ViewportWidth::Pixels(px)) => properties.push(format!("width={}", px)),
+            None => {}
+        }
+        if let Some(scale) = self.initial_scale {
+            properties.push(format!("initial-scale={}", scale));
+        }
+        if let Some(scalable) = self.user_scalable {
+            let scalable = if scalable { "yes" } else { "no" };
+            properties.push(format!("user-scalable={}", scalable));
+        }
+        for (key, value) in &self.other {
+            properties.push(format!("{}={}", key, value));
+        }
+        write!(f, "{}", properties.join(", "))
+    }
+}
+
+/// Inspect the metadata declared in the head of a document.
+pub trait Inspectable {
+    /// Get the redirect of the first valid `<meta http-equiv="refresh">`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<meta http-equiv="refresh" content="3; url=/moved">"#;
+    /// let refresh = parse(html).unwrap().meta_refresh().unwrap();
+    ///
+    /// assert_eq!(refresh, MetaRefresh { delay: 3, url: Some("/moved".to_string()) });
+    /// ```
+    fn meta_refresh(&self) -> Option<MetaRefresh>;
+
+    /// Get the `href` of the first `<link rel="canonical">`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<link rel="canonical" href="https://example.com/">"#;
+    /// assert_eq!(parse(html).unwrap().canonical(), Some("https://example.com/"));
+    /// ```
+    fn canonical(&self) -> Option<&str>;
+
+    /// Get the language variants declared by the
+    /// `<link rel="alternate" hreflang="...">` elements.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<link rel="alternate" hreflang="fr" href="/fr/">"#;
+    /// let alternates = parse(html).unwrap().alternates();
+    ///
+    /// assert_eq!(alternates[0].hreflang, "fr");
+    /// assert_eq!(alternates[0].href, "/fr/");
+    /// ```
+    fn alternates(&self) -> Vec<AlternateLink>;
+
+    /// Get the viewport declared by the first `<meta name="viewport">`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"<meta name="viewport" content="width=1024">"#;
+    /// let viewport = parse(html).unwrap().viewport().unwrap();
+    ///
+    /// assert_eq!(viewport.width, Some(ViewportWidth::Pixels(1024)));
+    /// ```
+    fn viewport(&self) -> Option<Viewport>;
+
+    /// Set the viewport, updating the first `<meta name="viewport">` or
+    /// adding one at the end of the `<head>`.
+    ///
+    /// Returns `false` if there is neither a viewport meta nor a head
+    /// to add it into.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<head></head>").unwrap();
+    /// let viewport = Viewport::default()
+    ///     .width(ViewportWidth::DeviceWidth)
+    ///     .initial_scale(1.0);
+    /// dom.set_viewport(&viewport);
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<head><meta name="viewport" content="width=device-width, initial-scale=1"></head>"#
+    /// );
+    /// ```
+    fn set_viewport(&mut self, viewport: &Viewport) -> bool;
+}
+
+fn has_token(element: &Element, name: &str, token: &str) -> bool {
+    element.get_attr(name).is_some_and(|value| {
+        value
+            .split_ascii_whitespace()
+            .any(|t| t.eq_ignore_ascii_case(token))
+    })
+}
+
+fn meta_refresh_of(metas: Vec<&Element>) -> Option<MetaRefresh> {
+    metas
+        .into_iter()
+        .filter(|meta| has_token(meta, "http-equiv", "refresh"))
+        .find_map(|meta| MetaRefresh::parse(meta.get_attr("content")?))
+}
+
+fn canonical_of(links: Vec<&Element>) -> Option<&str> {
+    links
+        .into_iter()
+        .filter(|link| has_token(link, "rel", "canonical"))
+        .find_map(|link| link.get_attr("href"))
+}
+
+fn alternates_of(links: Vec<&Element>) -> Vec<AlternateLink> {
+    links
+        .into_iter()
+        .filter(|link| has_token(link, "rel", "alternate"))
+        .filter_map(|link| {
+            Some(AlternateLink {
+                hreflang: link.get_attr("hreflang")?.to_string(),
+                href: link.get_attr("href")?.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn is_viewport(meta: &Element) -> bool {
+    has_token(meta, "name", "viewport")
+}
+
+fn viewport_of(metas: Vec<&Element>) -> Option<Viewport> {
+    let meta = metas.into_iter().find(|meta| is_viewport(meta))?;
+    Some(Viewport::parse(meta.get_attr("content").unwrap_or("")))
+}
+
+fn set_viewport_in<T: Queryable + Editable>(target: &mut T, viewport: &Viewport) -> bool {
+    let content = viewport.to_string();
+    let mut updated = false;
+    target.execute_for(&Selector::from("meta"), |meta| {
+        if !updated && is_viewport(meta) {
+            meta.set_attr("content", &content);
+            updated = true;
+        }
+    });
+    if updated {
+        return true;
+    }
+    match target.query_mut(&Selector::from("head")) {
+        Some(head) => {
+            let meta = Node::new_element(
+                "meta",
+                vec![("name", "viewport"), ("content", &content)],
+                vec![],
+            );
+            head.children.push(meta);
+            true
+        }
+        None => false,
+    }
+}
+
+impl Inspectable for Vec<Node> {
+    fn meta_refresh(&self) -> Option<MetaRefresh> {
+        meta_refresh_of(self.query_all(&Selector::from("meta")))
+    }
+
+    fn canonical(&self) -> Option<&str> {
+        canonical_of(self.query_all(&Selector::from("link")))
+    }
+
+    fn alternates(&self) -> Vec<AlternateLink> {
+        alternates_of(self.query_all(&Selector::from("link")))
+    }
+
+    fn viewport(&self) -> Option<Viewport> {
+        viewport_of(self.query_all(&Selector::from("meta")))
+    }
+
+    fn set_viewport(&mut self, viewport: &Viewport) -> bool {
+        set_viewport_in(self, viewport)
+    }
+}
+
+impl Inspectable for Element {
+    fn meta_refresh(&self) -> Option<MetaRefresh> {
+        meta_refresh_of(self.query_all(&Selector::from("meta")))
+    }
+
+    fn canonical(&self) -> Option<&str> {
+        canonical_of(self.query_all(&Selector::from("link")))
+    }
+
+    fn alternates(&self) -> Vec<AlternateLink> {
+        alternates_of(self.query_all(&Selector::from("link")))
+    }
+
+    fn viewport(&self) -> Option<Viewport> {
+        viewport_of(self.query_all(&Selector::from("meta")))
+    }
+
+    fn set_viewport(&mut self, viewport: &Viewport) -> bool {
+        set_viewport_in(self, viewport)
+    }
+}
@@ -0,0 +1,241 @@
+use super::walk::{walk_element_mut, walk_mut};
+use crate::data::{JSX_ATTRS, JSX_EVENTS, VOID_TAGS};
+use crate::{css, entity, Element, Node};
+
+/// Naming of the attributes, for [`Jsxifiable::rename_attrs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrProfile {
+    /// Html names, like `class` and `onclick`.
+    Html,
+    /// JSX names, like `className` and `onClick`.
+    Jsx,
+}
+
+/// Translate between html and JSX.
+pub trait Jsxifiable {
+    /// Stringify into JSX: the attributes get their JSX name, the inline
+    /// styles become objects, the void elements are self-closed, and the
+    /// braces of the text, the scripts and the comments are escaped.
+    ///
+    /// The values of the event handlers are kept as strings, so they
+    /// need to be rewritten into functions.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse(r#"<label style="margin-top: 0">Name {required}</label><br>"#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     dom.jsx(),
+    ///     r#"<label style={{ marginTop: "0" }}>Name {'{'}required{'}'}</label><br />"#
+    /// );
+    /// ```
+    fn jsx(&self) -> String;
+
+    /// Rename the attributes to their name in `profile`, like `class`
+    /// to `className` for [`AttrProfile::Jsx`] and back for
+    /// [`AttrProfile::Html`]. The values, including the styles, are
+    /// kept as is.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<td colspan="2">A</td>"#).unwrap();
+    /// dom.rename_attrs(AttrProfile::Jsx);
+    /// assert_eq!(dom.html(), r#"<td colSpan="2">A</td>"#);
+    ///
+    /// dom.rename_attrs(AttrProfile::Html);
+    /// assert_eq!(dom.html(), r#"<td colspan="2">A</td>"#);
+    /// ```
+    fn rename_attrs(&mut self, profile: AttrProfile) -> &mut Self;
+}
+
+/// Name of the attribute `name` in JSX, which is kept if it has no
+/// other name, like for `data-*` and `aria-*`.
+fn jsx_name(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    if let Some((_, jsx)) = JSX_ATTRS.iter().find(|(html, _)| *html == lower) {
+        return jsx.to_string();
+    }
+    if lower == "ondblclick" {
+        return "onDoubleClick".to_string();
+    }
+    if let Some(event) = JSX_EVENTS.iter().find(|e| e.to_ascii_lowercase() == lower) {
+        return event.to_string();
+    }
+    match name.strip_prefix("on") {
+        Some(event) if !event.is_empty() && !event.contains('-') => {
+            let mut chars = event.chars();
+            let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+            format!("on{}{}", first, chars.as_str())
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Name of the attribute `name` in html, the reverse of [`jsx_name`].
+fn html_name(name: &str) -> String {
+    if let Some((html, _)) = JSX_ATTRS.iter().find(|(_, jsx)| *jsx == name) {
+        return html.to_string();
+    }
+    if name == "onDoubleClick" {
+        return "ondblclick".to_string();
+    }
+    let is_event =
+        name.starts_with("on") && name[2..].starts_with(|c: char| c.is_ascii_uppercase());
+    if is_event {
+        name.to_ascii_lowercase()
+    } else {
+        name.to_string()
+    }
+}
+
+fn rename_element_attrs(element: &mut Element, profile: AttrProfile) {
+    for (name, _) in element.attrs.iter_mut() {
+        *name = match profile {
+            AttrProfile::Html => html_name(name),
+            AttrProfile::Jsx => jsx_name(name),
+        };
+    }
+}
+
+/// Name of a CSS property in a JSX style object, quoted if it is a
+/// custom property.
+fn style_key(property: &str) -> String {
+    if property.starts_with("--") {
+        return format!(r#""{}""#, property);
+    }
+    let property = match property.strip_prefix("-ms-") {
+        Some(rest) => format!("ms-{}", rest),
+        None => property.trim_start_matches('-').to_string(),
+    };
+    let mut key = String::new();
+    let mut upper = property.starts_with("webkit-") || property.starts_with("moz-");
+    for ch in property.chars() {
+        match ch {
+            '-' => upper = true,
+            _ if upper => {
+                key.push(ch.to_ascii_uppercase());
+                upper = false;
+            }
+            _ => key.push(ch),
+        }
+    }
+    key
+}
+
+fn js_string(value: &str) -> String {
+    format!(r#""{}""#, value.replace('\\', r"\\").replace('"', r#"\""#))
+}
+
+fn style_object(style: &str) -> String {
+    let properties = css::declarations(&entity::decode(style))
+        .iter()
+        .map(|(property, value)| format!("{}: {}", style_key(property), js_string(value)))
+        .collect::<Vec<_>>();
+    if properties.is_empty() {
+        "{{}}".to_string()
+    } else {
+        format!("{{{{ {} }}}}", properties.join(", "))
+    }
+}
+
+fn jsx_text(text: &str) -> String {
+    let mut jsx = String::new();
+    for ch in text.chars() {
+        match ch {
+            '{' => jsx.push_str("{'{'}"),
+            '}' => jsx.push_str("{'}'}"),
+            '>' => jsx.push_str("&gt;"),
+            _ => jsx.push(ch),
+        }
+    }
+    jsx
+}
+
+/// Content of a `<script>` or `<style>`, as a template literal.
+fn template_literal(text: &str) -> String {
+    let escaped = text
+        .replace('\\', r"\\")
+        .replace('`', r"\`")
+        .replace("${", r"\${");
+    format!("{{`{}`}}", escaped)
+}
+
+fn element_jsx(element: &Element) -> String {
+    let mut jsx = format!("<{}", element.name);
+    for (name, value) in &element.attrs {
+        let name = jsx_name(name);
+        if name == "style" {
+            jsx.push_str(&format!(" style={}", style_object(value)));
+        } else if value.is_empty() {
+            jsx.push_str(&format!(" {}", name));
+        } else {
+            jsx.push_str(&format!(r#" {}="{}""#, name, value.replace('"', "&quot;")));
+        }
+    }
+    if VOID_TAGS.contains(&element.name.as_str()) {
+        jsx.push_str(" />");
+        return jsx;
+    }
+    jsx.push('>');
+    if matches!(element.name.as_str(), "script" | "style") {
+        let text: String = element
+            .children
+            .iter()
+            .filter_map(|node| match node {
+                Node::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        if !text.is_empty() {
+            jsx.push_str(&template_literal(&text));
+        }
+    } else {
+        jsx.push_str(&element.children.jsx());
+    }
+    jsx.push_str(&format!("</{}>", element.name));
+    jsx
+}
+
+impl Jsxifiable for Element {
+    fn jsx(&self) -> String {
+        element_jsx(self)
+    }
+
+    fn rename_attrs(&mut self, profile: AttrProfile) -> &mut Self {
+        walk_element_mut(self, &mut |el| rename_element_attrs(el, profile));
+        self
+    }
+}
+
+impl Jsxifiable for Node {
+    fn jsx(&self) -> String {
+        match self {
+            Node::Element(element) => element_jsx(element),
+            Node::Text(text) | Node::Template(text) => jsx_text(text),
+            Node::Comment(comment) => format!("{{/*{}*/}}", comment.replace("*/", "* /")),
+            Node::Doctype(_) | Node::ProcessingInstruction(_) => String::new(),
+        }
+    }
+
+    fn rename_attrs(&mut self, profile: AttrProfile) -> &mut Self {
+        if let Node::Element(element) = self {
+            element.rename_attrs(profile);
+        }
+        self
+    }
+}
+
+impl Jsxifiable for Vec<Node> {
+    fn jsx(&self) -> String {
+        self.iter().map(Node::jsx).collect()
+    }
+
+    fn rename_attrs(&mut self, profile: AttrProfile) -> &mut Self {
+        walk_mut(self, &mut |el| rename_element_attrs(el, profile));
+        self
+    }
+}
@@ -0,0 +1,263 @@
+use std::ops::Range;
+
+use super::{Rewritable, Selector};
+use crate::{Element, Node};
+
+/// Value found in the text by a [`ValueMatcher`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocalValue {
+    Number(f64),
+    /// An amount with its currency, being a symbol like `$` or a code
+    /// like `USD`.
+    Currency {
+        currency: String,
+        amount: f64,
+    },
+    Date {
+        year: i32,
+        month: u32,
+        day: u32,
+    },
+}
+
+/// Find and parse the first value of a kind in a text, for
+/// [`Localizable::localize`].
+pub trait ValueMatcher {
+    /// Get the byte range and the value of the first match in `text`
+    /// starting from the byte `from`, which is a character boundary.
+    fn find(&self, text: &str, from: usize) -> Option<(Range<usize>, LocalValue)>;
+}
+
+/// Matches the numbers, like `-1,234.5`, with `,` grouping the thousands
+/// and `.` before the decimals.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NumberMatcher;
+
+/// Matches the amounts after a currency symbol, like `$12.50`, or after
+/// a currency code and a space, like `EUR 12.50`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CurrencyMatcher;
+
+/// Matches the ISO 8601 dates, like `2024-03-15`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DateMatcher;
+
+const CURRENCY_SYMBOLS: [char; 6] = ['$', '€', '£', '¥', '₹', '₩'];
+
+/// Check if `text` has no word character around `range`, so that the
+/// values are not matched inside words, like `A4`.
+fn is_standalone(text: &str, range: &Range<usize>) -> bool {
+    let before = text[..range.start].chars().next_back();
+    let mut after = text[range.end..].chars();
+    let continued = match after.next() {
+        Some('.' | ',') => after.next().is_some_and(|c| c.is_ascii_digit()),
+        Some(c) => c.is_alphanumeric(),
+        None => false,
+    };
+    !before.is_some_and(|c| c.is_alphanumeric() || matches!(c, '.' | ',' | '-')) && !continued
+}
+
+/// Parse the number starting at `start`, returning its end.
+fn scan_number(text: &str, start: usize) -> Option<(usize, f64)> {
+    let bytes = text.as_bytes();
+    let mut end = start;
+    if bytes.get(end) == Some(&b'-') {
+        end += 1;
+    }
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+    let integer = digits(end);
+    if integer == 0 {
+        return None;
+    }
+    end += integer;
+    // Groups of thousands, only after at most 3 digits.
+    if integer <= 3 {
+        while bytes.get(end) == Some(&b',') && digits(end + 1) == 3 {
+            end += 4;
+        }
+    }
+    if bytes.get(end) == Some(&b'.') && digits(end + 1) > 0 {
+        end += 1 + digits(end + 1);
+    }
+    let number = text[start..end].replace(',', "").parse().ok()?;
+    Some((end, number))
+}
+
+impl ValueMatcher for NumberMatcher {
+    fn find(&self, text: &str, mut from: usize) -> Option<(Range<usize>, LocalValue)> {
+        while let Some(offset) = text[from..].find(|c: char| c.is_ascii_digit() || c == '-') {
+            let start = from + offset;
+            if let Some((end, number)) = scan_number(text, start) {
+                if is_standalone(text, &(start..end)) {
+                    return Some((start..end, LocalValue::Number(number)));
+                }
+                from = end;
+            } else {
+                from = start + 1;
+            }
+        }
+        None
+    }
+}
+
+impl ValueMatcher for CurrencyMatcher {
+    fn find(&self, text: &str, from: usize) -> Option<(Range<usize>, LocalValue)> {
+        for (offset, ch) in text[from..].char_indices() {
+            let start = from + offset;
+            let (currency, number_start) = if CURRENCY_SYMBOLS.contains(&ch) {
+                (ch.to_string(), start + ch.len_utf8())
+            } else {
+                match text.get(start..start + 4) {
+                    Some(code)
+                        if code.as_bytes()[..3].iter().all(u8::is_ascii_uppercase)
+                            && code.ends_with(' ')
+                            && !text[..start].ends_with(char::is_alphanumeric) =>
+                    {
+                        (code[..3].to_string(), start + 4)
+                    }
+                    _ => continue,
+                }
+            };
+            if let Some((end, amount)) = scan_number(text, number_start) {
+                if is_standalone(text, &(number_start..end)) {
+                    return Some((start..end, LocalValue::Currency { currency, amount }));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl ValueMatcher for DateMatcher {
+    fn find(&self, text: &str, from: usize) -> Option<(Range<usize>, LocalValue)> {
+        let bytes = text.as_bytes();
+        for start in from..bytes.len().saturating_sub(9) {
+            let date = &bytes[start..start + 10];
+            let is_date = date.iter().enumerate().all(|(i, b)| match i {
+                4 | 7 => *b == b'-',
+                _ => b.is_ascii_digit(),
+            });
+            if !is_date || !is_standalone(text, &(start..start + 10)) {
+                continue;
+            }
+            let number = |range: Range<usize>| text[start..][range].parse().ok();
+            let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+            if (1..=12).contains(&month) && (1..=31).contains(&day) {
+                let value = LocalValue::Date {
+                    year: year as i32,
+                    month,
+                    day,
+                };
+                return Some((start..start + 10, value));
+            }
+        }
+        None
+    }
+}
+
+/// Localize the values written in the text, like the numbers and the
+/// dates.
+pub trait Localizable {
+    /// Find the values in the prose text with the `matchers`, and replace
+    /// each of them by the text returned by `format`, given the value and
+    /// the text it was parsed from. The values for which `format` returns
+    /// `None` are left as is.
+    ///
+    /// Only the text inside the elements matching `scope` is localized,
+    /// if any. When several matchers find a value at the same place, the
+    /// first one wins. The returned text is inserted as is, with its
+    /// character references. Returns the number of values replaced.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<p>Paid $1,234.5 on 2024-03-15 for 3 items</p>").unwrap();
+    /// dom.localize(None, &[&DateMatcher, &CurrencyMatcher], |value, _| match value {
+    ///     LocalValue::Currency { amount, .. } => Some(format!("{:.2} €", amount)),
+    ///     LocalValue::Date { year, month, day } => Some(format!("{}.{}.{}", day, month, year)),
+    ///     LocalValue::Number(_) => None,
+    /// });
+    ///
+    /// assert_eq!(dom.html(), "<p>Paid 1234.50 € on 15.3.2024 for 3 items</p>");
+    /// ```
+    fn localize(
+        &mut self,
+        scope: Option<&Selector>,
+        matchers: &[&dyn ValueMatcher],
+        format: impl FnMut(&LocalValue, &str) -> Option<String>,
+    ) -> usize;
+}
+
+/// Replace the values of `text` found by the `matchers`.
+fn localize_text(
+    text: &str,
+    matchers: &[&dyn ValueMatcher],
+    format: &mut impl FnMut(&LocalValue, &str) -> Option<String>,
+    count: &mut usize,
+) -> Option<String> {
+    let mut localized = String::with_capacity(text.len());
+    let mut from = 0;
+    let before = *count;
+    loop {
+        let found = matchers
+            .iter()
+            .filter_map(|matcher| matcher.find(text, from))
+            .min_by_key(|(range, _)| range.start);
+        let Some((range, value)) = found else {
+            break;
+        };
+        localized.push_str(&text[from..range.start]);
+        match format(&value, &text[range.clone()]) {
+            Some(formatted) => {
+                localized.push_str(&formatted);
+                *count += 1;
+            }
+            None => localized.push_str(&text[range.clone()]),
+        }
+        from = range.end;
+    }
+    localized.push_str(&text[from..]);
+    (*count > before).then_some(localized)
+}
+
+fn localize_in<T: Rewritable>(
+    target: &mut T,
+    scope: Option<&Selector>,
+    matchers: &[&dyn ValueMatcher],
+    mut format: impl FnMut(&LocalValue, &str) -> Option<String>,
+) -> usize {
+    let mut count = 0;
+    target.rewrite_text(scope, |text| {
+        let text = localize_text(text, matchers, &mut format, &mut count)?;
+        Some(vec![Node::Text(text)])
+    });
+    count
+}
+
+impl Localizable for Vec<Node> {
+    fn localize(
+        &mut self,
+        scope: Option<&Selector>,
+        matchers: &[&dyn ValueMatcher],
+        format: impl FnMut(&LocalValue, &str) -> Option<String>,
+    ) -> usize {
+        localize_in(self, scope, matchers, format)
+    }
+}
+
+impl Localizable for Element {
+    fn localize(
+        &mut self,
+        scope: Option<&Selector>,
+        matchers: &[&dyn ValueMatcher],
+        format: impl FnMut(&LocalValue, &str) -> Option<String>,
+    ) -> usize {
+        localize_in(self, scope, matchers, format)
+    }
+}
@@ -1,11 +1,72 @@
 //! Traits about editing, querying and stringifying the [`Element`](struct.Element.html) / [`Node`](enum.Node.html).
 
+mod audit;
+mod compose;
+mod diff;
 mod edit;
+mod email;
+mod footnote;
+mod forest;
+mod harden;
 mod html;
+mod ids;
+mod image;
+mod inspect;
+mod jsx;
+mod locale;
+mod pipe;
+mod pipeline;
+mod prose;
+mod publish;
 mod query;
+mod readability;
+mod render;
 mod selector;
+mod sourcemap;
+mod stream;
+mod structure;
+mod theme;
+mod typeset;
+mod walk;
 
-pub use edit::Editable;
-pub use html::Htmlifiable;
+pub use audit::{AltIssue, Auditable, FixedWidth, HeadIssue, KeepPolicy, PrunedCss, WeightReport};
+pub use compose::{
+    merge_documents, Asset, Composable, Generated, IncludeError, MergeOptions, TitlePolicy,
+};
+pub use diff::{
+    apply_patch, diff, patches_to_json, stats, stats_delta, DocumentStats, Patch, StatsDelta,
+    PATCH_FORMAT_VERSION,
+};
+pub use edit::{Editable, FROZEN_ATTR};
+pub use email::{decode_email, EmailObfuscation, Obfuscatable, OBFUSCATED_EMAIL_ATTR};
+pub use footnote::Footnotable;
+pub use forest::{DocumentForest, DocumentOrigin, NestedDocument};
+pub use harden::{
+    AttrPolicy, Hardenable, HardenedIframe, IframePolicy, LinkPolicy, RemovedAttr, SchemeAction,
+    SchemePolicy, SchemeRule, TrackerMatch, TrackerSignature, UrlChange,
+};
+pub(crate) use html::{close_tag, open_tag};
+pub use html::{FormatOptions, HtmlOptions, Htmlifiable};
+pub use ids::IdGenerator;
+pub use image::{eager_image, Optimizable, PictureSource};
+pub use inspect::{AlternateLink, Inspectable, MetaRefresh, Viewport, ViewportWidth};
+pub use jsx::{AttrProfile, Jsxifiable};
+pub use locale::{
+    CurrencyMatcher, DateMatcher, LocalValue, Localizable, NumberMatcher, ValueMatcher,
+};
+pub(crate) use pipe::PipeTarget;
+pub use pipe::{Pipe, PipeReport};
+pub use pipeline::{Pipeline, PipelineError, PipelineReport, PipelineStats};
+pub use prose::{emoji_shortcodes, ProseEdit, ProseSegment, Rewritable};
+pub(crate) use prose::{is_prose, rewrite_element, rewrite_nodes};
+pub use publish::{HeaderComment, Publishable};
 pub use query::Queryable;
-pub use selector::Selector;
+pub(crate) use query::{query_editable_mut, query_element_editable_mut};
+pub use readability::{stitch_pages, Landmark, Readable};
+pub use render::Renderable;
+pub use selector::{Selector, SelectorBuilder, SelectorError, Specificity, XPath, XPathItem};
+pub use sourcemap::{Mapping, SourceMappable, SOURCE_SPAN_ATTR};
+pub use stream::{render_slot, Streamable};
+pub use structure::{Heading, HeadingOverflow, Restructurable};
+pub use theme::Themable;
+pub use typeset::{split_every, Direction, Typesettable, WordBreak};
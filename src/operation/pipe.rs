@@ -0,0 +1,242 @@
+use super::selector::{Mark, Matcher};
+use super::walk::is_frozen;
+use super::{Selector, SelectorError};
+use crate::{Element, Node};
+
+/// Edit of a [`Pipe`], applied to the elements matching its selector.
+enum PipeEdit<'a> {
+    Remove,
+    AddClass(String),
+    SetAttr(String, String),
+    InsertEnd(Node),
+    ForEach(Box<dyn FnMut(&mut Element) + 'a>),
+}
+
+/// Result of [`Pipe::run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipeReport {
+    /// Number of elements each edit was applied to, in the order of the
+    /// edits. The removed elements are counted, not their descendants.
+    pub matched: Vec<usize>,
+}
+
+impl PipeReport {
+    /// Iterate over the indexes of the edits which matched no element,
+    /// like those with a mistyped selector.
+    pub fn unmatched(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.matched.len()).filter(|&edit| self.matched[edit] == 0)
+    }
+}
+
+/// What is edited by a [`Pipe`].
+pub(crate) enum PipeTarget<'a> {
+    Nodes(&'a mut Vec<Node>),
+    Element(&'a mut Element),
+}
+
+/// Edits chained with [`Editable::pipe`](super::Editable::pipe), and
+/// applied by [`Pipe::run`].
+///
+/// The edits are applied in order to each element, parents before
+/// children, in a single traversal of the tree. The selectors depending
+/// on the ancestors or the siblings of the elements, like `div > p`, are
+/// matched in a traversal of their own, after the previous edits.
+pub struct Pipe<'a> {
+    target: PipeTarget<'a>,
+    edits: Vec<(Selector, PipeEdit<'a>)>,
+    error: Option<SelectorError>,
+}
+
+impl<'a> Pipe<'a> {
+    pub(crate) fn new(target: PipeTarget<'a>) -> Self {
+        Pipe {
+            target,
+            edits: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Add the edit, or keep the error of the first invalid selector.
+    fn edit(mut self, selector: &str, edit: PipeEdit<'a>) -> Self {
+        if self.error.is_none() {
+            match Selector::parse(selector) {
+                Ok(selector) => self.edits.push((selector, edit)),
+                Err(error) => self.error = Some(error),
+            }
+        }
+        self
+    }
+
+    /// Remove the elements matching `selector`.
+    pub fn remove(self, selector: &str) -> Self {
+        self.edit(selector, PipeEdit::Remove)
+    }
+
+    /// Add `class` to the elements matching `selector`, unless they
+    /// already have it.
+    pub fn add_class(self, selector: &str, class: &str) -> Self {
+        self.edit(selector, PipeEdit::AddClass(class.to_string()))
+    }
+
+    /// Set the attribute `name` of the elements matching `selector`,
+    /// adding it if needed.
+    pub fn set_attr(self, selector: &str, name: &str, value: &str) -> Self {
+        self.edit(
+            selector,
+            PipeEdit::SetAttr(name.to_string(), value.to_string()),
+        )
+    }
+
+    /// Insert `node` as the last child of the elements matching
+    /// `selector`.
+    pub fn insert_end(self, selector: &str, node: Node) -> Self {
+        self.edit(selector, PipeEdit::InsertEnd(node))
+    }
+
+    /// Call `f` for the elements matching `selector`.
+    pub fn for_each(self, selector: &str, f: impl FnMut(&mut Element) + 'a) -> Self {
+        self.edit(selector, PipeEdit::ForEach(Box::new(f)))
+    }
+
+    /// Apply the edits and report how many elements each of them matched,
+    /// or return the error of the first invalid selector without editing
+    /// anything.
+    ///
+    /// ```
+    /// use html_editor::{parse, Node};
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<body><p class="ad">Buy</p><img src="a.png"></body>"#).unwrap();
+    /// let report = dom
+    ///     .pipe()
+    ///     .remove(".ad")
+    ///     .add_class("img", "lazy")
+    ///     .insert_end("body", Node::Comment("end".to_string()))
+    ///     .remove("video")
+    ///     .run()?;
+    ///
+    /// assert_eq!(dom.html(), r#"<body><img src="a.png" class="lazy"><!--end--></body>"#);
+    /// assert_eq!(report.matched, vec![1, 1, 1, 0]);
+    /// assert_eq!(report.unmatched().collect::<Vec<_>>(), vec![3]);
+    ///
+    /// let error = dom.pipe().remove("p").remove("p:hover").run().unwrap_err();
+    /// assert_eq!(error.message, "Unsupported pseudo-class :hover");
+    /// # Ok::<(), SelectorError>(())
+    /// ```
+    pub fn run(self) -> Result<PipeReport, SelectorError> {
+        let Pipe {
+            mut target,
+            edits,
+            error,
+        } = self;
+        if let Some(error) = error {
+            return Err(error);
+        }
+        let (selectors, mut edits): (Vec<_>, Vec<_>) = edits.into_iter().unzip();
+        let mut report = PipeReport {
+            matched: vec![0; edits.len()],
+        };
+        let mut start = 0;
+        while start < selectors.len() {
+            // Contextual selectors are marked before their traversal, so
+            // they can't follow other edits in the same one.
+            let contextual = selectors[start].is_contextual();
+            let end = match contextual {
+                true => start + 1,
+                false => (start..selectors.len())
+                    .find(|&i| selectors[i].is_contextual())
+                    .unwrap_or(selectors.len()),
+            };
+            let (selectors, edits) = (&selectors[start..end], &mut edits[start..end]);
+            let counts = &mut report.matched[start..end];
+            match &mut target {
+                PipeTarget::Nodes(nodes) => {
+                    let marks: Vec<_> = selectors.iter().map(|s| s.marks(nodes)).collect();
+                    let matchers = matchers(selectors, &marks);
+                    run_nodes(nodes, &matchers, edits, counts);
+                }
+                PipeTarget::Element(element) => {
+                    if is_frozen(element) {
+                        return Ok(report);
+                    }
+                    let marks: Vec<_> =
+                        selectors.iter().map(|s| s.element_marks(element)).collect();
+                    let matchers = matchers(selectors, &marks);
+                    run_element(element, 0, &matchers, edits, counts, false);
+                }
+            }
+            start = end;
+        }
+        Ok(report)
+    }
+}
+
+fn matchers<'a>(selectors: &'a [Selector], marks: &'a [Option<Vec<Mark>>]) -> Vec<Matcher<'a>> {
+    selectors
+        .iter()
+        .zip(marks)
+        .map(|(selector, marks)| Matcher::new(selector, marks.as_deref()))
+        .collect()
+}
+
+fn run_nodes(
+    nodes: &mut Vec<Node>,
+    matchers: &[Matcher],
+    edits: &mut [PipeEdit],
+    counts: &mut [usize],
+) {
+    for (index, node) in std::mem::take(nodes).into_iter().enumerate() {
+        match node {
+            Node::Element(mut el) if !is_frozen(&el) => {
+                if run_element(&mut el, index, matchers, edits, counts, true) {
+                    nodes.push(Node::Element(el));
+                }
+            }
+            node => nodes.push(node),
+        }
+    }
+}
+
+/// Apply the edits to `element`, the `index`th of its siblings, and its
+/// descendants, returning `false` if it is removed, which the element
+/// being edited by the pipe is not.
+fn run_element(
+    element: &mut Element,
+    index: usize,
+    matchers: &[Matcher],
+    edits: &mut [PipeEdit],
+    counts: &mut [usize],
+    removable: bool,
+) -> bool {
+    let mut children = Vec::with_capacity(matchers.len());
+    for (i, (matcher, edit)) in matchers.iter().zip(edits.iter_mut()).enumerate() {
+        // Matched after the previous edits of the element.
+        let (matched, child) = matcher.child(index, element);
+        children.push(child);
+        if !matched || matches!(edit, PipeEdit::Remove) && !removable {
+            continue;
+        }
+        counts[i] += 1;
+        match edit {
+            PipeEdit::Remove => return false,
+            PipeEdit::AddClass(class) => add_class(element, class),
+            PipeEdit::SetAttr(name, value) => element.set_attr(name, value),
+            PipeEdit::InsertEnd(node) => element.children.push(node.clone()),
+            PipeEdit::ForEach(f) => f(element),
+        }
+    }
+    run_nodes(&mut element.children, &children, edits, counts);
+    true
+}
+
+fn add_class(element: &mut Element, class: &str) {
+    let classes = element.get_attr("class").unwrap_or_default();
+    if classes.split_ascii_whitespace().any(|c| c == class) {
+        return;
+    }
+    let classes = match classes.trim() {
+        "" => class.to_string(),
+        classes => format!("{} {}", classes, class),
+    };
+    element.set_attr("class", &classes);
+}
@@ -0,0 +1,189 @@
+use std::{error, fmt, thread};
+
+use super::{Editable, Htmlifiable, Selector};
+use crate::{parse_with_options, Element, Node, ParseOptions};
+
+type Step = Box<dyn Fn(&mut Vec<Node>) -> Result<(), String> + Send + Sync>;
+
+/// Failure of one of the documents of [`Pipeline::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineError {
+    /// Index of the document among the inputs.
+    pub index: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "document {}: {}", self.index, self.message)
+    }
+}
+
+impl error::Error for PipelineError {}
+
+/// Statistics aggregated over the documents of [`Pipeline::run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipelineStats {
+    pub documents: usize,
+    pub failed: usize,
+    pub input_bytes: usize,
+    /// Size of the documents processed successfully.
+    pub output_bytes: usize,
+}
+
+/// Result of [`Pipeline::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineReport {
+    /// Html of each document, in the order of the inputs.
+    pub outputs: Vec<Result<String, PipelineError>>,
+    pub stats: PipelineStats,
+}
+
+impl PipelineReport {
+    /// Iterate over the documents that failed.
+    pub fn errors(&self) -> impl Iterator<Item = &PipelineError> {
+        self.outputs
+            .iter()
+            .filter_map(|output| output.as_ref().err())
+    }
+}
+
+/// Operations applied in order to many documents, like for processing a
+/// whole site.
+///
+/// The selectors are compiled once, when the steps are added, and the
+/// documents can be processed on several threads.
+///
+/// ```
+/// use html_editor::operation::*;
+///
+/// let pipeline = Pipeline::new()
+///     .remove(".ad")
+///     .for_each("a", |a| a.attrs.push(("rel".to_string(), "noopener".to_string())));
+/// let report = pipeline.run([r#"<a href="/">Home</a><p class=ad>Buy</p>"#, "<p>Text"]);
+///
+/// assert_eq!(report.outputs[0], Ok(r#"<a href="/" rel="noopener">Home</a>"#.to_string()));
+/// assert_eq!(report.outputs[1].as_ref().unwrap_err().index, 1);
+/// assert_eq!(report.stats.failed, 1);
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    options: ParseOptions,
+    threads: usize,
+    steps: Vec<Step>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the options to parse the documents with.
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Process the documents on up to `threads` threads. They are
+    /// processed on the calling thread by default.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Add a step editing the documents.
+    pub fn step(self, f: impl Fn(&mut Vec<Node>) + Send + Sync + 'static) -> Self {
+        self.try_step(move |dom| {
+            f(dom);
+            Ok(())
+        })
+    }
+
+    /// Add a step which can fail, skipping the next steps for the
+    /// document and reporting its error.
+    pub fn try_step(
+        mut self,
+        f: impl Fn(&mut Vec<Node>) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.steps.push(Box::new(f));
+        self
+    }
+
+    /// Add a step removing the elements matching `selector`.
+    pub fn remove(self, selector: &str) -> Self {
+        let selector = Selector::from(selector);
+        self.step(move |dom| {
+            dom.remove_by(&selector);
+        })
+    }
+
+    /// Add a step calling `f` for the elements matching `selector`.
+    pub fn for_each(
+        self,
+        selector: &str,
+        f: impl Fn(&mut Element) + Send + Sync + 'static,
+    ) -> Self {
+        let selector = Selector::from(selector);
+        self.step(move |dom| dom.execute_for(&selector, &f))
+    }
+
+    /// Parse `html`, apply the steps to it, and return the result.
+    pub fn apply(&self, html: &str) -> Result<String, String> {
+        let mut dom = parse_with_options(html, &self.options)?;
+        for step in &self.steps {
+            step(&mut dom)?;
+        }
+        Ok(dom.html())
+    }
+
+    /// Apply the steps to each of `inputs`, reporting the result of each
+    /// document and statistics over all of them.
+    pub fn run<I>(&self, inputs: I) -> PipelineReport
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str> + Sync,
+    {
+        let inputs: Vec<I::Item> = inputs.into_iter().collect();
+        let process = |index: usize, input: &I::Item| {
+            self.apply(input.as_ref())
+                .map_err(|message| PipelineError { index, message })
+        };
+
+        let outputs: Vec<_> = if self.threads <= 1 || inputs.len() <= 1 {
+            inputs
+                .iter()
+                .enumerate()
+                .map(|(i, input)| process(i, input))
+                .collect()
+        } else {
+            let size = inputs.len().div_ceil(self.threads);
+            thread::scope(|scope| {
+                let handles: Vec<_> = inputs
+                    .chunks(size)
+                    .enumerate()
+                    .map(|(chunk, inputs)| {
+                        scope.spawn(move || {
+                            let offset = chunk * size;
+                            let outputs = inputs.iter().enumerate();
+                            outputs
+                                .map(|(i, input)| process(offset + i, input))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| handle.join().expect("a pipeline step panicked"))
+                    .collect()
+            })
+        };
+
+        let stats = PipelineStats {
+            documents: inputs.len(),
+            failed: outputs.iter().filter(|output| output.is_err()).count(),
+            input_bytes: inputs.iter().map(|input| input.as_ref().len()).sum(),
+            output_bytes: outputs.iter().flatten().map(String::len).sum(),
+        };
+        PipelineReport { outputs, stats }
+    }
+}
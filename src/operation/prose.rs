@@ -0,0 +1,785 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use super::selector::Matcher;
+use super::walk::is_frozen;
+use super::Selector;
+use crate::{data::INLINE_TAGS, entity, Element, Node};
+
+/// Elements whose text is not prose, and must not be rewritten.
+const NON_PROSE_TAGS: [&str; 8] = [
+    "code", "kbd", "math", "pre", "samp", "script", "style", "textarea",
+];
+
+/// Contiguous prose, running across the inline elements like `<em>`,
+/// found by [`Rewritable::prose_segments`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProseSegment {
+    /// The text, with its character references decoded.
+    pub text: String,
+    /// The byte offsets in `text` where each of its text nodes starts,
+    /// the first one being 0.
+    pub boundaries: Vec<usize>,
+}
+
+/// Replacement of some text of a [`ProseSegment`], written back by
+/// [`Rewritable::edit_prose`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProseEdit {
+    /// The index of the segment among the ones of
+    /// [`Rewritable::prose_segments`].
+    pub segment: usize,
+    /// The byte range of the replaced text in the segment.
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// Rewrite the prose text of documents, leaving the code alone.
+pub trait Rewritable {
+    /// Replace the prose text nodes by the nodes returned by `rewrite`,
+    /// which can split them into several nodes. Text inside `<code>`,
+    /// `<pre>`, `<kbd>`, `<samp>`, `<script>`, `<style>`, `<textarea>`
+    /// and `<math>` is left untouched, and so is the one outside of the
+    /// elements matching `scope`, if any.
+    ///
+    /// The text is given raw, with its character references, and the
+    /// returned nodes are inserted as is. Returns the number of text
+    /// nodes replaced.
+    ///
+    /// ```
+    /// use html_editor::{parse, Node};
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<p>Rust</p><code>Rust</code>").unwrap();
+    /// dom.rewrite_text(None, |text| {
+    ///     let strong = Node::new_element("strong", vec![], vec![Node::Text(text.to_string())]);
+    ///     Some(vec![strong])
+    /// });
+    ///
+    /// assert_eq!(dom.html(), "<p><strong>Rust</strong></p><code>Rust</code>");
+    /// ```
+    fn rewrite_text(
+        &mut self,
+        scope: Option<&Selector>,
+        rewrite: impl FnMut(&str) -> Option<Vec<Node>>,
+    ) -> usize;
+
+    /// Replace the `:shortcode:` tokens of the prose text by their value
+    /// in `shortcodes`, like the ones of [`emoji_shortcodes`], within the
+    /// elements matching `scope` if any. Unknown shortcodes are kept.
+    ///
+    /// Returns the number of shortcodes replaced.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<p>Ship it :rocket: at 10:30:</p><code>:rocket:</code>").unwrap();
+    /// dom.replace_shortcodes(None, &emoji_shortcodes());
+    ///
+    /// assert_eq!(dom.html(), "<p>Ship it 🚀 at 10:30:</p><code>:rocket:</code>");
+    /// ```
+    fn replace_shortcodes(
+        &mut self,
+        scope: Option<&Selector>,
+        shortcodes: &HashMap<String, String>,
+    ) -> usize;
+
+    /// Turn the straight quotes of the prose text into curly ones and
+    /// `--`, `---` and `...` into en dashes, em dashes and ellipses,
+    /// within the elements matching `scope` if any, leaving attributes
+    /// untouched, like SmartyPants does.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = parse(r#"<p>"It's 5--6 o'clock..." -- <em>'90s</em></p><code>"x"</code>"#)
+    ///     .unwrap()
+    ///     .smartypants(None)
+    ///     .html();
+    ///
+    /// assert_eq!(html, "<p>“It’s 5–6 o’clock…” – <em>’90s</em></p><code>\"x\"</code>");
+    /// ```
+    fn smartypants(&mut self, scope: Option<&Selector>) -> &mut Self;
+
+    /// Get the runs of prose text, to be checked or rewritten by language
+    /// tools. A segment goes on across the inline elements, like `<a>` or
+    /// `<strong>`, and is interrupted by the other ones, like `<p>` or
+    /// `<br>`. The segments of whitespace only are left out, and so is
+    /// the text of the elements left untouched by
+    /// [`Rewritable::rewrite_text`], like `<code>`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse("<p>Fish &amp; <b>chips</b>.</p><pre>fn</pre><p>Tea</p>").unwrap();
+    /// let segments = dom.prose_segments();
+    ///
+    /// assert_eq!(segments[0].text, "Fish & chips.");
+    /// assert_eq!(segments[0].boundaries, vec![0, 7, 12]);
+    /// assert_eq!(segments[1].text, "Tea");
+    /// ```
+    fn prose_segments(&self) -> Vec<ProseSegment>;
+
+    /// Write the `edits` of the [`Rewritable::prose_segments`] back into
+    /// their text nodes, which must not have changed in between. The text
+    /// of an edit goes into the text node where its range starts, and the
+    /// replaced text is removed from all the nodes it spans, so the
+    /// formatting remains where the text is kept.
+    ///
+    /// The edits which overlap a previous one, or whose range is not in
+    /// the segment, are ignored. The edited text nodes are written back
+    /// with `&`, `<` and `>` escaped. Returns the number of edits applied.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<p>Teh <em>quick</em> fox</p>").unwrap();
+    /// let edits = vec![
+    ///     ProseEdit { segment: 0, range: 0..3, text: "The".to_string() },
+    ///     ProseEdit { segment: 0, range: 4..13, text: "slow & smart".to_string() },
+    /// ];
+    ///
+    /// assert_eq!(dom.edit_prose(&edits), 2);
+    /// assert_eq!(dom.html(), "<p>The <em>slow &amp; smart</em></p>");
+    /// ```
+    fn edit_prose(&mut self, edits: &[ProseEdit]) -> usize;
+
+    /// Split the elements matching `selector`, like the long `<p>`, into
+    /// one element per sentence. The inline elements across a sentence
+    /// boundary are split too, and the copies have the attributes of the
+    /// original ones but the `id`.
+    ///
+    /// A sentence ends with `.`, `!`, `?` or `…`, and the closing quotes
+    /// or brackets after them, when followed by whitespace and a character
+    /// which is not lowercase. The abbreviations like `Dr.` or `e.g.` and
+    /// the initials do not end a sentence. Returns the number of elements
+    /// added.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<p id="a">Hi Dr. Who. <b>Run! Now.</b> Ok</p>"#).unwrap();
+    /// dom.split_paragraph_at_sentences(&Selector::from("p"));
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<p id="a">Hi Dr. Who.</p><p><b>Run!</b></p><p><b>Now.</b></p><p>Ok</p>"#
+    /// );
+    /// ```
+    fn split_paragraph_at_sentences(&mut self, selector: &Selector) -> usize;
+}
+
+/// Common emoji shortcodes, to be used with
+/// [`Rewritable::replace_shortcodes`]. Insert more of them in the
+/// returned map as needed.
+pub fn emoji_shortcodes() -> HashMap<String, String> {
+    [
+        ("+1", "👍"),
+        ("-1", "👎"),
+        ("100", "💯"),
+        ("bug", "🐛"),
+        ("check", "✔️"),
+        ("clap", "👏"),
+        ("coffee", "☕"),
+        ("cry", "😢"),
+        ("eyes", "👀"),
+        ("fire", "🔥"),
+        ("heart", "❤️"),
+        ("info", "ℹ️"),
+        ("joy", "😂"),
+        ("laughing", "😆"),
+        ("memo", "📝"),
+        ("ok_hand", "👌"),
+        ("party", "🥳"),
+        ("pray", "🙏"),
+        ("rocket", "🚀"),
+        ("smile", "😄"),
+        ("sparkles", "✨"),
+        ("star", "⭐"),
+        ("tada", "🎉"),
+        ("thinking", "🤔"),
+        ("thumbsdown", "👎"),
+        ("thumbsup", "👍"),
+        ("warning", "⚠️"),
+        ("wave", "👋"),
+        ("white_check_mark", "✅"),
+        ("wink", "😉"),
+        ("x", "❌"),
+        ("zap", "⚡"),
+    ]
+    .into_iter()
+    .map(|(code, emoji)| (code.to_string(), emoji.to_string()))
+    .collect()
+}
+
+/// The prose text nodes, in the document order, with the runs of them
+/// forming the [`ProseSegment`]s, as indices of `texts`.
+#[derive(Default)]
+struct ProseRuns<'a> {
+    texts: Vec<&'a str>,
+    runs: Vec<Vec<usize>>,
+    current: Vec<usize>,
+}
+
+impl ProseRuns<'_> {
+    fn interrupt(&mut self) {
+        let run = std::mem::take(&mut self.current);
+        if run.iter().any(|&i| !self.texts[i].trim().is_empty()) {
+            self.runs.push(run);
+        }
+    }
+}
+
+fn collect_prose<'a>(nodes: &'a [Node], prose: &mut ProseRuns<'a>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => {
+                prose.current.push(prose.texts.len());
+                prose.texts.push(text);
+            }
+            Node::Element(el) if INLINE_TAGS.contains(&el.name.as_str()) && !is_frozen(el) => {
+                collect_prose(&el.children, prose)
+            }
+            Node::Element(el) => {
+                prose.interrupt();
+                if is_prose(el) && !is_frozen(el) {
+                    collect_prose(&el.children, prose);
+                    prose.interrupt();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Get the same text nodes as [`collect_prose`], to write them back.
+fn collect_prose_mut<'a>(nodes: &'a mut [Node], texts: &mut Vec<&'a mut String>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => texts.push(text),
+            Node::Element(el) if is_prose(el) && !is_frozen(el) => {
+                collect_prose_mut(&mut el.children, texts)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn prose_runs(nodes: &[Node]) -> ProseRuns<'_> {
+    let mut prose = ProseRuns::default();
+    collect_prose(nodes, &mut prose);
+    prose.interrupt();
+    prose
+}
+
+fn prose_segments_of(nodes: &[Node]) -> Vec<ProseSegment> {
+    let prose = prose_runs(nodes);
+    let segments = prose.runs.iter().map(|run| {
+        let mut segment = ProseSegment {
+            text: String::new(),
+            boundaries: Vec::with_capacity(run.len()),
+        };
+        for &i in run {
+            segment.boundaries.push(segment.text.len());
+            segment.text.push_str(&entity::decode(prose.texts[i]));
+        }
+        segment
+    });
+    segments.collect()
+}
+
+fn edit_nodes_prose(nodes: &mut [Node], edits: &[ProseEdit]) -> usize {
+    let runs = prose_runs(nodes).runs;
+    let segments = prose_segments_of(nodes);
+    let mut edits: Vec<&ProseEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.segment, edit.range.start));
+
+    let mut count = 0;
+    let mut edited: HashMap<usize, String> = HashMap::new();
+    let mut last_end = None;
+    let mut valid = Vec::new();
+    for edit in edits {
+        let Some(segment) = segments.get(edit.segment) else {
+            continue;
+        };
+        let overlaps = last_end.is_some_and(|(i, end)| i == edit.segment && edit.range.start < end);
+        if overlaps || segment.text.get(edit.range.clone()).is_none() {
+            continue;
+        }
+        last_end = Some((edit.segment, edit.range.end));
+        valid.push(edit);
+    }
+    // From the last edit, for the offsets of the previous ones to remain.
+    for edit in valid.into_iter().rev() {
+        let segment = &segments[edit.segment];
+        let run = &runs[edit.segment];
+        let piece_end = |k: usize| {
+            segment
+                .boundaries
+                .get(k + 1)
+                .copied()
+                .unwrap_or(segment.text.len())
+        };
+        let first = (0..run.len())
+            .rfind(|&k| segment.boundaries[k] <= edit.range.start)
+            .unwrap_or(0);
+        for (k, &i) in run.iter().enumerate().skip(first) {
+            let (start, end) = (segment.boundaries[k], piece_end(k));
+            if k > first && start >= edit.range.end {
+                break;
+            }
+            let text = edited
+                .entry(i)
+                .or_insert_with(|| segment.text[start..end].to_string());
+            let removed = edit.range.start.max(start) - start..edit.range.end.min(end) - start;
+            let replacement = if k == first { edit.text.as_str() } else { "" };
+            text.replace_range(removed, replacement);
+        }
+        count += 1;
+    }
+
+    let mut texts = Vec::new();
+    collect_prose_mut(nodes, &mut texts);
+    for (i, text) in edited {
+        *texts[i] = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+    }
+    count
+}
+
+/// Abbreviations whose `.` does not end a sentence.
+const ABBREVIATIONS: [&str; 12] = [
+    "dr", "e.g", "etc", "i.e", "jr", "mr", "mrs", "ms", "prof", "sr", "st", "vs",
+];
+
+/// Check if the text before a `.` ends with an abbreviation or an
+/// initial.
+fn is_abbreviation(before: &str) -> bool {
+    let word = before
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(|c: char| !c.is_alphanumeric());
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(initial), None) => initial.is_uppercase(),
+        _ => ABBREVIATIONS.contains(&word.to_lowercase().as_str()),
+    }
+}
+
+/// Get the byte offsets where each sentence of `text` but the last ends,
+/// with the ones where the next sentence starts, after the whitespace.
+fn sentence_breaks(text: &str) -> Vec<(usize, usize)> {
+    let mut breaks = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, ch)) = chars.next() {
+        if !matches!(ch, '.' | '!' | '?' | '…') {
+            continue;
+        }
+        let mut end = i + ch.len_utf8();
+        while let Some(&(j, c)) = chars.peek() {
+            if !matches!(
+                c,
+                '.' | '!' | '?' | '…' | '"' | '\'' | ')' | ']' | '”' | '’' | '»'
+            ) {
+                break;
+            }
+            end = j + c.len_utf8();
+            chars.next();
+        }
+        let next = text[end..].trim_start();
+        let start = text.len() - next.len();
+        let capitalized = next.chars().next().is_some_and(|c| !c.is_lowercase());
+        if start > end && capitalized && !(ch == '.' && is_abbreviation(&text[..i])) {
+            breaks.push((end, start));
+        }
+    }
+    breaks
+}
+
+/// Concatenated raw text of `nodes` and their inline descendants.
+fn inline_text(nodes: &[Node], text: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(el) if INLINE_TAGS.contains(&el.name.as_str()) => {
+                inline_text(&el.children, text)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Copy of `element` without its children and its id.
+fn shell(element: &Element, children: Vec<Node>) -> Element {
+    Element {
+        name: element.name.clone(),
+        attrs: element
+            .attrs
+            .iter()
+            .filter(|(key, _)| key != "id")
+            .cloned()
+            .collect(),
+        children,
+    }
+}
+
+/// Split `nodes` between the bytes `end` and `start` of their
+/// [`inline_text`], counting from `offset`. The nodes before `end` are
+/// left, and the ones after `start` are returned, or `None` if the text
+/// is not that long.
+fn split_inline(
+    nodes: &mut Vec<Node>,
+    end: usize,
+    start: usize,
+    offset: &mut usize,
+) -> Option<Vec<Node>> {
+    for i in 0..nodes.len() {
+        let mut after = match &mut nodes[i] {
+            Node::Text(text) => {
+                let text_start = *offset;
+                *offset += text.len();
+                if *offset <= end {
+                    continue;
+                }
+                let rest = text
+                    .get(start.saturating_sub(text_start)..)
+                    .unwrap_or_default()
+                    .to_string();
+                text.truncate(end - text_start);
+                if rest.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![Node::Text(rest)]
+                }
+            }
+            Node::Element(el) if INLINE_TAGS.contains(&el.name.as_str()) => {
+                let Some(children) = split_inline(&mut el.children, end, start, offset) else {
+                    continue;
+                };
+                if children.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![Node::Element(shell(el, children))]
+                }
+            }
+            _ => continue,
+        };
+        after.extend(nodes.drain(i + 1..));
+        let emptied = match &nodes[i] {
+            Node::Text(text) => text.is_empty(),
+            Node::Element(el) => el.children.is_empty(),
+            _ => false,
+        };
+        if emptied {
+            nodes.remove(i);
+        }
+        return Some(after);
+    }
+    None
+}
+
+/// Split `element` into one element per sentence.
+fn split_sentences(mut element: Element) -> Vec<Element> {
+    let mut text = String::new();
+    inline_text(&element.children, &mut text);
+    let mut sentences = Vec::new();
+    let mut rest_start = 0;
+    for (end, start) in sentence_breaks(&text) {
+        let mut offset = 0;
+        let Some(after) = split_inline(
+            &mut element.children,
+            end - rest_start,
+            start - rest_start,
+            &mut offset,
+        ) else {
+            break;
+        };
+        let next = shell(&element, after);
+        sentences.push(std::mem::replace(&mut element, next));
+        rest_start = start;
+    }
+    sentences.push(element);
+    sentences
+}
+
+fn split_nodes_sentences(nodes: &mut Vec<Node>, matcher: Matcher) -> usize {
+    let mut count = 0;
+    let mut split = Vec::with_capacity(nodes.len());
+    for (index, node) in std::mem::take(nodes).into_iter().enumerate() {
+        match node {
+            Node::Element(mut el) if !is_frozen(&el) => {
+                let (matched, children) = matcher.child(index, &el);
+                if matched {
+                    let sentences = split_sentences(el);
+                    count += sentences.len() - 1;
+                    split.extend(sentences.into_iter().map(Node::Element));
+                } else {
+                    count += split_nodes_sentences(&mut el.children, children);
+                    split.push(Node::Element(el));
+                }
+            }
+            node => split.push(node),
+        }
+    }
+    *nodes = split;
+    count
+}
+
+pub(crate) fn is_prose(element: &Element) -> bool {
+    !NON_PROSE_TAGS.contains(&element.name.as_str())
+}
+
+/// Replace the text nodes of `nodes` and their descendants by the result
+/// of `rewrite`, skipping the frozen elements and the ones for which
+/// `enter` is `false`, and the ones outside of the elements matched by
+/// `scope` unless `in_scope`.
+pub(crate) fn rewrite_nodes(
+    nodes: &mut Vec<Node>,
+    scope: Option<Matcher>,
+    in_scope: bool,
+    enter: &dyn Fn(&Element) -> bool,
+    rewrite: &mut impl FnMut(&str) -> Option<Vec<Node>>,
+) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    // The index of the node before the text nodes were replaced, as
+    // the marks of the matcher know it.
+    let mut index = 0;
+    while i < nodes.len() {
+        match &mut nodes[i] {
+            Node::Text(text) if in_scope => {
+                if let Some(replacement) = rewrite(text) {
+                    let len = replacement.len();
+                    nodes.splice(i..i + 1, replacement);
+                    count += 1;
+                    i += len;
+                    index += 1;
+                    continue;
+                }
+            }
+            Node::Element(el) => {
+                let (matched, children) = match scope {
+                    Some(scope) => {
+                        let (matched, children) = scope.child(index, el);
+                        (matched, Some(children))
+                    }
+                    None => (false, None),
+                };
+                count += rewrite_element(el, children, in_scope || matched, enter, rewrite);
+            }
+            _ => {}
+        }
+        i += 1;
+        index += 1;
+    }
+    count
+}
+
+/// Like [`rewrite_nodes`] for the children of `element`, matched by
+/// `scope`.
+pub(crate) fn rewrite_element(
+    element: &mut Element,
+    scope: Option<Matcher>,
+    in_scope: bool,
+    enter: &dyn Fn(&Element) -> bool,
+    rewrite: &mut impl FnMut(&str) -> Option<Vec<Node>>,
+) -> usize {
+    if is_frozen(element) || !enter(element) {
+        return 0;
+    }
+    rewrite_nodes(&mut element.children, scope, in_scope, enter, rewrite)
+}
+
+fn replace_text_shortcodes(
+    text: &str,
+    shortcodes: &HashMap<String, String>,
+    count: &mut usize,
+) -> Option<String> {
+    let is_code = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-');
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut found = false;
+    while let Some(start) = rest.find(':') {
+        replaced.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let len = rest[1..]
+            .find(|c: char| !is_code(c))
+            .unwrap_or(rest.len() - 1);
+        let value = rest[1..]
+            .get(len..)
+            .filter(|after| after.starts_with(':') && len > 0)
+            .and_then(|_| shortcodes.get(&rest[1..1 + len]));
+        match value {
+            Some(value) => {
+                replaced.push_str(value);
+                rest = &rest[len + 2..];
+                *count += 1;
+                found = true;
+            }
+            None => {
+                replaced.push(':');
+                rest = &rest[1..];
+            }
+        }
+    }
+    replaced.push_str(rest);
+    found.then_some(replaced)
+}
+
+/// Apply SmartyPants to `text`, knowing the character `before` it.
+fn smarten(text: &str, before: &mut Option<char>) -> Option<String> {
+    let mut smart = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let opening = before.is_none_or(|before| {
+            before.is_whitespace()
+                || matches!(before, '(' | '[' | '{' | '—' | '–' | '-' | '“' | '‘')
+        });
+        let replaced = match ch {
+            '"' if opening => '“',
+            '"' => '”',
+            '\'' if opening && !chars.peek().is_some_and(char::is_ascii_digit) => '‘',
+            '\'' => '’',
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                if chars.peek() == Some(&'-') {
+                    chars.next();
+                    '—'
+                } else {
+                    '–'
+                }
+            }
+            '.' if text_follows(&chars, "..") => {
+                chars.next();
+                chars.next();
+                '…'
+            }
+            ch => ch,
+        };
+        smart.push(replaced);
+        *before = Some(replaced);
+    }
+    (smart != text).then_some(smart)
+}
+
+fn text_follows(chars: &std::iter::Peekable<std::str::Chars>, text: &str) -> bool {
+    chars.clone().take(text.len()).eq(text.chars())
+}
+
+impl Rewritable for Vec<Node> {
+    fn rewrite_text(
+        &mut self,
+        scope: Option<&Selector>,
+        mut rewrite: impl FnMut(&str) -> Option<Vec<Node>>,
+    ) -> usize {
+        let marks = scope.and_then(|scope| scope.marks(self));
+        let scope = scope.map(|scope| Matcher::new(scope, marks.as_deref()));
+        rewrite_nodes(self, scope, scope.is_none(), &is_prose, &mut rewrite)
+    }
+
+    fn replace_shortcodes(
+        &mut self,
+        scope: Option<&Selector>,
+        shortcodes: &HashMap<String, String>,
+    ) -> usize {
+        let mut count = 0;
+        self.rewrite_text(scope, |text| {
+            let text = replace_text_shortcodes(text, shortcodes, &mut count)?;
+            Some(vec![Node::Text(text)])
+        });
+        count
+    }
+
+    fn smartypants(&mut self, scope: Option<&Selector>) -> &mut Self {
+        let mut before = None;
+        self.rewrite_text(scope, |text| {
+            let text = smarten(text, &mut before)?;
+            Some(vec![Node::Text(text)])
+        });
+        self
+    }
+
+    fn prose_segments(&self) -> Vec<ProseSegment> {
+        prose_segments_of(self)
+    }
+
+    fn edit_prose(&mut self, edits: &[ProseEdit]) -> usize {
+        edit_nodes_prose(self, edits)
+    }
+
+    fn split_paragraph_at_sentences(&mut self, selector: &Selector) -> usize {
+        let marks = selector.marks(self);
+        split_nodes_sentences(self, Matcher::new(selector, marks.as_deref()))
+    }
+}
+
+impl Rewritable for Element {
+    fn rewrite_text(
+        &mut self,
+        scope: Option<&Selector>,
+        mut rewrite: impl FnMut(&str) -> Option<Vec<Node>>,
+    ) -> usize {
+        let marks = scope.and_then(|scope| scope.element_marks(self));
+        let (in_scope, scope) = match scope {
+            Some(scope) => {
+                let (matched, children) = Matcher::new(scope, marks.as_deref()).child(0, self);
+                (matched, Some(children))
+            }
+            None => (true, None),
+        };
+        rewrite_element(self, scope, in_scope, &is_prose, &mut rewrite)
+    }
+
+    fn replace_shortcodes(
+        &mut self,
+        scope: Option<&Selector>,
+        shortcodes: &HashMap<String, String>,
+    ) -> usize {
+        let mut count = 0;
+        self.rewrite_text(scope, |text| {
+            let text = replace_text_shortcodes(text, shortcodes, &mut count)?;
+            Some(vec![Node::Text(text)])
+        });
+        count
+    }
+
+    fn smartypants(&mut self, scope: Option<&Selector>) -> &mut Self {
+        let mut before = None;
+        self.rewrite_text(scope, |text| {
+            let text = smarten(text, &mut before)?;
+            Some(vec![Node::Text(text)])
+        });
+        self
+    }
+
+    fn prose_segments(&self) -> Vec<ProseSegment> {
+        if !is_prose(self) || is_frozen(self) {
+            return Vec::new();
+        }
+        prose_segments_of(&self.children)
+    }
+
+    fn edit_prose(&mut self, edits: &[ProseEdit]) -> usize {
+        if !is_prose(self) || is_frozen(self) {
+            return 0;
+        }
+        edit_nodes_prose(&mut self.children, edits)
+    }
+
+    fn split_paragraph_at_sentences(&mut self, selector: &Selector) -> usize {
+        if is_frozen(self) {
+            return 0;
+        }
+        let marks = selector.element_marks(self);
+        let (_, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        split_nodes_sentences(&mut self.children, children)
+    }
+}
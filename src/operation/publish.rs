@@ -0,0 +1,242 @@
+use super::eager_image;
+use super::walk::{is_frozen, raw_text, retain, walk_mut};
+use crate::{Element, Node};
+
+/// Elements removed by [`Publishable::for_print`], being of no use on
+/// paper.
+const NON_PRINTABLE_TAGS: [&str; 10] = [
+    "button", "dialog", "input", "nav", "noscript", "object", "script", "select", "search",
+    "textarea",
+];
+
+/// Classes and ids of advertisements, kept as whole names to avoid
+/// matching `header` or `download`.
+const AD_NAMES: [&str; 8] = [
+    "ad",
+    "ads",
+    "adsbygoogle",
+    "advert",
+    "advertisement",
+    "banner-ad",
+    "sponsored",
+    "promo",
+];
+
+/// Structured comment at the top of a document, like the build and the
+/// license, set by [`Publishable::set_header_comment`].
+///
+/// It is written with its `name` on the first line and a `key: value`
+/// field per line, and recognized by its name to be replaced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderComment {
+    pub name: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl HeaderComment {
+    /// Create a header comment without any field.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a field, returning `self` for chaining.
+    pub fn field(mut self, key: &str, value: &str) -> Self {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Parse the text of a comment, or return `None` if it isn't a header
+    /// comment named `name`.
+    ///
+    /// ```
+    /// use html_editor::operation::HeaderComment;
+    ///
+    /// let header = HeaderComment::parse(" build-info\n build: 1a2b3c\n", "build-info").unwrap();
+    /// assert_eq!(header, HeaderComment::new("build-info").field("build", "1a2b3c"));
+    ///
+    /// assert!(HeaderComment::parse(" license ", "build-info").is_none());
+    /// ```
+    pub fn parse(comment: &str, name: &str) -> Option<Self> {
+        let mut lines = comment.trim().lines();
+        if lines.next()?.trim() != name {
+            return None;
+        }
+        let fields = lines
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .collect();
+        Some(Self {
+            name: name.to_string(),
+            fields,
+        })
+    }
+
+    /// Write the text of the comment, keeping each field on one line and
+    /// the comment from being closed early.
+    fn text(&self) -> String {
+        let clean = |text: &str| text.replace(['\r', '\n'], " ").replace("--", "- -");
+        let mut text = format!(" {}\n", clean(&self.name));
+        for (key, value) in &self.fields {
+            text.push_str(&format!(" {}: {}\n", clean(key), clean(value)));
+        }
+        text
+    }
+}
+
+/// Post-process documents for publishing.
+pub trait Publishable {
+    /// Prepare the document for print or PDF: remove the scripts, the
+    /// navigation, the advertisements and the interactive controls, load
+    /// the lazy images eagerly, and write the URL of the links in the
+    /// text after them.
+    ///
+    /// Links to fragments and to `javascript:`, or whose text already is
+    /// their URL, are left as is.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(concat!(
+    ///     r#"<nav>Menu</nav><p>See <a href="https://example.com">this</a>.</p>"#,
+    ///     r#"<button>Share</button><script>track()</script>"#,
+    /// ))
+    /// .unwrap();
+    /// dom.for_print();
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<p>See <a href="https://example.com">this</a> (https://example.com).</p>"#
+    /// );
+    /// ```
+    fn for_print(&mut self) -> &mut Self;
+
+    /// Insert the header comment at the top of the document, after the
+    /// doctype, replacing the previous comments of the same name, so that
+    /// running a build again doesn't stack them up. Returns `true` if a
+    /// previous one was replaced.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<!DOCTYPE html><!-- build-info\n build: old\n--><p>Hi</p>").unwrap();
+    /// let header = HeaderComment::new("build-info")
+    ///     .field("build", "1a2b3c")
+    ///     .field("license", "MIT");
+    ///
+    /// assert!(dom.set_header_comment(&header));
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     "<!DOCTYPE html><!-- build-info\n build: 1a2b3c\n license: MIT\n--><p>Hi</p>"
+    /// );
+    /// ```
+    fn set_header_comment(&mut self, header: &HeaderComment) -> bool;
+}
+
+/// Replace the header comments named like `header` among `nodes` by a
+/// single one, at the top after the doctype.
+fn set_nodes_header_comment(nodes: &mut Vec<Node>, header: &HeaderComment) -> bool {
+    let before = nodes.len();
+    nodes.retain(|node| match node {
+        Node::Comment(comment) => HeaderComment::parse(comment, &header.name).is_none(),
+        _ => true,
+    });
+    let replaced = nodes.len() < before;
+    let index = nodes
+        .iter()
+        .position(|node| matches!(node, Node::Doctype(_)))
+        .map_or(0, |index| index + 1);
+    nodes.insert(index, Node::Comment(header.text()));
+    replaced
+}
+
+fn is_printable(element: &Element) -> bool {
+    let is_ad = [element.get_attr("class"), element.get_attr("id")]
+        .into_iter()
+        .flatten()
+        .flat_map(str::split_ascii_whitespace)
+        .any(|name| {
+            let name = name.to_ascii_lowercase();
+            AD_NAMES.contains(&name.as_str()) || name.starts_with("ad-") || name.starts_with("ads-")
+        });
+    let role = element.get_attr("role").unwrap_or("");
+    !is_ad
+        && !NON_PRINTABLE_TAGS.contains(&element.name.as_str())
+        && !matches!(role, "navigation" | "search" | "button")
+}
+
+/// URL of a link to write after it when printed.
+fn printed_url(link: &Element) -> Option<String> {
+    let href = link.get_attr("href")?.trim();
+    let is_script = href
+        .get(..11)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("javascript:"));
+    if href.is_empty() || href.starts_with('#') || is_script {
+        return None;
+    }
+    if raw_text(&link.children).trim() == href {
+        return None;
+    }
+    Some(href.to_string())
+}
+
+fn expand_links(nodes: &mut Vec<Node>) {
+    let mut i = 0;
+    while i < nodes.len() {
+        match &mut nodes[i] {
+            Node::Element(el) if is_frozen(el) => {}
+            Node::Element(el) if el.name == "a" => {
+                if let Some(url) = printed_url(el) {
+                    i += 1;
+                    nodes.insert(i, Node::Text(format!(" ({})", url)));
+                }
+            }
+            Node::Element(el) => expand_links(&mut el.children),
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+fn prepare_print(nodes: &mut Vec<Node>) {
+    retain(nodes, &mut is_printable);
+    walk_mut(nodes, &mut |el| {
+        if let Some(Node::Element(img)) = eager_image(el).and_then(|mut nodes| nodes.pop()) {
+            *el = img;
+        }
+    });
+    expand_links(nodes);
+}
+
+impl Publishable for Vec<Node> {
+    fn for_print(&mut self) -> &mut Self {
+        prepare_print(self);
+        self
+    }
+
+    fn set_header_comment(&mut self, header: &HeaderComment) -> bool {
+        set_nodes_header_comment(self, header)
+    }
+}
+
+impl Publishable for Element {
+    fn for_print(&mut self) -> &mut Self {
+        if !is_frozen(self) {
+            prepare_print(&mut self.children);
+        }
+        self
+    }
+
+    fn set_header_comment(&mut self, header: &HeaderComment) -> bool {
+        if is_frozen(self) {
+            return false;
+        }
+        set_nodes_header_comment(&mut self.children, header)
+    }
+}
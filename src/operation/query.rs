@@ -1,3 +1,5 @@
+use super::selector::{Context, Matcher};
+use super::walk::is_frozen;
 use super::Selector;
 use crate::{Element, Node};
 
@@ -75,65 +77,134 @@ pub trait Queryable {
     fn query_mut(&mut self, selector: &Selector) -> Option<&mut Element>;
 }
 
-impl Queryable for Vec<Node> {
-    fn query(&self, selector: &Selector) -> Option<&Element> {
-        for node in self {
-            if let Some(element) = node.as_element() {
-                if let Some(elem) = element.query(selector) {
-                    return Some(elem);
-                }
+fn query_nodes<'a>(
+    nodes: &'a [Node],
+    selector: &Selector,
+    parent: Option<&Context<'a, '_>>,
+) -> Option<&'a Element> {
+    Context::children(nodes, parent).find_map(|context| query_element(&context, selector))
+}
+
+fn query_element<'a>(context: &Context<'a, '_>, selector: &Selector) -> Option<&'a Element> {
+    if selector.matches_in(context) {
+        Some(context.element)
+    } else {
+        query_nodes(&context.element.children, selector, Some(context))
+    }
+}
+
+fn query_all_nodes<'a>(
+    nodes: &'a [Node],
+    selector: &Selector,
+    parent: Option<&Context<'a, '_>>,
+    elements: &mut Vec<&'a Element>,
+) {
+    for context in Context::children(nodes, parent) {
+        query_all_element(&context, selector, elements);
+    }
+}
+
+fn query_all_element<'a>(
+    context: &Context<'a, '_>,
+    selector: &Selector,
+    elements: &mut Vec<&'a Element>,
+) {
+    // Recursively traverse the descendants nodes
+    query_all_nodes(&context.element.children, selector, Some(context), elements);
+    if selector.matches_in(context) {
+        elements.push(context.element);
+    }
+}
+
+/// Query the first element matched by `matcher` in `nodes`, skipping the
+/// frozen elements and their descendants if `editable`.
+fn query_nodes_mut<'a>(
+    nodes: &'a mut [Node],
+    matcher: Matcher,
+    editable: bool,
+) -> Option<&'a mut Element> {
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if let Some(element) = node.as_element_mut() {
+            if editable && is_frozen(element) {
+                continue;
+            }
+            let (matched, children) = matcher.child(index, element);
+            if let Some(elem) = query_element_mut(element, matched, children, editable) {
+                return Some(elem);
             }
         }
-        None
+    }
+    None
+}
+
+fn query_element_mut<'a>(
+    element: &'a mut Element,
+    matched: bool,
+    children: Matcher,
+    editable: bool,
+) -> Option<&'a mut Element> {
+    if matched {
+        Some(element)
+    } else {
+        query_nodes_mut(&mut element.children, children, editable)
+    }
+}
+
+/// Like [`Queryable::query_mut`], but outside of the frozen elements, for
+/// the edits to respect [`Editable::freeze`](super::Editable::freeze).
+pub(crate) fn query_editable_mut<'a>(
+    nodes: &'a mut [Node],
+    selector: &Selector,
+) -> Option<&'a mut Element> {
+    let marks = selector.marks(nodes);
+    query_nodes_mut(nodes, Matcher::new(selector, marks.as_deref()), true)
+}
+
+/// Like [`query_editable_mut`] within `element`.
+pub(crate) fn query_element_editable_mut<'a>(
+    element: &'a mut Element,
+    selector: &Selector,
+) -> Option<&'a mut Element> {
+    if is_frozen(element) {
+        return None;
+    }
+    let marks = selector.element_marks(element);
+    let (matched, children) = Matcher::new(selector, marks.as_deref()).child(0, element);
+    query_element_mut(element, matched, children, true)
+}
+
+impl Queryable for Vec<Node> {
+    fn query(&self, selector: &Selector) -> Option<&Element> {
+        query_nodes(self, selector, None)
     }
 
     fn query_all(&self, selector: &Selector) -> Vec<&Element> {
         let mut elements = Vec::new();
-        for node in self {
-            if let Some(element) = node.as_element() {
-                // Recursively traverse the descendants nodes
-                let sub_elements = element.query_all(selector);
-                elements.extend(sub_elements);
-            }
-        }
+        query_all_nodes(self, selector, None, &mut elements);
         elements
     }
 
     fn query_mut(&mut self, selector: &Selector) -> Option<&mut Element> {
-        for node in self {
-            if let Some(element) = node.as_element_mut() {
-                if let Some(elem) = element.query_mut(selector) {
-                    return Some(elem);
-                }
-            }
-        }
-        None
+        let marks = selector.marks(self);
+        query_nodes_mut(self, Matcher::new(selector, marks.as_deref()), false)
     }
 }
 
 impl Queryable for Element {
     fn query(&self, selector: &Selector) -> Option<&Element> {
-        if selector.matches(self) {
-            Some(self)
-        } else {
-            self.children.query(selector)
-        }
+        query_element(&Context::root(self), selector)
     }
 
     fn query_all(&self, selector: &Selector) -> Vec<&Element> {
-        let mut elements = self.children.query_all(selector);
-        if selector.matches(self) {
-            elements.push(self);
-        }
+        let mut elements = Vec::new();
+        query_all_element(&Context::root(self), selector, &mut elements);
         elements
     }
 
     fn query_mut(&mut self, selector: &Selector) -> Option<&mut Element> {
-        if selector.matches(self) {
-            Some(self)
-        } else {
-            self.children.query_mut(selector)
-        }
+        let marks = selector.element_marks(self);
+        let (matched, children) = Matcher::new(selector, marks.as_deref()).child(0, self);
+        query_element_mut(self, matched, children, false)
     }
 }
 
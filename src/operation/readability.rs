@@ -0,0 +1,507 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{structure::heading_level, walk::retain, Editable, Htmlifiable};
+use crate::{css, Element, Node};
+
+/// Candidates of the main content region, in order of preference.
+const MAIN_CONTENT_RULES: [fn(&Element) -> bool; 4] = [
+    |el| el.name == "main",
+    |el| el.get_attr("role") == Some("main"),
+    |el| el.name == "article",
+    |el| el.name == "body",
+];
+
+/// Keywords in the class or id of consent banners, which are only
+/// removed when they also look like overlays.
+const CONSENT_KEYWORDS: [&str; 6] = ["cookie", "consent", "gdpr", "cmp", "onetrust", "cookiebot"];
+
+/// Keywords in the class or id of overlays, which are only removed
+/// when they are also fixed.
+const OVERLAY_KEYWORDS: [&str; 6] = [
+    "overlay",
+    "modal",
+    "popup",
+    "backdrop",
+    "lightbox",
+    "interstitial",
+];
+
+/// Landmark region of a page, as defined by ARIA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Landmark {
+    /// `<header>` of the page, or `role="banner"`.
+    Banner,
+    /// `<nav>`, or `role="navigation"`, unless it is a breadcrumb.
+    Navigation,
+    /// Navigation labelled or classed as a breadcrumb, or a
+    /// `BreadcrumbList` microdata item.
+    Breadcrumb,
+    Main,
+    /// `<aside>`, or `role="complementary"`.
+    Complementary,
+    /// `<footer>` of the page, or `role="contentinfo"`.
+    ContentInfo,
+    Search,
+    /// Labelled `<form>`, or `role="form"`.
+    Form,
+    /// Labelled `<section>`, or `role="region"`.
+    Region,
+}
+
+/// Locate the main content of a document.
+pub trait Readable {
+    /// Find the element holding the main content of the document.
+    ///
+    /// The first `<main>` element is preferred, followed by the first
+    /// element with `role="main"`, the first `<article>` and finally
+    /// the `<body>`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = r#"
+    ///     <body>
+    ///         <nav>Home</nav>
+    ///         <article><p>Lorem Ipsum</p></article>
+    ///     </body>"#;
+    ///
+    /// let content = parse(html).unwrap().main_content().unwrap().html();
+    /// assert_eq!(content, "<article><p>Lorem Ipsum</p></article>");
+    /// ```
+    fn main_content(&self) -> Option<&Element>;
+
+    /// Find the element holding the main content of the document as
+    /// mutable. See [`Readable::main_content`] for how it is located.
+    ///
+    /// ```
+    /// use html_editor::{parse, Node};
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<body><main></main></body>").unwrap();
+    /// dom.main_content_mut()
+    ///     .unwrap()
+    ///     .children
+    ///     .push(Node::Text("Lorem Ipsum".to_string()));
+    ///
+    /// assert_eq!(dom.html(), "<body><main>Lorem Ipsum</main></body>");
+    /// ```
+    fn main_content_mut(&mut self) -> Option<&mut Element>;
+
+    /// Remove the consent banners and the overlays covering the page,
+    /// returning the removed elements.
+    ///
+    /// A class or the id of an element looks like a consent banner or an
+    /// overlay when it, or one of its parts split on `-`, `_` or the
+    /// case changes, is a keyword like `cookie` or `modal`.
+    ///
+    /// An element is removed if it looks like a consent banner and is
+    /// fixed or sticky by its inline style, is a dialog or has a
+    /// `z-index` of at least 1000. It is also removed if it is fixed and
+    /// either looks like an overlay, covers the whole viewport or has a
+    /// `z-index` of at least 1000.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(concat!(
+    ///     r#"<body><div class="cookie-notice" style="position: fixed">We use cookies</div>"#,
+    ///     r#"<p id="cookie-recipe">Lorem</p></body>"#,
+    /// )).unwrap();
+    /// dom.remove_overlays();
+    ///
+    /// assert_eq!(dom.html(), r#"<body><p id="cookie-recipe">Lorem</p></body>"#);
+    /// ```
+    fn remove_overlays(&mut self) -> Vec<Element>;
+
+    /// Extract the element targeted by a fragment identifier like
+    /// `#section-2`, the leading `#` being optional.
+    ///
+    /// When `section` is `true` and the element is a heading, the
+    /// following siblings are extracted too, up to the next heading of
+    /// the same or a higher level, which is what a reader expects when
+    /// following a deep link.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse(r#"<h2 id="a">A</h2><p>1</p><h3>B</h3><p>2</p><h2>C</h2>"#).unwrap();
+    /// let excerpt = dom.extract_fragment("#a", true).unwrap();
+    ///
+    /// assert_eq!(excerpt.html(), r#"<h2 id="a">A</h2><p>1</p><h3>B</h3><p>2</p>"#);
+    /// ```
+    fn extract_fragment(&self, fragment: &str, section: bool) -> Option<Vec<Node>>;
+
+    /// Find the landmark regions of the page, in document order for each
+    /// kind of landmark.
+    ///
+    /// Explicit roles win over the tags, and like for browsers,
+    /// `<header>` and `<footer>` are only landmarks when they are not
+    /// inside an `<article>`, `<aside>`, `<main>`, `<nav>` or `<section>`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse("<header>Logo</header><main><article><header>Title</header></article></main>").unwrap();
+    /// let landmarks = dom.landmarks();
+    ///
+    /// assert_eq!(landmarks[&Landmark::Banner].len(), 1);
+    /// assert_eq!(landmarks[&Landmark::Main][0].name, "main");
+    /// ```
+    fn landmarks(&self) -> HashMap<Landmark, Vec<&Element>>;
+}
+
+fn find(nodes: &[Node], rule: fn(&Element) -> bool) -> Option<&Element> {
+    for node in nodes {
+        if let Node::Element(el) = node {
+            if rule(el) {
+                return Some(el);
+            }
+            if let Some(found) = find(&el.children, rule) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn find_mut(nodes: &mut [Node], rule: fn(&Element) -> bool) -> Option<&mut Element> {
+    for node in nodes {
+        if let Node::Element(el) = node {
+            if rule(el) {
+                return Some(el);
+            }
+            if let Some(found) = find_mut(&mut el.children, rule) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Find the element with the given `id`, together with its siblings and
+/// its index among them.
+fn find_id<'a>(nodes: &'a [Node], id: &str) -> Option<(&'a [Node], usize)> {
+    for (i, node) in nodes.iter().enumerate() {
+        if let Node::Element(el) = node {
+            if el.get_attr("id") == Some(id) {
+                return Some((nodes, i));
+            }
+            if let Some(found) = find_id(&el.children, id) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+fn extract_from(nodes: &[Node], fragment: &str, section: bool) -> Option<Vec<Node>> {
+    let id = fragment.strip_prefix('#').unwrap_or(fragment);
+    let (siblings, index) = find_id(nodes, id)?;
+    let level = siblings[index].as_element().and_then(heading_level);
+    let level = match level {
+        Some(level) if section => level,
+        _ => return Some(vec![siblings[index].clone()]),
+    };
+    let end = siblings[index + 1..]
+        .iter()
+        .position(|node| {
+            node.as_element()
+                .and_then(heading_level)
+                .is_some_and(|other| other <= level)
+        })
+        .map_or(siblings.len(), |offset| index + 1 + offset);
+    Some(siblings[index..end].to_vec())
+}
+
+fn is_labelled(element: &Element) -> bool {
+    element.get_attr("aria-label").is_some() || element.get_attr("aria-labelledby").is_some()
+}
+
+fn is_breadcrumb(element: &Element) -> bool {
+    let contains = |name: &str| {
+        element
+            .get_attr(name)
+            .is_some_and(|value| value.to_ascii_lowercase().contains("breadcrumb"))
+    };
+    contains("aria-label") || contains("class") || contains("id")
+}
+
+fn landmark_of(element: &Element, sectioned: bool) -> Option<Landmark> {
+    if element
+        .get_attr("itemtype")
+        .is_some_and(|itemtype| itemtype.ends_with("/BreadcrumbList"))
+    {
+        return Some(Landmark::Breadcrumb);
+    }
+    let role = element
+        .get_attr("role")
+        .and_then(|role| role.split_ascii_whitespace().next())
+        .map(str::to_ascii_lowercase);
+    let landmark = match role.as_deref() {
+        Some("banner") => Landmark::Banner,
+        Some("navigation") => Landmark::Navigation,
+        Some("main") => Landmark::Main,
+        Some("complementary") => Landmark::Complementary,
+        Some("contentinfo") => Landmark::ContentInfo,
+        Some("search") => Landmark::Search,
+        Some("form") => Landmark::Form,
+        Some("region") => Landmark::Region,
+        Some(_) => return None,
+        None => match element.name.as_str() {
+            "header" if !sectioned => Landmark::Banner,
+            "nav" => Landmark::Navigation,
+            "main" => Landmark::Main,
+            "aside" => Landmark::Complementary,
+            "footer" if !sectioned => Landmark::ContentInfo,
+            "search" => Landmark::Search,
+            "form" if is_labelled(element) => Landmark::Form,
+            "section" if is_labelled(element) => Landmark::Region,
+            _ => return None,
+        },
+    };
+    match landmark {
+        Landmark::Navigation if is_breadcrumb(element) => Some(Landmark::Breadcrumb),
+        landmark => Some(landmark),
+    }
+}
+
+fn collect_landmarks<'a>(
+    nodes: &'a [Node],
+    sectioned: bool,
+    landmarks: &mut HashMap<Landmark, Vec<&'a Element>>,
+) {
+    for el in nodes.iter().filter_map(Node::as_element) {
+        collect_element_landmarks(el, sectioned, landmarks);
+    }
+}
+
+fn collect_element_landmarks<'a>(
+    element: &'a Element,
+    sectioned: bool,
+    landmarks: &mut HashMap<Landmark, Vec<&'a Element>>,
+) {
+    if let Some(landmark) = landmark_of(element, sectioned) {
+        landmarks.entry(landmark).or_default().push(element);
+    }
+    let sectioned = sectioned
+        || matches!(
+            element.name.as_str(),
+            "article" | "aside" | "main" | "nav" | "section"
+        );
+    collect_landmarks(&element.children, sectioned, landmarks);
+}
+
+/// Split `name` on `-`, `_` and the changes from lowercase to
+/// uppercase, like `CybotCookiebotDialog` into `Cybot`, `Cookiebot` and
+/// `Dialog`.
+fn name_parts(name: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+    for (i, ch) in name.char_indices() {
+        if ch == '-' || ch == '_' {
+            parts.push(&name[start..i]);
+            start = i + 1;
+        } else if ch.is_ascii_uppercase() && previous.is_some_and(|p: char| p.is_ascii_lowercase())
+        {
+            parts.push(&name[start..i]);
+            start = i;
+        }
+        previous = Some(ch);
+    }
+    parts.push(&name[start..]);
+    parts
+}
+
+fn has_keyword(element: &Element, keywords: &[&str]) -> bool {
+    let names = [element.get_attr("class"), element.get_attr("id")];
+    let is_keyword = |part: &str| keywords.iter().any(|k| part.eq_ignore_ascii_case(k));
+    names
+        .into_iter()
+        .flatten()
+        .flat_map(str::split_ascii_whitespace)
+        .any(|name| is_keyword(name) || name_parts(name).into_iter().any(is_keyword))
+}
+
+fn is_overlay(element: &Element) -> bool {
+    let declarations = css::declarations(element.get_attr("style").unwrap_or(""));
+    let value = |property: &str| {
+        declarations
+            .iter()
+            .rev()
+            .find(|(name, _)| name == property)
+            .map(|(_, value)| value.to_ascii_lowercase())
+    };
+    let position = value("position");
+    let on_top = value("z-index")
+        .and_then(|v| v.parse::<i64>().ok())
+        .is_some_and(|z| z >= 1000);
+    if has_keyword(element, &CONSENT_KEYWORDS) {
+        let dialog = element.name == "dialog"
+            || matches!(element.get_attr("role"), Some("dialog" | "alertdialog"));
+        return matches!(position.as_deref(), Some("fixed" | "sticky")) || dialog || on_top;
+    }
+    if position.as_deref() != Some("fixed") {
+        return false;
+    }
+    let is_zero = |property: &str| value(property).is_some_and(|v| v == "0" || v == "0px");
+    let is_full = |property: &str, unit: &str| {
+        value(property).is_some_and(|v| v == "100%" || v == format!("100{}", unit))
+    };
+    let covers_viewport = value("inset").is_some_and(|v| v.split_whitespace().all(|v| v == "0"))
+        || (is_zero("top") && is_zero("left") && is_zero("right") && is_zero("bottom"))
+        || (is_full("width", "vw") && is_full("height", "vh"));
+
+    has_keyword(element, &OVERLAY_KEYWORDS) || covers_viewport || on_top
+}
+
+fn remove_overlays_in(nodes: &mut Vec<Node>) -> Vec<Element> {
+    let mut removed = Vec::new();
+    retain(nodes, &mut |el| {
+        let overlay = is_overlay(el);
+        if overlay {
+            removed.push(el.clone());
+        }
+        !overlay
+    });
+    removed
+}
+
+impl Readable for Vec<Node> {
+    fn main_content(&self) -> Option<&Element> {
+        MAIN_CONTENT_RULES.iter().find_map(|rule| find(self, *rule))
+    }
+
+    fn main_content_mut(&mut self) -> Option<&mut Element> {
+        let rule = MAIN_CONTENT_RULES
+            .into_iter()
+            .find(|rule| find(self, *rule).is_some())?;
+        find_mut(self, rule)
+    }
+
+    fn remove_overlays(&mut self) -> Vec<Element> {
+        remove_overlays_in(self)
+    }
+
+    fn extract_fragment(&self, fragment: &str, section: bool) -> Option<Vec<Node>> {
+        extract_from(self, fragment, section)
+    }
+
+    fn landmarks(&self) -> HashMap<Landmark, Vec<&Element>> {
+        let mut landmarks = HashMap::new();
+        collect_landmarks(self, false, &mut landmarks);
+        landmarks
+    }
+}
+
+impl Readable for Element {
+    fn main_content(&self) -> Option<&Element> {
+        MAIN_CONTENT_RULES.iter().find_map(|rule| {
+            if rule(self) {
+                Some(self)
+            } else {
+                find(&self.children, *rule)
+            }
+        })
+    }
+
+    fn main_content_mut(&mut self) -> Option<&mut Element> {
+        let rule = MAIN_CONTENT_RULES
+            .into_iter()
+            .find(|rule| rule(self) || find(&self.children, *rule).is_some())?;
+        if rule(self) {
+            Some(self)
+        } else {
+            find_mut(&mut self.children, rule)
+        }
+    }
+
+    fn remove_overlays(&mut self) -> Vec<Element> {
+        remove_overlays_in(&mut self.children)
+    }
+
+    fn extract_fragment(&self, fragment: &str, section: bool) -> Option<Vec<Node>> {
+        let id = fragment.strip_prefix('#').unwrap_or(fragment);
+        if self.get_attr("id") == Some(id) {
+            return Some(vec![self.clone().into_node()]);
+        }
+        extract_from(&self.children, fragment, section)
+    }
+
+    fn landmarks(&self) -> HashMap<Landmark, Vec<&Element>> {
+        let mut landmarks = HashMap::new();
+        collect_element_landmarks(self, false, &mut landmarks);
+        landmarks
+    }
+}
+
+/// Normalized html of a block, which is empty for whitespace.
+fn block_key(node: &Node) -> String {
+    let mut nodes = vec![node.clone()];
+    nodes.trim().html()
+}
+
+/// Concatenate the main content of paginated documents into one
+/// document.
+///
+/// The first page is used as the frame of the result, and its main
+/// content (see [`Readable::main_content`]) is replaced by the main
+/// content of all the pages. Blocks that lead or trail a page and
+/// have already been seen on the previous pages, like a repeated
+/// article header or pagination footer, are only kept once. A page
+/// without recognizable main content contributes all of its nodes.
+///
+/// ```
+/// use html_editor::parse;
+/// use html_editor::operation::*;
+///
+/// let pages = vec![
+///     parse("<body><nav>Menu</nav><main><h1>Title</h1><p>One</p></main></body>").unwrap(),
+///     parse("<body><nav>Menu</nav><main><h1>Title</h1><p>Two</p></main></body>").unwrap(),
+/// ];
+///
+/// assert_eq!(
+///     stitch_pages(&pages).html(),
+///     "<body><nav>Menu</nav><main><h1>Title</h1><p>One</p><p>Two</p></main></body>"
+/// );
+/// ```
+pub fn stitch_pages(pages: &[Vec<Node>]) -> Vec<Node> {
+    let mut document = match pages.first() {
+        Some(first) => first.clone(),
+        None => return Vec::new(),
+    };
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut content: Vec<Node> = Vec::new();
+
+    for (i, page) in pages.iter().enumerate() {
+        let blocks = match page.main_content() {
+            Some(main) => &main.children,
+            None => page,
+        };
+        let keys: Vec<String> = blocks.iter().map(block_key).collect();
+        let is_repeated = |key: &String| key.is_empty() || seen.contains(key);
+
+        let (mut start, mut end) = (0, blocks.len());
+        if i > 0 {
+            while start < end && is_repeated(&keys[start]) {
+                start += 1;
+            }
+            while end > start && is_repeated(&keys[end - 1]) {
+                end -= 1;
+            }
+        }
+
+        content.extend_from_slice(&blocks[start..end]);
+        seen.extend(keys.into_iter().filter(|key| !key.is_empty()));
+    }
+
+    match document.main_content_mut() {
+        Some(main) => main.children = content,
+        None => document = content,
+    }
+    document
+}
@@ -0,0 +1,279 @@
+use super::walk::{has_class, raw_text, walk_element_mut, walk_mut};
+use super::{is_prose, rewrite_element, rewrite_nodes};
+use crate::{entity, Element, Node};
+
+/// Render the code and the math of documents with external tools.
+pub trait Renderable {
+    /// Pass the code of the `<pre><code>` blocks to `highlight`, together
+    /// with their language given by a `language-*` or `lang-*` class on
+    /// the `<code>` or the `<pre>`, and replace the content of the
+    /// `<code>` by the returned nodes.
+    ///
+    /// The code is given as plain text, with its character references
+    /// decoded, while the returned nodes are inserted as is: like parsed
+    /// ones, their text must be escaped, which is what highlighters
+    /// producing html do. Returns the number of blocks highlighted.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<pre><code class="language-rust">a &lt; b</code></pre>"#).unwrap();
+    /// dom.highlight_code(|code, language| {
+    ///     assert_eq!((code, language), ("a < b", Some("rust")));
+    ///     parse(r#"a <span class="op">&lt;</span> b"#).ok()
+    /// });
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<pre><code class="language-rust">a <span class="op">&lt;</span> b</code></pre>"#
+    /// );
+    /// ```
+    fn highlight_code(
+        &mut self,
+        highlight: impl FnMut(&str, Option<&str>) -> Option<Vec<Node>>,
+    ) -> usize;
+
+    /// Pass the formulas to `render`, together with whether they are
+    /// displayed as a block, and replace them by the returned nodes.
+    ///
+    /// Formulas are either delimited in the prose text by `$...$` or
+    /// `\(...\)`, or by `$$...$$` or `\[...\]` for display ones, or are
+    /// the content of `.math` elements, which are displayed if they are
+    /// `<div>` or have the `display` class. Like in Pandoc, an inline `$`
+    /// formula cannot start or end with a space, the closing `$` cannot
+    /// be followed by a digit, and `\$` is a literal dollar.
+    ///
+    /// The formulas are given with their character references decoded,
+    /// and are left as is when `render` returns `None`. Returns the
+    /// number of formulas rendered.
+    ///
+    /// ```
+    /// use html_editor::{parse, Node};
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<p>Area: $\\pi r^2$, for $5 or $10.</p>").unwrap();
+    /// dom.render_math(|tex, display| {
+    ///     let class = if display { "display" } else { "inline" };
+    ///     Some(vec![Node::new_element("var", vec![("class", class)], vec![Node::Text(tex.to_string())])])
+    /// });
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<p>Area: <var class="inline">\pi r^2</var>, for $5 or $10.</p>"#
+    /// );
+    /// ```
+    fn render_math(&mut self, render: impl FnMut(&str, bool) -> Option<Vec<Node>>) -> usize;
+}
+
+/// Formula found in a text by [`split_math`].
+struct Formula {
+    /// Byte range of the formula with its delimiters.
+    start: usize,
+    end: usize,
+    /// Byte range of the formula without its delimiters.
+    tex: (usize, usize),
+    display: bool,
+}
+
+fn split_math(text: &str) -> Vec<Formula> {
+    let bytes = text.as_bytes();
+    let mut formulas = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &text[i..];
+        if rest.starts_with("\\$") {
+            i += 2;
+            continue;
+        }
+        let delimited = |open: &str, close: &str, display: bool| {
+            let len = rest
+                .strip_prefix(open)?
+                .find(close)
+                .filter(|len| *len > 0)?;
+            let tex = i + open.len();
+            Some(Formula {
+                start: i,
+                end: tex + len + close.len(),
+                tex: (tex, tex + len),
+                display,
+            })
+        };
+        let inline_dollar = || {
+            let first = rest.strip_prefix('$')?.chars().next()?;
+            if first.is_whitespace() || first == '$' {
+                return None;
+            }
+            let j = (i + 2..bytes.len()).find(|&j| {
+                bytes[j] == b'$'
+                    && !bytes[j - 1].is_ascii_whitespace()
+                    && bytes[j - 1] != b'\\'
+                    && !bytes.get(j + 1).is_some_and(u8::is_ascii_digit)
+            })?;
+            Some(Formula {
+                start: i,
+                end: j + 1,
+                tex: (i + 1, j),
+                display: false,
+            })
+        };
+        let found = delimited("\\(", "\\)", false)
+            .or_else(|| delimited("\\[", "\\]", true))
+            .or_else(|| delimited("$$", "$$", true))
+            .or_else(inline_dollar);
+        match found {
+            Some(formula) => {
+                i = formula.end;
+                formulas.push(formula);
+            }
+            None => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    formulas
+}
+
+fn render_text_math(
+    text: &str,
+    render: &mut impl FnMut(&str, bool) -> Option<Vec<Node>>,
+    count: &mut usize,
+) -> Option<Vec<Node>> {
+    let mut nodes = Vec::new();
+    let mut last = 0;
+    let push_text = |nodes: &mut Vec<Node>, text: &str| {
+        if text.is_empty() {
+            return;
+        }
+        match nodes.last_mut() {
+            Some(Node::Text(last)) => last.push_str(text),
+            _ => nodes.push(Node::Text(text.to_string())),
+        }
+    };
+    for formula in split_math(text) {
+        let tex = entity::decode(&text[formula.tex.0..formula.tex.1]);
+        if let Some(rendered) = render(&tex, formula.display) {
+            push_text(&mut nodes, &text[last..formula.start]);
+            nodes.extend(rendered);
+            last = formula.end;
+            *count += 1;
+        }
+    }
+    if last == 0 {
+        return None;
+    }
+    push_text(&mut nodes, &text[last..]);
+    Some(nodes)
+}
+
+fn render_math_element(
+    element: &mut Element,
+    render: &mut impl FnMut(&str, bool) -> Option<Vec<Node>>,
+    count: &mut usize,
+) {
+    if !has_class(element, "math") {
+        return;
+    }
+    let display = element.name == "div" || has_class(element, "display");
+    let tex = entity::decode(&raw_text(&element.children));
+    let tex = tex.trim();
+    let tex = [("\\(", "\\)"), ("\\[", "\\]"), ("$$", "$$"), ("$", "$")]
+        .iter()
+        .find_map(|(open, close)| tex.strip_prefix(open)?.strip_suffix(close))
+        .unwrap_or(tex);
+    if let Some(rendered) = render(tex, display) {
+        element.children = rendered;
+        *count += 1;
+    }
+}
+
+fn language_of(element: &Element) -> Option<&str> {
+    element
+        .get_attr("class")?
+        .split_ascii_whitespace()
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+}
+
+fn highlight_block(
+    pre: &mut Element,
+    highlight: &mut impl FnMut(&str, Option<&str>) -> Option<Vec<Node>>,
+) -> bool {
+    if pre.name != "pre" {
+        return false;
+    }
+    let pre_language = language_of(pre).map(str::to_string);
+    let mut blocks = pre.children.iter_mut().filter(|node| match node {
+        Node::Text(text) => !text.trim().is_empty(),
+        _ => true,
+    });
+    let code = match (blocks.next(), blocks.next()) {
+        (Some(Node::Element(code)), None) if code.name == "code" => code,
+        _ => return false,
+    };
+
+    let language = language_of(code).map(str::to_string).or(pre_language);
+    let text = entity::decode(&raw_text(&code.children));
+    match highlight(&text, language.as_deref()) {
+        Some(nodes) => {
+            code.children = nodes;
+            true
+        }
+        None => false,
+    }
+}
+
+impl Renderable for Vec<Node> {
+    fn highlight_code(
+        &mut self,
+        mut highlight: impl FnMut(&str, Option<&str>) -> Option<Vec<Node>>,
+    ) -> usize {
+        let mut count = 0;
+        walk_mut(self, &mut |el| {
+            count += highlight_block(el, &mut highlight) as usize
+        });
+        count
+    }
+
+    fn render_math(&mut self, mut render: impl FnMut(&str, bool) -> Option<Vec<Node>>) -> usize {
+        let mut count = 0;
+        walk_mut(self, &mut |el| {
+            if is_prose(el) {
+                render_math_element(el, &mut render, &mut count)
+            }
+        });
+        let enter = |el: &Element| is_prose(el) && !has_class(el, "math");
+        rewrite_nodes(self, None, true, &enter, &mut |text| {
+            render_text_math(text, &mut render, &mut count)
+        });
+        count
+    }
+}
+
+impl Renderable for Element {
+    fn highlight_code(
+        &mut self,
+        mut highlight: impl FnMut(&str, Option<&str>) -> Option<Vec<Node>>,
+    ) -> usize {
+        let mut count = 0;
+        walk_element_mut(self, &mut |el| {
+            count += highlight_block(el, &mut highlight) as usize
+        });
+        count
+    }
+
+    fn render_math(&mut self, mut render: impl FnMut(&str, bool) -> Option<Vec<Node>>) -> usize {
+        let mut count = 0;
+        walk_element_mut(self, &mut |el| {
+            if is_prose(el) {
+                render_math_element(el, &mut render, &mut count)
+            }
+        });
+        let enter = |el: &Element| is_prose(el) && !has_class(el, "math");
+        rewrite_element(self, None, true, &enter, &mut |text| {
+            render_text_math(text, &mut render, &mut count)
+        });
+        count
+    }
+}
@@ -0,0 +1,150 @@
+use super::complex::{Combinator, ComplexSelector};
+use super::compound::CompoundSelector;
+use super::simple::{AttrOperator, AttributeSelector, Nth, SimpleSelector};
+use super::Selector;
+
+/// Build a [`Selector`] from its parts, without formatting and escaping
+/// a string to parse. Created by [`Selector::builder`].
+///
+/// The methods add the conditions on the current element, until a
+/// combinator method, like [`SelectorBuilder::child`], moves to the next
+/// one. An element without any condition matches any element, like `*`.
+#[derive(Debug, Clone, Default)]
+pub struct SelectorBuilder {
+    /// The selectors of the list already built.
+    selectors: Vec<ComplexSelector>,
+    /// The compound selectors of the selector being built, with the
+    /// combinator following each of them.
+    compounds: Vec<(CompoundSelector, Combinator)>,
+    /// The simple selectors of the current element.
+    current: Vec<SimpleSelector>,
+}
+
+impl SelectorBuilder {
+    fn with(mut self, simple_selector: SimpleSelector) -> Self {
+        self.current.push(simple_selector);
+        self
+    }
+
+    fn attr_with(self, name: &str, operator: AttrOperator, value: &str) -> Self {
+        self.with(SimpleSelector::Attribute(AttributeSelector {
+            name: name.to_string(),
+            value: Some((operator, value.to_string())),
+            case_insensitive: false,
+        }))
+    }
+
+    fn combine(mut self, combinator: Combinator) -> Self {
+        let compound = CompoundSelector::new(std::mem::take(&mut self.current));
+        self.compounds.push((compound, combinator));
+        self
+    }
+
+    /// Match the elements with the tag `name`, like `div`.
+    pub fn tag(self, name: &str) -> Self {
+        self.with(SimpleSelector::Tag(name.to_string()))
+    }
+
+    /// Match the elements with the class `name`, like `.ad`.
+    pub fn class(self, name: &str) -> Self {
+        self.with(SimpleSelector::Class(name.to_string()))
+    }
+
+    /// Match the element with the id `id`, like `#main`.
+    pub fn id(self, id: &str) -> Self {
+        self.with(SimpleSelector::Id(id.to_string()))
+    }
+
+    /// Match the elements with the attribute `name`, like `[href]`.
+    pub fn attr(self, name: &str) -> Self {
+        self.with(SimpleSelector::Attribute(AttributeSelector {
+            name: name.to_string(),
+            value: None,
+            case_insensitive: false,
+        }))
+    }
+
+    /// `[name=value]`
+    pub fn attr_eq(self, name: &str, value: &str) -> Self {
+        self.attr_with(name, AttrOperator::Equals, value)
+    }
+
+    /// `[name^=value]`
+    pub fn attr_prefix(self, name: &str, value: &str) -> Self {
+        self.attr_with(name, AttrOperator::Prefix, value)
+    }
+
+    /// `[name$=value]`
+    pub fn attr_suffix(self, name: &str, value: &str) -> Self {
+        self.attr_with(name, AttrOperator::Suffix, value)
+    }
+
+    /// `[name*=value]`
+    pub fn attr_contains(self, name: &str, value: &str) -> Self {
+        self.attr_with(name, AttrOperator::Contains, value)
+    }
+
+    /// Match the elements which match none of `selector`, like `:not()`.
+    pub fn not(self, selector: &Selector) -> Self {
+        self.with(SimpleSelector::Not(selector.0.clone()))
+    }
+
+    /// `:first-child`
+    pub fn first_child(self) -> Self {
+        self.with(SimpleSelector::FirstChild)
+    }
+
+    /// `:last-child`
+    pub fn last_child(self) -> Self {
+        self.with(SimpleSelector::LastChild)
+    }
+
+    /// `:nth-child(an+b)`
+    pub fn nth_child(self, a: i64, b: i64) -> Self {
+        self.with(SimpleSelector::NthChild(Nth { a, b }))
+    }
+
+    /// Move to a descendant of the current element, like `div p`.
+    pub fn descendant(self) -> Self {
+        self.combine(Combinator::Descendant)
+    }
+
+    /// Move to a child of the current element, like `ul > li`.
+    pub fn child(self) -> Self {
+        self.combine(Combinator::Child)
+    }
+
+    /// Move to the sibling right after the current element, like `h2 + p`.
+    pub fn next_sibling(self) -> Self {
+        self.combine(Combinator::NextSibling)
+    }
+
+    /// Move to a sibling after the current element, like `h2 ~ p`.
+    pub fn subsequent_sibling(self) -> Self {
+        self.combine(Combinator::SubsequentSibling)
+    }
+
+    /// Start another selector of the list, like `, ` does.
+    pub fn or(mut self) -> Self {
+        let selector = self.complex();
+        self.selectors.push(selector);
+        self
+    }
+
+    /// Take the selector being built.
+    fn complex(&mut self) -> ComplexSelector {
+        let subject = CompoundSelector::new(std::mem::take(&mut self.current));
+        let relatives = std::mem::take(&mut self.compounds)
+            .into_iter()
+            .rev()
+            .map(|(compound, combinator)| (combinator, compound))
+            .collect();
+        ComplexSelector { subject, relatives }
+    }
+
+    pub fn build(mut self) -> Selector {
+        let selector = self.complex();
+        self.selectors.push(selector);
+        Selector(self.selectors)
+    }
+}
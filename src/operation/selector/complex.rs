@@ -0,0 +1,197 @@
+use std::{fmt, ptr};
+
+use super::{compound::CompoundSelector, Context, Specificity};
+
+/// Relation between two compound selectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    /// `a b`, where `b` is inside `a`.
+    Descendant,
+    /// `a > b`, where `b` is a child of `a`.
+    Child,
+    /// `a + b`, where `b` is right after `a`.
+    NextSibling,
+    /// `a ~ b`, where `b` is after `a`, among the same parent.
+    SubsequentSibling,
+}
+
+impl Combinator {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '>' => Some(Combinator::Child),
+            '+' => Some(Combinator::NextSibling),
+            '~' => Some(Combinator::SubsequentSibling),
+            _ => None,
+        }
+    }
+
+    /// Get the combinator as written between two compound selectors.
+    fn separator(self) -> &'static str {
+        match self {
+            Combinator::Descendant => " ",
+            Combinator::Child => " > ",
+            Combinator::NextSibling => " + ",
+            Combinator::SubsequentSibling => " ~ ",
+        }
+    }
+}
+
+/// A sequence of compound selectors separated by combinators.
+#[derive(Debug, Clone)]
+pub struct ComplexSelector {
+    /// The compound selector the element itself matches.
+    pub subject: CompoundSelector,
+    /// The compound selectors before the subject, from the nearest one,
+    /// with the combinator following each of them.
+    pub relatives: Vec<(Combinator, CompoundSelector)>,
+}
+
+impl ComplexSelector {
+    pub fn matches(&self, context: &Context) -> bool {
+        self.subject.matches(context) && self.matches_relatives(0, context, None)
+    }
+
+    /// Check if the element matches with the farthest compound selector
+    /// related to `anchor` by the combinator.
+    fn matches_anchored(&self, context: &Context, anchor: (Combinator, &Context)) -> bool {
+        self.subject.matches(context) && self.matches_relatives(0, context, Some(anchor))
+    }
+
+    fn matches_relatives(
+        &self,
+        index: usize,
+        context: &Context,
+        anchor: Option<(Combinator, &Context)>,
+    ) -> bool {
+        let Some((combinator, compound)) = self.relatives.get(index) else {
+            return match anchor {
+                Some((combinator, anchor)) => is_related(combinator, anchor, context),
+                None => true,
+            };
+        };
+        match combinator {
+            Combinator::Descendant => {
+                let mut ancestor = context.parent;
+                while let Some(context) = ancestor {
+                    if compound.matches(context)
+                        && self.matches_relatives(index + 1, context, anchor)
+                    {
+                        return true;
+                    }
+                    ancestor = context.parent;
+                }
+                false
+            }
+            Combinator::Child => context.parent.is_some_and(|parent| {
+                compound.matches(parent) && self.matches_relatives(index + 1, parent, anchor)
+            }),
+            Combinator::NextSibling => context.previous_siblings().next().is_some_and(|sibling| {
+                compound.matches(&sibling) && self.matches_relatives(index + 1, &sibling, anchor)
+            }),
+            Combinator::SubsequentSibling => context.previous_siblings().any(|sibling| {
+                compound.matches(&sibling) && self.matches_relatives(index + 1, &sibling, anchor)
+            }),
+        }
+    }
+
+    pub fn specificity(&self) -> Specificity {
+        let (mut a, mut b, mut c) = self.subject.specificity();
+        for (_, compound) in &self.relatives {
+            let (a2, b2, c2) = compound.specificity();
+            (a, b, c) = (a + a2, b + b2, c + c2);
+        }
+        (a, b, c)
+    }
+
+    /// Match the type selectors with the exact case of the tags.
+    pub fn make_case_sensitive(&mut self) {
+        self.subject.make_case_sensitive();
+        for (_, compound) in &mut self.relatives {
+            compound.make_case_sensitive();
+        }
+    }
+
+    /// Check if matching depends on more than the element itself.
+    pub fn is_contextual(&self) -> bool {
+        !self.relatives.is_empty() || self.subject.is_contextual()
+    }
+}
+
+impl fmt::Display for ComplexSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (combinator, compound) in self.relatives.iter().rev() {
+            write!(f, "{}{}", compound, combinator.separator())?;
+        }
+        write!(f, "{}", self.subject)
+    }
+}
+
+/// A complex selector relative to an anchor element, like `> img` in
+/// `div:has(> img)`.
+#[derive(Debug, Clone)]
+pub struct RelativeSelector {
+    /// The combinator between the anchor and the selector.
+    pub combinator: Combinator,
+    pub selector: ComplexSelector,
+}
+
+impl RelativeSelector {
+    /// Check if an element related to the anchor matches.
+    pub fn matches(&self, anchor: &Context) -> bool {
+        let matches = |context: &Context| {
+            any_in_subtree(context, &|context| {
+                self.selector
+                    .matches_anchored(context, (self.combinator, anchor))
+            })
+        };
+        match self.combinator {
+            Combinator::Descendant | Combinator::Child => {
+                Context::children(&anchor.element.children, Some(anchor)).any(|c| matches(&c))
+            }
+            Combinator::NextSibling | Combinator::SubsequentSibling => {
+                anchor.next_siblings().any(|c| matches(&c))
+            }
+        }
+    }
+}
+
+impl fmt::Display for RelativeSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            self.combinator.separator().trim_start(),
+            self.selector
+        )
+    }
+}
+
+/// Check if the element or one of its descendants satisfies `predicate`.
+fn any_in_subtree(context: &Context, predicate: &dyn Fn(&Context) -> bool) -> bool {
+    predicate(context)
+        || Context::children(&context.element.children, Some(context))
+            .any(|child| any_in_subtree(&child, predicate))
+}
+
+/// Check if `context` is related to `anchor` by the combinator.
+fn is_related(combinator: Combinator, anchor: &Context, context: &Context) -> bool {
+    let is_anchor = |context: &Context| ptr::eq(context.element, anchor.element);
+    match combinator {
+        Combinator::Descendant => {
+            let mut ancestor = context.parent;
+            while let Some(context) = ancestor {
+                if is_anchor(context) {
+                    return true;
+                }
+                ancestor = context.parent;
+            }
+            false
+        }
+        Combinator::Child => context.parent.is_some_and(is_anchor),
+        Combinator::NextSibling => context
+            .previous_siblings()
+            .next()
+            .is_some_and(|sibling| is_anchor(&sibling)),
+        Combinator::SubsequentSibling => context.previous_siblings().any(|s| is_anchor(&s)),
+    }
+}
@@ -1,38 +1,318 @@
-use std::vec;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::{Arc, RwLock};
 
-use super::simple::SimpleSelector;
+use super::{
+    complex::ComplexSelector, max_specificity, simple::SimpleSelector, Context, Specificity,
+};
+use crate::operation::walk::raw_text;
+use crate::{entity, Element, Node};
+
+/// Names of the compiled selectors, shared by the selectors parsed again
+/// and again, like the same rules for each document.
+static NAMES: RwLock<BTreeSet<Arc<str>>> = RwLock::new(BTreeSet::new());
+
+/// Get the shared copy of `name`.
+fn intern(name: &str) -> Arc<str> {
+    if let Some(name) = NAMES.read().unwrap_or_else(|e| e.into_inner()).get(name) {
+        return name.clone();
+    }
+    let mut names = NAMES.write().unwrap_or_else(|e| e.into_inner());
+    match names.get(name) {
+        Some(name) => name.clone(),
+        None => {
+            let interned: Arc<str> = Arc::from(name);
+            names.insert(interned.clone());
+            interned
+        }
+    }
+}
+
+/// Get the bit of `class` in the masks of the classes, from its FNV-1a
+/// hash.
+fn class_bit(class: &str) -> u64 {
+    let hash = class.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    1 << (hash % 64)
+}
+
+/// The parts of a compound selector checked first, split out of its
+/// simple selectors when it is built, to reject most elements without
+/// going through them.
+#[derive(Debug, Clone, Default)]
+struct Compiled {
+    tag: Option<Arc<str>>,
+    id: Option<Arc<str>>,
+    classes: Vec<Arc<str>>,
+    /// The bits of the `classes`, to reject the elements missing one of
+    /// them before comparing the names, when there are several.
+    class_mask: u64,
+    case_sensitive: bool,
+    /// The indexes of the other simple selectors, checked afterwards.
+    rest: Vec<usize>,
+}
+
+impl Compiled {
+    fn new(parts: &[SimpleSelector]) -> Self {
+        let mut compiled = Compiled::default();
+        for (index, simple_selector) in parts.iter().enumerate() {
+            match simple_selector {
+                SimpleSelector::Tag(tag) if compiled.tag.is_none() => {
+                    compiled.tag = Some(intern(tag))
+                }
+                SimpleSelector::Id(id) if compiled.id.is_none() => compiled.id = Some(intern(id)),
+                SimpleSelector::Class(class) => {
+                    compiled.class_mask |= class_bit(class);
+                    compiled.classes.push(intern(class));
+                }
+                SimpleSelector::Universal => {}
+                SimpleSelector::CaseSensitive => compiled.case_sensitive = true,
+                _ => compiled.rest.push(index),
+            }
+        }
+        compiled
+    }
+
+    /// Check the type, id and class selectors.
+    fn matches(&self, element: &Element) -> bool {
+        if let Some(tag) = &self.tag {
+            let same = match self.case_sensitive {
+                true => **tag == element.name,
+                false => tag.eq_ignore_ascii_case(&element.name),
+            };
+            if !same {
+                return false;
+            }
+        }
+        if self.id.is_some() && element.get_attr("id") != self.id.as_deref() {
+            return false;
+        }
+        if self.classes.is_empty() {
+            return true;
+        }
+        let Some(classes) = element.get_attr("class") else {
+            return false;
+        };
+        if self.classes.len() > 1 {
+            let mask = classes
+                .split_ascii_whitespace()
+                .fold(0, |mask, class| mask | class_bit(class));
+            if mask & self.class_mask != self.class_mask {
+                return false;
+            }
+        }
+        self.classes.iter().all(|selector_class| {
+            classes
+                .split_ascii_whitespace()
+                .any(|element_class| element_class == &**selector_class)
+        })
+    }
+}
 
 /// A sequence of simple selectors that are not separated by a
 /// combinator. A compound selector represents a set of
 /// simultaneous conditions on a single element.
-#[derive(Debug)]
-pub struct CompoundSelector(pub Vec<SimpleSelector>);
+#[derive(Clone)]
+pub struct CompoundSelector {
+    parts: Vec<SimpleSelector>,
+    compiled: Compiled,
+}
 
-impl From<&str> for CompoundSelector {
-    fn from(selector: &str) -> Self {
-        let selector = selector.trim();
-        let mut simple_selectors = vec![];
+impl CompoundSelector {
+    pub fn new(parts: Vec<SimpleSelector>) -> Self {
+        let compiled = Compiled::new(&parts);
+        CompoundSelector { parts, compiled }
+    }
+
+    pub fn matches(&self, context: &Context) -> bool {
+        self.compiled.matches(context.element)
+            && self
+                .compiled
+                .rest
+                .iter()
+                .all(|&index| self.matches_simple(&self.parts[index], context))
+    }
 
-        let mut start = 0;
-        let mut end;
+    fn matches_simple(&self, simple_selector: &SimpleSelector, context: &Context) -> bool {
+        let element = context.element;
+        let case_sensitive = self.compiled.case_sensitive;
+        let same_tag = |other: &Element| match case_sensitive {
+            true => other.name == element.name,
+            false => other.name.eq_ignore_ascii_case(&element.name),
+        };
+        // Matching the same selectors over many elements, avoid
+        // allocating for each of them.
+        match simple_selector {
+            SimpleSelector::Universal | SimpleSelector::CaseSensitive => true,
+            SimpleSelector::Class(selector_class) => {
+                element.get_attr("class").is_some_and(|classes| {
+                    classes
+                        .split_ascii_whitespace()
+                        .any(|element_class| element_class == selector_class)
+                })
+            }
+            SimpleSelector::Id(selector_id) => element.get_attr("id") == Some(selector_id),
+            SimpleSelector::Tag(tag) if case_sensitive => tag == &element.name,
+            SimpleSelector::Tag(tag) => tag.eq_ignore_ascii_case(&element.name),
+            SimpleSelector::Namespace(namespace) => context.namespace == *namespace,
+            SimpleSelector::Attribute(attribute) => attribute.matches(element),
+            SimpleSelector::AttributePrefix(prefix) => {
+                element.attrs.iter().any(|(key, _)| has_prefix(key, prefix))
+            }
+            SimpleSelector::Not(selectors) => {
+                !selectors.iter().any(|selector| selector.matches(context))
+            }
+            SimpleSelector::Has(selectors) => {
+                selectors.iter().any(|selector| selector.matches(context))
+            }
+            SimpleSelector::OnlyChild => {
+                context.previous_siblings().next().is_none()
+                    && context.next_siblings().next().is_none()
+            }
+            SimpleSelector::Root => context.parent.is_none(),
+            SimpleSelector::FirstChild => context.previous_siblings().next().is_none(),
+            SimpleSelector::LastChild => context.next_siblings().next().is_none(),
+            SimpleSelector::NthChild(nth) => nth.matches(context.previous_siblings().count() + 1),
+            SimpleSelector::FirstOfType => !context
+                .previous_siblings()
+                .any(|sibling| same_tag(sibling.element)),
+            SimpleSelector::LastOfType => !context
+                .next_siblings()
+                .any(|sibling| same_tag(sibling.element)),
+            SimpleSelector::NthOfType(nth) => {
+                let previous = context
+                    .previous_siblings()
+                    .filter(|sibling| same_tag(sibling.element));
+                nth.matches(previous.count() + 1)
+            }
+            SimpleSelector::Empty => is_empty(element, false),
+            SimpleSelector::Blank => is_empty(element, true),
+            SimpleSelector::Custom(pseudo_class) => (pseudo_class.predicate)(element),
+            SimpleSelector::Contains(text) => {
+                entity::decode(&raw_text(&element.children)).contains(text.as_str())
+            }
+        }
+    }
 
-        while start < selector.len() {
-            end = selector[start + 1..]
-                .find(|c| c == '.' || c == '#')
-                .map(|n| n + start + 1)
-                .unwrap_or(selector.len());
-            let start_char = selector.chars().nth(start).unwrap();
+    pub fn specificity(&self) -> Specificity {
+        self.parts
+            .iter()
+            .fold((0, 0, 0), |(a, b, c), simple_selector| {
+                let (a2, b2, c2) = match simple_selector {
+                    SimpleSelector::Universal
+                    | SimpleSelector::Namespace(_)
+                    | SimpleSelector::CaseSensitive => (0, 0, 0),
+                    SimpleSelector::Id(_) => (1, 0, 0),
+                    SimpleSelector::Tag(_) => (0, 0, 1),
+                    // Like the selector list in its argument.
+                    SimpleSelector::Not(selectors) => max_specificity(selectors),
+                    SimpleSelector::Has(selectors) => selectors
+                        .iter()
+                        .map(|relative| relative.selector.specificity())
+                        .max()
+                        .unwrap_or_default(),
+                    _ => (0, 1, 0),
+                };
+                (a + a2, b + b2, c + c2)
+            })
+    }
 
-            use SimpleSelector::*;
-            simple_selectors.push(match start_char {
-                '.' => Class(selector[start + 1..end].to_string()),
-                '#' => Id(selector[start + 1..end].to_string()),
-                _ => Tag(selector[start..end].to_string()),
-            });
+    /// Check if the tags are compared with their exact case, with
+    /// `:case-sensitive`.
+    fn is_case_sensitive(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|simple_selector| matches!(simple_selector, SimpleSelector::CaseSensitive))
+    }
 
-            start = end;
+    /// Match the type selectors and the `*-of-type` pseudo-classes with
+    /// the exact case of the tags, adding `:case-sensitive` to the
+    /// compound selectors comparing them.
+    pub fn make_case_sensitive(&mut self) {
+        let mut compares_tags = false;
+        for simple_selector in &mut self.parts {
+            match simple_selector {
+                SimpleSelector::Tag(_)
+                | SimpleSelector::FirstOfType
+                | SimpleSelector::LastOfType
+                | SimpleSelector::NthOfType(_) => compares_tags = true,
+                SimpleSelector::Not(selectors) => selectors
+                    .iter_mut()
+                    .for_each(ComplexSelector::make_case_sensitive),
+                SimpleSelector::Has(selectors) => selectors
+                    .iter_mut()
+                    .for_each(|relative| relative.selector.make_case_sensitive()),
+                _ => {}
+            }
+        }
+        if compares_tags && !self.is_case_sensitive() {
+            self.parts.push(SimpleSelector::CaseSensitive);
         }
+        self.compiled = Compiled::new(&self.parts);
+    }
+
+    pub fn is_contextual(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|simple_selector| match simple_selector {
+                SimpleSelector::Not(selectors) => {
+                    selectors.iter().any(ComplexSelector::is_contextual)
+                }
+                // The descendants may change while the tree is edited, and so
+                // may the children seen by `:empty`, `:blank` and the custom
+                // pseudo-classes, which get a traversal of their own after the
+                // previous edits.
+                SimpleSelector::Has(_)
+                | SimpleSelector::Contains(_)
+                | SimpleSelector::Empty
+                | SimpleSelector::Blank
+                | SimpleSelector::Custom(_)
+                | SimpleSelector::Namespace(_)
+                | SimpleSelector::OnlyChild
+                | SimpleSelector::Root
+                | SimpleSelector::FirstChild
+                | SimpleSelector::LastChild
+                | SimpleSelector::NthChild(_)
+                | SimpleSelector::FirstOfType
+                | SimpleSelector::LastOfType
+                | SimpleSelector::NthOfType(_) => true,
+                _ => false,
+            })
+    }
+}
+
+impl fmt::Debug for CompoundSelector {
+    /// Write the simple selectors only, the compiled form being made from
+    /// them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CompoundSelector")
+            .field(&self.parts)
+            .finish()
+    }
+}
 
-        CompoundSelector(simple_selectors)
+impl fmt::Display for CompoundSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.parts.is_empty() {
+            return write!(f, "*");
+        }
+        self.parts
+            .iter()
+            .try_for_each(|simple| write!(f, "{}", simple))
     }
 }
+
+/// Check if the attribute `name` starts with `prefix`, ignoring the case.
+pub fn has_prefix(name: &str, prefix: &str) -> bool {
+    name.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+}
+
+fn is_empty(element: &Element, allow_whitespace: bool) -> bool {
+    element.children.iter().all(|node| match node {
+        Node::Comment(_) | Node::ProcessingInstruction(_) => true,
+        Node::Text(text) => text.is_empty() || allow_whitespace && text.trim().is_empty(),
+        _ => false,
+    })
+}
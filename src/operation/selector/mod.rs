@@ -1,20 +1,343 @@
+mod builder;
+mod complex;
 mod compound;
+mod parser;
 mod simple;
+mod token;
+mod xpath;
 
-use crate::Element;
+use std::fmt;
+use std::sync::Arc;
 
-use self::{compound::CompoundSelector, simple::SimpleSelector};
+use crate::{Element, Node};
+
+pub use self::builder::SelectorBuilder;
+use self::complex::ComplexSelector;
+pub(crate) use self::compound::has_prefix;
+use self::simple::{Namespace, PseudoClass};
+pub use self::xpath::{XPath, XPathItem};
 
 /// Basic selector. It follows the
 /// [CSS selector](https://developer.mozilla.org/en-US/docs/Web/CSS/CSS_Selectors)
 /// standard, but not all rules are supported now. Please refer
 /// to [`Selector::from`](Selector::from).
-#[derive(Debug)]
-pub struct Selector(Vec<CompoundSelector>);
+///
+/// It holds a list of selectors, like `script, style, noscript`, and
+/// matches the elements matching any of them. Parsing is done once, so
+/// keep the selector to match it against many documents.
+#[derive(Debug, Clone)]
+pub struct Selector(Vec<ComplexSelector>);
+
+/// Specificity of a selector, being the numbers of ids, of classes,
+/// attributes and pseudo-classes, and of types.
+pub type Specificity = (u32, u32, u32);
+
+fn max_specificity(selectors: &[ComplexSelector]) -> Specificity {
+    selectors
+        .iter()
+        .map(ComplexSelector::specificity)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Position of an element in the tree, to match the selectors depending
+/// on its ancestors or siblings.
+pub(crate) struct Context<'a, 'p> {
+    pub element: &'a Element,
+    pub parent: Option<&'p Context<'a, 'p>>,
+    /// The nodes containing the element, which is the `index`th of them.
+    pub siblings: &'a [Node],
+    pub index: usize,
+    /// The namespace of the element, from its `<svg>` or `<math>`
+    /// ancestors, computed while descending.
+    pub namespace: Namespace,
+}
+
+impl<'a, 'p> Context<'a, 'p> {
+    /// Get the context of an element matched on its own.
+    pub fn root(element: &'a Element) -> Self {
+        Context {
+            element,
+            parent: None,
+            siblings: &[],
+            index: 0,
+            namespace: Namespace::Html.of_element(&element.name),
+        }
+    }
+
+    /// Get the contexts of the elements among `nodes`.
+    pub fn children(
+        nodes: &'a [Node],
+        parent: Option<&'p Context<'a, 'p>>,
+    ) -> impl Iterator<Item = Context<'a, 'p>> {
+        let inherited = Self::inherited(parent);
+        nodes.iter().enumerate().filter_map(move |(index, node)| {
+            let element = node.as_element()?;
+            Some(Context {
+                element,
+                parent,
+                siblings: nodes,
+                index,
+                namespace: inherited.of_element(&element.name),
+            })
+        })
+    }
+
+    /// Get the namespace the children of `parent` inherit.
+    fn inherited(parent: Option<&Context>) -> Namespace {
+        parent.map_or(Namespace::Html, |parent| {
+            parent.namespace.of_children(&parent.element.name)
+        })
+    }
+
+    /// Get the contexts of the elements after this one, from the nearest
+    /// one.
+    pub fn next_siblings(&self) -> impl Iterator<Item = Context<'a, 'p>> {
+        let (siblings, parent, start) = (self.siblings, self.parent, self.index + 1);
+        let inherited = Self::inherited(parent);
+        siblings
+            .get(start..)
+            .unwrap_or(&[])
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, node)| {
+                let element = node.as_element()?;
+                Some(Context {
+                    element,
+                    parent,
+                    siblings,
+                    index: start + index,
+                    namespace: inherited.of_element(&element.name),
+                })
+            })
+    }
+
+    /// Get the contexts of the elements before this one, from the
+    /// nearest one.
+    pub fn previous_siblings(&self) -> impl Iterator<Item = Context<'a, 'p>> {
+        let (siblings, parent) = (self.siblings, self.parent);
+        let inherited = Self::inherited(parent);
+        siblings[..self.index]
+            .iter()
+            .enumerate()
+            .rev()
+            .filter_map(move |(index, node)| {
+                let element = node.as_element()?;
+                Some(Context {
+                    element,
+                    parent,
+                    siblings,
+                    index,
+                    namespace: inherited.of_element(&element.name),
+                })
+            })
+    }
+}
+
+/// Whether an element and its descendants match a selector, computed
+/// before editing the tree.
+#[derive(Debug, Default)]
+pub(crate) struct Mark {
+    matched: bool,
+    children: Vec<Mark>,
+}
+
+/// Matches the elements while editing the tree, from the marks computed
+/// beforehand if the selector depends on the context of the elements.
+#[derive(Clone, Copy)]
+pub(crate) enum Matcher<'a> {
+    Direct(&'a Selector),
+    Marked(&'a [Mark]),
+}
+
+impl<'a> Matcher<'a> {
+    pub fn new(selector: &'a Selector, marks: Option<&'a [Mark]>) -> Self {
+        match marks {
+            Some(marks) => Matcher::Marked(marks),
+            None => Matcher::Direct(selector),
+        }
+    }
+
+    /// Check if `element`, the `index`th of the nodes, matches, and get
+    /// the matcher for its children.
+    pub fn child(self, index: usize, element: &Element) -> (bool, Matcher<'a>) {
+        match self {
+            Matcher::Direct(selector) => (selector.matches(element), self),
+            Matcher::Marked(marks) => match marks.get(index) {
+                Some(mark) => (mark.matched, Matcher::Marked(&mark.children)),
+                None => (false, Matcher::Marked(&[])),
+            },
+        }
+    }
+}
+
+/// Error of an invalid or unsupported selector, returned by
+/// [`Selector::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// Byte offset of the error in the selector.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, complex) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", complex)?;
+        }
+        Ok(())
+    }
+}
 
 impl Selector {
+    /// Parse a selector like [`Selector::from`] does, but return an error
+    /// for the first of the list which is invalid or not supported, to
+    /// validate the selectors given by users.
+    ///
+    /// ```
+    /// use html_editor::operation::*;
+    ///
+    /// assert!(Selector::parse("ul > li, p").is_ok());
+    ///
+    /// let error = Selector::parse("ul > > li").unwrap_err();
+    /// assert_eq!(error.position, 5);
+    /// assert_eq!(error.to_string(), "Unexpected `>` at position 5");
+    /// ```
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        parser::parse_list(selector)
+            .into_iter()
+            .map(|result| result.map_err(|(position, message)| SelectorError { position, message }))
+            .collect::<Result<_, _>>()
+            .map(Selector)
+    }
+
+    /// Build a selector from its parts, without parsing a string.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let selector = Selector::builder()
+    ///     .tag("div")
+    ///     .class("ad")
+    ///     .attr_eq("data-src", "x\"]")
+    ///     .or()
+    ///     .tag("ul")
+    ///     .child()
+    ///     .tag("li")
+    ///     .build();
+    ///
+    /// let dom = parse(r#"<div class="ad" data-src='x"]'></div><ul><li></li></ul><li></li>"#).unwrap();
+    /// assert_eq!(dom.query_all(&selector).len(), 2);
+    /// ```
+    pub fn builder() -> SelectorBuilder {
+        SelectorBuilder::default()
+    }
+
+    /// Register a pseudo-class, like `:external-link`, matching the
+    /// elements for which `predicate` returns `true`, for the selectors
+    /// parsed afterwards. The leading `:` is optional, and the name is
+    /// case-insensitive like the built-in ones, which cannot be replaced.
+    /// Registering a name again replaces its predicate.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// Selector::register(":external-link", |el| {
+    ///     el.attrs.iter().any(|(k, v)| k == "href" && v.starts_with("http"))
+    /// });
+    ///
+    /// let dom = parse(r#"<a href="/home">Home</a><a href="https://example.com">Out</a>"#).unwrap();
+    /// let links = dom.query_all(&Selector::from("a:external-link"));
+    ///
+    /// assert_eq!(links.len(), 1);
+    /// assert_eq!(links[0].attrs[0].1, "https://example.com");
+    /// ```
+    pub fn register(name: &str, predicate: impl Fn(&Element) -> bool + Send + Sync + 'static) {
+        let name = name.strip_prefix(':').unwrap_or(name);
+        PseudoClass {
+            name: name.to_ascii_lowercase(),
+            predicate: Arc::new(predicate),
+        }
+        .register();
+    }
+
+    /// Get the [specificity](https://developer.mozilla.org/en-US/docs/Web/CSS/Specificity)
+    /// of the selector, or the highest one of its list, to order the CSS
+    /// rules applying to an element.
+    ///
+    /// ```
+    /// use html_editor::operation::Selector;
+    ///
+    /// assert_eq!(Selector::from("#nav > li.item a").specificity(), (1, 1, 2));
+    /// assert_eq!(Selector::from("p, a:not(#x, .y)").specificity(), (1, 0, 1));
+    /// assert_eq!(Selector::from("*").specificity(), (0, 0, 0));
+    /// ```
+    pub fn specificity(&self) -> Specificity {
+        max_specificity(&self.0)
+    }
+
+    /// Match the type selectors with the exact case of the tags, like in
+    /// XML or XHTML documents, instead of ignoring the ASCII case like in
+    /// html. The `*-of-type` pseudo-classes then count the siblings of
+    /// the exact same tag too.
+    ///
+    /// It is written back with the non-standard `:case-sensitive`
+    /// pseudo-class, which can be parsed as well.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse("<Item>A</Item><item>B</item>").unwrap();
+    ///
+    /// assert_eq!(dom.query_all(&Selector::from("ITEM")).len(), 2);
+    /// let selector = Selector::from("item").case_sensitive();
+    /// assert_eq!(dom.query(&selector).unwrap().html(), "<item>B</item>");
+    /// assert_eq!(selector.to_css_string(), "item:case-sensitive");
+    /// ```
+    pub fn case_sensitive(mut self) -> Self {
+        self.0
+            .iter_mut()
+            .for_each(ComplexSelector::make_case_sensitive);
+        self
+    }
+
+    /// Write the selector back into CSS, like its [`Display`](fmt::Display),
+    /// so that it is parsed into the same selector, to log or store it.
+    ///
+    /// ```
+    /// use html_editor::operation::Selector;
+    ///
+    /// let selector = Selector::from("ul>li.item:nth-child( 2n + 1 ) a[href^='https:' i]");
+    ///
+    /// assert_eq!(
+    ///     selector.to_css_string(),
+    ///     r#"ul > li.item:nth-child(2n+1) a[href^="https:" i]"#
+    /// );
+    /// assert_eq!(selector.to_string(), selector.to_css_string());
+    /// ```
+    pub fn to_css_string(&self) -> String {
+        self.to_string()
+    }
+
     /// Check if the `element` matches the `selector`.
     ///
+    /// The element is matched on its own, so a selector about its
+    /// ancestors, like `div p`, won't match it.
+    ///
     /// ```
     /// use html_editor::{Node, Element};
     /// use html_editor::operation::*;
@@ -30,35 +353,43 @@ impl Selector {
     /// assert_eq!(selector.matches(&element), true);
     /// ```
     pub fn matches(&self, element: &Element) -> bool {
-        let element_classes = element
-            .attrs
-            .iter()
-            .find(|(key, _)| key == "class")
-            .map(|(_, v)| v.split(' ').map(|name| name.trim()).collect::<Vec<_>>());
-        let element_id = element
-            .attrs
-            .iter()
-            .find(|(key, _)| key == "id")
-            .map(|(_, v)| v);
-
-        self.0.iter().any(|compound_selector| {
-            compound_selector
-                .0
-                .iter()
-                .all(|simple_selector| match simple_selector {
-                    SimpleSelector::Class(selector_class) => match &element_classes {
-                        Some(element_classes) => element_classes
-                            .iter()
-                            .any(|element_class| element_class == selector_class),
-                        None => false,
-                    },
-                    SimpleSelector::Id(selector_id) => match element_id {
-                        Some(element_id) => element_id == selector_id,
-                        None => false,
-                    },
-                    SimpleSelector::Tag(tag) => tag == &element.name,
-                })
-        })
+        self.matches_in(&Context::root(element))
+    }
+
+    pub(crate) fn matches_in(&self, context: &Context) -> bool {
+        self.0.iter().any(|complex| complex.matches(context))
+    }
+
+    pub(crate) fn is_contextual(&self) -> bool {
+        self.0.iter().any(ComplexSelector::is_contextual)
+    }
+
+    /// Mark the matches among `nodes`, or return `None` if the selector
+    /// doesn't depend on the context of the elements.
+    pub(crate) fn marks(&self, nodes: &[Node]) -> Option<Vec<Mark>> {
+        self.is_contextual().then(|| self.mark_nodes(nodes, None))
+    }
+
+    /// Mark the matches in `element`, as the only node, or return `None`
+    /// if the selector doesn't depend on the context of the elements.
+    pub(crate) fn element_marks(&self, element: &Element) -> Option<Vec<Mark>> {
+        self.is_contextual()
+            .then(|| vec![self.mark_element(&Context::root(element))])
+    }
+
+    fn mark_nodes(&self, nodes: &[Node], parent: Option<&Context>) -> Vec<Mark> {
+        let mut marks: Vec<Mark> = nodes.iter().map(|_| Mark::default()).collect();
+        for context in Context::children(nodes, parent) {
+            marks[context.index] = self.mark_element(&context);
+        }
+        marks
+    }
+
+    fn mark_element(&self, context: &Context) -> Mark {
+        Mark {
+            matched: self.matches_in(context),
+            children: self.mark_nodes(&context.element.children, Some(context)),
+        }
     }
 }
 
@@ -72,8 +403,13 @@ impl From<&str> for Selector {
     /// ```
     /// use html_editor::operation::Selector;
     ///
-    /// // Type Selector
+    /// // Type Selector, ignoring the case unless made case-sensitive
     /// Selector::from("span");
+    /// // Universal selector
+    /// Selector::from("*");
+    /// // Namespaced type selectors, with the `html`, `svg` and `math` prefixes
+    /// Selector::from("svg|a");
+    /// Selector::from("svg|*");
     /// // Class selector
     /// Selector::from(".class");
     /// // ID selector
@@ -83,12 +419,59 @@ impl From<&str> for Selector {
     /// Selector::from("h1, h2");
     /// // Compound selector
     /// Selector::from("input.username");
-    ///
-    /// // Disallowed input that may cause unexpected result
+    /// // Descendant combinator
     /// Selector::from("div span");
+    /// // Child combinator
+    /// Selector::from("ul > li");
+    /// // Next-sibling combinator
+    /// Selector::from("h2 + p");
+    /// // Subsequent-sibling combinator
+    /// Selector::from("label ~ input");
+    ///
+    /// // Attribute selectors
+    /// Selector::from("a[target]");
     /// Selector::from("a[target=_blank]");
+    /// Selector::from("a[href^='https://']");
+    /// Selector::from("img[src$=\".png\"]");
+    /// Selector::from("a[href*=example]");
+    /// Selector::from("p[class~=note]");
+    /// Selector::from("p[lang|=en]");
+    /// // Comparing the value ignoring the case
+    /// Selector::from("input[type=\"text\" i]");
+    /// // Any attribute starting with a prefix
+    /// Selector::from("[data-*]");
+    ///
+    /// // Negation pseudo-class
+    /// Selector::from("div:not(.ad, #main)");
+    /// // Relational pseudo-class
+    /// Selector::from("div:has(> img.hero)");
+    /// Selector::from("h2:has(+ p, ~ table)");
+    /// // Structural pseudo-classes
+    /// Selector::from("li:first-child");
+    /// Selector::from("li:last-child");
+    /// Selector::from("tr:nth-child(2n+1)");
+    /// Selector::from("tr:nth-child(even)");
+    /// Selector::from("p:first-of-type");
+    /// Selector::from("p:last-of-type");
+    /// Selector::from("table:nth-of-type(2)");
+    /// Selector::from("p:only-child");
+    /// Selector::from(":root > body");
+    /// // Elements without children, or only whitespace for `:blank`
+    /// Selector::from("p:empty");
+    /// Selector::from("p:blank");
+    /// // Elements whose text includes a string, like in jQuery
+    /// Selector::from("div:contains(\"Subscribe to our newsletter\")");
+    ///
+    /// // Escaped characters
+    /// Selector::from(r".foo\.bar");
+    /// Selector::from(r"#\31 23");
+    /// Selector::from(r#"[title="Say \"hi\""]"#);
     /// ```
+    ///
+    /// The selectors of the list which are invalid or not supported, like
+    /// `input:checked`, are ignored, so they match nothing. Use
+    /// [`Selector::parse`] to get an error instead.
     fn from(selector: &str) -> Self {
-        Selector(selector.split(',').map(CompoundSelector::from).collect())
+        Selector(parser::parse_list(selector).into_iter().flatten().collect())
     }
 }
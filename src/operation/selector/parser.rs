@@ -0,0 +1,412 @@
+use super::{
+    complex::{Combinator, ComplexSelector, RelativeSelector},
+    compound::CompoundSelector,
+    simple::{AttrOperator, AttributeSelector, Namespace, Nth, PseudoClass, SimpleSelector},
+    token::{tokenize, Token},
+};
+
+/// Error of a selector, at the token of the given index.
+pub struct ParseError {
+    pub index: usize,
+    pub message: String,
+}
+
+/// Parse each of the comma separated selectors of `selector`, giving
+/// the byte offset of the errors.
+pub fn parse_list(selector: &str) -> Vec<Result<ComplexSelector, (usize, String)>> {
+    let (tokens, offsets) = tokenize(selector);
+    split_list(&tokens, 0, parse_complex)
+        .into_iter()
+        .map(|result| {
+            result.map_err(|error| {
+                let offset = offsets.get(error.index).copied();
+                (offset.unwrap_or(selector.len()), error.message)
+            })
+        })
+        .collect()
+}
+
+/// Split the `tokens`, starting at the index `offset`, at the commas
+/// outside of parentheses.
+fn split_list<T>(
+    tokens: &[Token],
+    offset: usize,
+    parse: fn(&[Token], usize) -> Result<T, ParseError>,
+) -> Vec<Result<T, ParseError>> {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut complex_selectors = vec![];
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Delim('(') => depth += 1,
+            Token::Delim(')') => depth = depth.saturating_sub(1),
+            Token::Delim(',') if depth == 0 => {
+                complex_selectors.push(parse(&tokens[start..i], offset + start));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    complex_selectors.push(parse(&tokens[start..], offset + start));
+    complex_selectors
+}
+
+struct Cursor<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    /// The index of the first of the tokens, among all of them.
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(tokens: &'a [Token], offset: usize) -> Self {
+        Cursor {
+            tokens,
+            position: 0,
+            offset,
+        }
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek() == Some(&Token::Whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn expect_delim(&mut self, delim: char) -> Result<(), ParseError> {
+        match self.next() {
+            Some(Token::Delim(c)) if *c == delim => Ok(()),
+            token => Err(self.unexpected(token)),
+        }
+    }
+
+    /// Get the error at the token at `position`.
+    fn error_at(&self, position: usize, message: String) -> ParseError {
+        ParseError {
+            index: self.offset + position.min(self.tokens.len()),
+            message,
+        }
+    }
+
+    fn unexpected_at(&self, position: usize, token: Option<&Token>) -> ParseError {
+        let message = match token {
+            Some(token) => format!("Unexpected {}", token),
+            None => "Unexpected end of selector".to_string(),
+        };
+        self.error_at(position, message)
+    }
+
+    /// Get the error of the token just returned by `next`.
+    fn unexpected(&self, token: Option<&Token>) -> ParseError {
+        self.unexpected_at(self.position.saturating_sub(1), token)
+    }
+
+    /// Get the error of the token which `peek` returns.
+    fn unexpected_peek(&self) -> ParseError {
+        self.unexpected_at(self.position, self.peek())
+    }
+}
+
+fn parse_complex(tokens: &[Token], offset: usize) -> Result<ComplexSelector, ParseError> {
+    let mut cursor = Cursor::new(tokens, offset);
+    // Each compound selector with the combinator preceding it.
+    let mut compounds = vec![];
+    let mut combinator = None;
+
+    cursor.skip_whitespace();
+    while let Some(token) = cursor.peek() {
+        match token {
+            Token::Whitespace => {
+                cursor.next();
+                combinator.get_or_insert(Combinator::Descendant);
+            }
+            Token::Delim(c) if Combinator::from_char(*c).is_some() => {
+                if compounds.is_empty() || combinator.is_some_and(|c| c != Combinator::Descendant) {
+                    return Err(cursor.unexpected_peek());
+                }
+                cursor.next();
+                combinator = Combinator::from_char(*c);
+            }
+            _ if !compounds.is_empty() && combinator.is_none() => {
+                return Err(cursor.unexpected_peek());
+            }
+            _ => {
+                let compound = parse_compound(&mut cursor)?;
+                let preceding = combinator.take().unwrap_or(Combinator::Descendant);
+                compounds.push((preceding, compound));
+            }
+        }
+    }
+    if combinator.is_some_and(|c| c != Combinator::Descendant) {
+        return Err(cursor.unexpected_peek());
+    }
+
+    let Some((mut combinator, subject)) = compounds.pop() else {
+        return Err(cursor.error_at(0, "Empty selector".to_string()));
+    };
+    let mut relatives = vec![];
+    while let Some((preceding, compound)) = compounds.pop() {
+        relatives.push((combinator, compound));
+        combinator = preceding;
+    }
+
+    Ok(ComplexSelector { subject, relatives })
+}
+
+/// Parse a complex selector which may start with a combinator, like
+/// `> img`.
+fn parse_relative(tokens: &[Token], offset: usize) -> Result<RelativeSelector, ParseError> {
+    let mut cursor = Cursor::new(tokens, offset);
+    cursor.skip_whitespace();
+    let combinator = match cursor.peek() {
+        Some(Token::Delim(c)) => match Combinator::from_char(*c) {
+            Some(combinator) => {
+                cursor.next();
+                combinator
+            }
+            None => Combinator::Descendant,
+        },
+        _ => Combinator::Descendant,
+    };
+    Ok(RelativeSelector {
+        combinator,
+        selector: parse_complex(&tokens[cursor.position..], offset + cursor.position)?,
+    })
+}
+
+fn parse_compound(cursor: &mut Cursor) -> Result<CompoundSelector, ParseError> {
+    let mut simple_selectors = vec![];
+    loop {
+        use SimpleSelector::*;
+        let simple_selector = match cursor.peek() {
+            Some(Token::Ident(_) | Token::Delim('*')) if simple_selectors.is_empty() => {
+                if let Some(namespace) = parse_namespace(cursor)? {
+                    simple_selectors.push(namespace);
+                }
+                match cursor.next() {
+                    Some(Token::Ident(name)) => Tag(name.clone()),
+                    Some(Token::Delim('*')) => Universal,
+                    token => return Err(cursor.unexpected(token)),
+                }
+            }
+            Some(Token::Hash(id)) => {
+                cursor.next();
+                Id(id.clone())
+            }
+            Some(Token::Delim('.')) => {
+                cursor.next();
+                match cursor.next() {
+                    Some(Token::Ident(class)) => Class(class.clone()),
+                    token => return Err(cursor.unexpected(token)),
+                }
+            }
+            Some(Token::Delim('[')) => parse_attribute(cursor)?,
+            Some(Token::Delim(':')) => parse_pseudo_class(cursor)?,
+            Some(_) if simple_selectors.is_empty() => return Err(cursor.unexpected_peek()),
+            _ => break,
+        };
+        simple_selectors.push(simple_selector);
+    }
+    Ok(CompoundSelector::new(simple_selectors))
+}
+
+/// Parse the namespace prefix of a type selector, like `svg|` in
+/// `svg|circle`, which is `None` for `*|` and without prefix.
+fn parse_namespace(cursor: &mut Cursor) -> Result<Option<SimpleSelector>, ParseError> {
+    if cursor.tokens.get(cursor.position + 1) != Some(&Token::Delim('|')) {
+        return Ok(None);
+    }
+    let namespace = match cursor.next() {
+        Some(Token::Ident(prefix)) => match Namespace::from_prefix(prefix) {
+            Some(namespace) => Some(SimpleSelector::Namespace(namespace)),
+            None => {
+                let message = format!("Unknown namespace prefix {}", prefix);
+                return Err(cursor.error_at(cursor.position - 1, message));
+            }
+        },
+        _ => None,
+    };
+    cursor.next();
+    Ok(namespace)
+}
+
+/// Parse an attribute selector, from its `[` to its `]`.
+fn parse_attribute(cursor: &mut Cursor) -> Result<SimpleSelector, ParseError> {
+    cursor.expect_delim('[')?;
+    cursor.skip_whitespace();
+    let name = match cursor.next() {
+        Some(Token::Ident(name)) => name.clone(),
+        token => return Err(cursor.unexpected(token)),
+    };
+    cursor.skip_whitespace();
+    let name_only = |name| {
+        SimpleSelector::Attribute(AttributeSelector {
+            name,
+            value: None,
+            case_insensitive: false,
+        })
+    };
+    let operator = match cursor.next() {
+        Some(Token::Delim(']')) => return Ok(name_only(name)),
+        Some(Token::Delim('*')) if cursor.peek() == Some(&Token::Delim(']')) => {
+            cursor.next();
+            return Ok(SimpleSelector::AttributePrefix(name));
+        }
+        Some(Token::Delim('=')) => AttrOperator::Equals,
+        Some(Token::Delim(c @ ('^' | '$' | '*' | '~' | '|'))) => {
+            cursor.expect_delim('=')?;
+            match c {
+                '^' => AttrOperator::Prefix,
+                '$' => AttrOperator::Suffix,
+                '~' => AttrOperator::Includes,
+                '|' => AttrOperator::DashMatch,
+                _ => AttrOperator::Contains,
+            }
+        }
+        token => return Err(cursor.unexpected(token)),
+    };
+    cursor.skip_whitespace();
+    let value = match cursor.peek() {
+        Some(Token::Ident(value) | Token::String(value)) => {
+            cursor.next();
+            value.clone()
+        }
+        // Be lenient with a missing value, like `[alt=]`.
+        Some(Token::Delim(']')) => String::new(),
+        _ => return Err(cursor.unexpected_peek()),
+    };
+    cursor.skip_whitespace();
+    // The `s` flag asks for the default, case-sensitive comparison.
+    let case_insensitive = match cursor.peek() {
+        Some(Token::Ident(flag)) if matches!(flag.as_str(), "i" | "I" | "s" | "S") => {
+            let case_insensitive = flag.eq_ignore_ascii_case("i");
+            cursor.next();
+            cursor.skip_whitespace();
+            case_insensitive
+        }
+        _ => false,
+    };
+    cursor.expect_delim(']')?;
+
+    Ok(SimpleSelector::Attribute(AttributeSelector {
+        name,
+        value: Some((operator, value)),
+        case_insensitive,
+    }))
+}
+
+/// Parse a pseudo-class, from its `:`.
+fn parse_pseudo_class(cursor: &mut Cursor) -> Result<SimpleSelector, ParseError> {
+    cursor.expect_delim(':')?;
+    let name = match cursor.next() {
+        Some(Token::Ident(name)) => name.to_ascii_lowercase(),
+        token => return Err(cursor.unexpected(token)),
+    };
+    let name_position = cursor.position - 1;
+    match name.as_str() {
+        "not" => {
+            let (offset, arguments) = parse_arguments(cursor)?;
+            let selectors = split_list(arguments, offset, parse_complex);
+            Ok(SimpleSelector::Not(
+                selectors.into_iter().collect::<Result<_, _>>()?,
+            ))
+        }
+        "has" => {
+            let (offset, arguments) = parse_arguments(cursor)?;
+            let selectors = split_list(arguments, offset, parse_relative);
+            Ok(SimpleSelector::Has(
+                selectors.into_iter().collect::<Result<_, _>>()?,
+            ))
+        }
+        "nth-child" | "nth-of-type" => {
+            let (offset, argument) = parse_arguments(cursor)?;
+            let nth = Nth::parse(&stringify(argument)).ok_or_else(|| ParseError {
+                index: offset,
+                message: format!("Invalid argument of :{}", name),
+            })?;
+            match name.as_str() {
+                "nth-child" => Ok(SimpleSelector::NthChild(nth)),
+                _ => Ok(SimpleSelector::NthOfType(nth)),
+            }
+        }
+        "contains" => {
+            let (offset, argument) = parse_arguments(cursor)?;
+            match argument
+                .iter()
+                .filter(|token| **token != Token::Whitespace)
+                .collect::<Vec<_>>()[..]
+            {
+                [Token::String(text) | Token::Ident(text)] => {
+                    Ok(SimpleSelector::Contains(text.clone()))
+                }
+                _ => Err(ParseError {
+                    index: offset,
+                    message: format!("Invalid argument of :{}", name),
+                }),
+            }
+        }
+        "case-sensitive" => Ok(SimpleSelector::CaseSensitive),
+        "empty" => Ok(SimpleSelector::Empty),
+        "blank" => Ok(SimpleSelector::Blank),
+        "first-child" => Ok(SimpleSelector::FirstChild),
+        "last-child" => Ok(SimpleSelector::LastChild),
+        "only-child" => Ok(SimpleSelector::OnlyChild),
+        "root" => Ok(SimpleSelector::Root),
+        "first-of-type" => Ok(SimpleSelector::FirstOfType),
+        "last-of-type" => Ok(SimpleSelector::LastOfType),
+        _ => match PseudoClass::find(&name) {
+            Some(pseudo_class) => Ok(SimpleSelector::Custom(pseudo_class)),
+            None => {
+                Err(cursor.error_at(name_position, format!("Unsupported pseudo-class :{}", name)))
+            }
+        },
+    }
+}
+
+/// Get the tokens between the parentheses following a functional
+/// pseudo-class, with the index of the first of them.
+fn parse_arguments<'a>(cursor: &mut Cursor<'a>) -> Result<(usize, &'a [Token]), ParseError> {
+    cursor.expect_delim('(')?;
+    let start = cursor.position;
+    let mut depth = 0;
+    loop {
+        match cursor.next() {
+            Some(Token::Delim('(')) => depth += 1,
+            Some(Token::Delim(')')) if depth == 0 => {
+                let arguments = &cursor.tokens[start..cursor.position - 1];
+                return Ok((cursor.offset + start, arguments));
+            }
+            Some(Token::Delim(')')) => depth -= 1,
+            Some(_) => {}
+            None => return Err(cursor.unexpected(None)),
+        }
+    }
+}
+
+/// Write the tokens back into a string, without the escapes.
+fn stringify(tokens: &[Token]) -> String {
+    let mut string = String::new();
+    for token in tokens {
+        match token {
+            Token::Ident(ident) => string.push_str(ident),
+            Token::Hash(hash) => {
+                string.push('#');
+                string.push_str(hash);
+            }
+            Token::String(value) => string.push_str(value),
+            Token::Delim(c) => string.push(*c),
+            Token::Whitespace => string.push(' '),
+        }
+    }
+    string
+}
@@ -1,9 +1,405 @@
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use super::complex::{ComplexSelector, RelativeSelector};
+use super::token::{write_ident, write_string};
+use crate::Element;
+
+/// Pseudo-classes registered with [`Selector::register`](super::Selector::register).
+static PSEUDO_CLASSES: RwLock<Vec<PseudoClass>> = RwLock::new(Vec::new());
+
 /// A selector with a single component, such as a single
 /// id selector or type selector, that's not used in combination
 /// with or contains any other selector component or combinator.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SimpleSelector {
+    /// `*`, matching any element.
+    Universal,
     Class(String),
     Id(String),
+    /// `div`, matching the tags ignoring the ASCII case, like in html.
     Tag(String),
+    /// `:case-sensitive`, a non-standard extension matching any element
+    /// but making the type selector and the `*-of-type` pseudo-classes of
+    /// its compound selector compare the tags with their exact case, as
+    /// written by [`Selector::case_sensitive`](super::Selector::case_sensitive).
+    CaseSensitive,
+    /// The `ns|` prefix of a type selector, like in `svg|circle`,
+    /// matching the elements of the namespace.
+    Namespace(Namespace),
+    Attribute(AttributeSelector),
+    /// `[prefix*]`, like `[data-*]`, matching the elements with an
+    /// attribute whose name starts with the prefix.
+    AttributePrefix(String),
+    /// `:not(...)`, matching the elements which match none of the
+    /// selectors.
+    Not(Vec<ComplexSelector>),
+    /// `:has(...)`, matching the elements which have a related element,
+    /// like a descendant, matching one of the relative selectors.
+    Has(Vec<RelativeSelector>),
+    /// `:first-child`
+    FirstChild,
+    /// `:last-child`
+    LastChild,
+    /// `:nth-child(an+b)`
+    NthChild(Nth),
+    /// `:first-of-type`, matching the elements without a previous
+    /// sibling of the same tag.
+    FirstOfType,
+    /// `:last-of-type`
+    LastOfType,
+    /// `:nth-of-type(an+b)`, like `:nth-child` but counting the siblings
+    /// of the same tag only.
+    NthOfType(Nth),
+    /// `:only-child`, matching the elements without siblings.
+    OnlyChild,
+    /// `:root`, matching the elements at the top of the tree, without a
+    /// parent, like the `<html>` of a document or the top-level elements
+    /// of a fragment.
+    Root,
+    /// `:empty`, matching the elements without children, apart from
+    /// comments and processing instructions.
+    Empty,
+    /// `:blank`, like `:empty` but also allowing whitespace text.
+    Blank,
+    /// `:contains("text")`, a non-standard extension matching the
+    /// elements whose text, with their descendants, includes the text.
+    Contains(String),
+    /// A pseudo-class registered with
+    /// [`Selector::register`](super::Selector::register).
+    Custom(PseudoClass),
+}
+
+/// Write the selectors of a list, separated by commas.
+fn write_list(f: &mut fmt::Formatter<'_>, list: &[impl fmt::Display]) -> fmt::Result {
+    for (i, item) in list.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for SimpleSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimpleSelector::Universal => write!(f, "*"),
+            SimpleSelector::Class(class) => {
+                write!(f, ".")?;
+                write_ident(f, class)
+            }
+            SimpleSelector::Id(id) => {
+                write!(f, "#")?;
+                write_ident(f, id)
+            }
+            SimpleSelector::Tag(tag) => write_ident(f, tag),
+            SimpleSelector::CaseSensitive => write!(f, ":case-sensitive"),
+            SimpleSelector::Namespace(namespace) => write!(f, "{}|", namespace.prefix()),
+            SimpleSelector::Attribute(attribute) => write!(f, "{}", attribute),
+            SimpleSelector::AttributePrefix(prefix) => {
+                write!(f, "[")?;
+                write_ident(f, prefix)?;
+                write!(f, "*]")
+            }
+            SimpleSelector::Not(selectors) => {
+                write!(f, ":not(")?;
+                write_list(f, selectors)?;
+                write!(f, ")")
+            }
+            SimpleSelector::Has(selectors) => {
+                write!(f, ":has(")?;
+                write_list(f, selectors)?;
+                write!(f, ")")
+            }
+            SimpleSelector::FirstChild => write!(f, ":first-child"),
+            SimpleSelector::LastChild => write!(f, ":last-child"),
+            SimpleSelector::NthChild(nth) => write!(f, ":nth-child({})", nth),
+            SimpleSelector::FirstOfType => write!(f, ":first-of-type"),
+            SimpleSelector::LastOfType => write!(f, ":last-of-type"),
+            SimpleSelector::NthOfType(nth) => write!(f, ":nth-of-type({})", nth),
+            SimpleSelector::OnlyChild => write!(f, ":only-child"),
+            SimpleSelector::Root => write!(f, ":root"),
+            SimpleSelector::Empty => write!(f, ":empty"),
+            SimpleSelector::Blank => write!(f, ":blank"),
+            SimpleSelector::Contains(text) => {
+                write!(f, ":contains(")?;
+                write_string(f, text)?;
+                write!(f, ")")
+            }
+            SimpleSelector::Custom(pseudo_class) => {
+                write!(f, ":")?;
+                write_ident(f, &pseudo_class.name)
+            }
+        }
+    }
+}
+
+/// Namespace of an element, given by its foreign content ancestors, like
+/// `<svg>` or `<math>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    Html,
+    Svg,
+    MathMl,
+}
+
+impl Namespace {
+    /// Get the namespace of the prefix `html`, `svg` or `math`, ignoring
+    /// the case.
+    pub fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix.to_ascii_lowercase().as_str() {
+            "html" => Some(Namespace::Html),
+            "svg" => Some(Namespace::Svg),
+            "math" => Some(Namespace::MathMl),
+            _ => None,
+        }
+    }
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Namespace::Html => "html",
+            Namespace::Svg => "svg",
+            Namespace::MathMl => "math",
+        }
+    }
+
+    /// Get the namespace of an element named `name` among the children
+    /// inheriting this namespace, switched by `<svg>` and `<math>`.
+    pub fn of_element(self, name: &str) -> Self {
+        if name.eq_ignore_ascii_case("svg") {
+            Namespace::Svg
+        } else if name.eq_ignore_ascii_case("math") {
+            Namespace::MathMl
+        } else {
+            self
+        }
+    }
+
+    /// Get the namespace of the children of an element named `name` in
+    /// this namespace, like `<foreignObject>` going back to html.
+    pub fn of_children(self, name: &str) -> Self {
+        let is = |other: &str| name.eq_ignore_ascii_case(other);
+        match self.of_element(name) {
+            Namespace::Svg if is("foreignobject") || is("desc") || is("title") => Namespace::Html,
+            Namespace::MathMl if is("annotation-xml") => Namespace::Html,
+            namespace => namespace,
+        }
+    }
+}
+
+/// A user-defined pseudo-class, matching the elements for which its
+/// predicate returns `true`.
+#[derive(Clone)]
+pub struct PseudoClass {
+    pub name: String,
+    pub predicate: Arc<dyn Fn(&Element) -> bool + Send + Sync>,
+}
+
+impl fmt::Debug for PseudoClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PseudoClass(:{})", self.name)
+    }
+}
+
+impl PseudoClass {
+    /// Add the pseudo-class to the registry, replacing the one with the
+    /// same name.
+    pub fn register(self) {
+        let mut pseudo_classes = PSEUDO_CLASSES.write().unwrap_or_else(|e| e.into_inner());
+        pseudo_classes.retain(|pseudo_class| pseudo_class.name != self.name);
+        pseudo_classes.push(self);
+    }
+
+    /// Find the registered pseudo-class called `name`, in lowercase.
+    pub fn find(name: &str) -> Option<Self> {
+        let pseudo_classes = PSEUDO_CLASSES.read().unwrap_or_else(|e| e.into_inner());
+        pseudo_classes
+            .iter()
+            .find(|pseudo_class| pseudo_class.name == name)
+            .cloned()
+    }
+}
+
+/// The `an+b` argument of the `:nth-*` pseudo-classes, matching the
+/// positions `a*n + b` for any `n` starting from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nth {
+    pub a: i64,
+    pub b: i64,
+}
+
+impl Nth {
+    /// Check if the position, counting from 1, matches.
+    pub fn matches(&self, position: usize) -> bool {
+        let offset = position as i64 - self.b;
+        match self.a {
+            0 => offset == 0,
+            a => offset % a == 0 && offset / a >= 0,
+        }
+    }
+
+    /// Parse `an+b`, `odd` or `even`, ignoring the whitespace.
+    pub fn parse(nth: &str) -> Option<Self> {
+        let nth: String = nth
+            .split_whitespace()
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match nth.as_str() {
+            "odd" => return Some(Nth { a: 2, b: 1 }),
+            "even" => return Some(Nth { a: 2, b: 0 }),
+            _ => {}
+        }
+        let parse_int = |int: &str| match int.strip_prefix('+') {
+            Some(int) if !int.starts_with(['+', '-']) => int.parse::<i64>().ok(),
+            Some(_) => None,
+            None => int.parse::<i64>().ok(),
+        };
+        let Some((a, b)) = nth.split_once('n') else {
+            return Some(Nth {
+                a: 0,
+                b: parse_int(&nth)?,
+            });
+        };
+        let a = match a {
+            "" | "+" => 1,
+            "-" => -1,
+            a => parse_int(a)?,
+        };
+        let b = match b {
+            "" => 0,
+            b if b.starts_with(['+', '-']) => parse_int(b)?,
+            _ => return None,
+        };
+        Some(Nth { a, b })
+    }
+}
+
+impl fmt::Display for Nth {
+    /// Format as `an+b`, or `b` if `a` is 0.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.a {
+            0 => return write!(f, "{}", self.b),
+            1 => write!(f, "n")?,
+            -1 => write!(f, "-n")?,
+            a => write!(f, "{}n", a)?,
+        }
+        match self.b {
+            0 => Ok(()),
+            b => write!(f, "{:+}", b),
+        }
+    }
+}
+
+/// How an attribute selector compares the value of the attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrOperator {
+    /// `[attr=value]`
+    Equals,
+    /// `[attr^=value]`
+    Prefix,
+    /// `[attr$=value]`
+    Suffix,
+    /// `[attr*=value]`
+    Contains,
+    /// `[attr~=value]`, matching a value in the whitespace-separated
+    /// list of the attribute, like a class.
+    Includes,
+    /// `[attr|=value]`, matching the value or the value followed by `-`,
+    /// like the languages `en` and `en-US` for `[lang|=en]`.
+    DashMatch,
+}
+
+/// A selector about an attribute, like `[href]` or `[href^="https://"]`.
+#[derive(Debug, Clone)]
+pub struct AttributeSelector {
+    pub name: String,
+    /// The comparison to the value, or `None` if the attribute only has
+    /// to be present.
+    pub value: Option<(AttrOperator, String)>,
+    /// Whether the value is compared ignoring the ASCII case, with the
+    /// `i` flag like in `[type="text" i]`.
+    pub case_insensitive: bool,
+}
+
+impl fmt::Display for AttributeSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        write_ident(f, &self.name)?;
+        if let Some((operator, value)) = &self.value {
+            let operator = match operator {
+                AttrOperator::Equals => "=",
+                AttrOperator::Prefix => "^=",
+                AttrOperator::Suffix => "$=",
+                AttrOperator::Contains => "*=",
+                AttrOperator::Includes => "~=",
+                AttrOperator::DashMatch => "|=",
+            };
+            write!(f, "{}", operator)?;
+            write_string(f, value)?;
+            if self.case_insensitive {
+                write!(f, " i")?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+impl AttributeSelector {
+    pub fn matches(&self, element: &Element) -> bool {
+        let Some((_, value)) = element
+            .attrs
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(&self.name))
+        else {
+            return false;
+        };
+        if self.case_insensitive {
+            return self.matches_ignoring_case(value);
+        }
+        match &self.value {
+            None => true,
+            Some((AttrOperator::Equals, expected)) => value == expected,
+            Some((AttrOperator::DashMatch, expected)) => value
+                .strip_prefix(expected.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('-')),
+            // Like browsers, an empty string is contained in nothing.
+            Some((_, expected)) if expected.is_empty() => false,
+            Some((AttrOperator::Includes, expected)) => {
+                value.split_ascii_whitespace().any(|item| item == expected)
+            }
+            Some((AttrOperator::Prefix, expected)) => value.starts_with(expected.as_str()),
+            Some((AttrOperator::Suffix, expected)) => value.ends_with(expected.as_str()),
+            Some((AttrOperator::Contains, expected)) => value.contains(expected.as_str()),
+        }
+    }
+
+    fn matches_ignoring_case(&self, value: &str) -> bool {
+        let same = |part: Option<&str>, expected: &str| {
+            part.is_some_and(|part| part.eq_ignore_ascii_case(expected))
+        };
+        match &self.value {
+            None => true,
+            Some((AttrOperator::Equals, expected)) => value.eq_ignore_ascii_case(expected),
+            Some((AttrOperator::DashMatch, expected)) => {
+                same(value.get(..expected.len()), expected)
+                    && matches!(value.as_bytes().get(expected.len()), None | Some(b'-'))
+            }
+            Some((_, expected)) if expected.is_empty() => false,
+            Some((AttrOperator::Includes, expected)) => value
+                .split_ascii_whitespace()
+                .any(|item| item.eq_ignore_ascii_case(expected)),
+            Some((AttrOperator::Prefix, expected)) => same(value.get(..expected.len()), expected),
+            Some((AttrOperator::Suffix, expected)) => same(
+                value
+                    .len()
+                    .checked_sub(expected.len())
+                    .and_then(|start| value.get(start..)),
+                expected,
+            ),
+            Some((AttrOperator::Contains, expected)) => value
+                .to_ascii_lowercase()
+                .contains(&expected.to_ascii_lowercase()),
+        }
+    }
 }
@@ -0,0 +1,173 @@
+use std::fmt;
+
+/// Token of a selector, with the escapes resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// A name, like `div`, `my-class` or `foo\.bar`.
+    Ident(String),
+    /// `#` followed by a name.
+    Hash(String),
+    /// A quoted string, without its quotes.
+    String(String),
+    /// Any other character, like `.`, `>` or `[`.
+    Delim(char),
+    Whitespace,
+}
+
+impl fmt::Display for Token {
+    /// Format the token like in the selector, for the error messages.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "`{}`", ident),
+            Token::Hash(hash) => write!(f, "`#{}`", hash),
+            Token::String(value) => write!(f, "string {:?}", value),
+            Token::Delim(c) => write!(f, "`{}`", c),
+            Token::Whitespace => write!(f, "whitespace"),
+        }
+    }
+}
+
+/// Write `ident` with the escapes needed to read it back as a name.
+pub fn write_ident(f: &mut fmt::Formatter<'_>, ident: &str) -> fmt::Result {
+    for (i, c) in ident.chars().enumerate() {
+        let leading_digit = c.is_ascii_digit() && (i == 0 || i == 1 && ident.starts_with('-'));
+        if c.is_control() || leading_digit {
+            write!(f, "\\{:x} ", c as u32)?;
+        } else if is_name_char(c) {
+            write!(f, "{}", c)?;
+        } else {
+            write!(f, "\\{}", c)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write `value` as a double-quoted string.
+pub fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => write!(f, "\\{}", c)?,
+            c if c.is_control() => write!(f, "\\{:x} ", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Split the selector into tokens, with the byte offset of each of them.
+pub fn tokenize(selector: &str) -> (Vec<Token>, Vec<usize>) {
+    let mut tokens = vec![];
+    let mut offsets = vec![];
+    let mut rest = selector;
+
+    while let Some(c) = rest.chars().next() {
+        offsets.push(selector.len() - rest.len());
+        if c.is_whitespace() {
+            rest = rest.trim_start();
+            tokens.push(Token::Whitespace);
+        } else if c == '"' || c == '\'' {
+            let (string, after) = read_string(&rest[1..], c);
+            tokens.push(Token::String(string));
+            rest = after;
+        } else if c == '#' && starts_name(&rest[1..]) {
+            let (name, after) = read_name(&rest[1..]);
+            tokens.push(Token::Hash(name));
+            rest = after;
+        } else if starts_name(rest) {
+            let (name, after) = read_name(rest);
+            tokens.push(Token::Ident(name));
+            rest = after;
+        } else {
+            tokens.push(Token::Delim(c));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    (tokens, offsets)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+fn starts_name(selector: &str) -> bool {
+    let mut chars = selector.chars();
+    match chars.next() {
+        Some('\\') => !matches!(chars.next(), Some('\n' | '\r' | '\u{c}')),
+        Some(c) => is_name_char(c),
+        None => false,
+    }
+}
+
+fn read_name(selector: &str) -> (String, &str) {
+    let mut name = String::new();
+    let mut rest = selector;
+    loop {
+        match rest.chars().next() {
+            Some('\\') if starts_name(rest) => {
+                let (c, after) = read_escape(&rest[1..]);
+                name.push(c);
+                rest = after;
+            }
+            Some(c) if is_name_char(c) => {
+                name.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+            _ => return (name, rest),
+        }
+    }
+}
+
+/// Read the escape following a `\`, like `2e`, `31 ` or `.`.
+fn read_escape(selector: &str) -> (char, &str) {
+    let hex_len = selector
+        .chars()
+        .take(6)
+        .take_while(char::is_ascii_hexdigit)
+        .count();
+    if hex_len == 0 {
+        return match selector.chars().next() {
+            Some(c) => (c, &selector[c.len_utf8()..]),
+            None => ('\u{FFFD}', selector),
+        };
+    }
+
+    let code = u32::from_str_radix(&selector[..hex_len], 16).unwrap_or(0);
+    let c = match code {
+        0 => '\u{FFFD}',
+        code => char::from_u32(code).unwrap_or('\u{FFFD}'),
+    };
+    // A whitespace after the hex digits ends the escape.
+    let rest = &selector[hex_len..];
+    let rest = match rest.strip_prefix("\r\n") {
+        Some(after) => after,
+        None => rest
+            .strip_prefix([' ', '\t', '\n', '\r', '\u{c}'])
+            .unwrap_or(rest),
+    };
+    (c, rest)
+}
+
+/// Read a string until the `quote` closing it.
+fn read_string(selector: &str, quote: char) -> (String, &str) {
+    let mut string = String::new();
+    let mut rest = selector;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            c if c == quote => break,
+            '\\' => match rest.chars().next() {
+                // An escaped newline continues the string.
+                Some('\n') => rest = &rest[1..],
+                Some(_) => {
+                    let (c, after) = read_escape(rest);
+                    string.push(c);
+                    rest = after;
+                }
+                None => {}
+            },
+            c => string.push(c),
+        }
+    }
+    (string, rest)
+}
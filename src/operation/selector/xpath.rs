@@ -0,0 +1,355 @@
+use super::complex::{Combinator, ComplexSelector};
+use super::compound::CompoundSelector;
+use super::simple::{AttrOperator, AttributeSelector, Nth, SimpleSelector};
+use super::{Selector, SelectorError};
+use crate::operation::Queryable;
+use crate::{Element, Node};
+
+/// A path of [XPath 1.0](https://www.w3.org/TR/xpath/), as an
+/// alternative to the CSS selectors.
+///
+/// The path goes through the elements with the `/` and `//` steps or
+/// the `child::` and `descendant::` axes, testing their name or `*`, and
+/// can end with a `text()` or an attribute step, like `@href` or
+/// `attribute::*`. The predicates of the steps are:
+///
+/// - a position, like `[1]` or `[last()]`, before any other predicate;
+/// - `[@name]`, `[@name='value']`, `[contains(@name, 'value')]` and
+///   `[starts-with(@name, 'value')]` about the attributes;
+/// - `[contains(., 'text')]` about the text of the element;
+/// - `not(...)` and `and` combinations of the above.
+///
+/// A path not starting with `/` starts from the top-level nodes, like
+/// an absolute one.
+#[derive(Debug, Clone)]
+pub struct XPath {
+    selector: Selector,
+    last: LastStep,
+}
+
+/// What the last step of an [`XPath`] selects in the elements.
+#[derive(Debug, Clone)]
+enum LastStep {
+    Element,
+    Text,
+    /// The attribute with the name, or all of them.
+    Attribute(Option<String>),
+}
+
+/// Result of [`XPath::evaluate`].
+#[derive(Debug, Clone, Copy)]
+pub enum XPathItem<'a> {
+    Element(&'a Element),
+    /// A text node, raw with its character references.
+    Text(&'a str),
+    /// The value of an attribute.
+    Attribute(&'a str),
+}
+
+impl XPath {
+    /// Parse an XPath, returning an error for the invalid or the
+    /// unsupported ones.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse(r#"<div class="x"><p>One</p><p>Two</p></div><div><p>Three</p></div>"#).unwrap();
+    /// let xpath = XPath::parse("//div[@class='x']/p[1]").unwrap();
+    ///
+    /// let p = dom.query(xpath.selector()).unwrap();
+    /// assert_eq!(p.html(), "<p>One</p>");
+    ///
+    /// assert!(XPath::parse("//p[position() > 1]").is_err());
+    /// ```
+    pub fn parse(xpath: &str) -> Result<Self, SelectorError> {
+        XPathParser { xpath, position: 0 }.path()
+    }
+
+    /// Get the selector of the elements the path goes through, before
+    /// its `text()` or attribute step, to use with the operations like
+    /// [`Queryable`] or [`Editable`](crate::operation::Editable).
+    pub fn selector(&self) -> &Selector {
+        &self.selector
+    }
+
+    /// Get the elements, text nodes or attribute values selected by the
+    /// path in `target`, in the order of [`Queryable::query_all`].
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse(r#"<ul><li><a href="/a">A</a></li><li><a href="/b">B</a></li></ul>"#).unwrap();
+    ///
+    /// let hrefs: Vec<String> = XPath::parse("/ul/li/a/@href")
+    ///     .unwrap()
+    ///     .evaluate(&dom)
+    ///     .into_iter()
+    ///     .filter_map(|item| match item {
+    ///         XPathItem::Attribute(value) => Some(value.to_string()),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    ///
+    /// assert_eq!(hrefs, vec!["/a", "/b"]);
+    /// ```
+    pub fn evaluate<'a>(&self, target: &'a impl Queryable) -> Vec<XPathItem<'a>> {
+        let elements = target.query_all(&self.selector);
+        match &self.last {
+            LastStep::Element => elements.into_iter().map(XPathItem::Element).collect(),
+            LastStep::Text => elements
+                .into_iter()
+                .flat_map(|element| &element.children)
+                .filter_map(|node| match node {
+                    Node::Text(text) => Some(XPathItem::Text(text)),
+                    _ => None,
+                })
+                .collect(),
+            LastStep::Attribute(name) => elements
+                .into_iter()
+                .flat_map(|element| &element.attrs)
+                .filter(|(key, _)| name.as_ref().is_none_or(|name| key == name))
+                .map(|(_, value)| XPathItem::Attribute(value))
+                .collect(),
+        }
+    }
+}
+
+struct XPathParser<'a> {
+    xpath: &'a str,
+    position: usize,
+}
+
+impl<'a> XPathParser<'a> {
+    fn error(&self, message: &str) -> SelectorError {
+        SelectorError {
+            position: self.position,
+            message: message.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.xpath[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `expected` if the rest starts with it.
+    fn eat(&mut self, expected: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.rest().starts_with(expected);
+        if found {
+            self.position += expected.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), SelectorError> {
+        match self.eat(expected) {
+            true => Ok(()),
+            false => Err(self.error(&format!("Expected `{}`", expected))),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, SelectorError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.')))
+            .unwrap_or(rest.len());
+        // Not taking the `::` of an axis.
+        let end = rest[..end].find("::").unwrap_or(end);
+        if end == 0 {
+            return Err(self.error("Expected a name"));
+        }
+        self.position += end;
+        Ok(rest[..end].to_string())
+    }
+
+    fn literal(&mut self) -> Result<String, SelectorError> {
+        self.skip_whitespace();
+        let Some(quote) = self
+            .rest()
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\''))
+        else {
+            return Err(self.error("Expected a string"));
+        };
+        let Some(end) = self.rest()[1..].find(quote) else {
+            return Err(self.error("Unterminated string"));
+        };
+        let literal = self.rest()[1..end + 1].to_string();
+        self.position += end + 2;
+        Ok(literal)
+    }
+
+    fn path(mut self) -> Result<XPath, SelectorError> {
+        let mut compounds: Vec<(Combinator, CompoundSelector)> = Vec::new();
+        let mut combinator = match self.eat("//") {
+            true => Some(Combinator::Descendant),
+            false => {
+                self.eat("/");
+                None
+            }
+        };
+        loop {
+            if self.eat("text()") {
+                return self.finish(combinator, compounds, LastStep::Text);
+            }
+            if self.eat("@") || self.eat("attribute::") {
+                let name = match self.eat("*") {
+                    true => None,
+                    false => Some(self.name()?),
+                };
+                return self.finish(combinator, compounds, LastStep::Attribute(name));
+            }
+            let mut step_combinator = combinator.unwrap_or(Combinator::Child);
+            if self.eat("descendant::") {
+                step_combinator = Combinator::Descendant;
+            } else {
+                self.eat("child::");
+            }
+            let is_any = self.eat("*");
+            let mut simple_selectors = match is_any {
+                true => vec![],
+                false => vec![SimpleSelector::Tag(self.name()?)],
+            };
+            // The leading `/` goes to the top-level elements.
+            if compounds.is_empty() && step_combinator == Combinator::Child {
+                simple_selectors.push(SimpleSelector::Root);
+            }
+            let mut first = true;
+            while self.eat("[") {
+                simple_selectors.extend(self.predicate(is_any, first)?);
+                self.expect("]")?;
+                first = false;
+            }
+            compounds.push((step_combinator, CompoundSelector::new(simple_selectors)));
+
+            combinator = match () {
+                _ if self.eat("//") => Some(Combinator::Descendant),
+                _ if self.eat("/") => Some(Combinator::Child),
+                _ => return self.finish(None, compounds, LastStep::Element),
+            };
+        }
+    }
+
+    fn finish(
+        mut self,
+        combinator: Option<Combinator>,
+        mut compounds: Vec<(Combinator, CompoundSelector)>,
+        last: LastStep,
+    ) -> Result<XPath, SelectorError> {
+        self.skip_whitespace();
+        if !self.rest().is_empty() {
+            return Err(self.error("Unsupported XPath"));
+        }
+        // Like `//text()`, selecting in any element.
+        if combinator == Some(Combinator::Descendant) && !matches!(last, LastStep::Element) {
+            compounds.push((Combinator::Descendant, CompoundSelector::new(vec![])));
+        }
+        let Some((mut following, subject)) = compounds.pop() else {
+            return Err(self.error("Expected a step"));
+        };
+        let mut relatives = Vec::new();
+        while let Some((preceding, compound)) = compounds.pop() {
+            relatives.push((following, compound));
+            following = preceding;
+        }
+        Ok(XPath {
+            selector: Selector(vec![ComplexSelector { subject, relatives }]),
+            last,
+        })
+    }
+
+    /// Parse the predicate of a step testing `*` if `is_any`, which is
+    /// the `first` of the step.
+    fn predicate(
+        &mut self,
+        is_any: bool,
+        first: bool,
+    ) -> Result<Vec<SimpleSelector>, SelectorError> {
+        self.skip_whitespace();
+        let digits = self
+            .rest()
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest().len());
+        let position = match digits {
+            0 if self.eat("last()") => Some(None),
+            0 => None,
+            _ => {
+                let n = self.rest()[..digits]
+                    .parse()
+                    .map_err(|_| self.error("Invalid position"))?;
+                self.position += digits;
+                Some(Some(n))
+            }
+        };
+        if let Some(position) = position {
+            if !first {
+                return Err(self.error("A position must be the first predicate"));
+            }
+            return Ok(vec![match (position, is_any) {
+                (Some(b), true) => SimpleSelector::NthChild(Nth { a: 0, b }),
+                (Some(b), false) => SimpleSelector::NthOfType(Nth { a: 0, b }),
+                (None, true) => SimpleSelector::LastChild,
+                (None, false) => SimpleSelector::LastOfType,
+            }]);
+        }
+        let mut simple_selectors = vec![self.condition()?];
+        while self.eat("and ") {
+            simple_selectors.push(self.condition()?);
+        }
+        Ok(simple_selectors)
+    }
+
+    fn condition(&mut self) -> Result<SimpleSelector, SelectorError> {
+        let attribute = |name, value| {
+            SimpleSelector::Attribute(AttributeSelector {
+                name,
+                value,
+                case_insensitive: false,
+            })
+        };
+        if self.eat("not(") {
+            let condition = self.condition()?;
+            self.expect(")")?;
+            let subject = CompoundSelector::new(vec![condition]);
+            let relatives = vec![];
+            return Ok(SimpleSelector::Not(vec![ComplexSelector {
+                subject,
+                relatives,
+            }]));
+        }
+        if self.eat("@") {
+            let name = self.name()?;
+            if !self.eat("=") {
+                return Ok(attribute(name, None));
+            }
+            let value = self.literal()?;
+            return Ok(attribute(name, Some((AttrOperator::Equals, value))));
+        }
+        let operator = match () {
+            _ if self.eat("contains(") => AttrOperator::Contains,
+            _ if self.eat("starts-with(") => AttrOperator::Prefix,
+            _ => return Err(self.error("Unsupported predicate")),
+        };
+        let condition = if self.eat("@") {
+            let name = self.name()?;
+            self.expect(",")?;
+            attribute(name, Some((operator, self.literal()?)))
+        } else if operator == AttrOperator::Contains && self.eat(".") {
+            self.expect(",")?;
+            SimpleSelector::Contains(self.literal()?)
+        } else {
+            return Err(self.error("Unsupported predicate"));
+        };
+        self.expect(")")?;
+        Ok(condition)
+    }
+}
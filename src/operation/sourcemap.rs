@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+use super::html::{close_tag, open_tag};
+use super::walk::is_frozen;
+use super::Htmlifiable;
+use crate::{data::VOID_TAGS, Element, Node};
+
+/// Attribute holding the byte range of an element in the parsed input,
+/// like `data-source-span="12-40"`, when parsing with
+/// [`ParseOptions::source_spans`](crate::ParseOptions::source_spans).
+///
+/// Being an attribute, it follows the element through the edits.
+pub const SOURCE_SPAN_ATTR: &str = "data-source-span";
+
+/// Element of the output mapped to where it comes from in the input, as
+/// byte ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    pub source: Range<usize>,
+    pub output: Range<usize>,
+}
+
+/// Trace transformed documents back to their source.
+pub trait SourceMappable {
+    /// Stringify into html without the [`SOURCE_SPAN_ATTR`] attributes,
+    /// mapping each element which has one to its span in the output, in
+    /// the order of their start tags. The elements added by the edits
+    /// are not mapped.
+    ///
+    /// ```
+    /// use html_editor::{parse_with_options, ParseOptions};
+    /// use html_editor::operation::*;
+    ///
+    /// let html = "<p>Hi</p><div>Hello</div>";
+    /// let options = ParseOptions { source_spans: true, ..Default::default() };
+    /// let mut dom = parse_with_options(html, &options).unwrap();
+    /// dom.remove_by(&Selector::from("p"));
+    ///
+    /// let (output, mappings) = dom.html_with_source_map();
+    /// assert_eq!(output, "<div>Hello</div>");
+    /// assert_eq!(mappings, vec![Mapping { source: 9..25, output: 0..16 }]);
+    /// ```
+    fn html_with_source_map(&self) -> (String, Vec<Mapping>);
+
+    /// Stringify into html like [`SourceMappable::html_with_source_map`],
+    /// but with the elements frozen by
+    /// [`Editable::freeze`](super::Editable::freeze) written byte for byte
+    /// as they are in the `source` they were parsed from, like embeds
+    /// which must stay exact. The frozen elements without a whole span,
+    /// like the ones closed implicitly, are stringified as usual.
+    ///
+    /// ```
+    /// use html_editor::{parse_with_options, ParseOptions};
+    /// use html_editor::operation::*;
+    ///
+    /// let html = "<div class=embed><B>Partner</B></div><p class=ad>Ad</p>";
+    /// let options = ParseOptions { source_spans: true, ..Default::default() };
+    /// let mut dom = parse_with_options(html, &options).unwrap();
+    /// dom.freeze(&Selector::from(".embed")).remove_by(&Selector::from(".ad"));
+    ///
+    /// assert_eq!(dom.html_with_source(html), "<div class=embed><B>Partner</B></div>");
+    /// ```
+    fn html_with_source(&self, source: &str) -> String;
+}
+
+fn parse_span(value: &str) -> Option<Range<usize>> {
+    let (start, end) = value.split_once('-')?;
+    Some(start.parse().ok()?..end.parse().ok()?)
+}
+
+/// Get the source of the frozen `element`, if its span covers the whole
+/// of it, up to its end tag.
+fn frozen_source<'a>(element: &Element, span: &Range<usize>, source: &'a str) -> Option<&'a str> {
+    let html = source.get(span.clone())?;
+    let is_whole = VOID_TAGS.contains(&element.name.as_str())
+        || html.ends_with("/>")
+        || html.rfind("</").is_some_and(|end| {
+            let name = html[end + 2..].trim_end_matches(|c: char| c == '>' || c.is_whitespace());
+            name.eq_ignore_ascii_case(&element.name)
+        });
+    (is_frozen(element) && is_whole).then_some(html)
+}
+
+fn write_nodes(
+    nodes: &[Node],
+    source: Option<&str>,
+    output: &mut String,
+    mappings: &mut Vec<Mapping>,
+) {
+    for node in nodes {
+        match node {
+            Node::Element(el) => write_element(el, source, output, mappings),
+            node => output.push_str(&node.html()),
+        }
+    }
+}
+
+fn write_element(
+    element: &Element,
+    source: Option<&str>,
+    output: &mut String,
+    mappings: &mut Vec<Mapping>,
+) {
+    let span = element
+        .attrs
+        .iter()
+        .find(|(name, _)| name == SOURCE_SPAN_ATTR)
+        .and_then(|(_, value)| parse_span(value));
+    let frozen = span
+        .as_ref()
+        .zip(source)
+        .and_then(|(span, source)| frozen_source(element, span, source));
+    if let Some(html) = frozen {
+        return output.push_str(html);
+    }
+    let start = output.len();
+    let index = mappings.len();
+    let tag = Element {
+        name: element.name.clone(),
+        attrs: element
+            .attrs
+            .iter()
+            .filter(|(name, _)| name != SOURCE_SPAN_ATTR)
+            .cloned()
+            .collect(),
+        children: Vec::new(),
+    };
+    output.push_str(&open_tag(&tag));
+    if !VOID_TAGS.contains(&element.name.as_str()) {
+        write_nodes(&element.children, source, output, mappings);
+        output.push_str(&close_tag(&tag));
+    }
+    if let Some(source) = span {
+        let mapping = Mapping {
+            source,
+            output: start..output.len(),
+        };
+        mappings.insert(index, mapping);
+    }
+}
+
+impl SourceMappable for Vec<Node> {
+    fn html_with_source_map(&self) -> (String, Vec<Mapping>) {
+        let mut output = String::new();
+        let mut mappings = Vec::new();
+        write_nodes(self, None, &mut output, &mut mappings);
+        (output, mappings)
+    }
+
+    fn html_with_source(&self, source: &str) -> String {
+        let mut output = String::new();
+        write_nodes(self, Some(source), &mut output, &mut Vec::new());
+        output
+    }
+}
+
+impl SourceMappable for Element {
+    fn html_with_source_map(&self) -> (String, Vec<Mapping>) {
+        let mut output = String::new();
+        let mut mappings = Vec::new();
+        write_element(self, None, &mut output, &mut mappings);
+        (output, mappings)
+    }
+
+    fn html_with_source(&self, source: &str) -> String {
+        let mut output = String::new();
+        write_element(self, Some(source), &mut output, &mut Vec::new());
+        output
+    }
+}
@@ -0,0 +1,287 @@
+use std::io;
+
+use super::html::{close_tag, open_tag};
+use super::selector::Matcher;
+use super::walk::is_frozen;
+use super::{Htmlifiable, Queryable, Selector};
+use crate::{Element, Node};
+
+/// Stringify into html by chunks, and prepare the output of
+/// server-side rendering for the client.
+pub trait Streamable {
+    /// Serialize into html, passing it to `emit` by chunks cut at the
+    /// boundaries where a browser can start rendering: after the
+    /// `</head>` and after each top-level child of the `<body>`. For
+    /// fragments without `<html>`, `<head>` or `<body>`, a chunk is
+    /// emitted after each top-level node.
+    ///
+    /// The concatenation of the chunks is exactly [`Htmlifiable::html`].
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse("<html><head><title>T</title></head><body><h1>A</h1><p>B</p></body></html>").unwrap();
+    /// let mut chunks = Vec::new();
+    /// dom.html_chunks(|chunk| chunks.push(chunk.to_string()));
+    ///
+    /// assert_eq!(
+    ///     chunks,
+    ///     vec!["<html><head><title>T</title></head>", "<body><h1>A</h1>", "<p>B</p>", "</body></html>"]
+    /// );
+    /// ```
+    fn html_chunks(&self, emit: impl FnMut(&str));
+
+    /// Write the chunks of [`Streamable::html_chunks`] into `writer`,
+    /// flushing it after each of them.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let dom = parse("<p>A</p><p>B</p>").unwrap();
+    /// let mut buffer = Vec::new();
+    /// dom.write_html(&mut buffer).unwrap();
+    ///
+    /// assert_eq!(buffer, b"<p>A</p><p>B</p>");
+    /// ```
+    fn write_html(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        let mut result = Ok(());
+        self.html_chunks(|chunk| {
+            if result.is_ok() {
+                result = writer
+                    .write_all(chunk.as_bytes())
+                    .and_then(|_| writer.flush());
+            }
+        });
+        result
+    }
+
+    /// Mark the first element matching `selector` as the placeholder of
+    /// the slot `id`, to be filled later by a chunk built with
+    /// [`render_slot`]. The current content of the element is shown
+    /// until then.
+    ///
+    /// Returns `false` if no element matches.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<div class="comments">Loading...</div>"#).unwrap();
+    /// dom.defer_slot(&Selector::from(".comments"), "comments");
+    ///
+    /// assert_eq!(dom.html(), r#"<div class="comments" data-slot="comments">Loading...</div>"#);
+    /// ```
+    fn defer_slot(&mut self, selector: &Selector, id: &str) -> bool;
+
+    /// Number the elements matching one of the component `roots` in
+    /// depth-first order, parents before children, storing the number
+    /// in their `data-hid` so that a client runtime can hydrate them.
+    ///
+    /// The numbering only depends on the document, so that rendering it
+    /// again gives the same ids. Returns the number of annotated elements.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<div class="counter"><button>+</button></div><form></form>"#).unwrap();
+    /// dom.annotate_hydration(&[Selector::from(".counter"), Selector::from("form")]);
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<div class="counter" data-hid="0"><button>+</button></div><form data-hid="1"></form>"#
+    /// );
+    /// ```
+    fn annotate_hydration(&mut self, roots: &[Selector]) -> usize;
+}
+
+fn annotate_nodes(nodes: &mut [Node], roots: &[Matcher], count: &mut usize) {
+    for (index, node) in nodes.iter_mut().enumerate() {
+        if let Node::Element(el) = node {
+            annotate_element(el, index, roots, count);
+        }
+    }
+}
+
+/// Number `element`, the `index`th of its siblings, if one of the
+/// `roots` matches it, and then its descendants.
+fn annotate_element(element: &mut Element, index: usize, roots: &[Matcher], count: &mut usize) {
+    if is_frozen(element) {
+        return;
+    }
+    let (matched, children): (Vec<_>, Vec<_>) =
+        roots.iter().map(|root| root.child(index, element)).unzip();
+    if matched.contains(&true) {
+        element.set_attr("data-hid", &count.to_string());
+        *count += 1;
+    }
+    annotate_nodes(&mut element.children, &children, count);
+}
+
+/// Build the chunk filling the slot `id` marked by
+/// [`Streamable::defer_slot`] with `nodes`, to stream after the rest of
+/// the page: the nodes are sent in a `<template>`, followed by an inline
+/// script swapping them with the placeholder.
+///
+/// ```
+/// use html_editor::{parse, operation::*};
+///
+/// let chunk = render_slot("comments", &parse("<p>First!</p>").unwrap());
+/// assert!(chunk.starts_with(r#"<template data-slot-content="comments"><p>First!</p></template><script>"#));
+/// ```
+pub fn render_slot(id: &str, nodes: &[Node]) -> String {
+    let mut attr = String::new();
+    let mut literal = String::new();
+    for ch in id.chars() {
+        match ch {
+            '&' => attr.push_str("&amp;"),
+            '"' => attr.push_str("&quot;"),
+            '<' => attr.push_str("&lt;"),
+            ch => attr.push(ch),
+        }
+        if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+            literal.push(ch);
+        } else {
+            let mut units = [0; 2];
+            for unit in ch.encode_utf16(&mut units) {
+                literal.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    let html: String = nodes.iter().map(Node::html).collect();
+    format!(
+        concat!(
+            r#"<template data-slot-content="{}">{}</template><script>"#,
+            r#"(function(s){{var t=s.previousElementSibling,e=document.querySelectorAll("[data-slot]");"#,
+            r#"for(var i=0;i<e.length;i++)if(e[i].getAttribute("data-slot")==="{}"){{e[i].replaceWith(t.content);break}}"#,
+            r#"t.remove();s.remove()}})(document.currentScript)</script>"#,
+        ),
+        attr, html, literal
+    )
+}
+
+/// Buffer of the html not emitted yet.
+struct Chunker<F: FnMut(&str)> {
+    buffer: String,
+    emit: F,
+}
+
+impl<F: FnMut(&str)> Chunker<F> {
+    fn push(&mut self, html: &str) {
+        self.buffer.push_str(html);
+    }
+
+    fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            (self.emit)(&self.buffer);
+            self.buffer.clear();
+        }
+    }
+}
+
+fn is_document_element(node: &Node) -> bool {
+    node.as_element()
+        .is_some_and(|el| matches!(el.name.as_str(), "html" | "head" | "body"))
+}
+
+/// Serialize `nodes`, flushing after each of them if `at_boundary`.
+fn stream_nodes<F: FnMut(&str)>(nodes: &[Node], at_boundary: bool, chunker: &mut Chunker<F>) {
+    for node in nodes {
+        match node {
+            Node::Element(el) => stream_element(el, chunker),
+            node => chunker.push(&node.html()),
+        }
+        if at_boundary {
+            chunker.flush();
+        }
+    }
+}
+
+fn stream_element<F: FnMut(&str)>(element: &Element, chunker: &mut Chunker<F>) {
+    match element.name.as_str() {
+        "html" => {
+            chunker.push(&open_tag(element));
+            stream_nodes(&element.children, false, chunker);
+            chunker.push(&close_tag(element));
+        }
+        "head" => {
+            chunker.push(&element.html());
+            chunker.flush();
+        }
+        "body" => {
+            chunker.push(&open_tag(element));
+            stream_nodes(&element.children, true, chunker);
+            chunker.push(&close_tag(element));
+        }
+        _ => chunker.push(&element.html()),
+    }
+}
+
+impl Streamable for Vec<Node> {
+    fn html_chunks(&self, emit: impl FnMut(&str)) {
+        let mut chunker = Chunker {
+            buffer: String::new(),
+            emit,
+        };
+        let fragment = !self.iter().any(is_document_element);
+        stream_nodes(self, fragment, &mut chunker);
+        chunker.flush();
+    }
+
+    fn defer_slot(&mut self, selector: &Selector, id: &str) -> bool {
+        match self.query_mut(selector) {
+            Some(element) => {
+                element.set_attr("data-slot", id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn annotate_hydration(&mut self, roots: &[Selector]) -> usize {
+        let mut count = 0;
+        let marks: Vec<_> = roots.iter().map(|root| root.marks(self)).collect();
+        let matchers: Vec<_> = roots
+            .iter()
+            .zip(&marks)
+            .map(|(root, marks)| Matcher::new(root, marks.as_deref()))
+            .collect();
+        annotate_nodes(self, &matchers, &mut count);
+        count
+    }
+}
+
+impl Streamable for Element {
+    fn html_chunks(&self, emit: impl FnMut(&str)) {
+        let mut chunker = Chunker {
+            buffer: String::new(),
+            emit,
+        };
+        stream_element(self, &mut chunker);
+        chunker.flush();
+    }
+
+    fn defer_slot(&mut self, selector: &Selector, id: &str) -> bool {
+        match self.query_mut(selector) {
+            Some(element) => {
+                element.set_attr("data-slot", id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn annotate_hydration(&mut self, roots: &[Selector]) -> usize {
+        let mut count = 0;
+        let marks: Vec<_> = roots.iter().map(|root| root.element_marks(self)).collect();
+        let matchers: Vec<_> = roots
+            .iter()
+            .zip(&marks)
+            .map(|(root, marks)| Matcher::new(root, marks.as_deref()))
+            .collect();
+        annotate_element(self, 0, &matchers, &mut count);
+        count
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use super::walk::{is_frozen, raw_text, walk_element_mut, walk_mut};
+use super::{query_editable_mut, query_element_editable_mut, Selector};
+use crate::{entity, Element, Node};
+
+/// Attributes holding a whitespace-separated list of id references.
+const IDREF_ATTRS: [&str; 10] = [
+    "for",
+    "headers",
+    "aria-activedescendant",
+    "aria-controls",
+    "aria-describedby",
+    "aria-details",
+    "aria-errormessage",
+    "aria-flowto",
+    "aria-labelledby",
+    "aria-owns",
+];
+
+/// What to do with the headings pushed beyond `<h6>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeadingOverflow {
+    /// Keep them as `<h6>`.
+    Clamp,
+    /// Turn them into `<p role="heading" aria-level="n">`, which keeps
+    /// the outline for assistive technologies and can be styled by the
+    /// `aria-level` attribute.
+    Paragraph,
+}
+
+/// Heading of the outline of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: u8,
+    pub id: Option<String>,
+    /// Raw text of the heading, with whitespace collapsed.
+    pub text: String,
+}
+
+/// Reorganize the structure of the document.
+pub trait Restructurable {
+    /// Shift the level of all the headings by `delta`.
+    ///
+    /// Levels are clamped at `<h1>`, and the ones over `<h6>` are treated
+    /// according to `overflow`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = parse("<h1>Title</h1><h5>Note</h5><h6>Aside</h6>")
+    ///     .unwrap()
+    ///     .shift_headings(1, HeadingOverflow::Paragraph)
+    ///     .html();
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     r#"<h2>Title</h2><h6>Note</h6><p role="heading" aria-level="7">Aside</p>"#
+    /// );
+    /// ```
+    fn shift_headings(&mut self, delta: i8, overflow: HeadingOverflow) -> &mut Self;
+
+    /// Shift the headings so that the highest one becomes `start_level`,
+    /// keeping their relative levels.
+    ///
+    /// This is useful when embedding external content under an existing
+    /// heading hierarchy.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = parse("<h1>Title</h1><h2>Section</h2>")
+    ///     .unwrap()
+    ///     .rebase_headings(3, HeadingOverflow::Clamp)
+    ///     .html();
+    ///
+    /// assert_eq!(html, "<h3>Title</h3><h4>Section</h4>");
+    /// ```
+    fn rebase_headings(&mut self, start_level: u8, overflow: HeadingOverflow) -> &mut Self;
+
+    /// Prefix all the ids with `prefix`, together with the references
+    /// to them in `for`, `headers`, `aria-*` idrefs and `href="#id"`, so
+    /// that multiple copies of a fragment can be embedded in one page
+    /// without collisions.
+    ///
+    /// References to ids that are not defined inside `self` are left
+    /// untouched, as they point outside of the fragment.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let html = parse(r##"<h2 id="intro">Intro</h2><a href="#intro">Top</a><a href="#nav">Nav</a>"##)
+    ///     .unwrap()
+    ///     .prefix_ids("post-1-")
+    ///     .html();
+    ///
+    /// assert_eq!(
+    ///     html,
+    ///     r##"<h2 id="post-1-intro">Intro</h2><a href="#post-1-intro">Top</a><a href="#nav">Nav</a>"##
+    /// );
+    /// ```
+    fn prefix_ids(&mut self, prefix: &str) -> &mut Self;
+
+    /// Get the `<h1>` to `<h6>` headings in document order.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let outline = parse(r#"<h1 id="top">Title</h1><section><h2> A   <em>b</em></h2></section>"#)
+    ///     .unwrap()
+    ///     .outline();
+    ///
+    /// assert_eq!(outline[0].id.as_deref(), Some("top"));
+    /// assert_eq!((outline[1].level, outline[1].text.as_str()), (2, "A b"));
+    /// ```
+    fn outline(&self) -> Vec<Heading>;
+
+    /// Insert a table of contents of the headings whose level is within
+    /// `levels` into the first element matching `target`, as a nested
+    /// `<ol>` in a `<nav class="toc">`.
+    ///
+    /// Headings without id are given one, made from their text and
+    /// deduplicated against the other ids. Running this again replaces
+    /// the table inserted before. Returns `false`, without changing
+    /// anything, if no element matches `target` outside of the frozen
+    /// elements.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<aside></aside><h2>Setup</h2><h3>Linux</h3><h2>Usage</h2>").unwrap();
+    /// dom.inject_toc(&Selector::from("aside"), 2..=3);
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     concat!(
+    ///         r##"<aside><nav class="toc"><ol><li><a href="#setup">Setup</a><ol><li><a href="#linux">Linux</a></li></ol></li>"##,
+    ///         r##"<li><a href="#usage">Usage</a></li></ol></nav></aside>"##,
+    ///         r##"<h2 id="setup">Setup</h2><h3 id="linux">Linux</h3><h2 id="usage">Usage</h2>"##,
+    ///     )
+    /// );
+    /// ```
+    fn inject_toc(&mut self, target: &Selector, levels: RangeInclusive<u8>) -> bool;
+}
+
+pub(crate) fn heading_level(element: &Element) -> Option<u8> {
+    match element.name.as_str() {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn highest_heading(nodes: &[Node]) -> Option<u8> {
+    nodes
+        .iter()
+        .filter_map(Node::as_element)
+        .filter_map(highest_heading_in)
+        .min()
+}
+
+fn highest_heading_in(element: &Element) -> Option<u8> {
+    heading_level(element)
+        .into_iter()
+        .chain(highest_heading(&element.children))
+        .min()
+}
+
+fn shift_element(element: &mut Element, delta: i8, overflow: HeadingOverflow) {
+    if is_frozen(element) {
+        return;
+    }
+    if let Some(level) = heading_level(element) {
+        let level = (level as i16 + delta as i16).max(1);
+        if level <= 6 {
+            element.name = format!("h{}", level);
+        } else {
+            match overflow {
+                HeadingOverflow::Clamp => element.name = "h6".to_string(),
+                HeadingOverflow::Paragraph => {
+                    element.name = "p".to_string();
+                    element.set_attr("role", "heading");
+                    element.set_attr("aria-level", &level.to_string());
+                }
+            }
+        }
+    }
+    element.children.shift_headings(delta, overflow);
+}
+
+/// Collect the ids of `nodes` and their descendants, with the ones of the
+/// frozen elements if `frozen`.
+fn collect_ids(nodes: &[Node], ids: &mut HashSet<String>, frozen: bool) {
+    for el in nodes.iter().filter_map(Node::as_element) {
+        collect_element_ids(el, ids, frozen);
+    }
+}
+
+fn collect_element_ids(element: &Element, ids: &mut HashSet<String>, frozen: bool) {
+    if !frozen && is_frozen(element) {
+        return;
+    }
+    if let Some(id) = element.get_attr("id") {
+        ids.insert(id.to_string());
+    }
+    collect_ids(&element.children, ids, frozen);
+}
+
+fn prefix_element_ids(element: &mut Element, prefix: &str, ids: &HashSet<String>) {
+    if is_frozen(element) {
+        return;
+    }
+    for (key, value) in element.attrs.iter_mut() {
+        if key == "id" {
+            *value = format!("{}{}", prefix, value);
+        } else if key == "href" {
+            if let Some(id) = value.strip_prefix('#').filter(|id| ids.contains(*id)) {
+                *value = format!("#{}{}", prefix, id);
+            }
+        } else if IDREF_ATTRS.contains(&key.as_str()) {
+            *value = value
+                .split_ascii_whitespace()
+                .map(|id| match ids.contains(id) {
+                    true => format!("{}{}", prefix, id),
+                    false => id.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+    }
+    prefix_nodes_ids(&mut element.children, prefix, ids);
+}
+
+fn prefix_nodes_ids(nodes: &mut [Node], prefix: &str, ids: &HashSet<String>) {
+    for node in nodes.iter_mut() {
+        if let Node::Element(el) = node {
+            prefix_element_ids(el, prefix, ids);
+        }
+    }
+}
+
+fn heading_of(element: &Element) -> Option<Heading> {
+    let text = raw_text(&element.children);
+    Some(Heading {
+        level: heading_level(element)?,
+        id: element.get_attr("id").map(str::to_string),
+        text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+    })
+}
+
+fn collect_outline(nodes: &[Node], outline: &mut Vec<Heading>) {
+    for el in nodes.iter().filter_map(Node::as_element) {
+        collect_element_outline(el, outline);
+    }
+}
+
+fn collect_element_outline(element: &Element, outline: &mut Vec<Heading>) {
+    outline.extend(heading_of(element));
+    collect_outline(&element.children, outline);
+}
+
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for ch in entity::decode(text).chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    match slug.is_empty() {
+        true => "section".to_string(),
+        false => slug.to_string(),
+    }
+}
+
+/// Give `element` an id if it is a heading within `levels` without one,
+/// and push it into `headings`.
+fn identify_heading(
+    element: &mut Element,
+    levels: &RangeInclusive<u8>,
+    ids: &mut HashSet<String>,
+    headings: &mut Vec<Heading>,
+) {
+    let mut heading = match heading_of(element) {
+        Some(heading) if levels.contains(&heading.level) => heading,
+        _ => return,
+    };
+    if heading.id.as_deref().is_none_or(str::is_empty) {
+        let slug = slugify(&heading.text);
+        let id = (1..)
+            .map(|n| match n {
+                1 => slug.clone(),
+                n => format!("{}-{}", slug, n - 1),
+            })
+            .find(|id| !ids.contains(id))
+            .unwrap();
+        ids.insert(id.clone());
+        element.set_attr("id", &id);
+        heading.id = Some(id);
+    }
+    headings.push(heading);
+}
+
+fn toc_of(headings: Vec<Heading>) -> Node {
+    // Lists being built, from the outermost one, with their level.
+    let mut stack: Vec<(u8, Vec<Node>)> = vec![(headings.first().map_or(1, |h| h.level), vec![])];
+    let close = |stack: &mut Vec<(u8, Vec<Node>)>| {
+        let (_, items) = stack.pop().unwrap();
+        let (_, parent) = stack.last_mut().unwrap();
+        if let Some(Node::Element(li)) = parent.last_mut() {
+            li.children.push(Node::new_element("ol", vec![], items));
+        }
+    };
+    for heading in headings {
+        while stack.len() > 1 && stack.last().unwrap().0 > heading.level {
+            close(&mut stack);
+        }
+        let (level, items) = stack.last_mut().unwrap();
+        if heading.level > *level && !items.is_empty() {
+            stack.push((heading.level, vec![]));
+        } else if heading.level < *level {
+            *level = heading.level;
+        }
+        let href = format!("#{}", heading.id.unwrap_or_default());
+        let link = Node::new_element("a", vec![("href", &href)], vec![Node::Text(heading.text)]);
+        stack
+            .last_mut()
+            .unwrap()
+            .1
+            .push(Node::new_element("li", vec![], vec![link]));
+    }
+    while stack.len() > 1 {
+        close(&mut stack);
+    }
+    let (_, items) = stack.pop().unwrap();
+    Node::new_element(
+        "nav",
+        vec![("class", "toc")],
+        vec![Node::new_element("ol", vec![], items)],
+    )
+}
+
+fn place_toc(target: &mut Element, toc: Node) {
+    let existing = target.children.iter_mut().find(|node| {
+        node.as_element().is_some_and(|el| {
+            el.name == "nav" && el.get_attr("class").is_some_and(|class| class == "toc")
+        })
+    });
+    match existing {
+        Some(existing) => *existing = toc,
+        None => target.children.push(toc),
+    }
+}
+
+impl Restructurable for Vec<Node> {
+    fn shift_headings(&mut self, delta: i8, overflow: HeadingOverflow) -> &mut Self {
+        for node in self.iter_mut() {
+            if let Node::Element(el) = node {
+                shift_element(el, delta, overflow);
+            }
+        }
+        self
+    }
+
+    fn rebase_headings(&mut self, start_level: u8, overflow: HeadingOverflow) -> &mut Self {
+        if let Some(highest) = highest_heading(self) {
+            self.shift_headings(start_level as i8 - highest as i8, overflow);
+        }
+        self
+    }
+
+    fn prefix_ids(&mut self, prefix: &str) -> &mut Self {
+        // The frozen ids are kept, and so are the references to them.
+        let mut ids = HashSet::new();
+        collect_ids(self, &mut ids, false);
+        prefix_nodes_ids(self, prefix, &ids);
+        self
+    }
+
+    fn outline(&self) -> Vec<Heading> {
+        let mut outline = Vec::new();
+        collect_outline(self, &mut outline);
+        outline
+    }
+
+    fn inject_toc(&mut self, target: &Selector, levels: RangeInclusive<u8>) -> bool {
+        if query_editable_mut(self, target).is_none() {
+            return false;
+        }
+        let mut ids = HashSet::new();
+        collect_ids(self, &mut ids, true);
+        let mut headings = Vec::new();
+        walk_mut(self, &mut |el| {
+            identify_heading(el, &levels, &mut ids, &mut headings)
+        });
+        if let Some(element) = query_editable_mut(self, target) {
+            place_toc(element, toc_of(headings));
+        }
+        true
+    }
+}
+
+impl Restructurable for Element {
+    fn shift_headings(&mut self, delta: i8, overflow: HeadingOverflow) -> &mut Self {
+        shift_element(self, delta, overflow);
+        self
+    }
+
+    fn rebase_headings(&mut self, start_level: u8, overflow: HeadingOverflow) -> &mut Self {
+        if let Some(highest) = highest_heading_in(self) {
+            self.shift_headings(start_level as i8 - highest as i8, overflow);
+        }
+        self
+    }
+
+    fn prefix_ids(&mut self, prefix: &str) -> &mut Self {
+        let mut ids = HashSet::new();
+        collect_element_ids(self, &mut ids, false);
+        prefix_element_ids(self, prefix, &ids);
+        self
+    }
+
+    fn outline(&self) -> Vec<Heading> {
+        let mut outline = Vec::new();
+        collect_element_outline(self, &mut outline);
+        outline
+    }
+
+    fn inject_toc(&mut self, target: &Selector, levels: RangeInclusive<u8>) -> bool {
+        if query_element_editable_mut(self, target).is_none() {
+            return false;
+        }
+        let mut ids = HashSet::new();
+        collect_element_ids(self, &mut ids, true);
+        let mut headings = Vec::new();
+        walk_element_mut(self, &mut |el| {
+            identify_heading(el, &levels, &mut ids, &mut headings)
+        });
+        if let Some(element) = query_element_editable_mut(self, target) {
+            place_toc(element, toc_of(headings));
+        }
+        true
+    }
+}
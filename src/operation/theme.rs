@@ -0,0 +1,290 @@
+use super::walk::{walk_element_mut, walk_mut};
+use super::{Queryable, Selector};
+use crate::{css, Element, Node};
+
+/// Marker of the `<style>` added by [`Themable::add_color_scheme`].
+const SCHEME_ATTR: &str = "data-color-scheme";
+
+/// Properties whose value is passed to the mapping of
+/// [`Themable::map_colors`].
+const COLOR_PROPERTIES: [&str; 3] = ["color", "background", "background-color"];
+
+/// Theme stored documents, like for a dark mode.
+pub trait Themable {
+    /// Add the theme `class` to the `<html>`, removing the classes of the
+    /// other themes listed in `others`. Returns `false` if there is no
+    /// `<html>`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<html class="light"></html>"#).unwrap();
+    /// dom.set_theme_class("dark", &["light"]);
+    ///
+    /// assert_eq!(dom.html(), r#"<html class="dark"></html>"#);
+    /// ```
+    fn set_theme_class(&mut self, class: &str, others: &[&str]) -> bool;
+
+    /// Pass the `color`, `background` and `background-color` of the
+    /// inline styles to `map`, with their property, and replace them by
+    /// the returned value. An `!important` is kept, and not given to
+    /// `map`. Returns the number of values replaced.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<p style="color: black; margin: 0">Text</p>"#).unwrap();
+    /// dom.map_colors(|_, value| (value == "black").then(|| "white".to_string()));
+    ///
+    /// assert_eq!(dom.html(), r#"<p style="color: white; margin: 0">Text</p>"#);
+    /// ```
+    fn map_colors(&mut self, map: impl FnMut(&str, &str) -> Option<String>) -> usize;
+
+    /// Declare that the page supports both light and dark color schemes,
+    /// and add `dark_css` in a `@media (prefers-color-scheme: dark)`
+    /// style at the end of the `<head>`.
+    ///
+    /// Running it again replaces the style added before. Returns `false`
+    /// if there is no `<head>`.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<head></head>").unwrap();
+    /// dom.add_color_scheme("body { background: #111; }");
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     concat!(
+    ///         r#"<head><meta name="color-scheme" content="light dark">"#,
+    ///         "<style data-color-scheme>@media (prefers-color-scheme: dark) { ",
+    ///         "body { background: #111; } }</style></head>",
+    ///     )
+    /// );
+    /// ```
+    fn add_color_scheme(&mut self, dark_css: &str) -> bool;
+
+    /// Pass the declarations of each inline style to `convert`, which
+    /// can replace them by classes, like the ones of a utility CSS
+    /// framework.
+    ///
+    /// The returned classes are appended to the `class` of the element,
+    /// and the declarations `convert` left in place are kept in its
+    /// style, which is removed if none are. Returns the number of
+    /// elements converted.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse(r#"<p style="margin: 0; color: #123">Text</p>"#).unwrap();
+    /// dom.styles_to_classes(|declarations| {
+    ///     let mut classes = vec![];
+    ///     declarations.retain(|(property, value)| match (property.as_str(), value.as_str()) {
+    ///         ("margin", "0") => {
+    ///             classes.push("m-0".to_string());
+    ///             false
+    ///         }
+    ///         _ => true,
+    ///     });
+    ///     Some(classes)
+    /// });
+    ///
+    /// assert_eq!(dom.html(), r#"<p style="color: #123" class="m-0">Text</p>"#);
+    /// ```
+    fn styles_to_classes(
+        &mut self,
+        convert: impl FnMut(&mut Vec<(String, String)>) -> Option<Vec<String>>,
+    ) -> usize;
+}
+
+fn set_element_theme_class(html: &mut Element, class: &str, others: &[&str]) {
+    let mut classes: Vec<&str> = html
+        .get_attr("class")
+        .unwrap_or("")
+        .split_ascii_whitespace()
+        .filter(|name| !others.contains(name))
+        .collect();
+    if !classes.contains(&class) {
+        classes.push(class);
+    }
+    let classes = classes.join(" ");
+    html.set_attr("class", &classes);
+}
+
+fn map_element_colors(
+    element: &mut Element,
+    map: &mut impl FnMut(&str, &str) -> Option<String>,
+    count: &mut usize,
+) {
+    let style = match element.get_attr("style") {
+        Some(style) => style,
+        None => return,
+    };
+    let mut declarations = css::declarations(style);
+    let mut changed = false;
+    for (property, value) in declarations.iter_mut() {
+        if !COLOR_PROPERTIES.contains(&property.as_str()) {
+            continue;
+        }
+        let (color, important) = match value.strip_suffix("!important") {
+            Some(color) => (color.trim_end(), " !important"),
+            None => (value.as_str(), ""),
+        };
+        if let Some(color) = map(property, color) {
+            *value = format!("{}{}", color, important);
+            *count += 1;
+            changed = true;
+        }
+    }
+    if changed {
+        element.set_attr("style", &css::serialize(&declarations));
+    }
+}
+
+fn convert_element_style(
+    element: &mut Element,
+    convert: &mut impl FnMut(&mut Vec<(String, String)>) -> Option<Vec<String>>,
+) -> bool {
+    let mut declarations = match element.get_attr("style") {
+        Some(style) => css::declarations(style),
+        None => return false,
+    };
+    let added = match convert(&mut declarations) {
+        Some(added) => added,
+        None => return false,
+    };
+    let mut classes: Vec<&str> = element
+        .get_attr("class")
+        .unwrap_or("")
+        .split_ascii_whitespace()
+        .collect();
+    for class in added
+        .iter()
+        .flat_map(|class| class.split_ascii_whitespace())
+    {
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+    let classes = classes.join(" ");
+    if !classes.is_empty() {
+        element.set_attr("class", &classes);
+    }
+    if declarations.is_empty() {
+        element.remove_attr("style");
+    } else {
+        element.set_attr("style", &css::serialize(&declarations));
+    }
+    true
+}
+
+fn is_scheme_meta(element: &Element) -> bool {
+    element.name == "meta"
+        && element
+            .get_attr("name")
+            .is_some_and(|name| name.eq_ignore_ascii_case("color-scheme"))
+}
+
+fn add_head_color_scheme(head: &mut Element, dark_css: &str) {
+    head.children.retain(|node| match node {
+        Node::Element(el) => el.get_attr(SCHEME_ATTR).is_none(),
+        _ => true,
+    });
+    let has_meta = head
+        .children
+        .iter()
+        .filter_map(Node::as_element)
+        .any(is_scheme_meta);
+    if !has_meta {
+        let meta = Node::new_element(
+            "meta",
+            vec![("name", "color-scheme"), ("content", "light dark")],
+            vec![],
+        );
+        head.children.push(meta);
+    }
+    let css = format!(
+        "@media (prefers-color-scheme: dark) {{ {} }}",
+        dark_css.trim()
+    );
+    let style = Node::new_element("style", vec![(SCHEME_ATTR, "")], vec![Node::Text(css)]);
+    head.children.push(style);
+}
+
+fn set_theme_class_in<T: Queryable>(target: &mut T, class: &str, others: &[&str]) -> bool {
+    match target.query_mut(&Selector::from("html")) {
+        Some(html) => {
+            set_element_theme_class(html, class, others);
+            true
+        }
+        None => false,
+    }
+}
+
+fn add_color_scheme_in<T: Queryable>(target: &mut T, dark_css: &str) -> bool {
+    match target.query_mut(&Selector::from("head")) {
+        Some(head) => {
+            add_head_color_scheme(head, dark_css);
+            true
+        }
+        None => false,
+    }
+}
+
+impl Themable for Vec<Node> {
+    fn set_theme_class(&mut self, class: &str, others: &[&str]) -> bool {
+        set_theme_class_in(self, class, others)
+    }
+
+    fn map_colors(&mut self, mut map: impl FnMut(&str, &str) -> Option<String>) -> usize {
+        let mut count = 0;
+        walk_mut(self, &mut |el| map_element_colors(el, &mut map, &mut count));
+        count
+    }
+
+    fn add_color_scheme(&mut self, dark_css: &str) -> bool {
+        add_color_scheme_in(self, dark_css)
+    }
+
+    fn styles_to_classes(
+        &mut self,
+        mut convert: impl FnMut(&mut Vec<(String, String)>) -> Option<Vec<String>>,
+    ) -> usize {
+        let mut count = 0;
+        walk_mut(self, &mut |el| {
+            count += convert_element_style(el, &mut convert) as usize
+        });
+        count
+    }
+}
+
+impl Themable for Element {
+    fn set_theme_class(&mut self, class: &str, others: &[&str]) -> bool {
+        set_theme_class_in(self, class, others)
+    }
+
+    fn map_colors(&mut self, mut map: impl FnMut(&str, &str) -> Option<String>) -> usize {
+        let mut count = 0;
+        walk_element_mut(self, &mut |el| map_element_colors(el, &mut map, &mut count));
+        count
+    }
+
+    fn add_color_scheme(&mut self, dark_css: &str) -> bool {
+        add_color_scheme_in(self, dark_css)
+    }
+
+    fn styles_to_classes(
+        &mut self,
+        mut convert: impl FnMut(&mut Vec<(String, String)>) -> Option<Vec<String>>,
+    ) -> usize {
+        let mut count = 0;
+        walk_element_mut(self, &mut |el| {
+            count += convert_element_style(el, &mut convert) as usize
+        });
+        count
+    }
+}
@@ -0,0 +1,315 @@
+use super::walk::{is_frozen, raw_text};
+use super::{is_prose, Rewritable, Selector};
+use crate::entity::reference_len;
+use crate::{Element, Node};
+
+/// Break opportunity inserted by [`Typesettable::hyphenate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordBreak {
+    /// `&shy;`, shown as a hyphen when the word is broken.
+    SoftHyphen,
+    /// `<wbr>`, breaking the word without a hyphen, like in URLs.
+    Wbr,
+}
+
+/// Direction of the text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    /// Get the direction of a strong character, being a letter.
+    fn of(ch: char) -> Option<Self> {
+        match ch as u32 {
+            0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF => Some(Direction::Rtl),
+            _ if ch.is_alphabetic() => Some(Direction::Ltr),
+            _ => None,
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Direction::Ltr => Direction::Rtl,
+            Direction::Rtl => Direction::Ltr,
+        }
+    }
+
+    /// Value of the `dir` attribute.
+    fn attr(self) -> &'static str {
+        match self {
+            Direction::Ltr => "ltr",
+            Direction::Rtl => "rtl",
+        }
+    }
+}
+
+/// Lay out the prose text, with word breaks and bidi isolation.
+pub trait Typesettable {
+    /// Insert break opportunities into the words of the prose text, at
+    /// the positions in characters returned by `split` for each word,
+    /// like the ones of a hyphenation dictionary or [`split_every`],
+    /// within the elements matching `scope` if any. Returns the number of
+    /// breaks inserted.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<p>Donaudampfschiff</p><code>Donaudampfschiff</code>").unwrap();
+    /// dom.hyphenate(None, WordBreak::SoftHyphen, |word| match word {
+    ///     "Donaudampfschiff" => vec![5, 10],
+    ///     _ => vec![],
+    /// });
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     "<p>Donau&shy;dampf&shy;schiff</p><code>Donaudampfschiff</code>"
+    /// );
+    /// ```
+    fn hyphenate(
+        &mut self,
+        scope: Option<&Selector>,
+        mark: WordBreak,
+        split: impl FnMut(&str) -> Vec<usize>,
+    ) -> usize;
+
+    /// Isolate the runs of prose text written in the direction opposite
+    /// to `base`, like Arabic in an English page, so that they don't mix
+    /// up with the text around them.
+    ///
+    /// An element containing just such a text gets a `dir` attribute,
+    /// and the other runs are wrapped in a `<bdi>`. The elements which
+    /// already have a `dir` are left as is. Returns the number of runs
+    /// isolated.
+    ///
+    /// ```
+    /// use html_editor::parse;
+    /// use html_editor::operation::*;
+    ///
+    /// let mut dom = parse("<p>User שלום posted 3 times</p><li>مرحبا</li>").unwrap();
+    /// dom.isolate_bidi(Direction::Ltr);
+    ///
+    /// assert_eq!(
+    ///     dom.html(),
+    ///     r#"<p>User <bdi>שלום</bdi> posted 3 times</p><li dir="rtl">مرحبا</li>"#
+    /// );
+    /// ```
+    fn isolate_bidi(&mut self, base: Direction) -> usize;
+}
+
+/// Split the words longer than `length` characters every `length`
+/// characters, to be used with [`Typesettable::hyphenate`].
+///
+/// ```
+/// use html_editor::parse;
+/// use html_editor::operation::*;
+///
+/// let mut dom = parse("<p>Call getElementsByTagName now</p>").unwrap();
+/// dom.hyphenate(None, WordBreak::Wbr, split_every(8));
+///
+/// assert_eq!(dom.html(), "<p>Call getEleme<wbr>ntsByTag<wbr>Name now</p>");
+/// ```
+pub fn split_every(length: usize) -> impl FnMut(&str) -> Vec<usize> {
+    let length = length.max(1);
+    move |word| {
+        let count = word.chars().count();
+        (1..count.div_ceil(length)).map(|i| i * length).collect()
+    }
+}
+
+/// Insert the breaks returned by `split` into the words of `text`,
+/// skipping the character references like `&amp;`.
+fn hyphenate_text(
+    text: &str,
+    mark: WordBreak,
+    split: &mut impl FnMut(&str) -> Vec<usize>,
+    count: &mut usize,
+) -> Option<Vec<Node>> {
+    let mut nodes = Vec::new();
+    let mut piece = String::with_capacity(text.len());
+    let mut rest = text;
+    let before = *count;
+    while let Some(ch) = rest.chars().next() {
+        let end = if ch == '&' {
+            reference_len(rest).unwrap_or(1)
+        } else if ch.is_alphanumeric() {
+            rest.find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(rest.len())
+        } else {
+            ch.len_utf8()
+        };
+        let (segment, after) = rest.split_at(end);
+        rest = after;
+        if !ch.is_alphanumeric() {
+            piece.push_str(segment);
+            continue;
+        }
+
+        let mut breaks = split(segment);
+        breaks.sort_unstable();
+        breaks.dedup();
+        let mut breaks = breaks.into_iter().peekable();
+        for (i, c) in segment.chars().enumerate() {
+            // The breaks before `i` were taken already, so it is at `i`.
+            if breaks.next_if(|&b| b <= i).is_some() && i > 0 {
+                *count += 1;
+                match mark {
+                    WordBreak::SoftHyphen => piece.push_str("&shy;"),
+                    WordBreak::Wbr => {
+                        nodes.push(Node::Text(std::mem::take(&mut piece)));
+                        nodes.push(Node::new_element("wbr", vec![], vec![]));
+                    }
+                }
+            }
+            piece.push(c);
+        }
+    }
+    if *count == before {
+        return None;
+    }
+    if !piece.is_empty() {
+        nodes.push(Node::Text(piece));
+    }
+    Some(nodes)
+}
+
+/// Get the byte ranges of the strong characters of `text`, with their
+/// direction, skipping the character references.
+fn strong_chars(text: &str) -> Vec<(usize, usize, Direction)> {
+    let mut strong = Vec::new();
+    let mut i = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        let len = reference_len(&text[i..]).unwrap_or(ch.len_utf8());
+        if let Some(direction) = Direction::of(ch) {
+            strong.push((i, i + len, direction));
+        }
+        i += len;
+    }
+    strong
+}
+
+/// Wrap the runs of `text` in the direction opposite to `base` in a
+/// `<bdi>`, with the neutral characters between their letters.
+fn isolate_text(text: &str, base: Direction, count: &mut usize) -> Option<Vec<Node>> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut in_run = false;
+    for (start, end, direction) in strong_chars(text) {
+        match runs.last_mut() {
+            _ if direction == base => in_run = false,
+            Some(run) if in_run => run.1 = end,
+            _ => {
+                runs.push((start, end));
+                in_run = true;
+            }
+        }
+    }
+    if runs.is_empty() {
+        return None;
+    }
+
+    let mut nodes = Vec::new();
+    let mut last = 0;
+    for (start, end) in runs {
+        if start > last {
+            nodes.push(Node::Text(text[last..start].to_string()));
+        }
+        let run = Node::Text(text[start..end].to_string());
+        nodes.push(Node::new_element("bdi", vec![], vec![run]));
+        *count += 1;
+        last = end;
+    }
+    if last < text.len() {
+        nodes.push(Node::Text(text[last..].to_string()));
+    }
+    Some(nodes)
+}
+
+fn isolate_nodes(nodes: &mut Vec<Node>, base: Direction, count: &mut usize) {
+    let mut i = 0;
+    while i < nodes.len() {
+        match &mut nodes[i] {
+            Node::Text(text) => {
+                if let Some(replacement) = isolate_text(text, base, count) {
+                    let len = replacement.len();
+                    nodes.splice(i..i + 1, replacement);
+                    i += len;
+                    continue;
+                }
+            }
+            Node::Element(el) => isolate_element(el, base, count),
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+fn isolate_element(element: &mut Element, base: Direction, count: &mut usize) {
+    if !is_prose(element)
+        || is_frozen(element)
+        || matches!(element.name.as_str(), "bdi" | "bdo")
+        || element.get_attr("dir").is_some()
+    {
+        return;
+    }
+    let only_text = element
+        .children
+        .iter()
+        .all(|node| matches!(node, Node::Text(_)));
+    if only_text {
+        let strong = strong_chars(&raw_text(&element.children));
+        let opposite = base.opposite();
+        if !strong.is_empty() && strong.iter().all(|(_, _, d)| *d == opposite) {
+            element.set_attr("dir", opposite.attr());
+            *count += 1;
+            return;
+        }
+    }
+    isolate_nodes(&mut element.children, base, count);
+}
+
+impl Typesettable for Vec<Node> {
+    fn hyphenate(
+        &mut self,
+        scope: Option<&Selector>,
+        mark: WordBreak,
+        mut split: impl FnMut(&str) -> Vec<usize>,
+    ) -> usize {
+        let mut count = 0;
+        self.rewrite_text(scope, |text| {
+            hyphenate_text(text, mark, &mut split, &mut count)
+        });
+        count
+    }
+
+    fn isolate_bidi(&mut self, base: Direction) -> usize {
+        let mut count = 0;
+        isolate_nodes(self, base, &mut count);
+        count
+    }
+}
+
+impl Typesettable for Element {
+    fn hyphenate(
+        &mut self,
+        scope: Option<&Selector>,
+        mark: WordBreak,
+        mut split: impl FnMut(&str) -> Vec<usize>,
+    ) -> usize {
+        let mut count = 0;
+        self.rewrite_text(scope, |text| {
+            hyphenate_text(text, mark, &mut split, &mut count)
+        });
+        count
+    }
+
+    fn isolate_bidi(&mut self, base: Direction) -> usize {
+        let mut count = 0;
+        if is_frozen(self) {
+            return count;
+        }
+        isolate_nodes(&mut self.children, base, &mut count);
+        count
+    }
+}
@@ -0,0 +1,69 @@
+//! Plain depth-first traversals shared by the operations that apply to
+//! every element rather than to the ones matching a selector.
+//!
+//! The editing traversals skip the frozen elements, marked with
+//! [`FROZEN_ATTR`], and their descendants.
+
+use super::FROZEN_ATTR;
+use crate::{Element, Node};
+
+/// Check if the element was frozen by
+/// [`Editable::freeze`](super::Editable::freeze).
+pub(crate) fn is_frozen(element: &Element) -> bool {
+    element.attrs.iter().any(|(name, _)| name == FROZEN_ATTR)
+}
+
+/// Call `f` for every element in `nodes` and their descendants, parents
+/// before children.
+pub(crate) fn walk_mut(nodes: &mut [Node], f: &mut impl FnMut(&mut Element)) {
+    for node in nodes.iter_mut() {
+        if let Node::Element(el) = node {
+            walk_element_mut(el, f);
+        }
+    }
+}
+
+/// Call `f` for `element` and all of its descendants, parents before
+/// children.
+pub(crate) fn walk_element_mut(element: &mut Element, f: &mut impl FnMut(&mut Element)) {
+    if is_frozen(element) {
+        return;
+    }
+    f(element);
+    walk_mut(&mut element.children, f);
+}
+
+/// Remove the elements in `nodes` and their descendants for which `f`
+/// returns `false`. The children of removed elements are not visited.
+pub(crate) fn retain(nodes: &mut Vec<Node>, f: &mut impl FnMut(&Element) -> bool) {
+    nodes.retain(|node| match node {
+        Node::Element(el) => is_frozen(el) || f(el),
+        _ => true,
+    });
+    for node in nodes.iter_mut() {
+        match node {
+            Node::Element(el) if !is_frozen(el) => retain(&mut el.children, f),
+            _ => {}
+        }
+    }
+}
+
+/// Concatenated raw text of `nodes` and their descendants.
+pub(crate) fn raw_text(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(el) => text.push_str(&raw_text(&el.children)),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Check if `element` has the class `name`.
+pub(crate) fn has_class(element: &Element, name: &str) -> bool {
+    element
+        .get_attr("class")
+        .is_some_and(|class| class.split_ascii_whitespace().any(|c| c == name))
+}
@@ -9,10 +9,282 @@
 mod attrs;
 mod token;
 
-use crate::{data::VOID_TAGS, Element, Node};
+use crate::data::{HTML_TAGS, METADATA_TAGS, VOID_TAGS};
+use crate::operation::{close_tag, open_tag, SOURCE_SPAN_ATTR};
+use crate::{Element, Node};
 use token::Token;
 
-fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
+/// Options of [`parse_with_options()`](parse_with_options).
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Keep the `{{ ... }}` interpolations of Vue or Angular templates
+    /// verbatim, even when they contain `<` or `>`, in the text as well
+    /// as in the tags.
+    pub interpolation: bool,
+    /// Parse the `{% ... %}`, `{{ ... }}` and `{# ... #}` tags of Jinja,
+    /// Handlebars or Liquid templates, and the `<?php ... ?>`,
+    /// `<?= ... ?>` and `<% ... %>` code blocks of PHP or ERB, into
+    /// [`Node::Template`], preserving them exactly. Inside a start tag,
+    /// they are kept whole as attributes without value, and inside
+    /// attribute values as part of the value, even when they contain
+    /// quotes. The tags and blocks never closed are left as they are.
+    pub template_tags: bool,
+    /// What to do with the tags that are not html.
+    pub unknown_tags: UnknownTagPolicy,
+    /// Record the byte range of each element in the input, from its
+    /// start tag to its end tag, in its
+    /// [`SOURCE_SPAN_ATTR`](crate::operation::SOURCE_SPAN_ATTR) attribute,
+    /// to build source maps with
+    /// [`SourceMappable`](crate::operation::SourceMappable).
+    pub source_spans: bool,
+    /// Synthesize the missing `<html>`, `<head>` and `<body>` like the
+    /// browsers do, moving the leading metadata elements, like `<title>`,
+    /// into the `<head>` and the rest of the content into the `<body>`.
+    /// The synthesized elements are marked with the [`IMPLICIT_ATTR`]
+    /// attribute, to omit them with
+    /// [`HtmlOptions::omit_implicit_tags`](crate::operation::HtmlOptions::omit_implicit_tags).
+    pub implicit_tags: bool,
+}
+
+/// Attribute marking the elements synthesized when parsing with
+/// [`ParseOptions::implicit_tags`]. It is not written into the html.
+pub const IMPLICIT_ATTR: &str = "data-html-editor-implicit";
+
+/// Source spans of the elements being tokenized.
+struct Spans {
+    enabled: bool,
+    /// Index, name and start of the start tokens not closed yet.
+    open: Vec<(usize, String, usize)>,
+}
+
+impl Spans {
+    /// Record the span of the start tag `tokens` ends with.
+    fn start(&mut self, tokens: &mut [Token], start: usize, end: usize) {
+        if !self.enabled {
+            return;
+        }
+        let index = tokens.len() - 1;
+        if let Token::Start(name, _) = &tokens[index] {
+            if !VOID_TAGS.contains(&name.as_str()) {
+                self.open.push((index, name.clone(), start));
+            }
+        }
+        set_span(&mut tokens[index], start, end);
+    }
+
+    /// Extend the span of the start tag closed by `</name>` to `end`.
+    fn end(&mut self, tokens: &mut [Token], name: &str, end: usize) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(position) = self.open.iter().rposition(|(_, open, _)| open == name) {
+            let (index, _, start) = self.open[position];
+            set_span(&mut tokens[index], start, end);
+            self.open.truncate(position);
+        }
+    }
+}
+
+fn set_span(token: &mut Token, start: usize, end: usize) {
+    if let Token::Start(_, attrs) | Token::Closing(_, attrs) = token {
+        let span = format!("{}-{}", start, end);
+        match attrs.iter_mut().find(|(name, _)| name == SOURCE_SPAN_ATTR) {
+            Some((_, value)) => *value = span,
+            None => attrs.push((SOURCE_SPAN_ATTR.to_string(), span)),
+        }
+    }
+}
+
+/// What to do with the tags that are not html, like a mistyped `<dvi>`.
+///
+/// Custom elements, whose name has a `-` like `<my-button>`, and the
+/// content of `<svg>` and `<math>` are not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownTagPolicy {
+    #[default]
+    Keep,
+    /// Turn the tags into text, keeping their content.
+    Escape,
+    /// Remove the tags, keeping their content.
+    Drop,
+    /// Fail to parse. [`try_parse_with_options()`](try_parse_with_options)
+    /// keeps the tags instead.
+    Error,
+}
+
+fn implicit_element(name: &str, children: Vec<Node>) -> Element {
+    Element {
+        name: name.to_string(),
+        attrs: vec![(IMPLICIT_ATTR.to_string(), String::new())],
+        children,
+    }
+}
+
+fn is_named(node: &Node, names: &[&str]) -> bool {
+    node.as_element()
+        .is_some_and(|el| names.iter().any(|name| el.name.eq_ignore_ascii_case(name)))
+}
+
+/// Check if `node` can stay before the `<html>` or the `<body>`.
+fn is_prolog(node: &Node) -> bool {
+    match node {
+        Node::Text(text) => text.trim().is_empty(),
+        Node::Element(_) => false,
+        _ => true,
+    }
+}
+
+/// Synthesize the missing `<html>`, `<head>` and `<body>` for
+/// [`ParseOptions::implicit_tags`], keeping the order of the nodes.
+fn insert_implicit_tags(nodes: &mut Vec<Node>) {
+    if !nodes.iter().any(|node| is_named(node, &["html"])) {
+        let start = nodes
+            .iter()
+            .position(|node| !is_prolog(node))
+            .unwrap_or(nodes.len());
+        let children = nodes.split_off(start);
+        nodes.push(Node::Element(implicit_element("html", children)));
+    }
+    let Some(html) = nodes.iter_mut().find_map(|node| match node {
+        Node::Element(el) if el.name.eq_ignore_ascii_case("html") => Some(el),
+        _ => None,
+    }) else {
+        return;
+    };
+    let children = &mut html.children;
+    let head = match children.iter().position(|node| is_named(node, &["head"])) {
+        Some(head) => head,
+        None => {
+            let end = children
+                .iter()
+                .position(|node| !is_prolog(node) && !is_named(node, &METADATA_TAGS))
+                .unwrap_or(children.len());
+            let head = implicit_element("head", children.drain(..end).collect());
+            children.insert(0, Node::Element(head));
+            0
+        }
+    };
+    if !children
+        .iter()
+        .any(|node| is_named(node, &["body", "frameset"]))
+    {
+        let body = implicit_element("body", children.split_off(head + 1));
+        children.push(Node::Element(body));
+    }
+}
+
+fn is_known_tag(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    HTML_TAGS.contains(&name.as_str()) || name.contains('-')
+}
+
+fn escape_tag(tag: &str) -> Node {
+    let tag = tag
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    Node::Text(tag)
+}
+
+/// Apply `policy` to the unknown tags among `nodes` and their
+/// descendants.
+fn apply_unknown_tags(nodes: &mut Vec<Node>, policy: UnknownTagPolicy) -> Result<(), String> {
+    if policy == UnknownTagPolicy::Keep {
+        return Ok(());
+    }
+    for node in std::mem::take(nodes) {
+        let mut el = match node {
+            Node::Element(el) => el,
+            node => {
+                nodes.push(node);
+                continue;
+            }
+        };
+        if !matches!(el.name.to_ascii_lowercase().as_str(), "svg" | "math") {
+            apply_unknown_tags(&mut el.children, policy)?;
+        }
+        if is_known_tag(&el.name) {
+            nodes.push(Node::Element(el));
+            continue;
+        }
+        match policy {
+            UnknownTagPolicy::Keep => nodes.push(Node::Element(el)),
+            UnknownTagPolicy::Escape => {
+                nodes.push(escape_tag(&open_tag(&el)));
+                let close = close_tag(&el);
+                nodes.append(&mut el.children);
+                if !close.is_empty() {
+                    nodes.push(escape_tag(&close));
+                }
+            }
+            UnknownTagPolicy::Drop => nodes.append(&mut el.children),
+            UnknownTagPolicy::Error => return Err(format!("Unknown tag: <{}>", el.name)),
+        }
+    }
+    Ok(())
+}
+
+/// Delimiters of the template tags, the longest first.
+pub(crate) const TEMPLATE_DELIMITERS: [(&str, &str); 5] = [
+    ("{{!--", "--}}"),
+    ("{{{", "}}}"),
+    ("{{", "}}"),
+    ("{%", "%}"),
+    ("{#", "#}"),
+];
+
+/// Delimiters of the PHP and ERB code blocks, parsed into
+/// [`Node::Template`] like the template tags.
+pub(crate) const CODE_DELIMITERS: [(&str, &str); 3] =
+    [("<?php", "?>"), ("<?=", "?>"), ("<%", "%>")];
+
+/// Offsets of the next closing delimiters of the template tags, so the
+/// input is searched once for each of them instead of at each opening.
+#[derive(Default)]
+struct Closes(Vec<(&'static str, Option<usize>)>);
+
+impl Closes {
+    /// Whether `close` is found in `html` from the offset `from` on.
+    fn found(&mut self, html: &str, close: &'static str, from: usize) -> bool {
+        let search = |from: usize| html[from..].find(close).map(|end| from + end);
+        match self.0.iter_mut().find(|(c, _)| *c == close) {
+            Some((_, next)) => {
+                if matches!(*next, Some(offset) if offset < from) {
+                    *next = search(from);
+                }
+                next.is_some()
+            }
+            None => {
+                let next = search(from);
+                self.0.push((close, next));
+                next.is_some()
+            }
+        }
+    }
+}
+
+/// Delimiters of the closed template tag or code block starting `html`
+/// at the offset `i`.
+fn template_opening(
+    html: &str,
+    i: usize,
+    closes: &mut Closes,
+) -> Option<(&'static str, &'static str)> {
+    let rest = &html[i..];
+    TEMPLATE_DELIMITERS
+        .iter()
+        .chain(CODE_DELIMITERS.iter())
+        .find(|(open, close)| rest.starts_with(open) && closes.found(html, close, i + open.len()))
+        .copied()
+}
+
+/// Tokenize `html`, pushing the index and the byte offset of each tag
+/// token to `offsets`.
+fn html_to_stack(
+    html: &str,
+    options: &ParseOptions,
+    offsets: &mut Vec<(usize, usize)>,
+) -> Result<Vec<Token>, String> {
     let mut chars_stack = Vec::<char>::new();
     let mut token_stack = Vec::<Token>::new();
     let mut in_quotes: Option<char> = None;
@@ -21,8 +293,43 @@ fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
     let mut in_comment = false;
     let mut in_script = false;
     let mut in_style = false;
-    for ch in html.chars() {
-        if let Some(quote) = in_quotes {
+    let mut in_interpolation = false;
+    // Closing delimiter of the template tag, and the chars left before
+    // it can be found.
+    let mut in_template: Option<(&str, usize)> = None;
+    let mut closes = Closes::default();
+    let mut spans = Spans {
+        enabled: options.source_spans,
+        open: Vec::new(),
+    };
+    for (i, ch) in html.char_indices() {
+        let opening = match in_template {
+            None if options.template_tags && !in_comment && !in_script && !in_style => {
+                template_opening(html, i, &mut closes)
+            }
+            _ => None,
+        };
+        if let Some((open, close)) = opening {
+            if !in_brackets && !chars_stack.is_empty() {
+                token_stack.push(Token::Text(String::from_iter(chars_stack)));
+                chars_stack = Vec::new();
+            }
+            in_template = Some((close, open.chars().count() + close.chars().count()));
+        }
+
+        if let Some((close, left)) = in_template {
+            chars_stack.push(ch);
+            let left = left.saturating_sub(1);
+            in_template = Some((close, left));
+            let close: Vec<char> = close.chars().collect();
+            if left == 0 && ends_with(&chars_stack, &close) {
+                in_template = None;
+                if !in_brackets {
+                    token_stack.push(Token::Template(String::from_iter(chars_stack)));
+                    chars_stack = Vec::new();
+                }
+            }
+        } else if let Some(quote) = in_quotes {
             if ch == quote {
                 let previous_char = *chars_stack
                     .last()
@@ -32,6 +339,12 @@ fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
                 }
             }
             chars_stack.push(ch);
+        } else if in_interpolation {
+            chars_stack.push(ch);
+
+            if ends_with(&chars_stack, &['}', '}']) {
+                in_interpolation = false;
+            }
         } else if in_comment {
             chars_stack.push(ch);
 
@@ -50,7 +363,9 @@ fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
                 let script = String::from_iter(chars_stack[..len - 9].to_vec());
                 chars_stack = Vec::new();
                 token_stack.push(Token::Text(script));
+                offsets.push((token_stack.len(), i + 1 - 9));
                 token_stack.push(Token::End("script".to_string()));
+                spans.end(&mut token_stack, "script", i + 1);
                 in_script = false;
             }
         } else if in_style {
@@ -61,7 +376,9 @@ fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
                 let style = String::from_iter(chars_stack[..len - 8].to_vec());
                 chars_stack = Vec::new();
                 token_stack.push(Token::Text(style));
+                offsets.push((token_stack.len(), i + 1 - 8));
                 token_stack.push(Token::End("style".to_string()));
+                spans.end(&mut token_stack, "style", i + 1);
                 in_style = false;
             }
         } else {
@@ -79,7 +396,7 @@ fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
                     }
                     chars_stack.push(ch);
                 }
-                '>' => {
+                '>' if in_brackets => {
                     in_brackets = false;
                     chars_stack.push(ch);
                     // Turn the chars in `chars_stack` in to `String`
@@ -87,8 +404,16 @@ fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
                     let tag_text = String::from_iter(chars_stack);
                     chars_stack = Vec::new();
                     // Push the tag with the text we just got to the token stack.
-                    let tag = Token::from(tag_text.clone())?;
+                    let tag = Token::from(tag_text.clone(), options.template_tags)?;
+                    offsets.push((token_stack.len(), i + 1 - tag_text.len()));
                     token_stack.push(tag.clone());
+                    match &tag {
+                        Token::Start(..) | Token::Closing(..) => {
+                            spans.start(&mut token_stack, i + 1 - tag_text.len(), i + 1)
+                        }
+                        Token::End(name) => spans.end(&mut token_stack, name, i + 1),
+                        _ => {}
+                    }
                     // Handle special tags
                     if let Token::Start(tag_name, _) = tag {
                         let tag_name = tag_name.as_str();
@@ -113,14 +438,21 @@ fn html_to_stack(html: &str) -> Result<Vec<Token>, String> {
                             _ => {}
                         }
                     }
-                    chars_stack.push(ch)
+                    chars_stack.push(ch);
+                    if options.interpolation && ends_with(&chars_stack, &['{', '{']) {
+                        in_interpolation = true;
+                    }
                 }
             }
         }
     }
     if !chars_stack.is_empty() {
         let text = String::from_iter(chars_stack);
-        token_stack.push(Token::Text(text));
+        if in_template.is_some() && !in_brackets {
+            token_stack.push(Token::Template(text));
+        } else {
+            token_stack.push(Token::Text(text));
+        }
     }
     Ok(token_stack)
 }
@@ -195,7 +527,40 @@ fn stack_to_dom(token_stack: Vec<Token>) -> Result<Vec<Node>, String> {
     }
 }
 
-fn try_stack_to_dom(token_stack: Vec<Token>) -> Vec<Node> {
+/// Problem of the html fixed by
+/// [`try_parse_with_repairs()`](try_parse_with_repairs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// The element started by the tag at the byte `offset` is not
+    /// closed, and ends with its parent.
+    AutoClosed { name: String, offset: usize },
+    /// The end tag at the byte `offset` does not close any open element,
+    /// and is discarded.
+    DiscardedEndTag { name: String, offset: usize },
+    /// The html cannot be tokenized, because of `message`, and is
+    /// discarded.
+    Discarded { message: String },
+}
+
+/// Repairs made by [`try_stack_to_dom`], with the offsets of the tags
+/// from [`html_to_stack`].
+struct RepairLog<'a> {
+    offsets: &'a [(usize, usize)],
+    repairs: Vec<Repair>,
+}
+
+impl RepairLog<'_> {
+    fn offset(&self, index: usize) -> usize {
+        match self.offsets.binary_search_by_key(&index, |(i, _)| *i) {
+            Ok(position) => self.offsets[position].1,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// Build the nodes from `token_stack`, which starts at the index `base`
+/// of the whole stack, logging the repairs.
+fn try_stack_to_dom(token_stack: Vec<Token>, base: usize, log: &mut RepairLog) -> Vec<Node> {
     let mut nodes: Vec<Node> = Vec::new();
     let mut start_tags_stack: Vec<Token> = Vec::new();
     let mut start_tag_index = 0;
@@ -232,7 +597,14 @@ fn try_stack_to_dom(token_stack: Vec<Token>) -> Vec<Node> {
                     Some(token) => token.into_element(),
                     // It means the end tag is redundant, so we will omit
                     // it and just start the next loop.
-                    None => continue,
+                    None => {
+                        let offset = log.offset(base + i);
+                        log.repairs.push(Repair::DiscardedEndTag {
+                            name: tag.clone(),
+                            offset,
+                        });
+                        continue;
+                    }
                 };
 
                 if tag != &start_tag.name {
@@ -250,6 +622,8 @@ fn try_stack_to_dom(token_stack: Vec<Token>) -> Vec<Node> {
                             attrs: start_tag.attrs,
                             children: try_stack_to_dom(
                                 token_stack[start_tag_index + 1..i].to_vec(),
+                                base + start_tag_index + 1,
+                                log,
                             ),
                         }
                         .into_node(),
@@ -266,10 +640,19 @@ fn try_stack_to_dom(token_stack: Vec<Token>) -> Vec<Node> {
 
     if !start_tags_stack.is_empty() {
         if let Token::Start(name, attrs) = start_tags_stack[0].clone() {
+            let offset = log.offset(base + start_tag_index);
+            log.repairs.push(Repair::AutoClosed {
+                name: name.clone(),
+                offset,
+            });
             nodes.push(Node::Element(Element {
                 name,
                 attrs,
-                children: try_stack_to_dom(token_stack[start_tag_index + 1..].to_vec()),
+                children: try_stack_to_dom(
+                    token_stack[start_tag_index + 1..].to_vec(),
+                    base + start_tag_index + 1,
+                    log,
+                ),
             }));
         } else {
             unreachable!()
@@ -329,9 +712,28 @@ fn try_stack_to_dom(token_stack: Vec<Token>) -> Vec<Node> {
 /// ]
 /// ```
 pub fn parse(html: &str) -> Result<Vec<Node>, String> {
-    let stack = html_to_stack(html)?;
+    parse_with_options(html, &ParseOptions::default())
+}
 
-    stack_to_dom(stack)
+/// Alternative for [`parse()`](parse) with options.
+///
+/// ```
+/// use html_editor::{parse_with_options, ParseOptions, operation::*};
+///
+/// let options = ParseOptions { interpolation: true, ..Default::default() };
+/// let html = r#"<p :class="{ on: n > 0 }">{{ n < 10 ? n : "many" }}</p>"#;
+///
+/// assert_eq!(parse_with_options(html, &options).unwrap().html(), html);
+/// ```
+pub fn parse_with_options(html: &str, options: &ParseOptions) -> Result<Vec<Node>, String> {
+    let stack = html_to_stack(html, options, &mut Vec::new())?;
+
+    let mut nodes = stack_to_dom(stack)?;
+    apply_unknown_tags(&mut nodes, options.unknown_tags)?;
+    if options.implicit_tags {
+        insert_implicit_tags(&mut nodes);
+    }
+    Ok(nodes)
 }
 
 /// Alternative for [`parse()`](parse) with fault tolerance
@@ -351,15 +753,119 @@ pub fn parse(html: &str) -> Result<Vec<Node>, String> {
 /// assert_eq!(result, "<div><a>Ipsum</a></div>");
 /// ```
 pub fn try_parse(html: &str) -> Vec<Node> {
-    let stack = html_to_stack(html).unwrap_or_default();
+    try_parse_with_options(html, &ParseOptions::default())
+}
+
+/// Alternative for [`try_parse()`](try_parse) with options.
+pub fn try_parse_with_options(html: &str, options: &ParseOptions) -> Vec<Node> {
+    try_parse_with_repairs(html, options).0
+}
+
+/// Alternative for [`try_parse_with_options()`](try_parse_with_options)
+/// which also returns the repairs made to the html, in the order of
+/// the parsing, for the tools reporting them.
+///
+/// ```
+/// use html_editor::{try_parse_with_repairs, ParseOptions, Repair};
+/// use html_editor::operation::Htmlifiable;
+///
+/// let (nodes, repairs) = try_parse_with_repairs("<p>Ipsum</a>", &ParseOptions::default());
+///
+/// assert_eq!(nodes.html(), "<p>Ipsum</p>");
+/// assert_eq!(
+///     repairs,
+///     vec![
+///         Repair::AutoClosed { name: "p".to_string(), offset: 0 },
+///         Repair::DiscardedEndTag { name: "a".to_string(), offset: 8 },
+///     ]
+/// );
+/// ```
+pub fn try_parse_with_repairs(html: &str, options: &ParseOptions) -> (Vec<Node>, Vec<Repair>) {
+    let mut offsets = Vec::new();
+    let mut log = RepairLog {
+        offsets: &[],
+        repairs: Vec::new(),
+    };
+    let stack = match html_to_stack(html, options, &mut offsets) {
+        Ok(stack) => stack,
+        Err(message) => {
+            log.repairs.push(Repair::Discarded { message });
+            Vec::new()
+        }
+    };
+    log.offsets = &offsets;
+
+    let mut nodes = try_stack_to_dom(stack, 0, &mut log);
+    let policy = match options.unknown_tags {
+        UnknownTagPolicy::Error => UnknownTagPolicy::Keep,
+        policy => policy,
+    };
+    let _ = apply_unknown_tags(&mut nodes, policy);
+    if options.implicit_tags {
+        insert_implicit_tags(&mut nodes);
+    }
+    (nodes, log.repairs)
+}
 
-    try_stack_to_dom(stack)
+/// Front matter of a page, as used by static site generators.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frontmatter {
+    /// Block delimited by `---`, usually holding YAML.
+    Yaml(String),
+    /// Block delimited by `+++`, usually holding TOML.
+    Toml(String),
+}
+
+/// Parse html preceded by an optional front matter block, returning the
+/// raw content of the block, without its delimiters, besides the nodes.
+///
+/// The block must start on the first line and be closed by the same
+/// delimiter on its own line. Otherwise, the whole input is parsed as
+/// html.
+///
+/// ```
+/// use html_editor::{parse_with_frontmatter, Frontmatter, operation::*};
+///
+/// let (frontmatter, dom) = parse_with_frontmatter("---\ntitle: Hello\n---\n<h1>Hello</h1>").unwrap();
+///
+/// assert_eq!(frontmatter, Some(Frontmatter::Yaml("title: Hello\n".to_string())));
+/// assert_eq!(dom.html(), "<h1>Hello</h1>");
+/// ```
+pub fn parse_with_frontmatter(html: &str) -> Result<(Option<Frontmatter>, Vec<Node>), String> {
+    let (frontmatter, body) = split_frontmatter(html);
+    Ok((frontmatter, parse(body)?))
+}
+
+fn split_frontmatter(html: &str) -> (Option<Frontmatter>, &str) {
+    let source = html.strip_prefix('\u{feff}').unwrap_or(html);
+    let (first, mut rest) = source.split_once('\n').unwrap_or((source, ""));
+    let delimiter = first.trim_end();
+    if delimiter != "---" && delimiter != "+++" {
+        return (None, html);
+    }
+    let start = source.len() - rest.len();
+    while !rest.is_empty() {
+        let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+        if line.trim_end() == delimiter {
+            let content = source[start..source.len() - rest.len()].to_string();
+            let frontmatter = match delimiter {
+                "---" => Frontmatter::Yaml(content),
+                _ => Frontmatter::Toml(content),
+            };
+            return (Some(frontmatter), next);
+        }
+        rest = next;
+    }
+    (None, html)
 }
 
 // Use `&[char]` instead of `&str` to improve performance.
-fn ends_with(chars: &Vec<char>, end: &[char]) -> bool {
+fn ends_with(chars: &[char], end: &[char]) -> bool {
     let chars_len = chars.len();
     let end_len = end.len();
+    if chars_len < end_len {
+        return false;
+    }
     for i in 1..(end_len + 1) {
         if chars[chars_len - i] != end[end.len() - i] {
             return false;
@@ -1,126 +1,79 @@
-// Let's take `<img src="example.png" alt=image>` for example.
-enum AttrPos {
-    /// Includes `src`, `alt`
-    Key,
-    /// Includes `=`
-    Equal,
-    /// Includes `example.png`, `image`
-    Value(Option<char>),
-    /// Includes ` `
-    Space,
-}
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::parse::{CODE_DELIMITERS, TEMPLATE_DELIMITERS};
 
 /// Valid `attr_str` like: `src="example.png" alt=example disabled`
-pub fn parse(attr_str: String) -> Vec<(String, String)> {
-    let mut chars_stack: Vec<char> = Vec::new();
-    let mut key_stack: Vec<String> = Vec::new();
-    let mut value_stack: Vec<String> = Vec::new();
-    let mut attr_pos = AttrPos::Key;
-    for ch in attr_str.chars() {
-        match attr_pos {
-            AttrPos::Key => match ch {
-                '=' => {
-                    attr_pos = AttrPos::Equal;
-                    let key = String::from_iter(chars_stack);
-                    chars_stack = Vec::new();
-                    key_stack.push(key)
-                }
-                ' ' => {
-                    attr_pos = AttrPos::Space;
-                    let key = String::from_iter(chars_stack);
-                    chars_stack = Vec::new();
-                    key_stack.push(key);
-                    value_stack.push(String::new())
-                }
-                _ => chars_stack.push(ch),
-            },
-            AttrPos::Equal => match ch {
-                '\'' => attr_pos = AttrPos::Value(Some('\'')),
-                '\"' => attr_pos = AttrPos::Value(Some('\"')),
-                _ => {
-                    attr_pos = AttrPos::Value(None);
-                    chars_stack.push(ch)
-                }
-            },
-            AttrPos::Value(delimiter) => match delimiter {
-                None => {
-                    if ch == ' ' {
-                        attr_pos = AttrPos::Space;
-                        let value = String::from_iter(chars_stack);
-                        chars_stack = Vec::new();
-                        value_stack.push(value)
-                    } else {
-                        chars_stack.push(ch);
-                    }
-                }
-                Some(quote) => {
-                    if ch == quote {
-                        if chars_stack.is_empty() {
-                            value_stack.push(String::new());
-                            attr_pos = AttrPos::Space;
-                            continue;
-                        }
-                        let last_char = chars_stack
-                            .last()
-                            .expect("cannot accesss the last char in `chars_stack`");
-                        if last_char == &'\\' {
-                            chars_stack.push(ch);
-                            continue;
-                        } else {
-                            attr_pos = AttrPos::Space;
-                            let value = String::from_iter(chars_stack);
-                            chars_stack = Vec::new();
-                            value_stack.push(value)
-                        }
-                    } else {
-                        chars_stack.push(ch)
-                    }
-                }
-            },
-            AttrPos::Space => {
-                if ch != ' ' {
-                    attr_pos = AttrPos::Key;
-                    chars_stack.push(ch);
-                }
+///
+/// The attributes are kept in order, and their names verbatim, so the
+/// ones of template syntaxes like `@click`, `:src`, `(click)` or
+/// `[(ngModel)]` survive. Template tags like `{{ ... }}` or
+/// `{% ... %}`, and code blocks like `<?= ... ?>` if `code_blocks`, are
+/// read whole, as a name or inside a value.
+pub fn parse(attr_str: &str, code_blocks: bool) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut chars = attr_str.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut key = String::new();
+        // A stray `=` is taken as part of the name rather than looping.
+        if let Some(ch) = chars.next_if_eq(&'=') {
+            key.push(ch);
+        }
+        if !read_template(&mut chars, &mut key, code_blocks) {
+            while let Some(ch) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+                key.push(ch);
             }
         }
-    }
-
-    let err_info = format!("cannot parse the attributes: {}", attr_str);
-    let err_info = err_info.as_str();
+        if key.is_empty() {
+            return attrs;
+        }
 
-    if !chars_stack.is_empty() {
-        let str = String::from_iter(chars_stack);
-        match attr_pos {
-            AttrPos::Key => {
-                key_stack.push(str);
-                value_stack.push(String::new());
-            }
-            AttrPos::Value(delimiter) => {
-                if delimiter.is_none() {
-                    value_stack.push(str);
-                } else {
-                    panic!("{}", err_info)
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            match chars.next_if(|c| *c == '"' || *c == '\'') {
+                Some(quote) => loop {
+                    if read_template(&mut chars, &mut value, code_blocks) {
+                        continue;
+                    }
+                    match chars.next() {
+                        Some(ch) if ch == quote && !value.ends_with('\\') => break,
+                        Some(ch) => value.push(ch),
+                        None => break,
+                    }
+                },
+                None => {
+                    read_template(&mut chars, &mut value, code_blocks);
+                    while let Some(ch) = chars.next_if(|c| !c.is_whitespace()) {
+                        value.push(ch);
+                    }
                 }
             }
-            _ => {}
         }
+        attrs.push((key, value));
     }
+}
 
-    if key_stack.len() != value_stack.len() {
-        panic!(
-            "{}\nkey:\t{:?}\nvalue:\t{:?}",
-            err_info, key_stack, value_stack
-        )
+/// Read the template tag or code block starting at `chars` into
+/// `text`, if there is one, returning whether there was.
+fn read_template(chars: &mut Peekable<Chars>, text: &mut String, code_blocks: bool) -> bool {
+    if chars.peek() != Some(&'{') && (!code_blocks || chars.peek() != Some(&'<')) {
+        return false;
     }
-
-    let mut attrs = Vec::new();
-    let len = key_stack.len();
-    for _ in 0..len {
-        attrs.push((
-            key_stack.pop().expect(err_info),
-            value_stack.pop().expect(err_info),
-        ));
+    let rest: String = chars.clone().collect();
+    let code = CODE_DELIMITERS.iter().filter(|_| code_blocks);
+    let mut delimiters = TEMPLATE_DELIMITERS.iter().chain(code);
+    let len = delimiters.find_map(|(open, close)| {
+        let end = rest.strip_prefix(open)?.find(close)?;
+        Some(rest[..open.len() + end + close.len()].chars().count())
+    });
+    match len {
+        Some(len) => {
+            text.extend(chars.take(len));
+            true
+        }
+        None => false,
     }
-    attrs
 }
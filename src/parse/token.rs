@@ -13,12 +13,18 @@ pub enum Token {
     Doctype(Doctype),
     /// Like `<!-- comment -->`
     Comment(String),
+    /// Like `<?xml version="1.0"?>`
+    ProcessingInstruction(String),
     /// Any text
     Text(String),
+    /// Like `{% if user %}`
+    Template(String),
 }
 
 impl Token {
-    pub fn from(tag: String) -> Result<Self, String> {
+    /// Tokenize the `tag`, reading the code blocks of its attributes
+    /// whole if `code_blocks`.
+    pub fn from(tag: String, code_blocks: bool) -> Result<Self, String> {
         if tag.ends_with("/>") {
             let tag_name_start = tag[1..tag.len()]
                 .chars()
@@ -33,30 +39,19 @@ impl Token {
                 None => tag.len() - 2,
             };
             let tag_name = tag[tag_name_start..tag_name_end].to_string();
-            let attr_str = tag[tag_name_end..tag.len() - 2].trim().to_string();
-            Ok(Self::Closing(tag_name, attrs::parse(attr_str)))
+            let attr_str = &tag[tag_name_end..tag.len() - 2];
+            Ok(Self::Closing(tag_name, attrs::parse(attr_str, code_blocks)))
         } else if tag.starts_with("</") {
             Ok(Self::End(tag[2..tag.len() - 1].trim().to_string()))
         } else if tag.starts_with("<!--") {
             Ok(Self::from_comment(tag))
         } else if tag.starts_with("<!") {
             Ok(Self::Doctype(Doctype::Html))
-        } else if tag.starts_with("<?") {
-            let attr = tag[2..tag.len() - 2].to_string();
-            let attr = attrs::parse(attr);
-            let version = attr
-                .iter()
-                .find(|(name, _)| name == "version")
-                .expect("cannot find version attribute in xml declaration")
-                .1
-                .to_string();
-            let encoding = attr
-                .iter()
-                .find(|(name, _)| name == "encoding")
-                .expect("cannot find encoding attribute in xml declaration")
-                .1
-                .to_string();
-            Ok(Self::Doctype(Doctype::Xml { version, encoding }))
+        } else if let Some(instruction) = tag.strip_prefix("<?") {
+            let instruction = instruction.strip_suffix('>').unwrap_or(instruction);
+            let instruction = instruction.strip_suffix('?').unwrap_or(instruction);
+            Ok(Self::xml_declaration(instruction)
+                .unwrap_or_else(|| Self::ProcessingInstruction(instruction.to_string())))
         } else if tag.starts_with('<') {
             let tag_name_start = tag[1..tag.len()]
                 .chars()
@@ -71,13 +66,31 @@ impl Token {
                 None => tag.len() - 1,
             };
             let tag_name = tag[tag_name_start..tag_name_end].to_string();
-            let attr_str = tag[tag_name_end..tag.len() - 1].trim().to_string();
-            Ok(Self::Start(tag_name, attrs::parse(attr_str)))
+            let attr_str = &tag[tag_name_end..tag.len() - 1];
+            Ok(Self::Start(tag_name, attrs::parse(attr_str, code_blocks)))
         } else {
             Err(format!("Invalid tag: {}", tag))
         }
     }
 
+    /// The XML declaration of the processing `instruction`, if it is
+    /// written back the same by [`Doctype::Xml`], with only a version and
+    /// an encoding.
+    fn xml_declaration(instruction: &str) -> Option<Self> {
+        let attrs = attrs::parse(instruction.strip_prefix("xml ")?, false);
+        let [(version_name, version), (encoding_name, encoding)] = &attrs[..] else {
+            return None;
+        };
+        let written = format!(r#"xml version="{}" encoding="{}""#, version, encoding);
+        if version_name != "version" || encoding_name != "encoding" || written != instruction {
+            return None;
+        }
+        Some(Self::Doctype(Doctype::Xml {
+            version: version.clone(),
+            encoding: encoding.clone(),
+        }))
+    }
+
     #[inline]
     pub fn from_comment(comment: String) -> Self {
         Self::Comment(comment[4..comment.len() - 3].to_string())
@@ -112,7 +125,9 @@ impl Token {
 
             Self::Doctype(doctype) => Node::Doctype(doctype),
             Self::Comment(comment) => Node::Comment(comment),
+            Self::ProcessingInstruction(instruction) => Node::ProcessingInstruction(instruction),
             Self::Text(text) => Node::Text(text),
+            Self::Template(template) => Node::Template(template),
         }
     }
 
@@ -0,0 +1,78 @@
+//! Minimal handling of the `srcset` attribute of images and sources.
+
+/// Image candidate of a `srcset`, like `photo-640.jpg 640w`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Candidate {
+    pub url: String,
+    /// Width (`640w`) or density (`2x`) descriptor, if any.
+    pub descriptor: Option<String>,
+}
+
+impl Candidate {
+    /// Width of the candidate, if it has a `w` descriptor.
+    pub fn width(&self) -> Option<u32> {
+        self.descriptor.as_deref()?.strip_suffix('w')?.parse().ok()
+    }
+}
+
+/// Split a `srcset` into its candidates.
+///
+/// Commas inside a url, like in `data:` urls, do not separate candidates,
+/// following the parsing rules of browsers.
+pub(crate) fn parse(srcset: &str) -> Vec<Candidate> {
+    let mut candidates = Vec::new();
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+        let end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let url = &rest[..end];
+        rest = &rest[end..];
+        if url.ends_with(',') {
+            candidates.push(Candidate {
+                url: url.trim_end_matches(',').to_string(),
+                descriptor: None,
+            });
+            continue;
+        }
+
+        let mut depth = 0;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| match c {
+                '(' => {
+                    depth += 1;
+                    false
+                }
+                ')' => {
+                    depth -= 1;
+                    false
+                }
+                ',' => depth <= 0,
+                _ => false,
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        let descriptor = rest[..end].trim();
+        candidates.push(Candidate {
+            url: url.to_string(),
+            descriptor: (!descriptor.is_empty()).then(|| descriptor.to_string()),
+        });
+        rest = &rest[end..];
+    }
+}
+
+/// Join candidates back into a `srcset`.
+pub(crate) fn serialize(candidates: &[Candidate]) -> String {
+    candidates
+        .iter()
+        .map(|candidate| match &candidate.descriptor {
+            Some(descriptor) => format!("{} {}", candidate.url, descriptor),
+            None => candidate.url.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
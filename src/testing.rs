@@ -0,0 +1,93 @@
+//! Helpers to test html transforms against golden files.
+//!
+//! Requires the `testing` feature.
+
+use std::{env, fs, path::Path};
+
+use crate::operation::Pipeline;
+
+/// Environment variable which, when set to anything but `0` or an empty
+/// string, makes [`transform_fixture`] write the expected files instead
+/// of checking them.
+pub const UPDATE_FIXTURES_ENV: &str = "HTML_EDITOR_UPDATE_FIXTURES";
+
+/// Apply `pipeline` to the html in `input_path`, and check that the
+/// result is the content of `expected_path`.
+///
+/// If the [`UPDATE_FIXTURES_ENV`] variable is set, the result is written
+/// into `expected_path` instead, creating it if needed.
+///
+/// # Panics
+///
+/// Panics if a file can't be read or written, if the pipeline fails, or
+/// if the result differs from the expected one, pointing to the first
+/// line which differs.
+///
+/// ```no_run
+/// use html_editor::operation::Pipeline;
+/// use html_editor::testing::transform_fixture;
+///
+/// let pipeline = Pipeline::new().remove("script");
+/// transform_fixture("tests/fixtures/page.html", "tests/fixtures/page.expected.html", &pipeline);
+/// ```
+#[track_caller]
+pub fn transform_fixture(
+    input_path: impl AsRef<Path>,
+    expected_path: impl AsRef<Path>,
+    pipeline: &Pipeline,
+) {
+    let (input_path, expected_path) = (input_path.as_ref(), expected_path.as_ref());
+    let input = fs::read_to_string(input_path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", input_path.display(), e));
+    let output = pipeline
+        .apply(&input)
+        .unwrap_or_else(|e| panic!("failed to transform {}: {}", input_path.display(), e));
+
+    if updating() {
+        if let Some(dir) = expected_path.parent() {
+            fs::create_dir_all(dir)
+                .unwrap_or_else(|e| panic!("failed to create {}: {}", dir.display(), e));
+        }
+        fs::write(expected_path, &output)
+            .unwrap_or_else(|e| panic!("failed to write {}: {}", expected_path.display(), e));
+        return;
+    }
+
+    let expected = fs::read_to_string(expected_path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read {}: {}\nset {}=1 to create it",
+            expected_path.display(),
+            e,
+            UPDATE_FIXTURES_ENV
+        )
+    });
+    if output != expected {
+        let (line, expected_line, output_line) = first_difference(&expected, &output);
+        panic!(
+            "{} doesn't match {} at line {}\nexpected: {:?}\n  output: {:?}\nset {}=1 to update it",
+            input_path.display(),
+            expected_path.display(),
+            line,
+            expected_line,
+            output_line,
+            UPDATE_FIXTURES_ENV
+        );
+    }
+}
+
+fn updating() -> bool {
+    env::var_os(UPDATE_FIXTURES_ENV).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Get the first differing line, counting from 1, in both texts.
+fn first_difference<'a>(expected: &'a str, output: &'a str) -> (usize, &'a str, &'a str) {
+    let mut expected_lines = expected.split('\n');
+    let mut output_lines = output.split('\n');
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), output_lines.next()) {
+            (Some(e), Some(o)) if e == o => line += 1,
+            (e, o) => return (line, e.unwrap_or(""), o.unwrap_or("")),
+        }
+    }
+}
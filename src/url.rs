@@ -0,0 +1,74 @@
+//! Minimal URL inspection, just enough for the operations to classify
+//! the URLs found in attributes.
+
+use crate::entity;
+
+/// Get the lowercased scheme of `url`, like `https` or `javascript`.
+///
+/// Like browsers do, character references are decoded first, leading
+/// whitespace and control characters are ignored, and so are tabs and
+/// newlines inside the scheme.
+pub(crate) fn scheme(url: &str) -> Option<String> {
+    let url = entity::decode(url);
+    let url = url.trim_start_matches(|c: char| c.is_ascii_whitespace() || c.is_ascii_control());
+    let (scheme, _) = url.split_once(':')?;
+    let scheme: String = scheme
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(scheme)
+}
+
+/// Scheme and authority of an absolute or protocol-relative URL.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Origin {
+    /// `None` for protocol-relative URLs like `//example.com`.
+    pub scheme: Option<String>,
+    /// Lowercased host, with the port unless it is the default one.
+    pub host: String,
+}
+
+impl Origin {
+    /// Check if a link to `self` stays on `other`.
+    pub fn is_same(&self, other: &Origin) -> bool {
+        self.host == other.host
+            && match (&self.scheme, &other.scheme) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+/// Get the origin of `url`, or `None` if it has no authority, like
+/// relative URLs or `mailto:` ones.
+pub(crate) fn origin(url: &str) -> Option<Origin> {
+    let url = url.trim();
+    let (scheme, rest) = match url.strip_prefix("//") {
+        Some(rest) => (None, rest),
+        None => {
+            let scheme = scheme(url)?;
+            let (_, rest) = url.split_once(':')?;
+            (Some(scheme), rest.strip_prefix("//")?)
+        }
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority
+        .rsplit('@')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let default_port = match scheme.as_deref() {
+        Some("http") | Some("ws") => Some(":80"),
+        Some("https") | Some("wss") => Some(":443"),
+        _ => None,
+    };
+    let host = match default_port {
+        Some(port) => host.strip_suffix(port).unwrap_or(&host).to_string(),
+        None => host,
+    };
+    (!host.is_empty()).then_some(Origin { scheme, host })
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use html_editor::operation::*;
+use html_editor::parse;
+
+const HTML: &str = r#"
+    <div>
+        <img src="cat.png">
+        <img src="dog.png" alt="A pet">
+        <img src="bird.png" alt="A pet">
+        <img src="cat.png" alt="">
+        <p><img src="fish.png"></p>
+    </div>"#;
+
+#[test]
+fn audit_alt() {
+    let issues = parse(HTML).unwrap().audit_alt();
+    let src = |s: &str| Some(s.to_string());
+    assert_eq!(
+        issues,
+        vec![
+            AltIssue::Missing {
+                src: src("cat.png")
+            },
+            AltIssue::Duplicate {
+                src: src("dog.png"),
+                alt: "A pet".to_string()
+            },
+            AltIssue::Duplicate {
+                src: src("bird.png"),
+                alt: "A pet".to_string()
+            },
+            AltIssue::Missing {
+                src: src("fish.png")
+            },
+        ]
+    );
+}
+
+#[test]
+fn fill_alt_keeps_decorative_images() {
+    let alts = HashMap::from([("cat.png".to_string(), "A cat".to_string())]);
+    let mut dom = parse(HTML).unwrap();
+
+    assert_eq!(dom.fill_alt(&alts), 1);
+    let cats: Vec<_> = dom
+        .query_all(&Selector::from("img"))
+        .into_iter()
+        .filter_map(|img| img.attrs.iter().find(|(k, _)| k == "alt"))
+        .map(|(_, v)| v.as_str())
+        .collect();
+    assert_eq!(cats, vec!["A cat", "A pet", "A pet", ""]);
+}
+
+#[test]
+fn fill_alt_with_callback() {
+    let mut dom = parse(HTML).unwrap();
+    assert_eq!(dom.fill_alt_with(|_| Some("Image".to_string())), 2);
+    assert!(dom
+        .audit_alt()
+        .iter()
+        .all(|issue| !matches!(issue, AltIssue::Missing { .. })));
+}
+
+#[test]
+fn weight_report() {
+    let html = r#"<!DOCTYPE html><html><head><title>T</title><script>let a;</script></head><body><header><nav>Home</nav></header><main style="color: red"><p>Lorem</p></main><footer>(c)</footer><script src="a.js"></script></body></html>"#;
+    let report = parse(html).unwrap().weight_report();
+
+    assert_eq!(
+        report,
+        WeightReport {
+            total: html.len(),
+            head: "<head><title>T</title><script>let a;</script></head>".len(),
+            header: "<header><nav>Home</nav></header>".len(),
+            nav: 0,
+            main: r#"<main style="color: red"><p>Lorem</p></main>"#.len(),
+            aside: 0,
+            footer: "<footer>(c)</footer>".len(),
+            other: "<!DOCTYPE html><html><body><script src=\"a.js\"></script></body></html>".len(),
+            scripts: "<script>let a;</script><script src=\"a.js\"></script>".len(),
+            inline_css: r#"style="color: red""#.len(),
+        }
+    );
+}
+
+const HEAD: &str = r#"
+    <head>
+        <meta charset="utf-8">
+        <title>First</title>
+        <meta name="viewport" content="width=device-width">
+        <meta http-equiv="Content-Type" content="text/html; charset=ISO-8859-1">
+        <meta http-equiv="refresh" content="5; url=/next">
+        <title>Second</title>
+        <meta name="Viewport" content="width=1024">
+    </head>"#;
+
+#[test]
+fn lint_head() {
+    let issues = parse(HEAD).unwrap().lint_head();
+    assert_eq!(
+        issues,
+        vec![
+            HeadIssue::MultipleTitles(2),
+            HeadIssue::MultipleCharsets(vec!["utf-8".to_string(), "ISO-8859-1".to_string()]),
+            HeadIssue::MultipleViewports(2),
+            HeadIssue::MetaRefresh("5; url=/next".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn fix_head() {
+    let mut first = parse(HEAD).unwrap();
+    assert_eq!(first.fix_head(KeepPolicy::First).len(), 4);
+    assert_eq!(
+        first.lint_head(),
+        vec![HeadIssue::MetaRefresh("5; url=/next".to_string())]
+    );
+    assert_eq!(
+        first.query(&Selector::from("title")).unwrap().html(),
+        "<title>First</title>"
+    );
+
+    let mut last = parse(HEAD).unwrap();
+    last.fix_head(KeepPolicy::Last);
+    assert_eq!(
+        last.query(&Selector::from("title")).unwrap().html(),
+        "<title>Second</title>"
+    );
+    assert_eq!(last.query_all(&Selector::from("meta")).len(), 3);
+}
+
+#[test]
+fn fixed_widths() {
+    let html = r#"
+        <div style="width: 1200px !important">
+            <table style="min-width:800PX; width: 100%">
+                <td style="width: 50px">A</td>
+            </table>
+        </div>"#;
+    let widths = parse(html).unwrap().fixed_widths(320.0);
+    assert_eq!(
+        widths,
+        vec![FixedWidth {
+            tag: "div".to_string(),
+            property: "width".to_string(),
+            px: 1200.0,
+        },]
+    );
+}
+
+#[test]
+fn prune_css() {
+    let mut dom = parse(concat!(
+        "<style>/* base */ @import url(a.css); .card, .gone > p { margin: 0 }",
+        r#"@media (max-width: 600px) { .gone { display: none } } .md\:flex:not(.x) { display: flex }"#,
+        r#"#main a[href=".pdf"] { color: red } @font-face { font-family: A }</style>"#,
+        r#"<main id="main"><div class="card md:flex extra"><a class="extra">A</a><p class="gone-not">B</p></div></main>"#,
+    ))
+    .unwrap();
+    let pruned = dom.prune_css();
+
+    assert_eq!(
+        pruned,
+        PrunedCss {
+            classes: vec!["extra".to_string(), "gone-not".to_string()],
+            selectors: vec![".gone > p".to_string(), ".gone".to_string()],
+        }
+    );
+    assert_eq!(
+        dom.html(),
+        concat!(
+            "<style>@import url(a.css);.card{ margin: 0 }",
+            r#".md\:flex:not(.x){ display: flex }#main a[href=".pdf"]{ color: red }"#,
+            "@font-face{ font-family: A }</style>",
+            r#"<main id="main"><div class="card md:flex"><a>A</a><p>B</p></div></main>"#,
+        )
+    );
+
+    let mut unchanged = parse("<style>p { color: red }</style><p>A</p>").unwrap();
+    assert_eq!(unchanged.prune_css(), PrunedCss::default());
+    assert_eq!(unchanged.html(), "<style>p { color: red }</style><p>A</p>");
+}
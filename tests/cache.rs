@@ -0,0 +1,40 @@
+#![cfg(feature = "cache")]
+
+use std::sync::Arc;
+
+use html_editor::operation::*;
+use html_editor::{ParseOptions, TemplateCache, UnknownTagPolicy};
+
+#[test]
+fn cache_shares_parsed_nodes() {
+    let cache = TemplateCache::new();
+    let first = cache.get("<ul><li>1</li></ul>").unwrap();
+    let second = cache.get("<ul><li>1</li></ul>").unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+
+    let other = cache.get("<ul><li>2</li></ul>").unwrap();
+    assert!(!Arc::ptr_eq(&first, &other));
+    assert_eq!(cache.len(), 2);
+
+    let mut edited = second;
+    Arc::make_mut(&mut edited).remove_by(&Selector::from("li"));
+    assert_eq!(edited.html(), "<ul></ul>");
+    assert_eq!(first.html(), "<ul><li>1</li></ul>");
+
+    cache.clear();
+    assert!(cache.is_empty());
+    assert!(!Arc::ptr_eq(
+        &first,
+        &cache.get("<ul><li>1</li></ul>").unwrap()
+    ));
+}
+
+#[test]
+fn cache_skips_failed_templates() {
+    let cache = TemplateCache::with_options(ParseOptions {
+        unknown_tags: UnknownTagPolicy::Error,
+        ..Default::default()
+    });
+    assert!(cache.get("<blink></blink>").is_err());
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,245 @@
+use html_editor::operation::*;
+use html_editor::{parse, Element, Node};
+
+#[test]
+fn split_and_assemble() {
+    let html = concat!(
+        "<body><header><nav>Menu</nav></header>",
+        r#"<article class="card"><h2>A</h2></article><article class="card"><h2>B</h2></article>"#,
+        "<footer>End</footer></body>",
+    );
+    let mut dom = parse(html).unwrap();
+    let fragments = dom.split_components(&[
+        ("header", Selector::from("header")),
+        ("nav", Selector::from("nav")),
+        ("card", Selector::from(".card")),
+    ]);
+
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<body><include src="header"></include><include src="card"></include>"#,
+            r#"<include src="card-2"></include><footer>End</footer></body>"#,
+        )
+    );
+    let mut names: Vec<&String> = fragments.keys().collect();
+    names.sort();
+    assert_eq!(names, vec!["card", "card-2", "header"]);
+    assert_eq!(
+        fragments["card-2"].html(),
+        r#"<article class="card"><h2>B</h2></article>"#
+    );
+
+    dom.assemble(&fragments);
+    assert_eq!(dom.html(), html);
+}
+
+#[test]
+fn assemble_keeps_unknown_markers() {
+    let mut dom = parse(r#"<include src="missing"></include>"#).unwrap();
+    dom.assemble(&Default::default());
+    assert_eq!(dom.html(), r#"<include src="missing"></include>"#);
+}
+
+#[test]
+fn resolve_includes() {
+    let mut dom = parse(concat!(
+        r#"<body><include src="header"></include><main>"#,
+        r#"<include src="card"></include><include src="card"></include></main></body>"#,
+    ))
+    .unwrap();
+    let mut calls = Vec::new();
+    let count = dom
+        .resolve_includes(2, |src| {
+            calls.push(src.to_string());
+            match src {
+                "header" => Some(r#"<header><include src="nav"></include></header>"#.to_string()),
+                "nav" => Some("<nav>Menu</nav>".to_string()),
+                "card" => Some("<p>A</p><p>B</p>".to_string()),
+                _ => None,
+            }
+        })
+        .unwrap();
+
+    assert_eq!(count, 4);
+    assert_eq!(calls, vec!["header", "nav", "card", "card"]);
+    assert_eq!(
+        dom.html(),
+        "<body><header><nav>Menu</nav></header><main><p>A</p><p>B</p><p>A</p><p>B</p></main></body>"
+    );
+}
+
+#[test]
+fn resolve_includes_errors() {
+    let resolver = |src: &str| match src {
+        "a" => Some(r#"<include src="b"></include>"#.to_string()),
+        "b" => Some(r#"<div><include src="c"></include></div>"#.to_string()),
+        _ => None,
+    };
+    let chain = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+
+    let mut dom = parse(r#"<include src="a"></include>"#).unwrap();
+    assert_eq!(
+        dom.resolve_includes(8, resolver),
+        Err(IncludeError::NotFound(chain(&["a", "b", "c"])))
+    );
+    let mut dom = parse(r#"<include src="a"></include>"#).unwrap();
+    assert_eq!(
+        dom.resolve_includes(1, resolver),
+        Err(IncludeError::TooDeep(chain(&["a", "b"])))
+    );
+}
+
+#[test]
+fn merge_documents_dedupes_head() {
+    let documents = vec![
+        parse(concat!(
+            r#"<!DOCTYPE html><html lang="en"><head><meta charset="utf-8"><title>Report</title>"#,
+            r#"<link rel="stylesheet" href="a.css"><script src="chart.js"></script></head>"#,
+            r#"<body class="report"><h1>One</h1></body></html>"#,
+        ))
+        .unwrap(),
+        parse(concat!(
+            r#"<html><head><meta charset="UTF-8"><title>Other</title>"#,
+            r#"<link rel="stylesheet" href="a.css"><link rel="stylesheet" href="b.css">"#,
+            r#"<script src="chart.js"></script></head><body><h1>Two</h1></body></html>"#,
+        ))
+        .unwrap(),
+        parse("<p>Three</p>").unwrap(),
+    ];
+
+    assert_eq!(
+        merge_documents(documents, &MergeOptions::default()).html(),
+        concat!(
+            r#"<!DOCTYPE html><html lang="en"><head><meta charset="utf-8"><title>Report</title>"#,
+            r#"<link rel="stylesheet" href="a.css"><script src="chart.js"></script>"#,
+            r#"<link rel="stylesheet" href="b.css"></head>"#,
+            r#"<body class="report"><h1>One</h1><h1>Two</h1><p>Three</p></body></html>"#,
+        )
+    );
+}
+
+#[test]
+fn merge_documents_titles() {
+    let documents = || {
+        vec![
+            parse("<title>A</title><p>1</p>").unwrap(),
+            parse("<title>B</title><p>2</p>").unwrap(),
+            parse("<title>A</title><p>3</p>").unwrap(),
+        ]
+    };
+    let merged = |title| {
+        let options = MergeOptions {
+            separator: vec![],
+            title,
+        };
+        merge_documents(documents(), &options)
+            .query(&Selector::from("title"))
+            .unwrap()
+            .html()
+    };
+
+    assert_eq!(merged(TitlePolicy::First), "<title>A</title>");
+    assert_eq!(merged(TitlePolicy::Last), "<title>A</title>");
+    assert_eq!(
+        merged(TitlePolicy::Join(", ".to_string())),
+        "<title>A, B</title>"
+    );
+}
+
+#[test]
+fn merge_documents_separator() {
+    let documents = vec![parse("<p>1</p>").unwrap(), parse("<p>2</p>").unwrap()];
+    let options = MergeOptions {
+        separator: parse("<hr><!-- page -->").unwrap(),
+        ..MergeOptions::default()
+    };
+
+    assert_eq!(
+        merge_documents(documents, &options).html(),
+        "<html><head></head><body><p>1</p><hr><!-- page --><p>2</p></body></html>"
+    );
+    assert_eq!(
+        merge_documents(vec![], &MergeOptions::default()).html(),
+        "<html><head></head><body></body></html>"
+    );
+}
+
+#[test]
+fn generate_placeholders() {
+    let html = concat!(
+        r#"<section><div data-generate="chart" data-value="3"></div>"#,
+        r#"<div data-generate="qr" data-value="a"></div><div data-generate="qr" data-value="b"></div>"#,
+        r#"<div data-generate="unknown"></div></section>"#,
+    );
+    let mut dom = parse(html).unwrap();
+    let mut names = Vec::new();
+    let count = dom.generate_placeholders(
+        |kind, placeholder| {
+            let value = placeholder
+                .attrs
+                .iter()
+                .find(|(name, _)| name == "data-value")
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default();
+            match kind {
+                "chart" => Some(Generated::Nodes(vec![
+                    Node::Text(value.clone()),
+                    Node::Text("%".to_string()),
+                ])),
+                "qr" => Some(Generated::Asset {
+                    asset: Asset {
+                        name: format!("qr-{}.svg", value),
+                        mime: "image/svg+xml".to_string(),
+                        data: vec![],
+                    },
+                    element: Element::new("img", vec![], vec![]),
+                }),
+                _ => None,
+            }
+        },
+        |asset| {
+            names.push(asset.name.clone());
+            format!("/{}", asset.name)
+        },
+    );
+
+    assert_eq!(count, 3);
+    assert_eq!(names, vec!["qr-a.svg", "qr-b.svg"]);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<section>3%<img src="/qr-a.svg"><img src="/qr-b.svg">"#,
+            r#"<div data-generate="unknown"></div></section>"#,
+        )
+    );
+}
+
+#[test]
+fn split_components_contextual_selector() {
+    let mut dom =
+        parse(r#"<main><div class="card">A</div></main><div class="card">B</div>"#).unwrap();
+    let fragments = dom.split_components(&[("card", Selector::from("main > .card"))]);
+
+    assert_eq!(fragments.len(), 1);
+    assert_eq!(fragments["card"].html(), r#"<div class="card">A</div>"#);
+    assert_eq!(
+        dom.html(),
+        r#"<main><include src="card"></include></main><div class="card">B</div>"#
+    );
+}
+
+#[test]
+fn frozen_components() {
+    let embed = r#"<div class="embed"><nav>N</nav><include src="x"></include></div>"#;
+    let mut dom = parse(&format!("{}<nav>M</nav>", embed)).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    let mut fragments = dom.split_components(&[("nav", Selector::from("nav"))]);
+    assert_eq!(fragments.len(), 1);
+    assert_eq!(fragments["nav"].html(), "<nav>M</nav>");
+
+    fragments.insert("x".to_string(), vec![Node::Text("X".to_string())]);
+    dom.assemble(&fragments);
+    assert_eq!(dom.html(), format!("{}<nav>M</nav>", embed));
+}
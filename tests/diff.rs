@@ -0,0 +1,123 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+fn round_trip(old: &str, new: &str) -> Vec<Patch> {
+    let mut old = parse(old).unwrap();
+    let new = parse(new).unwrap();
+    let patches = diff(&old, &new);
+    apply_patch(&mut old, &patches).unwrap();
+    assert_eq!(old.html(), new.html());
+    patches
+}
+
+#[test]
+fn diff_identical() {
+    let html = "<div><p>Same</p><!-- comment --></div>";
+    assert!(round_trip(html, html).is_empty());
+}
+
+#[test]
+fn diff_attributes() {
+    let patches = round_trip(
+        r#"<div><section><p id="a">Text</p></section></div>"#,
+        r#"<div><section><p class="b">Text</p></section></div>"#,
+    );
+    assert_eq!(patches.len(), 2);
+    assert!(matches!(
+        &patches[0],
+        Patch::SetAttr { path, name, value } if *path == [0, 0, 0] && name == "class" && value == "b"
+    ));
+    assert!(matches!(
+        &patches[1],
+        Patch::RemoveAttr { path, name } if *path == [0, 0, 0] && name == "id"
+    ));
+}
+
+#[test]
+fn diff_moves() {
+    let patches = round_trip(
+        "<ol><li>One</li><li>Two</li><li>Three</li><li>Four</li></ol>",
+        "<ol><li>Four</li><li>One</li><li>Three</li><li>Two</li></ol>",
+    );
+    assert!(patches
+        .iter()
+        .all(|patch| matches!(patch, Patch::Move { .. })));
+    assert_eq!(patches.len(), 2);
+}
+
+#[test]
+fn diff_mixed() {
+    round_trip(
+        "<h1>Title</h1><p>Intro</p><ul><li>A</li><li>B</li></ul><footer>End</footer>",
+        "<ul><li>B</li><li>C</li></ul><h2>Title</h2><p>Intro, edited</p><span>New</span>",
+    );
+}
+
+#[test]
+fn apply_patch_fails_on_mismatch() {
+    let mut dom = parse("<p>Text</p>").unwrap();
+    let patch = Patch::SetText {
+        path: vec![0],
+        text: "Other".to_string(),
+    };
+    assert!(apply_patch(&mut dom, &[patch]).is_err());
+}
+
+#[test]
+fn patches_to_json_escapes() {
+    let patches = vec![
+        Patch::SetText {
+            path: vec![1, 0],
+            text: "Say \"hi\"\\\n".to_string(),
+        },
+        Patch::Insert {
+            path: vec![2],
+            node: html_editor::Node::new_element("br", vec![], vec![]),
+        },
+        Patch::Move {
+            from: vec![3],
+            to: vec![0],
+        },
+        Patch::Remove { path: vec![4] },
+    ];
+    assert_eq!(
+        patches_to_json(&patches),
+        concat!(
+            r#"{"version":1,"patches":["#,
+            r#"{"op":"setText","path":[1,0],"text":"Say \"hi\"\\\n"},"#,
+            r#"{"op":"insert","path":[2],"html":"<br>"},"#,
+            r#"{"op":"move","from":[3],"to":[0]},"#,
+            r#"{"op":"remove","path":[4]}]}"#,
+        )
+    );
+}
+
+#[test]
+fn stats_delta_summary() {
+    let old = parse("<div><p>A</p><p>B</p><!--note--></div><img src=a.png>").unwrap();
+    let new = parse("<div><p>A</p><span>B</span></div><img src=a.png><img src=b.png>").unwrap();
+    let delta = stats_delta(&old, &new);
+
+    assert_eq!(delta.added.get("span"), Some(&1));
+    assert_eq!(delta.added.get("img"), Some(&1));
+    assert_eq!(delta.removed.get("p"), Some(&1));
+    assert_eq!(delta.removed.len(), 1);
+    assert_eq!(delta.scripts_removed(), 0);
+    assert_eq!(
+        delta.bytes_saved(),
+        stats(&old).bytes as isize - stats(&new).bytes as isize
+    );
+    assert!(delta.bytes_saved() < 0);
+    assert_eq!(stats(&old).comments - stats(&new).comments, 1);
+
+    let delta = stats_delta(&old, &old);
+    assert_eq!(
+        delta,
+        StatsDelta {
+            old_bytes: 55,
+            new_bytes: 55,
+            ..Default::default()
+        }
+    );
+    assert_eq!(delta.to_string(), "55 -> 55 bytes");
+}
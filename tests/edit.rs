@@ -84,7 +84,311 @@ fn replace() {
                 children: vec![Node::Text(new_text)],
             });
             Ok(node)
-        }).unwrap()
+        })
+        .unwrap()
         .html();
     assert_eq!(html, REPLACED_HTML);
 }
+
+#[test]
+fn edit_by_descendant() {
+    let html = "<nav><ul><li>A</li></ul></nav><ul><li>B</li></ul>";
+    let selector = Selector::from("nav li");
+
+    let mut dom = parse(html).unwrap();
+    dom.insert_to(&selector, Node::Text("!".to_string()));
+    assert_eq!(
+        dom.html(),
+        "<nav><ul><li>A!</li></ul></nav><ul><li>B</li></ul>"
+    );
+
+    let mut dom = parse(html).unwrap();
+    dom.remove_by(&selector);
+    assert_eq!(dom.html(), "<nav><ul></ul></nav><ul><li>B</li></ul>");
+
+    let mut dom = parse(html).unwrap();
+    dom.execute_for(&Selector::from("ul li"), |li| li.children.clear());
+    assert_eq!(
+        dom.html(),
+        "<nav><ul><li></li></ul></nav><ul><li></li></ul>"
+    );
+
+    let mut dom = parse(html).unwrap();
+    dom.replace_with(&selector, &|_| Ok(Node::Comment("li".to_string())))
+        .unwrap();
+    assert_eq!(
+        dom.html(),
+        "<nav><ul><!--li--></ul></nav><ul><li>B</li></ul>"
+    );
+
+    let mut dom = parse(html).unwrap();
+    dom.query_mut(&selector).unwrap().children.clear();
+    assert_eq!(
+        dom.html(),
+        "<nav><ul><li></li></ul></nav><ul><li>B</li></ul>"
+    );
+}
+
+#[test]
+fn remove_by_child() {
+    let mut dom = parse("<div><p>A</p><section><p>B</p></section></div>").unwrap();
+    dom.remove_by(&Selector::from("div > p"));
+    assert_eq!(dom.html(), "<div><section><p>B</p></section></div>");
+}
+
+#[test]
+fn remove_by_next_sibling() {
+    let mut dom = parse("<ul><li>A</li><li>B</li><li>C</li></ul>").unwrap();
+    dom.remove_by(&Selector::from("li + li"));
+    assert_eq!(dom.html(), "<ul><li>A</li></ul>");
+}
+
+#[test]
+fn replace_with_only_text() {
+    let replace = |html: &str| {
+        let mut dom = parse(html).unwrap();
+        let result = dom
+            .replace_with(&Selector::from("p"), &|p| {
+                Ok(Node::Text(p.only_text()?.to_uppercase()))
+            })
+            .map(|dom| dom.html());
+        result
+    };
+
+    assert_eq!(replace("<p>a</p><p></p>").unwrap(), "A");
+    assert!(replace("<p><b>a</b></p>").is_err());
+}
+
+#[test]
+fn remove_by_attribute() {
+    let mut dom = parse(r#"<a href="https://x.org">X</a><a href="/y">Y</a>"#).unwrap();
+    dom.remove_by(&Selector::from("a[href^='https://']"));
+    assert_eq!(dom.html(), r#"<a href="/y">Y</a>"#);
+}
+
+#[test]
+fn edit_by_not() {
+    let mut dom = parse(r#"<div class="ad"></div><div class="post"></div>"#).unwrap();
+    dom.execute_for(&Selector::from("div:not(.ad)"), |div| {
+        div.attrs.push(("id".to_string(), "x".to_string()))
+    });
+    dom.remove_by(&Selector::from("div:not(#x)"));
+    assert_eq!(dom.html(), r#"<div class="post" id="x"></div>"#);
+}
+
+#[test]
+fn execute_for_first_child() {
+    let mut dom = parse("<ul><li>A</li><li>B</li></ul>").unwrap();
+    dom.execute_for(&Selector::from("li:first-child"), |li| {
+        li.attrs.push(("class".to_string(), "first".to_string()))
+    });
+    assert_eq!(dom.html(), r#"<ul><li class="first">A</li><li>B</li></ul>"#);
+}
+
+#[test]
+fn execute_for_nth_child() {
+    let mut dom = parse("<table><tr></tr><tr></tr><tr></tr></table>").unwrap();
+    dom.execute_for(&Selector::from("tr:nth-child(even)"), |tr| {
+        tr.attrs.push(("class".to_string(), "striped".to_string()))
+    });
+    assert_eq!(
+        dom.html(),
+        r#"<table><tr></tr><tr class="striped"></tr><tr></tr></table>"#
+    );
+}
+
+#[test]
+fn remove_by_empty() {
+    let html = "<p></p><p> </p><p><!-- x --></p><p><br></p><p>Text</p>";
+
+    let mut dom = parse(html).unwrap();
+    dom.remove_by(&Selector::from("p:empty"));
+    assert_eq!(dom.html(), "<p> </p><p><br></p><p>Text</p>");
+
+    let mut dom = parse(html).unwrap();
+    dom.remove_by(&Selector::from("p:blank"));
+    assert_eq!(dom.html(), "<p><br></p><p>Text</p>");
+
+    let mut dom = parse(html).unwrap();
+    dom.remove_by(&Selector::from(":empty"));
+    assert_eq!(dom.html(), "<p> </p><p></p><p>Text</p>");
+}
+
+#[test]
+fn remove_by_has() {
+    let mut dom = parse(r#"<div><p class="ad">Ad</p></div><div><p>Text</p></div>"#).unwrap();
+    dom.remove_by(&Selector::from("div:has(> .ad)"));
+    assert_eq!(dom.html(), "<div><p>Text</p></div>");
+}
+
+#[test]
+fn freeze() {
+    let html = r#"<div id="embed"> <p class="ad">Partner</p> </div> <p class="ad">Ad</p>"#;
+    let mut dom = parse(html).unwrap();
+    dom.freeze(&Selector::from("#embed"))
+        .trim()
+        .remove_by(&Selector::from(".ad"))
+        .insert_to(&Selector::from("div"), Node::Text("!".to_string()))
+        .execute_for(&Selector::from("p"), |p| p.children.clear());
+    let replaced = dom
+        .replace_with(&Selector::from("p"), &|_| Ok(Node::Comment(String::new())))
+        .unwrap();
+    assert_eq!(
+        replaced.html(),
+        r#"<div id="embed"> <p class="ad">Partner</p> </div>"#
+    );
+
+    let mut embed = dom[0].as_element().unwrap().clone();
+    embed.remove_by(&Selector::from("p"));
+    assert_eq!(embed.children.len(), 3);
+
+    embed.attrs.retain(|(name, _)| name != FROZEN_ATTR);
+    embed.remove_by(&Selector::from("p"));
+    assert_eq!(embed.html(), r#"<div id="embed">  </div>"#);
+}
+
+#[test]
+fn remove_by_selector_list() {
+    let html = "<script>a()</script><p>Text<style>p{}</style></p><noscript>No JS</noscript>";
+    let mut dom = parse(html).unwrap();
+    dom.remove_by(&Selector::from("script,style ,\n noscript"));
+    assert_eq!(dom.html(), "<p>Text</p>");
+}
+
+#[test]
+fn universal_selector() {
+    let mut dom = parse(r#"<div data-x="1"><p>A</p><span data-x="2">B</span></div>"#).unwrap();
+    dom.execute_for(&Selector::from("*"), |el| {
+        el.attrs.push(("class".to_string(), "all".to_string()))
+    });
+    dom.remove_by(&Selector::from("div *[data-x]"));
+    assert_eq!(
+        dom.html(),
+        r#"<div data-x="1" class="all"><p class="all">A</p></div>"#
+    );
+}
+
+#[test]
+fn truncate_attr() {
+    let html = r#"<meta name="description" content="Ünïcödé 😀 text"><p title="a &hellip;&#8230; b">Long</p>"#;
+    let truncated = |selector: &str, name: &str, max_len| {
+        parse(html)
+            .unwrap()
+            .truncate_attr(&Selector::from(selector), name, max_len)
+            .html()
+    };
+
+    assert_eq!(
+        truncated("meta", "content", 9),
+        r#"<meta name="description" content="Ünïcödé…"><p title="a &hellip;&#8230; b">Long</p>"#
+    );
+    assert_eq!(
+        truncated("meta", "content", 10),
+        r#"<meta name="description" content="Ünïcödé 😀…"><p title="a &hellip;&#8230; b">Long</p>"#
+    );
+    assert_eq!(truncated("meta", "content", 14), html);
+    assert_eq!(
+        truncated("p", "TITLE", 4),
+        r#"<meta name="description" content="Ünïcödé 😀 text"><p title="a &hellip;…">Long</p>"#
+    );
+    assert_eq!(
+        truncated("p", "title", 1),
+        r#"<meta name="description" content="Ünïcödé 😀 text"><p title="…">Long</p>"#
+    );
+    assert_eq!(truncated("div", "title", 1), html);
+}
+
+#[test]
+fn pipe() {
+    let html = r#"<div class="post"><p class="ad">Buy</p><img src="a.png" class="lazy"><p>Text</p></div><p>Out</p>"#;
+    let mut dom = parse(html).unwrap();
+    let mut visited = 0;
+    dom.pipe()
+        .add_class("p", "text")
+        .remove(".ad")
+        .add_class("img", "lazy")
+        .set_attr("div > p", "data-in", "1")
+        .insert_end(".post", Node::Text("!".to_string()))
+        .for_each("p", |_| visited += 1)
+        .run()
+        .unwrap();
+    assert_eq!(visited, 2);
+    assert_eq!(
+        dom.html(),
+        r#"<div class="post"><img src="a.png" class="lazy"><p class="text" data-in="1">Text</p>!</div><p class="text">Out</p>"#
+    );
+
+    let mut div = dom[0].as_element().unwrap().clone();
+    div.pipe().remove("div").remove("img").run().unwrap();
+    assert_eq!(
+        div.html(),
+        r#"<div class="post"><p class="text" data-in="1">Text</p>!</div>"#
+    );
+
+    let before = dom.html();
+    let error = dom
+        .pipe()
+        .remove("p")
+        .add_class("p[", "x")
+        .run()
+        .unwrap_err();
+    assert_eq!(error.position, 2);
+    assert_eq!(dom.html(), before);
+
+    dom.freeze(&Selector::from(".post"));
+    dom.pipe().remove("p").run().unwrap();
+    assert_eq!(
+        dom.html(),
+        r#"<div class="post"><img src="a.png" class="lazy"><p class="text" data-in="1">Text</p>!</div>"#
+    );
+}
+
+#[test]
+fn pipe_report() {
+    let html = r#"<html><body><div><p>One</p><p>Two</p></div><p>Three</p></body></html>"#;
+    let mut dom = parse(html).unwrap();
+    let report = dom
+        .pipe()
+        .add_class("p", "text")
+        .remove("div > p")
+        .insert_end("body", Node::Comment("end".to_string()))
+        .set_attr("section", "hidden", "")
+        .run()
+        .unwrap();
+    assert_eq!(report.matched, vec![3, 2, 1, 0]);
+    assert_eq!(report.unmatched().collect::<Vec<_>>(), vec![3]);
+
+    // The element of the pipe is edited but not removed.
+    let mut body = dom[0].as_element().unwrap().children[0]
+        .as_element()
+        .unwrap()
+        .clone();
+    let report = body
+        .pipe()
+        .remove("body")
+        .add_class("body", "x")
+        .run()
+        .unwrap();
+    assert_eq!(report.matched, vec![0, 1]);
+
+    dom.freeze(&Selector::from("body"));
+    let report = dom.pipe().remove("p").run().unwrap();
+    assert_eq!(report.matched, vec![0]);
+}
+
+#[test]
+fn pipe_empty_after_edits() {
+    // `:empty` sees the children left by the previous edits.
+    let mut dom = parse(r#"<div><p class="ad">Buy</p></div><div><p>Text</p></div>"#).unwrap();
+    let report = dom
+        .pipe()
+        .remove(".ad")
+        .add_class("div:empty", "empty")
+        .run()
+        .unwrap();
+    assert_eq!(report.matched, vec![1, 1]);
+    assert_eq!(
+        dom.html(),
+        r#"<div class="empty"></div><div><p>Text</p></div>"#
+    );
+}
@@ -0,0 +1,43 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn obfuscate_emails() {
+    let html = concat!(
+        r#"<p>Mail <a href="MAILTO:info@example.com?subject=Hi&amp;body=x">info@example.com</a>, "#,
+        "or jane.doe+news@mail.example.org. Not @handle, a@b or x@y.c.</p>",
+        "<code>root@example.com</code>",
+    );
+    let mut dom = parse(html).unwrap();
+
+    assert_eq!(dom.obfuscate_emails(EmailObfuscation::Entities), 3);
+    let output = dom.html();
+    assert!(!output.contains("info@"));
+    assert!(!output.contains("jane.doe"));
+    assert!(output.contains("<code>root@example.com</code>"));
+    assert_eq!(dom.obfuscate_emails(EmailObfuscation::Entities), 0);
+
+    assert_eq!(dom.reveal_emails(), 3);
+    assert_eq!(dom.html(), html.replace("MAILTO:", "mailto:"));
+
+    let mut dom = parse("<p>Mail info@example.com.</p>").unwrap();
+    dom.obfuscate_emails(EmailObfuscation::Reversed);
+    assert_eq!(
+        dom.html(),
+        r#"<p>Mail <span data-obfuscated-email="reversed" style="unicode-bidi:bidi-override;direction:rtl">moc.elpmaxe@ofni</span>.</p>"#
+    );
+    dom.reveal_emails();
+    assert_eq!(dom.html(), "<p>Mail info@example.com.</p>");
+}
+
+#[test]
+fn frozen_emails() {
+    let embed = r#"<div class="embed"><a href="mailto:a@b.io">a@b.io</a></div>"#;
+    let mut dom = parse(&format!("{}<p>c@d.io</p>", embed)).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    assert_eq!(dom.obfuscate_emails(EmailObfuscation::Reversed), 1);
+    assert!(dom.html().starts_with(embed));
+    assert_eq!(dom.reveal_emails(), 1);
+    assert_eq!(dom.html(), format!("{}<p>c@d.io</p>", embed));
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+
+use html_editor::error::ErrorDetail;
+
+#[derive(Debug)]
+struct Outer(std::num::ParseIntError);
+
+impl fmt::Display for Outer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bad attribute")
+    }
+}
+
+impl std::error::Error for Outer {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[test]
+fn error_chain() {
+    let error = ErrorDetail::from(Outer("x".parse::<u8>().unwrap_err()))
+        .with_context("while reading <td colspan>")
+        .with_context("while replacing <table>");
+    let line = line!() - 3;
+
+    assert_eq!(
+        error.context().collect::<Vec<_>>(),
+        vec!["while replacing <table>", "while reading <td colspan>"]
+    );
+    assert_eq!(error.source().unwrap().to_string(), "bad attribute");
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "while replacing <table>: while reading <td colspan>: Error at tests/error.rs:{}:17: bad attribute: invalid digit found in string",
+            line
+        )
+    );
+    assert_eq!(
+        format!("{:?}", error),
+        format!(
+            "while replacing <table> (at tests/error.rs:{}:10)\nwhile reading <td colspan> (at tests/error.rs:{}:10)\nError at tests/error.rs:{}:17\n\nCaused by:\n    0: bad attribute\n    1: invalid digit found in string",
+            line + 2,
+            line + 1,
+            line
+        )
+    );
+}
+
+#[derive(Debug)]
+struct Shared(std::rc::Rc<str>);
+
+impl fmt::Display for Shared {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Shared {}
+
+#[test]
+fn error_from_non_send_source() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let error = ErrorDetail::from(Shared("not shared across threads".into()));
+    assert_send_sync(&error);
+    assert_eq!(
+        error.source().unwrap().to_string(),
+        "not shared across threads"
+    );
+}
+
+#[test]
+fn error_without_source() {
+    let error = ErrorDetail::new();
+    assert!(error.source().is_none());
+    assert_eq!(
+        format!("{:?}", error),
+        format!("Error at tests/error.rs:{}:17", line!() - 4)
+    );
+}
@@ -0,0 +1,67 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn collect_footnotes() {
+    let mut dom = parse(
+        "<html><body><p>A<aside>First</aside> B<aside>Second <em>note</em></aside></p></body></html>",
+    )
+    .unwrap();
+    let selector = Selector::from("aside");
+    assert_eq!(dom.collect_footnotes(&selector), 2);
+
+    let body = dom.query_mut(&Selector::from("body")).unwrap();
+    body.children
+        .insert(0, parse("<p>C<aside>Third</aside></p>").unwrap().remove(0));
+    assert_eq!(dom.collect_footnotes(&selector), 1);
+    assert_eq!(dom.collect_footnotes(&selector), 0);
+
+    let notes = dom.query_all(&Selector::from("li"));
+    let ids: Vec<_> = notes
+        .iter()
+        .map(|li| li.attrs.iter().find(|(k, _)| k == "id").unwrap().1.as_str())
+        .collect();
+    assert_eq!(ids, vec!["fn-1", "fn-2", "fn-3"]);
+    assert_eq!(
+        dom.query(&Selector::from("section")).unwrap().html(),
+        concat!(
+            r##"<section class="footnotes" role="doc-endnotes"><ol>"##,
+            r##"<li id="fn-1">First <a href="#fnref-1" role="doc-backlink">↩</a></li>"##,
+            r##"<li id="fn-2">Second <em>note</em> <a href="#fnref-2" role="doc-backlink">↩</a></li>"##,
+            r##"<li id="fn-3">Third <a href="#fnref-3" role="doc-backlink">↩</a></li>"##,
+            r##"</ol></section>"##,
+        )
+    );
+    assert!(dom.html().starts_with(
+        r##"<html><body><p>C<sup id="fnref-3"><a href="#fn-3" role="doc-noteref">3</a></sup></p>"##
+    ));
+}
+
+#[test]
+fn collect_footnotes_contextual_selector() {
+    let selector = Selector::from(".c .fn");
+    let mut dom =
+        parse(r#"<div class="c"><span class="fn">A</span></div><span class="fn">B</span>"#)
+            .unwrap();
+    assert_eq!(dom.collect_footnotes(&selector), 1);
+    assert!(dom.html().contains(r#"<span class="fn">B</span>"#));
+
+    let mut div = parse(r#"<div class="c"><p><span class="fn">A</span></p></div>"#)
+        .unwrap()
+        .remove(0);
+    assert_eq!(
+        div.as_element_mut().unwrap().collect_footnotes(&selector),
+        1
+    );
+}
+
+#[test]
+fn frozen_footnotes() {
+    let embed = r#"<div class="embed"><span class="fn">A</span></div>"#;
+    let mut dom = parse(&format!(r#"{}<p><span class="fn">B</span></p>"#, embed)).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    assert_eq!(dom.collect_footnotes(&Selector::from(".fn")), 1);
+    assert!(dom.html().starts_with(embed));
+    assert!(dom.html().contains(r#"<li id="fn-1">B "#));
+}
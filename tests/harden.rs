@@ -0,0 +1,253 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn strip_event_handlers() {
+    let mut dom = parse(
+        r#"<div onload="init()"><img src=" JaVa&#x09;script:x"><img src="java	script:alert(1)"><a href="/safe">Ok</a><p title="javascript: a tutorial">T</p></div>"#,
+    )
+    .unwrap();
+    let removed = dom.strip_event_handlers();
+
+    assert_eq!(
+        dom.html(),
+        r#"<div><img><img><a href="/safe">Ok</a><p title="javascript: a tutorial">T</p></div>"#
+    );
+    assert_eq!(
+        removed,
+        vec![
+            RemovedAttr {
+                tag: "div".to_string(),
+                name: "onload".to_string(),
+                value: "init()".to_string(),
+            },
+            RemovedAttr {
+                tag: "img".to_string(),
+                name: "src".to_string(),
+                value: " JaVa&#x09;script:x".to_string(),
+            },
+            RemovedAttr {
+                tag: "img".to_string(),
+                name: "src".to_string(),
+                value: "java\tscript:alert(1)".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn strip_event_handlers_on_element() {
+    let mut dom = parse(r#"<button ONCLICK="go()">Go</button>"#).unwrap();
+    let button = dom.query_mut(&Selector::from("button")).unwrap();
+    assert_eq!(button.strip_event_handlers().len(), 1);
+    assert_eq!(button.html(), "<button>Go</button>");
+}
+
+#[test]
+fn harden_iframes_never_loosens() {
+    let policy = IframePolicy {
+        sandbox: vec!["allow-scripts".to_string(), "allow-forms".to_string()],
+        referrer_policy: Some("no-referrer".to_string()),
+        lazy: true,
+        allow: vec!["fullscreen".to_string()],
+    };
+    let mut dom = parse(
+        r#"<iframe sandbox="allow-scripts allow-same-origin"></iframe><iframe allow="camera; fullscreen 'self'"></iframe>"#,
+    )
+    .unwrap();
+    let changed = dom.harden_iframes(&policy);
+
+    let frames = dom.query_all(&Selector::from("iframe"));
+    let attr = |i: usize, name: &str| {
+        frames[i]
+            .attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+    assert_eq!(attr(0, "sandbox"), Some("allow-scripts"));
+    assert_eq!(attr(0, "loading"), Some("lazy"));
+    assert_eq!(attr(1, "sandbox"), Some("allow-scripts allow-forms"));
+    assert_eq!(attr(1, "allow"), Some("fullscreen 'self'"));
+    assert_eq!(changed.len(), 2);
+    assert!(changed[1].changed.contains(&"allow".to_string()));
+
+    // Hardening again changes nothing.
+    assert!(dom.harden_iframes(&policy).is_empty());
+}
+
+#[test]
+fn harden_links_by_origin() {
+    let policy = LinkPolicy {
+        origins: vec!["https://Example.com:443".to_string()],
+        target_blank: false,
+    };
+    let mut dom = parse(
+        r#"<a href="/about">1</a><a href="https://example.com/x">2</a><a href="http://example.com">3</a><a href="//cdn.example.net">4</a><a href="mailto:me@example.com">5</a><a rel="nofollow">6</a>"#,
+    )
+    .unwrap();
+    assert_eq!(dom.harden_links(&policy), 2);
+
+    let rels: Vec<_> = dom
+        .query_all(&Selector::from("a"))
+        .into_iter()
+        .map(|a| {
+            a.attrs
+                .iter()
+                .find(|(k, _)| k == "rel")
+                .map(|(_, v)| v.as_str())
+        })
+        .collect();
+    assert_eq!(
+        rels,
+        vec![
+            None,
+            None,
+            Some("noopener noreferrer"),
+            Some("noopener noreferrer"),
+            None,
+            Some("nofollow"),
+        ]
+    );
+
+    // Already hardened links are not counted again.
+    assert_eq!(dom.harden_links(&policy), 0);
+}
+
+#[test]
+fn trackers() {
+    let html = r#"
+        <head>
+            <script async src="https://www.googletagmanager.com/gtag/js?id=G-1"></script>
+            <script>window.dataLayer = []; gtag('js', new Date());</script>
+            <script src="/app.js"></script>
+        </head>
+        <body>
+            <noscript><img src="https://www.facebook.com/tr?id=1"></noscript>
+            <img src="https://www.facebook.com/logo.png">
+            <img src="https://mail.example.com/open.gif" height="1px" width="1">
+            <script src="https://stats.example.com/s.js"></script>
+        </body>"#;
+
+    let mut signatures = TrackerSignature::builtin();
+    signatures.push(TrackerSignature::new(
+        "Example",
+        &["stats.example.com"],
+        &[],
+    ));
+
+    let mut dom = parse(html).unwrap();
+    let found: Vec<_> = dom
+        .find_trackers(&signatures)
+        .into_iter()
+        .map(|tracker| tracker.name)
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            "Google Analytics",
+            "Google Analytics",
+            "Facebook Pixel",
+            "Tracking pixel",
+            "Example"
+        ]
+    );
+
+    assert_eq!(dom.remove_trackers(&signatures).len(), 5);
+    assert_eq!(
+        dom.trim().html(),
+        r#"<head><script src="/app.js"></script></head><body><noscript></noscript><img src="https://www.facebook.com/logo.png"></body>"#
+    );
+}
+
+#[test]
+fn apply_scheme_policy() {
+    let mut dom = parse(concat!(
+        r#"<a href="DATA:text/html,x">A</a><img src="blob:https://example.com/1">"#,
+        r#"<img srcset="/a.png 1x, data:image/png,x 2x"><video poster="data:image/png,y"></video>"#,
+        r#"<a href="/relative">B</a>"#,
+    ))
+    .unwrap();
+    let policy = SchemePolicy {
+        rules: vec![
+            SchemeRule::new("data", SchemeAction::Block),
+            SchemeRule::new("data", SchemeAction::Allow).attr("poster"),
+            SchemeRule::new("blob", SchemeAction::Rewrite("about:blank".to_string())),
+            SchemeRule::new("data", SchemeAction::Block).on("video"),
+        ],
+    };
+    let changes = dom.apply_scheme_policy(&policy);
+
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<a>A</a><img src="about:blank"><img srcset="/a.png 1x"><video></video>"#,
+            r#"<a href="/relative">B</a>"#,
+        )
+    );
+    assert_eq!(changes.len(), 4);
+    assert_eq!(
+        changes[2],
+        UrlChange {
+            tag: "img".to_string(),
+            name: "srcset".to_string(),
+            value: "/a.png 1x, data:image/png,x 2x".to_string(),
+            replacement: Some("/a.png 1x".to_string()),
+        }
+    );
+}
+
+#[test]
+fn strip_attrs_with_prefix() {
+    let html = r#"<div ng-app="x" class="a"><p NG-click="go()" aria-label="p">Hi</p></div>"#;
+    let mut dom = parse(html).unwrap();
+
+    assert_eq!(dom.query_all(&Selector::from("[ng-*]")).len(), 2);
+    assert_eq!(dom.query_all(&Selector::from("p[aria-*]")).len(), 1);
+    assert!(dom.query(&Selector::from("[data-*]")).is_none());
+
+    let removed = dom.strip_attrs_with_prefix("ng-");
+    assert_eq!(
+        dom.html(),
+        r#"<div class="a"><p aria-label="p">Hi</p></div>"#
+    );
+    assert_eq!(
+        removed,
+        vec![
+            RemovedAttr {
+                tag: "div".to_string(),
+                name: "ng-app".to_string(),
+                value: "x".to_string(),
+            },
+            RemovedAttr {
+                tag: "p".to_string(),
+                name: "NG-click".to_string(),
+                value: "go()".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn strip_attributes() {
+    let html = r#"<div ID="main" onclick="x()"><img SRC="a.png" alt="" width="1"><a href="/" src="b"></a></div>"#;
+    let mut dom = parse(html).unwrap();
+    let policy = AttrPolicy::new()
+        .allow(&["id"])
+        .allow_on("IMG", &["src", "alt"])
+        .allow_on("a", &["href"]);
+    let removed = dom.strip_attributes(&policy);
+
+    assert_eq!(
+        dom.html(),
+        r#"<div ID="main"><img SRC="a.png" alt><a href="/"></a></div>"#
+    );
+    let names: Vec<_> = removed
+        .iter()
+        .map(|r| (r.tag.as_str(), r.name.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![("div", "onclick"), ("img", "width"), ("a", "src")]
+    );
+}
@@ -21,3 +21,126 @@ fn trimmed_html() {
         r#"<div><span id="class">Hello</span><span class="id">World</span></div>"#
     );
 }
+
+#[test]
+fn quoted_attributes() {
+    let mut dom = parse("<p>Hi</p>").unwrap();
+    let p = dom.query_mut(&Selector::from("p")).unwrap();
+    p.attrs
+        .push(("title".to_string(), r#"it's "quoted""#.to_string()));
+    p.attrs.push(("alt".to_string(), r#"say "hi""#.to_string()));
+
+    let html = dom.html();
+    assert_eq!(
+        html,
+        r#"<p title="it's &quot;quoted&quot;" alt='say "hi"'>Hi</p>"#
+    );
+    let reparsed = parse(&html).unwrap();
+    let p = reparsed.query(&Selector::from("p")).unwrap();
+    assert_eq!(p.attrs[0].1, "it's &quot;quoted&quot;");
+    assert_eq!(p.attrs[1].1, r#"say "hi""#);
+    assert_eq!(reparsed.html(), html);
+}
+
+#[test]
+fn html_with_max_line_length() {
+    let dom = parse(HTML).unwrap();
+    assert_eq!(dom.html_with_options(&HtmlOptions::default()), HTML);
+
+    let html = concat!(
+        r#"<div id="main" class="content wide" data-role="page">"#,
+        "<p>The quick brown fox jumps over the lazy dog</p>",
+        "<pre>keep   this preformatted line as it is</pre>",
+        "<script>let veryLongName = 1; let another = 2;</script></div>",
+    );
+    let options = HtmlOptions {
+        max_line_length: Some(24),
+        ..Default::default()
+    };
+    let wrapped = parse(html).unwrap().html_with_options(&options);
+    assert_eq!(
+        wrapped,
+        concat!(
+            "<div id=\"main\"\nclass=\"content wide\"\ndata-role=\"page\"><p>The\n",
+            "quick brown fox jumps\nover the lazy dog</p><pre>keep   this preformatted line as it is</pre>",
+            "<script>let veryLongName = 1; let another = 2;</script></div>",
+        )
+    );
+    assert_eq!(parse(&wrapped).unwrap().html().replace('\n', " "), html);
+}
+
+#[test]
+fn format() {
+    let html = concat!(
+        "<!DOCTYPE html><html><head><title> Page </title><meta charset=\"utf-8\"></head>",
+        "<body><!-- nav --><nav><a href=\"/\">Home</a> | <a href=\"/about\"> About <span><div>Us</div></span></a></nav>",
+        "<pre>  keep\n    this </pre><p>Line<br> break <img src=\"x.png\"> and <textarea> raw  </textarea></p></body></html>",
+    );
+    let formatted = parse(html).unwrap().format(&FormatOptions {
+        indent: "\t".to_string(),
+    });
+
+    assert_eq!(
+        formatted,
+        concat!(
+            "<!DOCTYPE html>\n<html>\n\t<head>\n\t\t<title>Page</title>\n\t\t<meta charset=\"utf-8\">\n\t</head>\n",
+            "\t<body>\n\t\t<!-- nav -->\n\t\t<nav>\n\t\t\t<a href=\"/\">Home</a> |\n\t\t\t<a href=\"/about\">\n",
+            "\t\t\t\tAbout\n\t\t\t\t<span>\n\t\t\t\t\t<div>Us</div>\n\t\t\t\t</span>\n\t\t\t</a>\n\t\t</nav>\n",
+            "\t\t<pre>  keep\n    this </pre>\n\t\t<p>Line<br> break <img src=\"x.png\"> and <textarea> raw  </textarea></p>\n",
+            "\t</body>\n</html>\n",
+        )
+    );
+}
+
+#[test]
+fn format_idempotent() {
+    let documents = [
+        HTML,
+        "",
+        "  text  only  ",
+        "<p>a <b> b </b> c<i></i> d<!-- x --> e</p>",
+        "<div>Start<p>Middle</p>End <em>inline <p>block</p></em></div>",
+        "<ul><li>One <ul><li>Nested</li></ul></li><li><a>Two</a></li></ul>",
+        "<script>if (a < b) {\n  run();\n}</script><style> p { } </style>",
+        "<table><tr><td>1</td><td> 2 </td></tr></table><p>&amp; &nbsp;x</p>",
+        include_str!("fixtures/page.html"),
+    ];
+    let options = FormatOptions::default();
+    for html in documents {
+        let formatted = parse(html).unwrap().format(&options);
+        assert_eq!(
+            parse(&formatted).unwrap().format(&options),
+            formatted,
+            "{}",
+            html
+        );
+
+        let element = parse(&format!("<main>{}</main>", html)).unwrap()[0].clone();
+        let formatted = element.format(&options);
+        assert_eq!(parse(&formatted).unwrap().format(&options), formatted);
+    }
+}
+
+#[test]
+fn render_ansi() {
+    let strip = |ansi: &str| {
+        let mut text = String::new();
+        let mut rest = ansi;
+        while let Some(start) = rest.find('\x1b') {
+            text.push_str(&rest[..start]);
+            rest = &rest[start + rest[start..].find('m').unwrap() + 1..];
+        }
+        text + rest
+    };
+    let html = "<!DOCTYPE html><div title='say \"hi\"' hidden>\n<!-- a\nb -->\n</div>";
+    let dom = parse(html).unwrap();
+
+    let ansi = dom.render_ansi(false);
+    assert_eq!(strip(&ansi), dom.html());
+    assert!(ansi.contains("\x1b[90m<!-- a\x1b[0m\n\x1b[90mb -->\x1b[0m"));
+
+    let nodes = parse(&"<br>\n".repeat(9)).unwrap();
+    let numbered = strip(&nodes.render_ansi(true));
+    assert!(numbered.starts_with(" 1 │ <br>\n 2 │ <br>\n"));
+    assert!(numbered.ends_with("\n 9 │ <br>\n10 │ "));
+}
@@ -0,0 +1,38 @@
+use html_editor::operation::*;
+use html_editor::{parse, Element};
+
+#[test]
+fn ids_are_deterministic() {
+    let dom = parse(r#"<p id="a"></p><p></p>"#).unwrap();
+    let generate = |seed| {
+        let mut ids = IdGenerator::new(&dom, seed);
+        (0..5).map(|_| ids.next_id()).collect::<Vec<_>>()
+    };
+
+    let ids = generate(7);
+    assert_eq!(ids, generate(7));
+    assert_ne!(ids, generate(8));
+    assert!(ids.iter().all(|id| id.len() == 9 && id.starts_with("id-")));
+
+    let other = parse(r#"<p id="a"></p><p>!</p>"#).unwrap();
+    assert_ne!(IdGenerator::new(&other, 7).next_id(), ids[0]);
+}
+
+#[test]
+fn ids_never_collide() {
+    let dom = parse("<p></p>").unwrap();
+    let first = IdGenerator::new(&dom, 0).next_id();
+
+    let mut ids = IdGenerator::new(&dom, 0);
+    let mut element = Element::new("p", vec![("id", &first)], vec![]);
+    assert_eq!(ids.assign(&mut element), first);
+    let mut generated: Vec<String> = (0..1000).map(|_| ids.next_id()).collect();
+    assert!(!generated.contains(&first));
+    generated.sort();
+    generated.dedup();
+    assert_eq!(generated.len(), 1000);
+
+    let mut element = Element::new("p", vec![], vec![]);
+    let id = ids.assign(&mut element);
+    assert_eq!(element.attrs, vec![("id".to_string(), id)]);
+}
@@ -0,0 +1,169 @@
+use html_editor::operation::*;
+use html_editor::{parse, Node};
+
+#[test]
+fn generate_srcset_merges_widths() {
+    let mut dom = parse(r#"<img srcset="a-320.jpg 320w, a-1600.jpg 1600w, a@2x.jpg 2x">"#).unwrap();
+    dom.execute_for(&Selector::from("img"), |img| {
+        img.attrs.push(("src".to_string(), "a.jpg".to_string()));
+    });
+    let count = dom.generate_srcset(&Selector::from("img"), &[800, 320], None, |src, width| {
+        src.replace(".jpg", &format!("-{}.jpg", width))
+    });
+
+    assert_eq!(count, 1);
+    assert_eq!(
+        dom.html(),
+        r#"<img srcset="a-320.jpg 320w, a-800.jpg 800w, a-1600.jpg 1600w" src="a.jpg">"#
+    );
+}
+
+#[test]
+fn generate_srcset_skips_images_without_src() {
+    let mut dom = parse(r#"<img data-src="lazy.jpg"><div src="x"></div>"#).unwrap();
+    let count = dom.generate_srcset(&Selector::from("img, div"), &[320], None, |src, _| {
+        src.to_string()
+    });
+    assert_eq!(count, 0);
+    assert_eq!(
+        dom.html(),
+        r#"<img data-src="lazy.jpg"><div src="x"></div>"#
+    );
+}
+
+#[test]
+fn generate_srcset_keeps_data_urls() {
+    let mut dom = parse(r#"<img srcset="data:image/gif;base64,R0lG,ODlh 1w">"#).unwrap();
+    dom.execute_for(&Selector::from("img"), |img| {
+        img.attrs.push(("src".to_string(), "b.gif".to_string()));
+    });
+    dom.generate_srcset(&Selector::from("img"), &[2], None, |_, width| {
+        format!("b-{}.gif", width)
+    });
+    assert_eq!(
+        dom.html(),
+        r#"<img srcset="data:image/gif;base64,R0lG,ODlh 1w, b-2.gif 2w" src="b.gif">"#
+    );
+}
+
+#[test]
+fn to_picture_maps_srcset() {
+    let mut dom =
+        parse(r#"<picture><img src="kept.png"></picture><p><img class="hero"></p>"#).unwrap();
+    dom.execute_for(&Selector::from(".hero"), |img| {
+        img.attrs
+            .push(("srcset".to_string(), "/a.jpg?v=1 1x, /b.c/a 2x".to_string()));
+        img.attrs.push(("sizes".to_string(), "50vw".to_string()));
+    });
+    let sources = [
+        PictureSource::new("image/avif", "avif"),
+        PictureSource::new("image/webp", "webp"),
+    ];
+    let count = dom.to_picture(&Selector::from("img"), &sources);
+
+    assert_eq!(count, 1);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<picture><img src="kept.png"></picture><p><picture>"#,
+            r#"<source type="image/avif" srcset="/a.avif?v=1 1x, /b.c/a.avif 2x" sizes="50vw">"#,
+            r#"<source type="image/webp" srcset="/a.webp?v=1 1x, /b.c/a.webp 2x" sizes="50vw">"#,
+            r#"<img class="hero" srcset="/a.jpg?v=1 1x, /b.c/a 2x" sizes="50vw"></picture></p>"#,
+        )
+    );
+
+    assert_eq!(dom.to_picture(&Selector::from("img"), &sources), 0);
+}
+
+#[test]
+fn inject_noscript_stays_in_sync() {
+    let mut dom = parse(concat!(
+        r#"<div><img data-src="a.jpg"> <noscript><img src="x.jpg"></noscript></div>"#,
+        r#"<script src="app.js"></script>"#,
+    ))
+    .unwrap();
+    let selector = Selector::from("img, script");
+    let count = dom.inject_noscript(&selector, |el| match el.name.as_str() {
+        "script" => Some(vec![Node::Text("JavaScript is required.".to_string())]),
+        _ => eager_image(el),
+    });
+    assert_eq!(count, 2);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<div><img data-src="a.jpg"><noscript data-noscript-fallback>"#,
+            r#"<img src="a.jpg"></noscript> <noscript><img src="x.jpg"></noscript></div>"#,
+            r#"<script src="app.js"></script><noscript data-noscript-fallback>"#,
+            r#"JavaScript is required.</noscript>"#,
+        )
+    );
+
+    dom.execute_for(&Selector::from("img"), |img| {
+        if let Some((_, src)) = img.attrs.iter_mut().find(|(key, _)| key == "data-src") {
+            *src = "b.jpg".to_string();
+        }
+    });
+    let count = dom.inject_noscript(&selector, eager_image);
+    assert_eq!(count, 1);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<div><img data-src="b.jpg"><noscript data-noscript-fallback>"#,
+            r#"<img src="b.jpg"></noscript> <noscript><img src="x.jpg"></noscript></div>"#,
+            r#"<script src="app.js"></script>"#,
+        )
+    );
+}
+
+#[test]
+fn to_picture_contextual_selector() {
+    let mut dom = parse(r#"<figure><img src="a.jpg"></figure><img src="b.jpg">"#).unwrap();
+    let sources = [PictureSource::new("image/webp", "webp")];
+    assert_eq!(dom.to_picture(&Selector::from("figure > img"), &sources), 1);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<figure><picture><source type="image/webp" srcset="a.webp">"#,
+            r#"<img src="a.jpg"></picture></figure><img src="b.jpg">"#,
+        )
+    );
+}
+
+#[test]
+fn inject_noscript_contextual_selector() {
+    let mut dom =
+        parse(r#"<div><img data-src="a.jpg"><img data-src="b.jpg"></div><img data-src="c.jpg">"#)
+            .unwrap();
+    // The second image is found after the fallback of the first one.
+    let count = dom.inject_noscript(&Selector::from("div > img"), eager_image);
+    assert_eq!(count, 2);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<div><img data-src="a.jpg"><noscript data-noscript-fallback><img src="a.jpg"></noscript>"#,
+            r#"<img data-src="b.jpg"><noscript data-noscript-fallback><img src="b.jpg"></noscript></div>"#,
+            r#"<img data-src="c.jpg">"#,
+        )
+    );
+}
+
+#[test]
+fn frozen_images() {
+    let embed = r#"<div class="embed"><img src="a.jpg"><img data-src="b.jpg"></div>"#;
+    let mut dom = parse(&format!(r#"{}<img src="c.jpg">"#, embed)).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    let sources = [PictureSource::new("image/webp", "webp")];
+    assert_eq!(dom.to_picture(&Selector::from("img"), &sources), 1);
+    assert_eq!(
+        dom.inject_noscript(&Selector::from("img[data-src]"), eager_image),
+        0
+    );
+    assert_eq!(
+        dom.html(),
+        format!(
+            r#"{}<picture><source type="image/webp" srcset="c.webp"><img src="c.jpg"></picture>"#,
+            embed
+        )
+    );
+}
@@ -0,0 +1,89 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn parse_meta_refresh() {
+    let parse = |content| MetaRefresh::parse(content);
+    let refresh = |delay, url: Option<&str>| {
+        Some(MetaRefresh {
+            delay,
+            url: url.map(str::to_string),
+        })
+    };
+
+    assert_eq!(parse("5"), refresh(5, None));
+    assert_eq!(parse(" 5 ; url = /next "), refresh(5, Some("/next")));
+    assert_eq!(parse("1.5, URL=\"/a b\" x"), refresh(1, Some("/a b")));
+    assert_eq!(parse("0; /direct"), refresh(0, Some("/direct")));
+    assert_eq!(parse("2;url"), refresh(2, Some("url")));
+    assert_eq!(parse(""), None);
+    assert_eq!(parse("-1; url=/"), None);
+}
+
+#[test]
+fn head_links() {
+    let dom = parse(
+        r#"
+        <head>
+            <meta http-equiv="refresh" content="never">
+            <meta http-equiv="Refresh" content="0; url=https://example.com/new">
+            <link rel="Canonical" href="https://example.com/page">
+            <link rel="alternate" hreflang="en" href="https://example.com/en/page">
+            <link rel="alternate" hreflang="x-default" href="https://example.com/page">
+            <link rel="alternate" type="application/rss+xml" href="/feed.xml">
+        </head>"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        dom.meta_refresh().unwrap().url.as_deref(),
+        Some("https://example.com/new")
+    );
+    assert_eq!(dom.canonical(), Some("https://example.com/page"));
+    assert_eq!(
+        dom.alternates(),
+        vec![
+            AlternateLink {
+                hreflang: "en".to_string(),
+                href: "https://example.com/en/page".to_string(),
+            },
+            AlternateLink {
+                hreflang: "x-default".to_string(),
+                href: "https://example.com/page".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn viewport_round_trip() {
+    let viewport =
+        Viewport::parse("width=320; initial-scale=0.5, maximum-scale=2, user-scalable=maybe");
+    assert_eq!(viewport.width, Some(ViewportWidth::Pixels(320)));
+    assert_eq!(viewport.initial_scale, Some(0.5));
+    assert_eq!(viewport.user_scalable, None);
+    assert_eq!(
+        viewport.to_string(),
+        "width=320, initial-scale=0.5, maximum-scale=2, user-scalable=maybe"
+    );
+}
+
+#[test]
+fn set_viewport() {
+    let mut dom =
+        parse(r#"<head><meta name="viewport" content="width=1024"></head><body></body>"#).unwrap();
+    let viewport = dom
+        .viewport()
+        .unwrap()
+        .width(ViewportWidth::DeviceWidth)
+        .user_scalable(true);
+    assert!(dom.set_viewport(&viewport));
+    assert_eq!(dom.query_all(&Selector::from("meta")).len(), 1);
+    assert_eq!(
+        dom.viewport().unwrap().to_string(),
+        "width=device-width, user-scalable=yes"
+    );
+
+    let mut fragment = parse("<p>No head</p>").unwrap();
+    assert!(!fragment.set_viewport(&viewport));
+}
@@ -0,0 +1,76 @@
+use html_editor::operation::*;
+use html_editor::{parse, Node};
+
+#[test]
+fn jsx() {
+    let mut dom = parse(concat!(
+        "<!DOCTYPE html><!-- a */ b --><p>{x}</p>",
+        r#"<p style="-webkit-line-clamp: 2; --gap: 4px; font-family: &quot;A&quot;">x &gt; y</p>"#,
+        "<script>let s = `${a}` + '\\n';</script>",
+    ))
+    .unwrap();
+    dom.push(Node::new_element(
+        "div",
+        vec![
+            ("class", "a"),
+            ("tabindex", "0"),
+            ("data-id", "1"),
+            ("aria-hidden", ""),
+        ],
+        vec![Node::new_element("input", vec![("readonly", "")], vec![])],
+    ));
+
+    assert_eq!(
+        dom.jsx(),
+        concat!(
+            "{/* a * / b */}<p>{'{'}x{'}'}</p>",
+            r#"<p style={{ WebkitLineClamp: "2", "--gap": "4px", fontFamily: "\"A\"" }}>"#,
+            "x &gt; y</p>",
+            r"<script>{`let s = \`\${a}\` + '\\n';`}</script>",
+            r#"<div className="a" tabIndex="0" data-id="1" aria-hidden><input readOnly /></div>"#,
+        )
+    );
+}
+
+#[test]
+fn jsx_events() {
+    let node = Node::new_element(
+        "button",
+        vec![
+            ("onclick", "go()"),
+            ("ondblclick", "zoom()"),
+            ("onfoo", "x"),
+        ],
+        vec![],
+    );
+    assert_eq!(
+        node.jsx(),
+        r#"<button onClick="go()" onDoubleClick="zoom()" onFoo="x"></button>"#
+    );
+}
+
+#[test]
+fn rename_attrs() {
+    let mut node = Node::new_element(
+        "label",
+        vec![
+            ("class", "a"),
+            ("for", "b"),
+            ("onmouseover", "c()"),
+            ("data-x", "d"),
+        ],
+        vec![],
+    );
+    node.rename_attrs(AttrProfile::Jsx);
+    assert_eq!(
+        node.html(),
+        r#"<label className="a" htmlFor="b" onMouseOver="c()" data-x="d"></label>"#
+    );
+
+    node.rename_attrs(AttrProfile::Jsx);
+    node.rename_attrs(AttrProfile::Html);
+    assert_eq!(
+        node.html(),
+        r#"<label class="a" for="b" onmouseover="c()" data-x="d"></label>"#
+    );
+}
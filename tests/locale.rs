@@ -0,0 +1,76 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+fn values(text: &str, matchers: &[&dyn ValueMatcher]) -> Vec<(String, LocalValue)> {
+    let mut values = Vec::new();
+    let mut dom = parse(text).unwrap();
+    dom.localize(None, matchers, |value, text| {
+        values.push((text.to_string(), value.clone()));
+        None
+    });
+    values
+}
+
+#[test]
+fn number_matcher() {
+    let found = values(
+        "<p>1,234.5 and -7, not A4, v1.2.3 or 12,34 but 3.</p>",
+        &[&NumberMatcher],
+    );
+    assert_eq!(
+        found,
+        vec![
+            ("1,234.5".to_string(), LocalValue::Number(1234.5)),
+            ("-7".to_string(), LocalValue::Number(-7.0)),
+            ("3".to_string(), LocalValue::Number(3.0)),
+        ]
+    );
+}
+
+#[test]
+fn currency_and_date_matchers() {
+    let currency = |currency: &str, amount| LocalValue::Currency {
+        currency: currency.to_string(),
+        amount,
+    };
+    let found = values(
+        "<p>€5 or EUR 12.50, not XEUR 1 or $x, on 2024-03-15 not 2024-13-01</p>",
+        &[&DateMatcher, &CurrencyMatcher, &NumberMatcher],
+    );
+    assert_eq!(
+        found,
+        vec![
+            ("€5".to_string(), currency("€", 5.0)),
+            ("EUR 12.50".to_string(), currency("EUR", 12.5)),
+            ("1".to_string(), LocalValue::Number(1.0)),
+            (
+                "2024-03-15".to_string(),
+                LocalValue::Date {
+                    year: 2024,
+                    month: 3,
+                    day: 15
+                }
+            ),
+            ("2024".to_string(), LocalValue::Number(2024.0)),
+        ]
+    );
+}
+
+#[test]
+fn localize_in_scope() {
+    let mut dom = parse(r#"<p class="price">1,000</p><p>1,000</p><code>1,000</code>"#).unwrap();
+    let count = dom.localize(
+        Some(&Selector::from(".price")),
+        &[&NumberMatcher],
+        |value, _| match value {
+            LocalValue::Number(number) => Some(format!("{}&nbsp;Kč", number)),
+            _ => None,
+        },
+    );
+
+    assert_eq!(count, 1);
+    assert_eq!(
+        dom.html(),
+        r#"<p class="price">1000&nbsp;Kč</p><p>1,000</p><code>1,000</code>"#
+    );
+}
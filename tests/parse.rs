@@ -1,5 +1,8 @@
 use html_editor::operation::*;
-use html_editor::{parse, try_parse};
+use html_editor::{
+    parse, parse_with_options, try_parse, try_parse_with_options, try_parse_with_repairs, Doctype,
+    Node, ParseOptions, Repair, UnknownTagPolicy,
+};
 
 #[test]
 fn paired_tag() {
@@ -112,6 +115,38 @@ fn autocomplete_multiple_unclosed_tags() {
     );
 }
 
+#[test]
+fn repairs() {
+    let repairs = |html: &str| try_parse_with_repairs(html, &ParseOptions::default()).1;
+    let auto_closed = |name: &str, offset| Repair::AutoClosed {
+        name: name.to_string(),
+        offset,
+    };
+    let discarded = |name: &str, offset| Repair::DiscardedEndTag {
+        name: name.to_string(),
+        offset,
+    };
+
+    assert_eq!(repairs("<div><br><p>Ok</p></div>"), vec![]);
+    assert_eq!(
+        repairs("<span><span>Ipsum</span>"),
+        vec![auto_closed("span", 0)]
+    );
+    assert_eq!(
+        repairs("<script>a < b</script></b><p>é</i>"),
+        vec![discarded("b", 22), auto_closed("p", 26), discarded("i", 31)]
+    );
+    assert_eq!(
+        repairs("<div><a>Ipsum</p></div>"),
+        vec![
+            auto_closed("div", 0),
+            auto_closed("a", 5),
+            discarded("p", 13),
+            discarded("div", 17),
+        ]
+    );
+}
+
 #[test]
 fn xml() {
     let mut html = parse(
@@ -127,3 +162,247 @@ fn xml() {
     .unwrap();
     html.trim();
 }
+
+#[test]
+fn frontmatter() {
+    use html_editor::{parse_with_frontmatter, Frontmatter};
+
+    let (frontmatter, dom) =
+        parse_with_frontmatter("+++\r\ntitle = \"Hi\"\r\ntags = []\r\n+++\r\n<p>Hi</p>").unwrap();
+    assert_eq!(
+        frontmatter,
+        Some(Frontmatter::Toml(
+            "title = \"Hi\"\r\ntags = []\r\n".to_string()
+        ))
+    );
+    assert_eq!(dom.html(), "<p>Hi</p>");
+
+    let (frontmatter, dom) = parse_with_frontmatter("---\n<hr>").unwrap();
+    assert_eq!(frontmatter, None);
+    assert_eq!(dom.html(), "---\n<hr>");
+
+    let (frontmatter, dom) = parse_with_frontmatter("---\n---\n<hr>").unwrap();
+    assert_eq!(frontmatter, Some(Frontmatter::Yaml(String::new())));
+    assert_eq!(dom.html(), "<hr>");
+}
+
+#[test]
+fn attributes_order_and_whitespace() {
+    let html = "<input\n\ttype=\"text\"  name = 'q' disabled\nvalue=a>";
+    let dom = parse(html).unwrap();
+
+    assert_eq!(
+        dom.html(),
+        r#"<input type="text" name="q" disabled value="a">"#
+    );
+}
+
+#[test]
+fn framework_attributes() {
+    let html = concat!(
+        r#"<my-list v-if="items.length > 0" :items="items" @click.prevent="go('a')" #header>"#,
+        r#"<li *ngFor="let i of items" [(ngModel)]="i" (click)="pick(i)" [value]='i["v"]'></li>"#,
+        "</my-list><p>a > b</p>",
+    );
+    let dom = parse(html).unwrap();
+
+    assert_eq!(dom.html(), html);
+    let li = dom.query(&Selector::from("li")).unwrap();
+    assert_eq!(li.attrs[1], ("[(ngModel)]".to_string(), "i".to_string()));
+    assert_eq!(
+        li.attrs[3],
+        ("[value]".to_string(), r#"i["v"]"#.to_string())
+    );
+}
+
+#[test]
+fn interpolation() {
+    let options = ParseOptions {
+        interpolation: true,
+        ..Default::default()
+    };
+    let html = r#"<td title={{ a>b }} {{ attrs }}>{{ x < 1 && "<b>" }}</td>"#;
+    let expected = r#"<td title="{{ a>b }}" {{ attrs }}>{{ x < 1 && "<b>" }}</td>"#;
+
+    assert_eq!(parse_with_options(html, &options).unwrap().html(), expected);
+    assert_eq!(try_parse_with_options(html, &options).html(), expected);
+    assert_ne!(try_parse(html).html(), expected);
+}
+
+#[test]
+fn template_tags() {
+    let options = ParseOptions {
+        template_tags: true,
+        ..Default::default()
+    };
+    let html = concat!(
+        "{% extends 'base.html' %}{# a <comment> #}",
+        r#"<ul {% if dark %}class="dark"{% endif %} title="{{ "a>b" }}">"#,
+        "{% for i in items %}<li>{{ i.name }} {{{ i.html }}}</li>{% endfor %}</ul>",
+        "{{!-- {{ old }} --}}",
+    );
+    let dom = parse_with_options(html, &options).unwrap();
+
+    assert!(matches!(&dom[0], Node::Template(t) if t == "{% extends 'base.html' %}"));
+    assert!(matches!(&dom[1], Node::Template(t) if t == "{# a <comment> #}"));
+    assert_eq!(
+        dom.html(),
+        html.replace(r#"%}class="dark"{%"#, r#"%} class="dark" {%"#)
+            .replace(r#"title="{{ "a>b" }}""#, r#"title='{{ "a>b" }}'"#)
+    );
+    let li = dom.query(&Selector::from("li")).unwrap();
+    assert!(matches!(
+        &li.children[..],
+        [Node::Template(_), Node::Text(_), Node::Template(_)]
+    ));
+}
+
+#[test]
+fn code_blocks() {
+    let html = concat!(
+        "<?php $items = load(); ?><ul>",
+        r#"<?php foreach ($items as $i): ?><li class="<?= $i->active ? "on" : "off" ?>"><?= $i > 0 ?></li>"#,
+        "<?php endforeach; ?></ul><% if user %><p><%= user.name %></p><% end %>",
+    );
+    let options = ParseOptions {
+        template_tags: true,
+        ..Default::default()
+    };
+    let mut dom = parse_with_options(html, &options).unwrap();
+
+    assert!(matches!(&dom[0], Node::Template(t) if t == "<?php $items = load(); ?>"));
+    assert_eq!(
+        dom.html(),
+        html.replace(
+            r#"class="<?= $i->active ? "on" : "off" ?>""#,
+            r#"class='<?= $i->active ? "on" : "off" ?>'"#
+        )
+    );
+
+    dom.remove_by(&Selector::from("p"));
+    assert!(dom.html().ends_with("</ul><% if user %><% end %>"));
+}
+
+#[test]
+fn unclosed_code_blocks() {
+    let options = ParseOptions {
+        template_tags: true,
+        ..Default::default()
+    };
+    let text = parse_with_options("<p>50<% off</p><p>today</p>", &options).unwrap();
+    assert_eq!(text.html(), "<p>50<% off</p><p>today</p>");
+    assert_eq!(text.query_all(&Selector::from("p")).len(), 2);
+
+    let attr = parse_with_options(r#"<a title="50<%">sale</a><p>today</p>"#, &options).unwrap();
+    assert_eq!(attr.html(), r#"<a title="50<%">sale</a><p>today</p>"#);
+    let a = attr.query(&Selector::from("a")).unwrap();
+    assert!(matches!(&a.children[..], [Node::Text(t)] if t == "sale"));
+}
+
+#[test]
+fn processing_instructions() {
+    let xml = concat!(
+        r#"<?xml version="1.0" standalone="yes"?>"#,
+        r#"<?xml-stylesheet type="text/xsl" href="style.xsl"?>"#,
+        "<feed><?target data?></feed>",
+    );
+    let dom = parse(xml).unwrap();
+
+    assert!(matches!(
+        &dom[0],
+        Node::ProcessingInstruction(pi) if pi == r#"xml version="1.0" standalone="yes""#
+    ));
+    assert!(matches!(&dom[1], Node::ProcessingInstruction(_)));
+    assert_eq!(dom.html(), xml);
+    assert_eq!(dom.clone().trim().html(), xml);
+
+    let declaration = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+    let dom = parse(declaration).unwrap();
+    assert!(matches!(
+        &dom[0],
+        Node::Doctype(Doctype::Xml { version, encoding }) if version == "1.0" && encoding == "UTF-8"
+    ));
+    assert_eq!(dom.html(), declaration);
+}
+
+#[test]
+fn unknown_tags() {
+    let html = concat!(
+        r#"<dvi class="a">Typo</dvi><my-card>Custom</my-card>"#,
+        r#"<svg><linearGradient></linearGradient></svg><p>Ok<foo/></p>"#,
+    );
+    let with = |unknown_tags| ParseOptions {
+        unknown_tags,
+        ..Default::default()
+    };
+
+    let kept = parse_with_options(html, &with(UnknownTagPolicy::Keep)).unwrap();
+    assert_eq!(kept.html(), parse(html).unwrap().html());
+
+    let escaped = parse_with_options(html, &with(UnknownTagPolicy::Escape)).unwrap();
+    assert_eq!(
+        escaped.html(),
+        concat!(
+            r#"&lt;dvi class="a"&gt;Typo&lt;/dvi&gt;<my-card>Custom</my-card>"#,
+            "<svg><linearGradient></linearGradient></svg><p>Ok&lt;foo&gt;&lt;/foo&gt;</p>",
+        )
+    );
+
+    let dropped = parse_with_options(html, &with(UnknownTagPolicy::Drop)).unwrap();
+    assert_eq!(
+        dropped.html(),
+        concat!(
+            "Typo<my-card>Custom</my-card>",
+            "<svg><linearGradient></linearGradient></svg><p>Ok</p>",
+        )
+    );
+
+    let error = parse_with_options(html, &with(UnknownTagPolicy::Error));
+    assert_eq!(error.unwrap_err(), "Unknown tag: <dvi>");
+    let tolerant = try_parse_with_options(html, &with(UnknownTagPolicy::Error));
+    assert_eq!(tolerant.html(), kept.html());
+}
+
+#[test]
+fn implicit_tags() {
+    let options = ParseOptions {
+        implicit_tags: true,
+        ..Default::default()
+    };
+    let omit = HtmlOptions {
+        omit_implicit_tags: true,
+        ..Default::default()
+    };
+    let html = "<!DOCTYPE html><title>Hi</title><meta charset=\"utf-8\"><p>One</p><p>Two</p>";
+    let dom = parse_with_options(html, &options).unwrap();
+    assert_eq!(
+        dom.html(),
+        concat!(
+            "<!DOCTYPE html><html><head><title>Hi</title><meta charset=\"utf-8\"></head>",
+            "<body><p>One</p><p>Two</p></body></html>"
+        )
+    );
+    assert_eq!(dom.html_with_options(&omit), html);
+    assert_eq!(
+        dom.query(&Selector::from("body > p")).unwrap().html(),
+        "<p>One</p>"
+    );
+
+    let html = "<html><body><p>One</p></body></html>";
+    let dom = parse_with_options(html, &options).unwrap();
+    assert_eq!(
+        dom.html(),
+        "<html><head></head><body><p>One</p></body></html>"
+    );
+    assert_eq!(dom.html_with_options(&omit), html);
+
+    let html = "<html><head></head><p>One</p></html>";
+    let dom = try_parse_with_options(html, &options);
+    assert_eq!(
+        dom.html(),
+        "<html><head></head><body><p>One</p></body></html>"
+    );
+
+    let dom = parse("<p>One</p>").unwrap();
+    assert_eq!(dom.html(), "<p>One</p>");
+}
@@ -0,0 +1,76 @@
+use html_editor::operation::*;
+use html_editor::{Node, ParseOptions};
+
+fn pipeline() -> Pipeline {
+    Pipeline::new()
+        .remove("script")
+        .for_each("img", |img| {
+            img.attrs.push(("loading".to_string(), "lazy".to_string()))
+        })
+        .try_step(|dom| match dom.query(&Selector::from("title")) {
+            Some(_) => Ok(()),
+            None => Err("missing title".to_string()),
+        })
+}
+
+#[test]
+fn run_sequential() {
+    let inputs = [
+        "<title>A</title><script>x()</script><img src=a.png>",
+        "<p>No title</p>",
+        "<title>C</title>",
+    ];
+    let report = pipeline().run(inputs);
+
+    assert_eq!(
+        report.outputs[0],
+        Ok(r#"<title>A</title><img src="a.png" loading="lazy">"#.to_string())
+    );
+    assert_eq!(
+        report.errors().collect::<Vec<_>>(),
+        vec![&PipelineError {
+            index: 1,
+            message: "missing title".to_string()
+        }]
+    );
+    assert_eq!(report.outputs[2], Ok("<title>C</title>".to_string()));
+    assert_eq!(
+        report.stats,
+        PipelineStats {
+            documents: 3,
+            failed: 1,
+            input_bytes: inputs.iter().map(|input| input.len()).sum(),
+            output_bytes: 48 + 16,
+        }
+    );
+}
+
+#[test]
+fn run_parallel_keeps_order() {
+    let inputs: Vec<String> = (0..25)
+        .map(|i| format!("<title>{}</title><script></script>", i))
+        .collect();
+    let sequential = pipeline().run(&inputs);
+    let parallel = pipeline().threads(4).run(&inputs);
+
+    assert_eq!(parallel, sequential);
+    for (i, output) in parallel.outputs.iter().enumerate() {
+        assert_eq!(output, &Ok(format!("<title>{}</title>", i)));
+    }
+}
+
+#[test]
+fn parse_errors_are_reported() {
+    let options = ParseOptions {
+        unknown_tags: html_editor::UnknownTagPolicy::Error,
+        ..Default::default()
+    };
+    let pipeline = Pipeline::new().parse_options(options).step(|dom| {
+        dom.push(Node::Text("!".to_string()));
+    });
+
+    assert_eq!(pipeline.apply("<p>Hi</p>"), Ok("<p>Hi</p>!".to_string()));
+    let report = pipeline.run(["<blink>Hi</blink>"]);
+    assert_eq!(report.stats.failed, 1);
+    assert_eq!(report.stats.output_bytes, 0);
+}
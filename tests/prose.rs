@@ -0,0 +1,181 @@
+use html_editor::operation::*;
+use html_editor::{parse, Node};
+
+#[test]
+fn rewrite_text_scope() {
+    let mut dom = parse("<nav>a</nav><main>a<pre>a</pre><p>a</p></main>").unwrap();
+    let count = dom.rewrite_text(Some(&Selector::from("main")), |text| {
+        Some(vec![Node::Text(text.to_uppercase())])
+    });
+    assert_eq!(count, 2);
+    assert_eq!(dom.html(), "<nav>a</nav><main>A<pre>a</pre><p>A</p></main>");
+}
+
+#[test]
+fn replace_shortcodes() {
+    let mut shortcodes = emoji_shortcodes();
+    shortcodes.insert("crab".to_string(), "🦀".to_string());
+    let mut dom = parse(concat!(
+        "<p>:crab::crab: :unknown: ::tada: :+1:</p>",
+        "<pre>:crab:</pre><script>let a = ':crab:';</script><aside>:crab:</aside>",
+    ))
+    .unwrap();
+    let count = dom.replace_shortcodes(Some(&Selector::from("p")), &shortcodes);
+
+    assert_eq!(count, 4);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            "<p>🦀🦀 :unknown: :🎉 👍</p>",
+            "<pre>:crab:</pre><script>let a = ':crab:';</script><aside>:crab:</aside>",
+        )
+    );
+}
+
+#[test]
+fn smartypants() {
+    let html = parse(concat!(
+        r#"<p title="'raw'">She said "<em>yes</em>" --- twice.</p>"#,
+        r#"<p>Rock 'n' roll, ('quoted') and ... "end"</p>"#,
+        r#"<kbd>--help</kbd><samp>...</samp><pre>"a"</pre>"#,
+    ))
+    .unwrap()
+    .smartypants(None)
+    .html();
+
+    assert_eq!(
+        html,
+        concat!(
+            r#"<p title="'raw'">She said “<em>yes</em>” — twice.</p>"#,
+            r#"<p>Rock ‘n’ roll, (‘quoted’) and … “end”</p>"#,
+            r#"<kbd>--help</kbd><samp>...</samp><pre>"a"</pre>"#,
+        )
+    );
+}
+
+#[test]
+fn prose_segments() {
+    let html = concat!(
+        "<div>Intro <a href='#'>link</a><br>Next</div>",
+        "<ul><li>One</li> <li>Two <code>x</code> end</li></ul>",
+    );
+    let dom = parse(html).unwrap();
+    let texts: Vec<String> = dom
+        .prose_segments()
+        .into_iter()
+        .map(|segment| segment.text)
+        .collect();
+    assert_eq!(texts, vec!["Intro link", "Next", "One", "Two ", " end"]);
+
+    let li = dom.query(&Selector::from("li")).unwrap();
+    assert_eq!(li.prose_segments()[0].boundaries, vec![0]);
+}
+
+#[test]
+fn edit_prose() {
+    let html = "<p>A <b>bold</b> <i>move</i></p><p>Tom &amp; Jerry</p>";
+    let mut dom = parse(html).unwrap();
+    let edit = |segment: usize, range: std::ops::Range<usize>, text: &str| ProseEdit {
+        segment,
+        range,
+        text: text.to_string(),
+    };
+    let edits = vec![
+        edit(1, 4..5, "and"),
+        edit(0, 2..6, "brave"),
+        edit(0, 4..8, "overlapping"),
+        edit(0, 11..11, "<!>"),
+        edit(1, 0..99, "out of range"),
+        edit(2, 0..0, "missing"),
+    ];
+
+    assert_eq!(dom.edit_prose(&edits), 3);
+    assert_eq!(
+        dom.html(),
+        "<p>A <b>brave</b> <i>move&lt;!&gt;</i></p><p>Tom and Jerry</p>"
+    );
+}
+
+#[test]
+fn split_paragraph_at_sentences() {
+    let html = concat!(
+        r#"<div><p class="long">He said "go." <a href="/x">Then <em id="e">we left. It</em> rained</a>."#,
+        " Run <code>a. B</code> now, e.g. twice... and J. Doe rests? <br>Yes!</p></div>",
+        "<p>Not. Selected.</p>",
+    );
+    let mut dom = parse(html).unwrap();
+
+    assert_eq!(
+        dom.split_paragraph_at_sentences(&Selector::from("div p")),
+        4
+    );
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<div><p class="long">He said "go."</p>"#,
+            r#"<p class="long"><a href="/x">Then <em id="e">we left.</em></a></p>"#,
+            r#"<p class="long"><a href="/x"><em>It</em> rained</a>.</p>"#,
+            r#"<p class="long">Run <code>a. B</code> now, e.g. twice... and J. Doe rests?</p>"#,
+            r#"<p class="long"><br>Yes!</p></div>"#,
+            "<p>Not. Selected.</p>",
+        )
+    );
+}
+
+#[test]
+fn rewrite_text_contextual_scope() {
+    let article_p = Selector::from("article > p");
+    let mut dom = parse("<article><p>a</p><div><p>a</p></div></article><p>a</p>").unwrap();
+    let count = dom.rewrite_text(Some(&article_p), |text| {
+        Some(vec![Node::Text(text.to_uppercase())])
+    });
+    assert_eq!(count, 1);
+    assert_eq!(
+        dom.html(),
+        "<article><p>A</p><div><p>a</p></div></article><p>a</p>"
+    );
+
+    let mut article = parse("<article>a<p>a</p></article>").unwrap().remove(0);
+    let article = article.as_element_mut().unwrap();
+    let count = article.rewrite_text(Some(&article_p), |text| {
+        Some(vec![Node::Text(text.to_uppercase())])
+    });
+    assert_eq!(count, 1);
+    assert_eq!(article.html(), "<article>a<p>A</p></article>");
+}
+
+#[test]
+fn replace_shortcodes_contextual_scope() {
+    let mut dom = parse("<article><p>:rocket:</p></article><p>:rocket:</p>").unwrap();
+    let count = dom.replace_shortcodes(Some(&Selector::from("article > p")), &emoji_shortcodes());
+    assert_eq!(count, 1);
+    assert_eq!(dom.html(), "<article><p>🚀</p></article><p>:rocket:</p>");
+}
+
+#[test]
+fn smartypants_contextual_scope() {
+    let mut dom = parse(r#"<blockquote><p>"a"</p></blockquote><p>"b"</p>"#).unwrap();
+    dom.smartypants(Some(&Selector::from("blockquote p")));
+    assert_eq!(
+        dom.html(),
+        r#"<blockquote><p>“a”</p></blockquote><p>"b"</p>"#
+    );
+}
+
+#[test]
+fn frozen_prose() {
+    let embed = r#"<div class="embed"><p>"Hi" :rocket:</p></div>"#;
+    let mut dom = parse(&format!(r#"{}<p>"Hi" :rocket:</p>"#, embed)).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    assert_eq!(dom.replace_shortcodes(None, &emoji_shortcodes()), 1);
+    dom.smartypants(None);
+    assert_eq!(dom.prose_segments().len(), 1);
+    let edit = ProseEdit {
+        segment: 0,
+        range: 0.."“Hi”".len(),
+        text: "Hey".to_string(),
+    };
+    assert_eq!(dom.edit_prose(&[edit]), 1);
+    assert_eq!(dom.html(), format!("{}<p>Hey 🚀</p>", embed));
+}
@@ -0,0 +1,57 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn for_print() {
+    let mut dom = parse(concat!(
+        r#"<body><div class="ad-slot">Buy</div><header class="header">Title</header>"#,
+        r##"<div role="navigation">Menu</div><p><a href="/docs">docs</a>, <a href="#top">top</a>, "##,
+        r#"<a href="javascript:void(0)">open</a>, <a href="https://a.com">https://a.com</a></p>"#,
+        r#"<img data-src="/a.png" loading="lazy"><form><input><label>Name</label></form>"#,
+        r#"<noscript>Enable JavaScript</noscript></body>"#,
+    ))
+    .unwrap();
+    dom.for_print();
+
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<body><header class="header">Title</header><p><a href="/docs">docs</a> (/docs), "#,
+            r##"<a href="#top">top</a>, <a href="javascript:void(0)">open</a>, "##,
+            r#"<a href="https://a.com">https://a.com</a></p><img src="/a.png">"#,
+            r#"<form><label>Name</label></form></body>"#,
+        )
+    );
+}
+
+#[test]
+fn set_header_comment() {
+    let header = HeaderComment::new("build-info")
+        .field("build", "1a2b3c")
+        .field("note", "a --> b\nc");
+    let mut dom = parse("<!-- other --><html><body></body></html>").unwrap();
+
+    assert!(!dom.set_header_comment(&header));
+    assert!(dom.set_header_comment(&header.clone().field("timestamp", "2026-10-14")));
+    assert_eq!(
+        dom.html(),
+        concat!(
+            "<!-- build-info\n build: 1a2b3c\n note: a - -> b c\n timestamp: 2026-10-14\n-->",
+            "<!-- other --><html><body></body></html>",
+        )
+    );
+
+    let mut body = dom.query(&Selector::from("body")).unwrap().clone();
+    assert!(!body.set_header_comment(&HeaderComment::new("license")));
+    assert_eq!(body.html(), "<body><!-- license\n--></body>");
+}
+
+#[test]
+fn frozen_print() {
+    let embed = r#"<div class="embed"><script>x()</script><a href="https://a.io">a</a></div>"#;
+    let mut dom = parse(&format!("{}<nav>N</nav>", embed)).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    dom.for_print();
+    assert_eq!(dom.html(), embed);
+}
@@ -54,3 +54,620 @@ fn html_editor_multiple_class_parsing() {
     let selector = Selector::from(".a");
     nodes.query(&selector).unwrap();
 }
+
+#[test]
+fn query_compound_classes() {
+    let html = concat!(
+        r#"<button id="save" class="btn primary"></button>"#,
+        "<button id=\"cancel\" class=\"primary\n\tbtn\"></button>",
+        r#"<button id="help" class="btn"></button><a id="link" class="btn primary"></a>"#,
+    );
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids(".btn.primary"), vec!["save", "cancel", "link"]);
+    assert_eq!(ids("button.primary.btn"), vec!["save", "cancel"]);
+    assert_eq!(ids("button#save.btn.primary"), vec!["save"]);
+    assert_eq!(ids(".btn.primary#help"), Vec::<String>::new());
+}
+
+#[test]
+fn query_descendant() {
+    let html = r#"<div class="a"><section><p id="x"></p></section></div><p id="y"></p><div><p id="z"></p></div>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("div p"), vec!["x", "z"]);
+    assert_eq!(ids(".a p"), vec!["x"]);
+    assert_eq!(ids("div section p"), vec!["x"]);
+    assert_eq!(ids("section div p"), Vec::<String>::new());
+    assert_eq!(ids("body p"), Vec::<String>::new());
+    assert_eq!(dom.query(&Selector::from("div p")).unwrap().attrs[0].1, "x");
+
+    // Ancestors outside of the queried element are not considered.
+    let section = dom.query(&Selector::from("section")).unwrap();
+    assert!(section.query(&Selector::from("div p")).is_none());
+    assert!(section.query(&Selector::from("section p")).is_some());
+}
+
+#[test]
+fn query_child() {
+    let html = r#"<ul id="a"><li id="b"><ul id="c"><li id="d"></li></ul></li></ul><ol><li id="e"></li></ol>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("ul > li"), vec!["d", "b"]);
+    assert_eq!(ids("#a > li"), vec!["b"]);
+    assert_eq!(ids("#a > ul"), Vec::<String>::new());
+    assert_eq!(ids("#a ul > li"), vec!["d"]);
+    assert_eq!(ids("li > ul > li"), vec!["d"]);
+    assert_eq!(ids("ol>li"), vec!["e"]);
+}
+
+#[test]
+fn query_next_sibling() {
+    let html = r#"<h2 id="a"></h2> text <p id="b"></p><p id="c"></p><div><h2 id="d"></h2><!-- --><p id="e"></p></div><p id="f"></p>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("h2 + p"), vec!["b", "e"]);
+    assert_eq!(ids("p + p"), vec!["c"]);
+    assert_eq!(ids("h2 + p + p"), vec!["c"]);
+    assert_eq!(ids("div + p"), vec!["f"]);
+    assert_eq!(ids("div > h2 + p"), vec!["e"]);
+    assert_eq!(ids("h2 + div p"), Vec::<String>::new());
+    assert_eq!(ids("p + div p"), vec!["e"]);
+}
+
+#[test]
+fn query_subsequent_sibling() {
+    let html = r#"<input id="a"><label id="b"></label><input id="c"><p id="d"></p><input id="e"><div><input id="f"></div>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("label ~ input"), vec!["c", "e"]);
+    assert_eq!(ids("input ~ input"), vec!["c", "e"]);
+    assert_eq!(ids("label ~ p ~ input"), vec!["e"]);
+    assert_eq!(ids("label ~ div input"), vec!["f"]);
+    assert_eq!(ids("p ~ label"), Vec::<String>::new());
+}
+
+#[test]
+fn query_attribute() {
+    let html = r#"<a id="a" href="https://example.com/x.png"></a><a id="b" href="/local" target="_blank"></a><img id="c" src="x.PNG" alt="">"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("[target]"), vec!["b"]);
+    assert_eq!(ids("a[target=_blank]"), vec!["b"]);
+    assert_eq!(ids("a[href^='https://']"), vec!["a"]);
+    assert_eq!(ids(r#"[href$=".png"]"#), vec!["a"]);
+    assert_eq!(ids("[src$=.png]"), Vec::<String>::new());
+    assert_eq!(ids("[href*=loc]"), vec!["b"]);
+    assert_eq!(ids("[HREF*=o]"), vec!["a", "b"]);
+    assert_eq!(ids("[alt]"), vec!["c"]);
+    assert_eq!(ids("[alt=]"), vec!["c"]);
+    assert_eq!(ids("[alt^='']"), Vec::<String>::new());
+}
+
+#[test]
+fn query_attribute_case() {
+    let html = r#"<input id="a" type="TEXT"><input id="b" type="text"><a id="c" href="/Docs/Intro.HTML"></a>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids(r#"input[type="text"]"#), vec!["b"]);
+    assert_eq!(ids(r#"input[type="text" i]"#), vec!["a", "b"]);
+    assert_eq!(ids("input[type=Text I ]"), vec!["a", "b"]);
+    assert_eq!(ids("input[type=TEXT s]"), vec!["a"]);
+    assert_eq!(ids("[href^='/docs/' i]"), vec!["c"]);
+    assert_eq!(ids("[href$='.html' i]"), vec!["c"]);
+    assert_eq!(ids("[href*=intro i]"), vec!["c"]);
+    assert_eq!(ids("[href*='' i]"), Vec::<String>::new());
+    assert_eq!(ids("[type=text x], [type=text i i]"), Vec::<String>::new());
+}
+
+#[test]
+fn query_only_child_and_root() {
+    let html = r#"<div id="a"><p id="b"><b id="c">Hi</b></p></div><div id="d"><p id="e"></p>text<p id="f"></p></div>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids(":only-child"), vec!["c", "b"]);
+    assert_eq!(ids(":root"), vec!["a", "d"]);
+    assert_eq!(ids(":root > :only-child"), vec!["b"]);
+    assert_eq!(ids("p:not(:only-child)"), vec!["e", "f"]);
+
+    let div = dom.query(&Selector::from("#d")).unwrap();
+    assert_eq!(div.query_all(&Selector::from(":root")).len(), 1);
+}
+
+#[test]
+fn query_namespaces() {
+    let html = concat!(
+        r#"<a id="a" href="/"><svg id="b"><a id="c"><circle id="d"></circle></a>"#,
+        r#"<foreignObject id="e"><p id="f"></p></foreignObject><title id="g"></title></svg></a>"#,
+        r#"<math id="h"><mi id="i">x</mi><annotation-xml id="j"><a id="k"></a></annotation-xml></math>"#,
+    );
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("a"), vec!["c", "a", "k"]);
+    assert_eq!(ids("svg|a"), vec!["c"]);
+    assert_eq!(ids("html|a"), vec!["a", "k"]);
+    assert_eq!(ids("*|a"), vec!["c", "a", "k"]);
+    assert_eq!(ids("svg|*"), vec!["d", "c", "e", "g", "b"]);
+    assert_eq!(ids("MATH|*"), vec!["i", "j", "h"]);
+    assert_eq!(ids("svg|foreignObject > html|p"), vec!["f"]);
+    assert_eq!(ids("svg|a ~ svg|title"), vec!["g"]);
+    assert_eq!(ids("svg|title:has(~ html|*)"), Vec::<String>::new());
+    assert_eq!(ids("math|mi + math|*"), vec!["j"]);
+    assert_eq!(
+        Selector::from("svg|circle, *|*").to_css_string(),
+        "svg|circle, *"
+    );
+
+    let error = Selector::parse("p, xlink|a").unwrap_err();
+    assert_eq!(
+        (error.position, error.message.as_str()),
+        (3, "Unknown namespace prefix xlink")
+    );
+    assert!(Selector::parse("svg|").is_err());
+    assert!(Selector::parse("svg|.x").is_err());
+}
+
+#[test]
+fn query_tag_case() {
+    let html = r#"<DIV id="a"><svg id="b"><foreignObject id="c"></foreignObject></svg></DIV><div id="d"></div>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: Selector| -> Vec<String> {
+        dom.query_all(&selector)
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids(Selector::from("div")), vec!["a", "d"]);
+    assert_eq!(ids(Selector::from("foreignobject")), vec!["c"]);
+    assert_eq!(ids(Selector::from("div").case_sensitive()), vec!["d"]);
+    assert_eq!(ids(Selector::from("DIV > svg").case_sensitive()), vec!["b"]);
+    assert_eq!(
+        ids(Selector::from("foreignobject").case_sensitive()),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        ids(Selector::from("*:not(DIV, svg)").case_sensitive()),
+        vec!["c", "d"]
+    );
+
+    let html = r#"<Item id="a"></Item><item id="b"></item><item id="c"></item>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: Selector| -> Vec<String> {
+        dom.query_all(&selector)
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+    assert_eq!(ids(Selector::from(":first-of-type")), vec!["a"]);
+    assert_eq!(
+        ids(Selector::from(":first-of-type").case_sensitive()),
+        vec!["a", "b"]
+    );
+    assert_eq!(
+        ids(Selector::from("item:nth-of-type(2)").case_sensitive()),
+        vec!["c"]
+    );
+    assert_eq!(ids(Selector::from(":last-of-type")), vec!["c"]);
+}
+
+#[test]
+fn case_sensitive_round_trip() {
+    for css in [
+        "DIV",
+        "DIV > svg",
+        "*:not(DIV, svg)",
+        "p:first-of-type",
+        "div:has(> Item)",
+    ] {
+        let selector = Selector::from(css).case_sensitive();
+        let written = selector.to_css_string();
+        let parsed = Selector::parse(&written).unwrap();
+        assert_eq!(parsed.to_css_string(), written);
+
+        // The mode is kept, matching the same elements.
+        let dom =
+            parse("<DIV><svg></svg><Item></Item><p></p></DIV><div><item></item></div>").unwrap();
+        assert_eq!(
+            dom.query_all(&parsed).len(),
+            dom.query_all(&selector).len(),
+            "{}",
+            written
+        );
+    }
+    assert_eq!(
+        Selector::from("DIV").case_sensitive().to_css_string(),
+        "DIV:case-sensitive"
+    );
+    assert_eq!(
+        Selector::from("*:not(DIV) > .x")
+            .case_sensitive()
+            .to_css_string(),
+        "*:not(DIV:case-sensitive) > .x"
+    );
+    assert_eq!(
+        Selector::from("div:case-sensitive").specificity(),
+        Selector::from("div").specificity()
+    );
+}
+
+#[test]
+fn query_attribute_lists() {
+    let html = concat!(
+        r#"<p id="a" class="note  warning" lang="en"></p>"#,
+        r#"<p id="b" class="notes" lang="en-US"></p>"#,
+        r#"<p id="c" class="" lang="english"></p>"#,
+    );
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("[class~=note]"), vec!["a"]);
+    assert_eq!(ids("[class~=warning]"), vec!["a"]);
+    assert_eq!(
+        ids("[class~='note warning'], [class~='']"),
+        Vec::<String>::new()
+    );
+    assert_eq!(ids("[class~=NOTES i]"), vec!["b"]);
+    assert_eq!(ids("[lang|=en]"), vec!["a", "b"]);
+    assert_eq!(ids("[lang|=EN i]"), vec!["a", "b"]);
+    assert_eq!(ids("[lang|=en-us]"), Vec::<String>::new());
+    assert_eq!(
+        Selector::from("[lang |= en]").to_css_string(),
+        r#"[lang|="en"]"#
+    );
+}
+
+#[test]
+fn query_escaped() {
+    let html = r#"<p id="123" class="md:flex"></p><p id="a" class="w-1/2" title='say "hi"'></p>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids(r"#\31 23"), vec!["123"]);
+    assert_eq!(ids(r".md\:flex"), vec!["123"]);
+    assert_eq!(ids(r".w-1\/2"), vec!["a"]);
+    assert_eq!(ids(r#"[title="say \"hi\""]"#), vec!["a"]);
+}
+
+#[test]
+fn query_not() {
+    let html = r#"<div id="a" class="ad"></div><div id="b"><p id="c"></p></div><ul><li id="d"></li></ul><ol><li id="e"></li></ol>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("div:not(.ad)"), vec!["b"]);
+    assert_eq!(ids(":not(div, ul, ol, li)"), vec!["c"]);
+    assert_eq!(ids("li:not(ul > li)"), vec!["e"]);
+    assert_eq!(ids("div:not(.ad):not(#b)"), Vec::<String>::new());
+}
+
+#[test]
+fn query_first_last_child() {
+    let html =
+        r#"<ul> <li id="a"></li> <li id="b"></li> <!-- --> <li id="c"></li> </ul><p id="d"></p>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("li:first-child"), vec!["a"]);
+    assert_eq!(ids("li:last-child"), vec!["c"]);
+    assert_eq!(ids("li:not(:first-child):not(:last-child)"), vec!["b"]);
+    assert_eq!(ids(":last-child"), vec!["c", "d"]);
+
+    let ul = dom.query(&Selector::from("ul")).unwrap();
+    assert!(ul
+        .query(&Selector::from("ul:first-child:last-child"))
+        .is_some());
+}
+
+#[test]
+fn query_nth_child() {
+    let html: String = (1..=10)
+        .map(|i| format!(r#"<li id="{}"></li>"#, i))
+        .collect();
+    let dom = parse(&format!("<ul>{}</ul>", html)).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("li:nth-child(3)"), vec!["3"]);
+    assert_eq!(ids("li:nth-child(+3)"), vec!["3"]);
+    assert_eq!(ids("li:nth-child(odd)"), vec!["1", "3", "5", "7", "9"]);
+    assert_eq!(ids("li:nth-child(EVEN)"), vec!["2", "4", "6", "8", "10"]);
+    assert_eq!(ids("li:nth-child(3n)"), vec!["3", "6", "9"]);
+    assert_eq!(ids("li:nth-child(3n + 1)"), vec!["1", "4", "7", "10"]);
+    assert_eq!(ids("li:nth-child(-n+3)"), vec!["1", "2", "3"]);
+    assert_eq!(ids("li:nth-child(n+8)"), vec!["8", "9", "10"]);
+    assert_eq!(ids("li:nth-child(-2n-1)"), Vec::<String>::new());
+    assert_eq!(ids("li:nth-child(0n+0)"), Vec::<String>::new());
+    assert_eq!(ids("li:nth-child(2n+-1)"), Vec::<String>::new());
+    assert_eq!(ids("li:nth-child(), li:nth-child(x)"), Vec::<String>::new());
+}
+
+#[test]
+fn query_of_type() {
+    let html = concat!(
+        r#"<div id="a"><h2 id="b"></h2><table id="c"></table><p id="d"></p>"#,
+        r#"<table id="e"></table><table id="f"></table></div><table id="g"></table>"#,
+    );
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("table:nth-of-type(2)"), vec!["e"]);
+    assert_eq!(ids("table:nth-of-type(odd)"), vec!["c", "f", "g"]);
+    assert_eq!(ids("table:first-of-type"), vec!["c", "g"]);
+    assert_eq!(ids("table:last-of-type"), vec!["f", "g"]);
+    assert_eq!(ids("div > :first-of-type"), vec!["b", "c", "d"]);
+    assert_eq!(ids("p:first-of-type:last-of-type"), vec!["d"]);
+    assert_eq!(ids("table:nth-of-type(x)"), Vec::<String>::new());
+}
+
+#[test]
+fn query_has() {
+    let html = concat!(
+        r#"<div id="a"><img class="hero"></div>"#,
+        r#"<div id="b"><p><img class="hero"></p></div>"#,
+        r#"<div id="c"><p>Text</p></div>"#,
+        r#"<h2 id="d"></h2><p></p><h2 id="e"></h2><table></table>"#,
+    );
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(ids("div:has(img.hero)"), vec!["a", "b"]);
+    assert_eq!(ids("div:has(> img.hero)"), vec!["a"]);
+    assert_eq!(ids("div:has(> p img)"), vec!["b"]);
+    assert_eq!(ids("div:has(p > img)"), vec!["b"]);
+    assert_eq!(ids("div:not(:has(img))"), vec!["c"]);
+    assert_eq!(ids("h2:has(+ p)"), vec!["d"]);
+    assert_eq!(ids("h2:has(~ table)"), vec!["d", "e"]);
+    assert_eq!(ids("h2:has(+ p, + table)"), vec!["d", "e"]);
+    assert_eq!(ids("div:has(div)"), Vec::<String>::new());
+    assert_eq!(ids("div:has(), div:has(> > p)"), Vec::<String>::new());
+}
+
+#[test]
+fn query_contains() {
+    let html = concat!(
+        r#"<div id="a"><p>Subscribe to our <b>newsletter</b></p></div>"#,
+        r#"<div id="b"><p>Fish &amp; chips</p></div>"#,
+        r#"<div id="c"><!-- newsletter --></div>"#,
+    );
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert_eq!(
+        ids(r#"div:contains("Subscribe to our newsletter")"#),
+        vec!["a"]
+    );
+    assert_eq!(ids("div:contains(newsletter)"), vec!["a"]);
+    assert_eq!(ids("div:contains('Fish & chips')"), vec!["b"]);
+    assert_eq!(ids("div:contains('subscribe')"), Vec::<String>::new());
+    assert_eq!(ids("div:not(:contains(''))"), Vec::<String>::new());
+    assert_eq!(
+        ids("div:contains(), div:contains(a b)"),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn query_custom_pseudo_class() {
+    let html = r#"<a id="a" href="/x"></a><a id="b" href="https://a.b"></a><p id="c"></p>"#;
+    let dom = parse(html).unwrap();
+    let ids = |selector: &str| -> Vec<String> {
+        dom.query_all(&Selector::from(selector))
+            .iter()
+            .map(|el| el.attrs[0].1.clone())
+            .collect()
+    };
+
+    assert!(Selector::parse("a:remote").is_err());
+    Selector::register(":remote", |el| {
+        el.attrs
+            .iter()
+            .any(|(k, v)| k == "href" && v.starts_with("https:"))
+    });
+    assert_eq!(ids("a:remote"), vec!["b"]);
+    assert_eq!(ids(":not(:REMOTE)"), vec!["a", "c"]);
+    assert_eq!(ids("div:has(a:remote), p:remote"), Vec::<String>::new());
+
+    Selector::register("remote", |el| el.name == "p");
+    assert_eq!(ids(":remote"), vec!["c"]);
+
+    Selector::register("first-child", |_| false);
+    assert_eq!(ids(":first-child"), vec!["a"]);
+}
+
+#[test]
+fn query_xpath() {
+    let html = r#"
+        <div class="x"><p>One</p><p id="b" class="y">Two <b>bold</b></p></div>
+        <div><p>Three</p><a href="https://a.com">A</a></div>"#;
+    let dom = parse(html).unwrap();
+    let html_of = |xpath: &str| -> Vec<String> {
+        let xpath = XPath::parse(xpath).unwrap();
+        dom.query_all(xpath.selector())
+            .iter()
+            .map(|element| element.html())
+            .collect()
+    };
+
+    assert_eq!(html_of("//div[@class='x']/p[1]"), vec!["<p>One</p>"]);
+    assert_eq!(html_of("/div[2]/p[last()]"), vec!["<p>Three</p>"]);
+    assert_eq!(html_of("/p"), Vec::<String>::new());
+    assert_eq!(html_of("div/*[2]").len(), 2);
+    assert_eq!(html_of("//p[contains(., 'bold')]/b"), vec!["<b>bold</b>"]);
+    assert_eq!(html_of("//a[starts-with(@href, 'https:')]").len(), 1);
+    assert_eq!(
+        html_of("//p[@id and not(contains(@class, 'z'))]/b").len(),
+        1
+    );
+    assert_eq!(html_of("/descendant::b"), vec!["<b>bold</b>"]);
+
+    let items = XPath::parse("//p/text()").unwrap();
+    let texts: Vec<_> = items
+        .evaluate(&dom)
+        .into_iter()
+        .filter_map(|item| match item {
+            XPathItem::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, vec!["One", "Two ", "Three"]);
+
+    let attrs = XPath::parse("//p[@id]/attribute::*")
+        .unwrap()
+        .evaluate(&dom);
+    assert_eq!(attrs.len(), 2);
+
+    let error = XPath::parse("//p[@id][1]").unwrap_err();
+    assert_eq!(error.message, "A position must be the first predicate");
+    assert!(XPath::parse("//p[").is_err());
+    assert!(XPath::parse("ancestor::p").is_err());
+}
+
+#[test]
+fn edit_xpath() {
+    let mut dom = parse("<ul><li>A</li><li>B</li></ul>").unwrap();
+    let xpath = XPath::parse("/ul/li[last()]").unwrap();
+    dom.remove_by(xpath.selector());
+    assert_eq!(dom.html(), "<ul><li>A</li></ul>");
+}
+
+#[test]
+fn query_forest() {
+    let html = concat!(
+        r#"<div class="x"><iframe srcdoc="<p class=&quot;x&quot;>Frame</p><iframe srcdoc='<p>Deep</p>'></iframe>"></iframe></div>"#,
+        "<template><p>Template</p><template><p>Inner</p></template></template>",
+        "<p>Top</p>",
+    );
+    let dom = parse(html).unwrap();
+    let forest = DocumentForest::new(&dom);
+
+    let documents: Vec<_> = forest
+        .documents()
+        .iter()
+        .map(|document| (document.origin, document.parent))
+        .collect();
+    assert_eq!(
+        documents,
+        vec![
+            (DocumentOrigin::Top, None),
+            (DocumentOrigin::Srcdoc, Some(0)),
+            (DocumentOrigin::Srcdoc, Some(1)),
+            (DocumentOrigin::Template, Some(0)),
+            (DocumentOrigin::Template, Some(3)),
+        ]
+    );
+
+    let texts: Vec<_> = forest
+        .query_all(&Selector::from("p"))
+        .into_iter()
+        .map(|(document, p)| (document, p.html()))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            (0, "<p>Top</p>".to_string()),
+            (1, r#"<p class="x">Frame</p>"#.to_string()),
+            (2, "<p>Deep</p>".to_string()),
+            (3, "<p>Template</p>".to_string()),
+            (4, "<p>Inner</p>".to_string()),
+        ]
+    );
+
+    // The selectors don't match across the documents.
+    assert!(forest.query(&Selector::from("div p")).is_none());
+    assert_eq!(forest.query(&Selector::from(".x")).unwrap().0, 0);
+    assert_eq!(forest.query(&Selector::from("p.x")).unwrap().0, 1);
+}
@@ -0,0 +1,145 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn main_content_preference() {
+    let dom = parse(r#"<body><article>A</article><div role="main">B</div></body>"#).unwrap();
+    assert_eq!(
+        dom.main_content().unwrap().html(),
+        r#"<div role="main">B</div>"#
+    );
+
+    let dom = parse("<body><p>Lorem</p></body>").unwrap();
+    assert_eq!(dom.main_content().unwrap().name, "body");
+
+    let dom = parse("<p>Lorem</p>").unwrap();
+    assert!(dom.main_content().is_none());
+}
+
+#[test]
+fn stitch_deduplicates_headers_and_footers() {
+    let page = |n: &str| {
+        parse(&format!(
+            r#"
+            <html>
+                <body>
+                    <main>
+                        <h1>Title</h1>
+                        <p>Page {}</p>
+                        <div class="pager">1 2 3</div>
+                    </main>
+                </body>
+            </html>"#,
+            n
+        ))
+        .unwrap()
+    };
+
+    let html = stitch_pages(&[page("1"), page("2"), page("3")])
+        .trim()
+        .html();
+    assert_eq!(
+        html,
+        r#"<html><body><main><h1>Title</h1><p>Page 1</p><div class="pager">1 2 3</div><p>Page 2</p><p>Page 3</p></main></body></html>"#
+    );
+}
+
+#[test]
+fn stitch_without_main_content() {
+    let pages = vec![parse("<p>A</p>").unwrap(), parse("<p>B</p>").unwrap()];
+    assert_eq!(stitch_pages(&pages).html(), "<p>A</p><p>B</p>");
+    assert!(stitch_pages(&[]).is_empty());
+}
+
+#[test]
+fn remove_overlays() {
+    let mut dom = parse(
+        r#"
+        <body>
+            <div id="CybotCookiebotDialog" role="dialog">Cookies</div>
+            <div style="position: fixed; inset: 0">Subscribe!</div>
+            <div style="position:fixed;z-index:99999">Sale</div>
+            <div class="modal">Fixed modal</div>
+            <div style="position: fixed; width: 100vw; height: 100vh">Wall</div>
+            <header style="position: fixed; top: 0; z-index: 10">Menu</header>
+            <div class="popup" style="position: fixed">Ad</div>
+            <p>Lorem</p>
+        </body>"#,
+    )
+    .unwrap();
+    let removed = dom.remove_overlays();
+
+    assert_eq!(removed.len(), 5);
+    assert_eq!(
+        dom.trim().html(),
+        r#"<body><div class="modal">Fixed modal</div><header style="position: fixed; top: 0; z-index: 10">Menu</header><p>Lorem</p></body>"#
+    );
+}
+
+#[test]
+fn keep_consent_like_content() {
+    let html = concat!(
+        r#"<body><div class="cmp-card">Card</div><section id="cookie-recipe">Recipe</section>"#,
+        r#"<p class="cookies">Cookies</p><div class="scmpx" style="position: fixed">Bar</div>"#,
+        r#"<div class="gdpr-banner" style="position: sticky; bottom: 0">Consent</div></body>"#,
+    );
+    let mut dom = parse(html).unwrap();
+    let removed = dom.remove_overlays();
+
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].attrs[0].1, "gdpr-banner");
+    assert_eq!(
+        dom.html(),
+        html.replace(
+            r#"<div class="gdpr-banner" style="position: sticky; bottom: 0">Consent</div>"#,
+            ""
+        )
+    );
+}
+
+#[test]
+fn extract_fragment() {
+    let dom = parse(concat!(
+        r#"<article><h2 id="intro">Intro</h2><p>Hello</p><h2 id="usage">Usage</h2>"#,
+        r#"<p id="note">Note</p><h3>Details</h3><p>More</p></article><h1>Other</h1>"#,
+    ))
+    .unwrap();
+
+    let usage = dom.extract_fragment("usage", true).unwrap();
+    assert_eq!(
+        usage.html(),
+        r#"<h2 id="usage">Usage</h2><p id="note">Note</p><h3>Details</h3><p>More</p>"#
+    );
+    let heading = dom.extract_fragment("#usage", false).unwrap();
+    assert_eq!(heading.html(), r#"<h2 id="usage">Usage</h2>"#);
+    let note = dom.extract_fragment("#note", true).unwrap();
+    assert_eq!(note.html(), r#"<p id="note">Note</p>"#);
+    assert!(dom.extract_fragment("#missing", true).is_none());
+}
+
+#[test]
+fn landmarks() {
+    let dom = parse(concat!(
+        r#"<body><header><nav aria-label="Breadcrumb">Home</nav><nav>Menu</nav></header>"#,
+        r#"<div role="main"><section aria-label="News"><footer>Meta</footer></section>"#,
+        r#"<section>Plain</section><form>Plain</form><ol itemtype="https://schema.org/BreadcrumbList"></ol></div>"#,
+        r#"<aside role="presentation">x</aside><div role="search"></div><footer>End</footer></body>"#,
+    ))
+    .unwrap();
+    let landmarks = dom.landmarks();
+    let names = |landmark: Landmark| -> Vec<&str> {
+        landmarks.get(&landmark).map_or(vec![], |els| {
+            els.iter().map(|el| el.name.as_str()).collect()
+        })
+    };
+
+    assert_eq!(names(Landmark::Banner), vec!["header"]);
+    assert_eq!(names(Landmark::Navigation), vec!["nav"]);
+    assert_eq!(names(Landmark::Breadcrumb), vec!["nav", "ol"]);
+    assert_eq!(names(Landmark::Main), vec!["div"]);
+    assert_eq!(names(Landmark::Region), vec!["section"]);
+    assert_eq!(names(Landmark::ContentInfo), vec!["footer"]);
+    assert_eq!(names(Landmark::Search), vec!["div"]);
+    assert!(names(Landmark::Complementary).is_empty());
+    assert!(names(Landmark::Form).is_empty());
+}
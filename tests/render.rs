@@ -0,0 +1,71 @@
+use html_editor::operation::*;
+use html_editor::{parse, Node};
+
+#[test]
+fn highlight_code() {
+    let mut dom = parse(concat!(
+        "<pre class=\"lang-sh\">\n<code>echo &quot;hi&quot; &amp;&amp; <b>exit</b></code>\n</pre>",
+        "<pre><code>plain</code></pre><pre><code>a</code><code>b</code></pre><code>inline</code>",
+    ))
+    .unwrap();
+    let mut seen = Vec::new();
+    let count = dom.highlight_code(|code, language| {
+        seen.push((code.to_string(), language.map(str::to_string)));
+        language.map(|_| {
+            vec![Node::new_element(
+                "mark",
+                vec![],
+                vec![Node::Text(code.replace('&', "&amp;"))],
+            )]
+        })
+    });
+
+    assert_eq!(count, 1);
+    assert_eq!(
+        seen,
+        vec![
+            ("echo \"hi\" && exit".to_string(), Some("sh".to_string())),
+            ("plain".to_string(), None),
+        ]
+    );
+    assert_eq!(
+        dom.html(),
+        concat!(
+            "<pre class=\"lang-sh\">\n<code><mark>echo \"hi\" &amp;&amp; exit</mark></code>\n</pre>",
+            "<pre><code>plain</code></pre><pre><code>a</code><code>b</code></pre><code>inline</code>",
+        )
+    );
+}
+
+#[test]
+fn render_math() {
+    let mut dom = parse(concat!(
+        r"<p>Inline \(a &lt; b\) and $$\sum_i x_i$$ or \[x\], costs \$5 or $ 3 $.</p>",
+        r#"<span class="math inline">\(y\)</span><div class="math">z</div><code>$x$</code>"#,
+    ))
+    .unwrap();
+    let mut formulas = Vec::new();
+    let count = dom.render_math(|tex, display| {
+        formulas.push((tex.to_string(), display));
+        (tex != "x").then(|| vec![Node::new_element("m", vec![], vec![])])
+    });
+
+    assert_eq!(count, 4);
+    assert_eq!(
+        formulas,
+        vec![
+            ("y".to_string(), false),
+            ("z".to_string(), true),
+            ("a < b".to_string(), false),
+            (r"\sum_i x_i".to_string(), true),
+            ("x".to_string(), true),
+        ]
+    );
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r"<p>Inline <m></m> and <m></m> or \[x\], costs \$5 or $ 3 $.</p>",
+            r#"<span class="math inline"><m></m></span><div class="math"><m></m></div><code>$x$</code>"#,
+        )
+    );
+}
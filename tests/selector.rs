@@ -4,15 +4,15 @@ use html_editor::operation::*;
 fn simple() {
     assert_eq!(
         format!("{:?}", Selector::from("div")),
-        r#"Selector([CompoundSelector([Tag("div")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("div")]), relatives: [] }])"#
     );
     assert_eq!(
         format!("{:?}", Selector::from(".class")),
-        r#"Selector([CompoundSelector([Class("class")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Class("class")]), relatives: [] }])"#
     );
     assert_eq!(
         format!("{:?}", Selector::from("#id")),
-        r#"Selector([CompoundSelector([Id("id")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Id("id")]), relatives: [] }])"#
     );
 }
 
@@ -20,15 +20,15 @@ fn simple() {
 fn compound() {
     assert_eq!(
         format!("{:?}", Selector::from("button.round")),
-        r#"Selector([CompoundSelector([Tag("button"), Class("round")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("button"), Class("round")]), relatives: [] }])"#
     );
     assert_eq!(
         format!("{:?}", Selector::from("div#app")),
-        r#"Selector([CompoundSelector([Tag("div"), Id("app")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("div"), Id("app")]), relatives: [] }])"#
     );
     assert_eq!(
         format!("{:?}", Selector::from("a.o#e")),
-        r#"Selector([CompoundSelector([Tag("a"), Class("o"), Id("e")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("a"), Class("o"), Id("e")]), relatives: [] }])"#
     );
 }
 
@@ -36,10 +36,239 @@ fn compound() {
 fn complex() {
     assert_eq!(
         format!("{:?}", Selector::from("h1, h2")),
-        r#"Selector([CompoundSelector([Tag("h1")]), CompoundSelector([Tag("h2")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("h1")]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Tag("h2")]), relatives: [] }])"#
     );
     assert_eq!(
         format!("{:?}", Selector::from(" h1,h2  ")),
-        r#"Selector([CompoundSelector([Tag("h1")]), CompoundSelector([Tag("h2")])])"#
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("h1")]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Tag("h2")]), relatives: [] }])"#
     );
 }
+
+#[test]
+fn descendant() {
+    assert_eq!(
+        format!("{:?}", Selector::from("ul  li a")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("a")]), relatives: [(Descendant, CompoundSelector([Tag("li")])), (Descendant, CompoundSelector([Tag("ul")]))] }])"#
+    );
+}
+
+#[test]
+fn child() {
+    let expected = r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("a")]), relatives: [(Child, CompoundSelector([Tag("li")])), (Descendant, CompoundSelector([Tag("ul")]))] }])"#;
+    assert_eq!(format!("{:?}", Selector::from("ul li > a")), expected);
+    assert_eq!(format!("{:?}", Selector::from("ul li>a")), expected);
+    assert_eq!(format!("{:?}", Selector::from(" ul  li  >a ")), expected);
+}
+
+#[test]
+fn next_sibling() {
+    let expected = r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("p")]), relatives: [(NextSibling, CompoundSelector([Tag("h2")])), (Child, CompoundSelector([Tag("div")]))] }])"#;
+    assert_eq!(format!("{:?}", Selector::from("div > h2 + p")), expected);
+    assert_eq!(format!("{:?}", Selector::from("div>h2+p")), expected);
+}
+
+#[test]
+fn subsequent_sibling() {
+    let expected = r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("input")]), relatives: [(SubsequentSibling, CompoundSelector([Tag("label")])), (NextSibling, CompoundSelector([Tag("h2")]))] }])"#;
+    assert_eq!(
+        format!("{:?}", Selector::from("h2 + label ~ input")),
+        expected
+    );
+    assert_eq!(format!("{:?}", Selector::from("h2+label~input")), expected);
+}
+
+#[test]
+fn attribute() {
+    assert_eq!(
+        format!("{:?}", Selector::from("a[href^='https://a.b > c']")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("a"), Attribute(AttributeSelector { name: "href", value: Some((Prefix, "https://a.b > c")), case_insensitive: false })]), relatives: [] }])"#
+    );
+    assert_eq!(
+        format!("{:?}", Selector::from("[hidden].x")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Attribute(AttributeSelector { name: "hidden", value: None, case_insensitive: false }), Class("x")]), relatives: [] }])"#
+    );
+    assert_eq!(
+        format!("{:?}", Selector::from(r#"[title="a, b"], p"#)),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Attribute(AttributeSelector { name: "title", value: Some((Equals, "a, b")), case_insensitive: false })]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Tag("p")]), relatives: [] }])"#
+    );
+}
+
+#[test]
+fn escaped() {
+    assert_eq!(
+        format!("{:?}", Selector::from(r".foo\.bar#\31 23\ x")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Class("foo.bar"), Id("123 x")]), relatives: [] }])"#
+    );
+    assert_eq!(
+        format!("{:?}", Selector::from(r#"[data-x='it\'s "ok"']"#)),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Attribute(AttributeSelector { name: "data-x", value: Some((Equals, "it's \"ok\"")), case_insensitive: false })]), relatives: [] }])"#
+    );
+    assert_eq!(
+        format!("{:?}", Selector::from(r".a\:hover, .\1F600")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Class("a:hover")]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Class("😀")]), relatives: [] }])"#
+    );
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(
+        format!("{:?}", Selector::from("p, input:checked, > a, a >, a[b, .")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("p")]), relatives: [] }])"#
+    );
+}
+
+#[test]
+fn not() {
+    assert_eq!(
+        format!("{:?}", Selector::from("div:not(.ad, #x)")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("div"), Not([ComplexSelector { subject: CompoundSelector([Class("ad")]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Id("x")]), relatives: [] }])]), relatives: [] }])"#
+    );
+    assert_eq!(
+        format!("{:?}", Selector::from(":NOT(p)")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Not([ComplexSelector { subject: CompoundSelector([Tag("p")]), relatives: [] }])]), relatives: [] }])"#
+    );
+    assert_eq!(
+        format!("{:?}", Selector::from("p:not(, a), p:not(a")),
+        "Selector([])"
+    );
+}
+
+#[test]
+fn attribute_prefix() {
+    assert_eq!(
+        format!("{:?}", Selector::from("p[data-*], [x*=y]")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Tag("p"), AttributePrefix("data-")]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Attribute(AttributeSelector { name: "x", value: Some((Contains, "y")), case_insensitive: false })]), relatives: [] }])"#
+    );
+}
+
+#[test]
+fn universal() {
+    assert_eq!(
+        format!("{:?}", Selector::from("*, *[data-x], div > *")),
+        r#"Selector([ComplexSelector { subject: CompoundSelector([Universal]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Universal, Attribute(AttributeSelector { name: "data-x", value: None, case_insensitive: false })]), relatives: [] }, ComplexSelector { subject: CompoundSelector([Universal]), relatives: [(Child, CompoundSelector([Tag("div")]))] }])"#
+    );
+    assert_eq!(format!("{:?}", Selector::from("p*, **")), "Selector([])");
+}
+
+#[test]
+fn parse_errors() {
+    let error = |selector: &str| {
+        let error = Selector::parse(selector).unwrap_err();
+        (error.position, error.message)
+    };
+
+    assert_eq!(error(""), (0, "Empty selector".to_string()));
+    assert_eq!(error("a, "), (2, "Empty selector".to_string()));
+    assert_eq!(error("> a"), (0, "Unexpected `>`".to_string()));
+    assert_eq!(error("a >"), (3, "Unexpected end of selector".to_string()));
+    assert_eq!(error("a[b"), (3, "Unexpected end of selector".to_string()));
+    assert_eq!(error("a[b!c]"), (3, "Unexpected `!`".to_string()));
+    assert_eq!(error("a[b~c]"), (4, "Unexpected `c`".to_string()));
+    assert_eq!(error("p.#x"), (2, "Unexpected `#x`".to_string()));
+    assert_eq!(
+        error("input:checked"),
+        (6, "Unsupported pseudo-class :checked".to_string())
+    );
+    assert_eq!(
+        error("p, li:nth-child(x)"),
+        (16, "Invalid argument of :nth-child".to_string())
+    );
+    assert_eq!(
+        error("p:contains(a, b)"),
+        (11, "Invalid argument of :contains".to_string())
+    );
+    assert_eq!(error("p:not(a, > b)"), (9, "Unexpected `>`".to_string()));
+    assert_eq!(
+        Selector::parse("a:has(> b").unwrap_err().to_string(),
+        "Unexpected end of selector at position 9"
+    );
+    assert!(Selector::parse("div:has(> img.hero), [data-*]").is_ok());
+}
+
+#[test]
+fn specificity() {
+    let specificity = |selector: &str| Selector::from(selector).specificity();
+
+    assert_eq!(specificity("*"), (0, 0, 0));
+    assert_eq!(specificity("li"), (0, 0, 1));
+    assert_eq!(specificity("ul li"), (0, 0, 2));
+    assert_eq!(specificity("ul li.item:first-child"), (0, 2, 2));
+    assert_eq!(specificity("a[href^='https://'][title]"), (0, 2, 1));
+    assert_eq!(specificity("#nav > li ~ *"), (1, 0, 1));
+    assert_eq!(specificity("p:not(.intro, #lead)"), (1, 0, 1));
+    assert_eq!(specificity("div:has(> img.hero)"), (0, 1, 2));
+    assert_eq!(specificity("a, .b, #c"), (1, 0, 0));
+}
+
+#[test]
+fn builder() {
+    let same = |built: Selector, parsed: &str| {
+        assert_eq!(
+            format!("{:?}", built),
+            format!("{:?}", Selector::from(parsed))
+        );
+    };
+
+    same(Selector::builder().tag("div").class("ad").build(), "div.ad");
+    same(
+        Selector::builder()
+            .tag("nav")
+            .descendant()
+            .tag("ul")
+            .child()
+            .tag("li")
+            .first_child()
+            .next_sibling()
+            .nth_child(2, 1)
+            .subsequent_sibling()
+            .last_child()
+            .build(),
+        "nav ul > li:first-child + :nth-child(2n+1) ~ :last-child",
+    );
+    same(
+        Selector::builder()
+            .id("main")
+            .attr("hidden")
+            .attr_eq("a", "1")
+            .attr_prefix("b", "2")
+            .attr_suffix("c", "3")
+            .attr_contains("d", "4")
+            .or()
+            .not(&Selector::from("p, .x"))
+            .build(),
+        "#main[hidden][a='1'][b^='2'][c$='3'][d*='4'], :not(p, .x)",
+    );
+    assert_eq!(
+        Selector::builder().child().class("a").build().specificity(),
+        (0, 1, 0)
+    );
+}
+
+#[test]
+fn display() {
+    let round_trip = |selector: &str, expected: &str| {
+        let parsed = Selector::from(selector);
+        assert_eq!(parsed.to_css_string(), expected);
+        let reparsed = Selector::parse(expected).unwrap();
+        assert_eq!(format!("{:?}", reparsed), format!("{:?}", parsed));
+    };
+
+    round_trip("div", "div");
+    round_trip("*, *.a", "*, *.a");
+    round_trip("div>p  +  a ~ b i", "div > p + a ~ b i");
+    round_trip(r".foo\.bar#\31 23\ x", r".foo\.bar#\31 23\ x");
+    round_trip(r#"[data-x='it\'s "ok"']"#, r#"[data-x="it's \"ok\""]"#);
+    round_trip(
+        "[type=TEXT i][data-*][href]",
+        r#"[type="TEXT" i][data-*][href]"#,
+    );
+    round_trip(
+        "li:nth-child(odd):nth-of-type(-n+3):nth-child(4):first-child:last-of-type",
+        "li:nth-child(2n+1):nth-of-type(-n+3):nth-child(4):first-child:last-of-type",
+    );
+    round_trip(
+        "a:not(.x, p > b):has(> img, + p):empty:blank",
+        "a:not(.x, p > b):has(> img, + p):empty:blank",
+    );
+    round_trip(r#"p:contains("a \"b\"")"#, r#"p:contains("a \"b\"")"#);
+}
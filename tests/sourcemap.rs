@@ -0,0 +1,103 @@
+use html_editor::operation::*;
+use html_editor::{parse_with_options, try_parse_with_options, Node, ParseOptions};
+
+#[test]
+fn source_spans() {
+    let html = "<ul>\n  <li>A<br></li>\n  <li>B<img/></li>\n</ul><script>x()</script><p>open";
+    let options = ParseOptions {
+        source_spans: true,
+        ..Default::default()
+    };
+    let dom = try_parse_with_options(html, &options);
+    let spans: Vec<&str> = dom
+        .query_all(&Selector::from("ul, li, br, img, script, p"))
+        .iter()
+        .map(|el| {
+            let (_, span) = el
+                .attrs
+                .iter()
+                .find(|(k, _)| k == SOURCE_SPAN_ATTR)
+                .unwrap();
+            let (start, end) = span.split_once('-').unwrap();
+            &html[start.parse().unwrap()..end.parse().unwrap()]
+        })
+        .collect();
+
+    assert_eq!(
+        spans,
+        vec![
+            "<br>",
+            "<li>A<br></li>",
+            "<img/>",
+            "<li>B<img/></li>",
+            "<ul>\n  <li>A<br></li>\n  <li>B<img/></li>\n</ul>",
+            "<script>x()</script>",
+            "<p>",
+        ]
+    );
+}
+
+#[test]
+fn html_with_source_map() {
+    let html = "<main><h1>Title</h1><p>Text</p></main>";
+    let options = ParseOptions {
+        source_spans: true,
+        ..Default::default()
+    };
+    let mut dom = parse_with_options(html, &options).unwrap();
+    dom.insert_to(
+        &Selector::from("main"),
+        Node::new_element("footer", vec![], vec![]),
+    );
+    dom.remove_by(&Selector::from("h1"));
+
+    let (output, mappings) = dom.html_with_source_map();
+    assert_eq!(output, "<main><p>Text</p><footer></footer></main>");
+    assert_eq!(
+        mappings,
+        vec![
+            Mapping {
+                source: 0..38,
+                output: 0..41,
+            },
+            Mapping {
+                source: 20..31,
+                output: 6..17,
+            },
+        ]
+    );
+    for mapping in &mappings[1..] {
+        assert_eq!(html[mapping.source.clone()], output[mapping.output.clone()]);
+    }
+}
+
+#[test]
+fn html_with_source() {
+    let html = concat!(
+        "<main><p class=ad>Ad</p>",
+        "<div class='embed'  data-x=1><IFRAME src=a.html></IFRAME><p class=ad>Partner</p> </div >",
+        "<section class=embed><p class=ad>open",
+        "</main>",
+    );
+    let options = ParseOptions {
+        source_spans: true,
+        ..Default::default()
+    };
+    let mut dom = try_parse_with_options(html, &options);
+    dom.freeze(&Selector::from(".embed"))
+        .remove_by(&Selector::from(".ad"));
+
+    // The embed closed implicitly has no whole span, so is stringified.
+    assert_eq!(
+        dom.html_with_source(html),
+        concat!(
+            "<main><div class='embed'  data-x=1><IFRAME src=a.html></IFRAME><p class=ad>Partner</p> </div >",
+            r#"<section class="embed"><p class="ad">open</p></section></main>"#,
+        )
+    );
+
+    let main = dom[0].as_element().unwrap();
+    assert_eq!(main.html_with_source(html), dom.html_with_source(html));
+    // Without the source, the frozen elements are stringified as usual.
+    assert!(!dom.html_with_source_map().0.contains("data-x=1"));
+}
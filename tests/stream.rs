@@ -0,0 +1,103 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+fn chunks_of(html: &str) -> Vec<String> {
+    let dom = parse(html).unwrap();
+    let mut chunks = Vec::new();
+    dom.html_chunks(|chunk| chunks.push(chunk.to_string()));
+    assert_eq!(chunks.concat(), dom.html());
+    chunks
+}
+
+#[test]
+fn html_chunks_document() {
+    let chunks = chunks_of(concat!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"></head>",
+        "<body><header>H</header><!-- c --><main><p>A</p><p>B</p></main></body></html>",
+    ));
+    assert_eq!(
+        chunks,
+        vec![
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"></head>",
+            "<body><header>H</header>",
+            "<!-- c -->",
+            "<main><p>A</p><p>B</p></main>",
+            "</body></html>",
+        ]
+    );
+}
+
+#[test]
+fn html_chunks_fragment() {
+    assert_eq!(chunks_of("<p>A</p>B<br>"), vec!["<p>A</p>", "B", "<br>"]);
+    assert!(chunks_of("").is_empty());
+}
+
+#[test]
+fn write_html_reports_errors() {
+    struct Broken;
+    impl std::io::Write for Broken {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let dom = parse("<p>A</p><p>B</p>").unwrap();
+    assert!(dom.write_html(&mut Broken).is_err());
+}
+
+#[test]
+fn render_slot_escapes_id() {
+    let chunk = render_slot(r#"a"</script>"#, &parse("<p>Late</p>").unwrap());
+    assert!(chunk.starts_with(concat!(
+        r#"<template data-slot-content="a&quot;&lt;/script>"><p>Late</p></template>"#,
+        r#"<script>(function(s){"#
+    )));
+    assert!(chunk.contains(r#"==="a\u0022\u003c\u002fscript\u003e""#));
+    assert_eq!(chunk.matches("</script>").count(), 1);
+}
+
+#[test]
+fn defer_slot_marks_first_match() {
+    let mut dom = parse("<aside>1</aside><aside>2</aside>").unwrap();
+    assert!(dom.defer_slot(&Selector::from("aside"), "ads"));
+    assert!(!dom.defer_slot(&Selector::from("footer"), "footer"));
+    assert_eq!(
+        dom.html(),
+        r#"<aside data-slot="ads">1</aside><aside>2</aside>"#
+    );
+}
+
+#[test]
+fn annotate_hydration_is_deterministic() {
+    let html = r#"<main><section><div class="card">A</div></section><div class="card"><div class="card">B</div></div></main>"#;
+    let render = || {
+        let mut dom = parse(html).unwrap();
+        let count = dom.annotate_hydration(&[Selector::from(".card")]);
+        (count, dom.html())
+    };
+    let (count, first) = render();
+    assert_eq!(count, 3);
+    assert_eq!(
+        first,
+        r#"<main><section><div class="card" data-hid="0">A</div></section><div class="card" data-hid="1"><div class="card" data-hid="2">B</div></div></main>"#
+    );
+    assert_eq!(render().1, first);
+}
+
+#[test]
+fn annotate_hydration_contextual_selector() {
+    let html = r#"<main><div class="card">A</div></main><div class="card">B</div>"#;
+    let roots = [Selector::from("main > .card")];
+    let mut dom = parse(html).unwrap();
+    assert_eq!(dom.annotate_hydration(&roots), 1);
+    assert_eq!(
+        dom.html(),
+        r#"<main><div class="card" data-hid="0">A</div></main><div class="card">B</div>"#
+    );
+
+    let mut main = parse(html).unwrap().remove(0);
+    assert_eq!(main.as_element_mut().unwrap().annotate_hydration(&roots), 1);
+}
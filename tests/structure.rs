@@ -0,0 +1,99 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn shift_headings_clamps() {
+    let html = parse("<h1>A</h1><div><h6>B</h6></div>")
+        .unwrap()
+        .shift_headings(2, HeadingOverflow::Clamp)
+        .html();
+    assert_eq!(html, "<h3>A</h3><div><h6>B</h6></div>");
+
+    let html = parse("<h2>A</h2><h3>B</h3>")
+        .unwrap()
+        .shift_headings(-3, HeadingOverflow::Clamp)
+        .html();
+    assert_eq!(html, "<h1>A</h1><h1>B</h1>");
+}
+
+#[test]
+fn rebase_headings_in_subtree() {
+    let mut dom = parse("<h1>Page</h1><section><h3>A</h3><h4>B</h4><h6>C</h6></section>").unwrap();
+    dom.query_mut(&Selector::from("section"))
+        .unwrap()
+        .rebase_headings(2, HeadingOverflow::Paragraph);
+    assert_eq!(
+        dom.html(),
+        r#"<h1>Page</h1><section><h2>A</h2><h3>B</h3><h5>C</h5></section>"#
+    );
+
+    let html = parse("<p>No headings</p>")
+        .unwrap()
+        .rebase_headings(3, HeadingOverflow::Clamp)
+        .html();
+    assert_eq!(html, "<p>No headings</p>");
+}
+
+#[test]
+fn prefix_ids_and_references() {
+    let html = parse(
+        r##"<form><label for="name">Name</label><input id="name"><small aria-describedby="hint outer"></small><p id="hint">Hint</p><a href="#hint">?</a><a href="/page#hint">!</a></form>"##,
+    )
+    .unwrap()
+    .prefix_ids("c1-")
+    .html();
+    assert_eq!(
+        html,
+        r##"<form><label for="c1-name">Name</label><input id="c1-name"><small aria-describedby="c1-hint outer"></small><p id="c1-hint">Hint</p><a href="#c1-hint">?</a><a href="/page#hint">!</a></form>"##
+    );
+}
+
+#[test]
+fn inject_toc_is_idempotent() {
+    let mut dom = parse(concat!(
+        r#"<body><div id="toc"></div><h1>Guide</h1><h2>Intro</h2><h2 id="intro">Again</h2>"#,
+        "<h4>Deep &amp; Nested</h4><h2>Intro</h2><h3>Skipped?</h3></body>",
+    ))
+    .unwrap();
+    assert!(dom.inject_toc(&Selector::from("#toc"), 2..=4));
+    let once = dom.html();
+    assert!(dom.inject_toc(&Selector::from("#toc"), 2..=4));
+    assert_eq!(dom.html(), once);
+    assert!(!dom.inject_toc(&Selector::from("#missing"), 2..=4));
+
+    assert_eq!(
+        once,
+        concat!(
+            r##"<body><div id="toc"><nav class="toc"><ol>"##,
+            r##"<li><a href="#intro-1">Intro</a></li>"##,
+            r##"<li><a href="#intro">Again</a><ol><li><a href="#deep-nested">Deep &amp; Nested</a></li></ol></li>"##,
+            r##"<li><a href="#intro-2">Intro</a><ol><li><a href="#skipped">Skipped?</a></li></ol></li>"##,
+            r##"</ol></nav></div><h1>Guide</h1><h2 id="intro-1">Intro</h2><h2 id="intro">Again</h2>"##,
+            r##"<h4 id="deep-nested">Deep &amp; Nested</h4><h2 id="intro-2">Intro</h2>"##,
+            r##"<h3 id="skipped">Skipped?</h3></body>"##,
+        )
+    );
+}
+
+#[test]
+fn frozen_structure() {
+    let embed = r#"<div class="embed"><h2 id="a">A</h2><aside></aside></div>"#;
+    let html = format!(
+        r##"{}<h2 id="b">B</h2><a href="#a">A</a><a href="#b">B</a>"##,
+        embed
+    );
+    let mut dom = parse(&html).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    dom.shift_headings(1, HeadingOverflow::Clamp)
+        .prefix_ids("p-");
+    // The only target is frozen.
+    assert!(!dom.inject_toc(&Selector::from("aside"), 1..=6));
+    assert_eq!(
+        dom.html(),
+        format!(
+            r##"{}<h3 id="p-b">B</h3><a href="#a">A</a><a href="#p-b">B</a>"##,
+            embed
+        )
+    );
+}
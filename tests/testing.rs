@@ -0,0 +1,37 @@
+#![cfg(feature = "testing")]
+
+use std::{env, fs, panic};
+
+use html_editor::operation::Pipeline;
+use html_editor::testing::{transform_fixture, UPDATE_FIXTURES_ENV};
+
+#[test]
+fn fixtures() {
+    let pipeline = Pipeline::new().remove("script");
+    transform_fixture(
+        "tests/fixtures/page.html",
+        "tests/fixtures/page.expected.html",
+        &pipeline,
+    );
+
+    let result = panic::catch_unwind(|| {
+        transform_fixture(
+            "tests/fixtures/page.html",
+            "tests/fixtures/page.expected.html",
+            &Pipeline::new(),
+        )
+    });
+    let message = result.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("at line 3"), "{}", message);
+
+    // Only this test sets the variable, so the others aren't affected.
+    let expected = env::temp_dir().join("html_editor_fixture/page.expected.html");
+    let _ = fs::remove_file(&expected);
+    env::set_var(UPDATE_FIXTURES_ENV, "1");
+    transform_fixture("tests/fixtures/page.html", &expected, &Pipeline::new());
+    env::remove_var(UPDATE_FIXTURES_ENV);
+    assert_eq!(
+        fs::read_to_string(&expected).unwrap(),
+        fs::read_to_string("tests/fixtures/page.html").unwrap()
+    );
+}
@@ -0,0 +1,94 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn set_theme_class() {
+    let mut dom = parse(r#"<html class="js light"><body></body></html>"#).unwrap();
+    assert!(dom.set_theme_class("dark", &["light", "sepia"]));
+    assert!(dom.set_theme_class("dark", &["light", "sepia"]));
+    assert_eq!(dom.html(), r#"<html class="js dark"><body></body></html>"#);
+
+    let mut fragment = parse("<p>Text</p>").unwrap();
+    assert!(!fragment.set_theme_class("dark", &[]));
+}
+
+#[test]
+fn map_colors() {
+    let mut dom = parse(concat!(
+        r#"<div style="background:#fff !important;border-color:#000">"#,
+        r#"<span style="COLOR: black">A</span><b style="color: red">B</b></div>"#,
+    ))
+    .unwrap();
+    let mut seen = vec![];
+    let count = dom.map_colors(|property, value| {
+        seen.push(format!("{}={}", property, value));
+        match value {
+            "#fff" => Some("#111".to_string()),
+            "black" => Some("#eee".to_string()),
+            _ => None,
+        }
+    });
+
+    assert_eq!(count, 2);
+    assert_eq!(seen, vec!["background=#fff", "color=black", "color=red"]);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<div style="background: #111 !important; border-color: #000">"#,
+            r#"<span style="color: #eee">A</span><b style="color: red">B</b></div>"#,
+        )
+    );
+}
+
+#[test]
+fn add_color_scheme() {
+    let mut dom = parse(r#"<head><meta name="color-scheme"></head>"#).unwrap();
+    assert!(dom.add_color_scheme("a { color: #8af; }"));
+    assert!(dom.add_color_scheme("a { color: #9bf; }"));
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<head><meta name="color-scheme"><style data-color-scheme>"#,
+            "@media (prefers-color-scheme: dark) { a { color: #9bf; } }</style></head>",
+        )
+    );
+
+    let mut fragment = parse("<p>Text</p>").unwrap();
+    assert!(!fragment.add_color_scheme(""));
+}
+
+#[test]
+fn styles_to_classes() {
+    let mut dom = parse(concat!(
+        r#"<div class="box" style="display:flex;gap:4px">"#,
+        r#"<span style="font-weight: bold">A</span><i style="color: red">B</i></div>"#,
+    ))
+    .unwrap();
+    let count = dom.styles_to_classes(|declarations| {
+        let classes: Vec<String> = declarations
+            .iter()
+            .map(
+                |(property, value)| match (property.as_str(), value.as_str()) {
+                    ("display", "flex") => Some("flex"),
+                    ("gap", "4px") => Some("gap-1 box"),
+                    ("font-weight", "bold") => Some("font-bold"),
+                    _ => None,
+                },
+            )
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        declarations.clear();
+        Some(classes)
+    });
+
+    assert_eq!(count, 2);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            r#"<div class="box flex gap-1"><span class="font-bold">A</span>"#,
+            r#"<i style="color: red">B</i></div>"#,
+        )
+    );
+}
@@ -0,0 +1,77 @@
+use html_editor::operation::*;
+use html_editor::parse;
+
+#[test]
+fn hyphenate() {
+    let html = r#"<p title="Incomprehensibilities">Incomprehensibilities &amp; caf&eacute; <b>ab</b></p><pre>Incomprehensibilities</pre>"#;
+    let mut dom = parse(html).unwrap();
+
+    assert_eq!(
+        dom.hyphenate(None, WordBreak::SoftHyphen, split_every(8)),
+        2
+    );
+    assert_eq!(
+        dom.html(),
+        r#"<p title="Incomprehensibilities">Incompre&shy;hensibil&shy;ities &amp; caf&eacute; <b>ab</b></p><pre>Incomprehensibilities</pre>"#
+    );
+
+    let mut dom = parse("<p>ab</p>").unwrap();
+    assert_eq!(
+        dom.hyphenate(None, WordBreak::Wbr, |_| vec![2, 0, 1, 1, 9]),
+        1
+    );
+    assert_eq!(dom.html(), "<p>a<wbr>b</p>");
+    assert_eq!(split_every(0)("abc"), vec![1, 2]);
+}
+
+#[test]
+fn isolate_bidi() {
+    let html = concat!(
+        "<p>Said: שלום, עולם! &amp; left</p>",
+        r#"<p dir="rtl">שלום</p><code>مرحبا</code><bdi>مرحبا</bdi>"#,
+        "<li> مرحبا 123 </li><li><b>مرحبا</b></li>",
+    );
+    let mut dom = parse(html).unwrap();
+
+    assert_eq!(dom.isolate_bidi(Direction::Ltr), 3);
+    assert_eq!(
+        dom.html(),
+        concat!(
+            "<p>Said: <bdi>שלום, עולם</bdi>! &amp; left</p>",
+            r#"<p dir="rtl">שלום</p><code>مرحبا</code><bdi>مرحبا</bdi>"#,
+            r#"<li dir="rtl"> مرحبا 123 </li><li><b dir="rtl">مرحبا</b></li>"#,
+        )
+    );
+
+    let mut dom = parse("<p>שלום CSS עולם</p>").unwrap();
+    assert_eq!(dom.isolate_bidi(Direction::Rtl), 1);
+    assert_eq!(dom.html(), "<p>שלום <bdi>CSS</bdi> עולם</p>");
+}
+
+#[test]
+fn hyphenate_contextual_scope() {
+    let mut dom = parse("<ul><li>abcdef</li><li>abcdef</li></ul><li>abcdef</li>").unwrap();
+    let scope = Selector::from("ul > li:first-child");
+    assert_eq!(
+        dom.hyphenate(Some(&scope), WordBreak::SoftHyphen, split_every(3)),
+        1
+    );
+    assert_eq!(
+        dom.html(),
+        "<ul><li>abc&shy;def</li><li>abcdef</li></ul><li>abcdef</li>"
+    );
+}
+
+#[test]
+fn frozen_typesetting() {
+    let embed = r#"<div class="embed"><p>abcdef שלום</p></div>"#;
+    let mut dom = parse(&format!("{}<p>abcdef</p>", embed)).unwrap();
+    dom.freeze(&Selector::from(".embed"));
+
+    assert_eq!(
+        dom.hyphenate(None, WordBreak::SoftHyphen, split_every(3)),
+        1
+    );
+    assert_eq!(dom.isolate_bidi(Direction::Ltr), 0);
+    assert_eq!(dom.html(), format!("{}<p>abc&shy;def</p>", embed));
+}